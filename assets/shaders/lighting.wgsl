@@ -0,0 +1,49 @@
+// Darkness overlay for levels with lighting (see src/systems/lighting.rs)
+//
+// Drawn on a quad covering the camera view: every pixel is darkened by the
+// level's ambient, except where the point lights reach. Light positions are
+// in world space, like the quad's `world_position`.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// Must match `MAX_LIGHTS` in src/constants.rs
+const MAX_LIGHTS: u32 = 32u;
+
+struct Light {
+    // xy: world position, z: radius in pixels
+    position_radius: vec4<f32>,
+    // rgb: color scaled by intensity
+    color: vec4<f32>,
+}
+
+struct Lighting {
+    // rgb: color of the darkness, a: ambient (how much of the level shows)
+    ambient: vec4<f32>,
+    light_count: u32,
+    lights: array<Light, MAX_LIGHTS>,
+}
+
+@group(2) @binding(0) var<uniform> lighting: Lighting;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = in.world_position.xy;
+
+    var light = vec3<f32>(0.0);
+    for (var i = 0u; i < min(lighting.light_count, MAX_LIGHTS); i++) {
+        let source = lighting.lights[i];
+        let distance = length(position - source.position_radius.xy);
+        let falloff = 1.0 - smoothstep(0.0, source.position_radius.z, distance);
+        light += source.color.rgb * falloff * falloff;
+    }
+
+    // How far the lights push back the darkness, and the color they cast
+    let strength = clamp(max(light.r, max(light.g, light.b)), 0.0, 1.0);
+    let tint = light / max(max(light.r, max(light.g, light.b)), 0.0001);
+    let darkness = 1.0 - clamp(lighting.ambient.a, 0.0, 1.0);
+
+    let color = mix(lighting.ambient.rgb, tint, strength);
+    // A faint glow of the light's color stays where it is brightest
+    let alpha = darkness * (1.0 - strength) + darkness * 0.2 * strength;
+    return vec4<f32>(color, alpha);
+}
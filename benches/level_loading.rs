@@ -0,0 +1,130 @@
+//! Benchmarks for level loading: parsing the level formats, building the
+//! collision map and spawning a level's tiles
+//!
+//! Run with: cargo bench --bench level_loading
+//!
+//! Every benchmark uses the same generated 200x50 level, larger than the
+//! shipped ones, so loader changes show up well above the noise
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use bevy_sidescroller::components::{
+    LevelData, LevelLoaded, TileGrid, TileSpawnQueue, TilesetInfo, TilesetRegistry,
+};
+use bevy_sidescroller::constants::{TILESET_TILE_SIZE, TILES_PER_COLUMN, TILES_PER_ROW};
+use bevy_sidescroller::systems::level_loader::{
+    create_collision_map, parse_csv_level, parse_symbol_level, parse_tiled_level, write_csv_level,
+    write_symbol_level, write_tiled_level, EMPTY_TILE,
+};
+use bevy_sidescroller::systems::spawn_queued_tiles;
+
+const LEVEL_WIDTH: u32 = 200;
+const LEVEL_HEIGHT: u32 = 50;
+
+/// Level in the CSV format with ground, a grass top, platforms, water pits
+/// and coins, so every tile group the loader handles is exercised
+fn large_level_csv() -> String {
+    let mut csv = format!("{},{}\n", LEVEL_WIDTH, LEVEL_HEIGHT);
+    for y in 0..LEVEL_HEIGHT {
+        let row: Vec<String> = (0..LEVEL_WIDTH)
+            .map(|x| {
+                let pit = x % 40 >= 30 && x % 40 < 34;
+                let tile = match y {
+                    _ if y >= 44 && pit => 189,
+                    44 => 180,
+                    45.. => 176,
+                    _ if y % 8 == 4 && x % 12 < 5 => 182,
+                    _ if y % 8 == 3 && x % 12 == 2 => 186,
+                    _ => EMPTY_TILE,
+                };
+                tile.to_string()
+            })
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn parsing(c: &mut Criterion) {
+    let csv = large_level_csv();
+    let level = parse_csv_level("bench.level.csv", &csv).expect("generated level parses");
+    let ron = ron::ser::to_string(&level).expect("generated level serializes");
+    let symbols = write_symbol_level(&level);
+    let tiled = write_tiled_level(&level);
+
+    let mut group = c.benchmark_group("level_file_200x50");
+    group.bench_function("csv", |b| {
+        b.iter(|| parse_csv_level("bench.level.csv", std::hint::black_box(&csv)))
+    });
+    group.bench_function("ron", |b| {
+        b.iter(|| ron::from_str::<LevelData>(std::hint::black_box(&ron)))
+    });
+    group.bench_function("symbol", |b| {
+        b.iter(|| parse_symbol_level("bench.level.txt", std::hint::black_box(&symbols)))
+    });
+    group.bench_function("tiled", |b| {
+        b.iter(|| parse_tiled_level("bench.tmj", std::hint::black_box(&tiled)))
+    });
+    group.bench_function("write_csv", |b| {
+        b.iter(|| write_csv_level(std::hint::black_box(&level)))
+    });
+    group.finish();
+}
+
+fn collision_map(c: &mut Criterion) {
+    c.bench_function("create_collision_map", |b| b.iter(create_collision_map));
+}
+
+/// World with the resources `setup_tilemap()` would insert for `level`,
+/// without the asset server, and a schedule running `spawn_queued_tiles()`
+fn spawn_world(level: &LevelData) -> (World, Schedule) {
+    let mut world = World::new();
+    let tiles = level
+        .tiles
+        .iter()
+        .filter(|&(_, index)| index != EMPTY_TILE)
+        .collect();
+    world.insert_resource(TileSpawnQueue { tiles, ..default() });
+    world.insert_resource(TilesetRegistry {
+        tilesets: vec![TilesetInfo {
+            tile_size: TILESET_TILE_SIZE,
+            tiles_per_row: TILES_PER_ROW,
+            tiles_per_column: TILES_PER_COLUMN,
+            texture_handle: Handle::default(),
+            layout_handle: Handle::default(),
+        }],
+        current_tileset: 0,
+    });
+    world.insert_resource(create_collision_map());
+    world.insert_resource(TileGrid::default());
+    world.insert_resource(level.clone());
+    world.init_resource::<Events<LevelLoaded>>();
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(spawn_queued_tiles);
+    (world, schedule)
+}
+
+fn spawning(c: &mut Criterion) {
+    let level =
+        parse_csv_level("bench.level.csv", &large_level_csv()).expect("generated level parses");
+
+    c.bench_function("spawn_tiles_200x50", |b| {
+        b.iter_batched(
+            || spawn_world(&level),
+            |(mut world, mut schedule)| {
+                // Spawning is spread over frames by `TILE_SPAWN_BUDGET`
+                while world.contains_resource::<TileSpawnQueue>() {
+                    schedule.run(&mut world);
+                }
+                world
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, parsing, collision_map, spawning);
+criterion_main!(benches);
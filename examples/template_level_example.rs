@@ -5,57 +5,217 @@
 //!
 //! Run with: cargo run --example template_level_example
 
-use bevy::prelude::*;
-// Note: This example requires the level systems to be properly exported
-// For now, we'll define minimal types to make the example compile
-use std::collections::HashMap;
+use bevy_sidescroller::components::{TileId, TileLayer};
+use bevy_sidescroller::systems::level_loader::EMPTY_TILE;
 
 fn main() {
     println!("Template Level Example");
-    println!("This example demonstrates level template concepts.");
-    println!("To run this example, first ensure all level systems are properly exported.");
-
-    // Uncomment when level systems are ready:
-    // App::new()
-    //     .add_plugins(DefaultPlugins)
-    //     .add_systems(Startup, (
-    //         setup_camera,
-    //         demonstrate_templates,
-    //     ))
-    //     .run();
-}
+    println!("Levels are built on the library's `TileLayer` by stamping templates onto it.");
 
-// Minimal types for demonstration
-#[derive(Clone)]
-pub struct LevelData {
-    pub width: u32,
-    pub height: u32,
-    pub tiles: Vec<Vec<u32>>,
+    demonstrate_templates();
+    demonstrate_advanced_templates();
+
+    let level = create_procedural_level(40, 20, 2024);
+    println!("\n=== Procedural Level (seed 2024) ===");
+    println!("{}", layer_to_string(&level));
 }
 
+/// A rectangular pattern of tiles, stamped onto a level with
+/// `place_template()`; `EMPTY_TILE` cells leave the level's tile as it is
 #[derive(Debug, Clone)]
 pub struct LevelTemplate {
     pub name: String,
-    pub pattern: Vec<Vec<u32>>,
+    pub pattern: Vec<Vec<TileId>>,
     pub width: u32,
     pub height: u32,
 }
 
-fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
+impl LevelTemplate {
+    fn new(name: &str, pattern: Vec<Vec<TileId>>) -> Self {
+        Self {
+            name: name.to_string(),
+            width: pattern.first().map_or(0, |row| row.len() as u32),
+            height: pattern.len() as u32,
+            pattern,
+        }
+    }
+
+    /// Builds a `width`x`height` template from the tile at each cell
+    fn from_fn(name: &str, width: u32, height: u32, tile: impl Fn(u32, u32) -> TileId) -> Self {
+        let pattern = (0..height)
+            .map(|y| (0..width).map(|x| tile(x, y)).collect())
+            .collect();
+        Self::new(name, pattern)
+    }
+
+    /// Ground: a `top` surface row over a `fill` row
+    pub fn ground_platform(width: u32, top: TileId, fill: TileId) -> Self {
+        Self::new(
+            "Ground Platform",
+            vec![vec![top; width as usize], vec![fill; width as usize]],
+        )
+    }
+
+    /// A single row of platform tiles
+    pub fn floating_platform(width: u32, tile: TileId) -> Self {
+        Self::new("Floating Platform", vec![vec![tile; width as usize]])
+    }
+
+    /// A one tile wide column
+    pub fn pillar(height: u32, tile: TileId) -> Self {
+        Self::new("Pillar", vec![vec![tile]; height as usize])
+    }
+
+    /// Steps rising to the right, filled underneath
+    pub fn staircase(width: u32, height: u32, tile: TileId) -> Self {
+        Self::from_fn("Staircase", width, height, |x, y| {
+            let step_top = height - 1 - (x * height / width.max(1)).min(height - 1);
+            if y >= step_top {
+                tile
+            } else {
+                EMPTY_TILE
+            }
+        })
+    }
+
+    /// Hollow box of `wall` tiles with a `floor` row at the bottom
+    pub fn room(width: u32, height: u32, wall: TileId, floor: TileId) -> Self {
+        Self::from_fn("Room", width, height, |x, y| {
+            if y == height - 1 {
+                floor
+            } else if y == 0 || x == 0 || x == width - 1 {
+                wall
+            } else {
+                EMPTY_TILE
+            }
+        })
+    }
+
+    /// A `deck` row held up by `support` columns at both ends
+    pub fn bridge(width: u32, deck: TileId, support: TileId) -> Self {
+        Self::from_fn("Bridge", width, 3, |x, y| {
+            if y == 0 {
+                deck
+            } else if x == 0 || x == width - 1 {
+                support
+            } else {
+                EMPTY_TILE
+            }
+        })
+    }
+
+    /// Solid column of `wall` tiles with a crenellated `top`
+    pub fn tower(width: u32, height: u32, wall: TileId, top: TileId) -> Self {
+        Self::from_fn("Tower", width, height, |x, y| match y {
+            0 if x % 2 == 1 => EMPTY_TILE,
+            0 => top,
+            _ => wall,
+        })
+    }
+
+    /// A gap `depth` tiles deep, walled on both sides
+    pub fn pit(width: u32, depth: u32, wall: TileId) -> Self {
+        Self::from_fn("Pit", width, depth, |x, _| {
+            if x == 0 || x == width - 1 {
+                wall
+            } else {
+                EMPTY_TILE
+            }
+        })
+    }
+
+    /// `grass` ground with flowers and trees above it
+    pub fn garden(width: u32, height: u32, grass: TileId, flower: TileId, tree: TileId) -> Self {
+        Self::from_fn("Garden", width, height, |x, y| {
+            if y == height - 1 {
+                grass
+            } else if y == height - 2 {
+                if x % 3 == 1 {
+                    tree
+                } else {
+                    flower
+                }
+            } else {
+                EMPTY_TILE
+            }
+        })
+    }
+
+    /// Rock with an opening at the bottom of its left side
+    pub fn cave_entrance(width: u32, height: u32, rock: TileId) -> Self {
+        let opening = height.div_ceil(2);
+        Self::from_fn("Cave Entrance", width, height, |x, y| {
+            if y >= height - opening && y < height - 1 && x < width / 2 {
+                EMPTY_TILE
+            } else {
+                rock
+            }
+        })
+    }
+}
+
+/// Stamps `template` onto the level with its top-left corner at `x`, `y`
+/// Returns false, leaving the level as it was, when it doesn't fit
+fn place_template(level_data: &mut TileLayer, template: &LevelTemplate, x: u32, y: u32) -> bool {
+    if x + template.width > level_data.width() || y + template.height > level_data.height() {
+        return false;
+    }
+    for (row_y, row) in template.pattern.iter().enumerate() {
+        for (row_x, &tile) in row.iter().enumerate() {
+            if tile != EMPTY_TILE {
+                level_data.set(x + row_x as u32, y + row_y as u32, tile);
+            }
+        }
+    }
+    true
+}
+
+/// Top-left corners where `template` fits over empty tiles only
+fn get_valid_positions(level_data: &TileLayer, template: &LevelTemplate) -> Vec<(u32, u32)> {
+    let fits = |x: u32, y: u32| {
+        (0..template.height).all(|row_y| {
+            (0..template.width)
+                .all(|row_x| level_data.get(x + row_x, y + row_y) == Some(EMPTY_TILE))
+        })
+    };
+    let max_x = level_data.width().saturating_sub(template.width);
+    let max_y = level_data.height().saturating_sub(template.height);
+    (0..=max_y)
+        .flat_map(|y| (0..=max_x).map(move |x| (x, y)))
+        .filter(|&(x, y)| fits(x, y))
+        .collect()
+}
+
+/// One character per tile: `.` for empty, `#` for anything else
+fn rows_to_string<'a>(rows: impl Iterator<Item = &'a [TileId]>) -> String {
+    rows.map(|row| {
+        row.iter()
+            .map(|&tile| if tile == EMPTY_TILE { '.' } else { '#' })
+            .collect::<String>()
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn template_to_string(template: &LevelTemplate) -> String {
+    format!(
+        "{}:\n{}",
+        template.name,
+        rows_to_string(template.pattern.iter().map(Vec::as_slice))
+    )
 }
 
-fn demonstrate_templates(mut commands: Commands) {
+fn layer_to_string(level_data: &TileLayer) -> String {
+    rows_to_string(level_data.rows())
+}
+
+fn demonstrate_templates() {
     println!("=== Level Template System Demo ===");
 
     // Create a new level
-    let mut level_data = LevelData {
-        width: 60,
-        height: 25,
-        tiles: vec![vec![255; 60]; 25], // Start with empty level
-    };
+    let mut level_data = TileLayer::filled(60, 25, EMPTY_TILE);
 
-    println!("Created empty level: {}x{}", level_data.width, level_data.height);
+    println!("Created empty level: {}x{}", level_data.width(), level_data.height());
 
     // === BASIC TEMPLATES ===
 
@@ -157,7 +317,7 @@ fn demonstrate_templates(mut commands: Commands) {
 }
 
 /// Creates a castle-like structure using multiple templates
-fn create_castle_structure(level_data: &mut LevelData, start_x: u32, start_y: u32) {
+fn create_castle_structure(level_data: &mut TileLayer, start_x: u32, start_y: u32) {
     // Base platform
     let base = LevelTemplate::ground_platform(15, 180, 176);
     place_template(level_data, &base, start_x, start_y + 12);
@@ -182,7 +342,7 @@ fn create_castle_structure(level_data: &mut LevelData, start_x: u32, start_y: u3
 }
 
 /// Creates a parkour course using various templates
-fn create_parkour_course(level_data: &mut LevelData, start_x: u32, start_y: u32) {
+fn create_parkour_course(level_data: &mut TileLayer, start_x: u32, start_y: u32) {
     // Series of platforms at different heights
     let platforms = vec![
         (start_x, start_y + 5),
@@ -208,21 +368,21 @@ fn create_parkour_course(level_data: &mut LevelData, start_x: u32, start_y: u32)
 }
 
 /// Saves the level using different formats for comparison
-fn save_template_level_examples(level_data: &LevelData) {
-    // Note: Saving functions would be implemented here
-    // when the level systems are properly exported
-    println!("✓ Would save as CSV: assets/levels/template_demo.csv");
-    println!("✓ Would save as symbols: assets/levels/template_demo.txt");
-    println!("Level data: {}x{} tiles", level_data.width, level_data.height);
+fn save_template_level_examples(level_data: &TileLayer) {
+    // The example doesn't write files; `level_loader::save_level_to_file`
+    // saves a whole `LevelData`
+    println!("✓ Would save as CSV: assets/levels/template_demo.level.csv");
+    println!("✓ Would save as symbols: assets/levels/template_demo.level.txt");
+    println!("Level data: {}x{} tiles", level_data.width(), level_data.height());
 }
 
 /// Validates template placements and shows statistics
-fn validate_template_examples(level_data: &LevelData) {
+fn validate_template_examples(level_data: &TileLayer) {
     println!("\n=== Level Statistics ===");
 
     // Count tile types
     let mut tile_counts = std::collections::HashMap::new();
-    for row in &level_data.tiles {
+    for row in level_data.rows() {
         for &tile in row {
             *tile_counts.entry(tile).or_insert(0) += 1;
         }
@@ -231,7 +391,7 @@ fn validate_template_examples(level_data: &LevelData) {
     println!("Tile distribution:");
     for (tile, count) in tile_counts {
         let tile_name = match tile {
-            255 => "Empty",
+            EMPTY_TILE => "Empty",
             180 => "Grass",
             176 => "Stone",
             181 => "Platform",
@@ -239,7 +399,7 @@ fn validate_template_examples(level_data: &LevelData) {
             185 => "Tree",
             _ => "Other",
         };
-        let percentage = (count as f32 / (level_data.width * level_data.height) as f32) * 100.0;
+        let percentage = (count as f32 / (level_data.width() * level_data.height()) as f32) * 100.0;
         println!("  {}: {} tiles ({:.1}%)", tile_name, count, percentage);
     }
 
@@ -291,15 +451,11 @@ fn demonstrate_advanced_templates() {
 }
 
 /// Example of procedural level generation with templates
-fn create_procedural_level(width: u32, height: u32, seed: u64) -> LevelData {
+fn create_procedural_level(width: u32, height: u32, seed: u64) -> TileLayer {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
-    let mut level_data = LevelData {
-        width,
-        height,
-        tiles: vec![vec![255; width as usize]; height as usize],
-    };
+    let mut level_data = TileLayer::filled(width, height, EMPTY_TILE);
 
     // Simple pseudo-random based on seed
     let mut hasher = DefaultHasher::new();
@@ -366,11 +522,7 @@ mod tests {
 
     #[test]
     fn test_template_placement() {
-        let mut level_data = LevelData {
-            width: 10,
-            height: 10,
-            tiles: vec![vec![255; 10]; 10],
-        };
+        let mut level_data = TileLayer::filled(10, 10, EMPTY_TILE);
 
         let template = LevelTemplate::floating_platform(3, 181);
         assert!(place_template(&mut level_data, &template, 0, 0));
@@ -385,9 +537,9 @@ mod tests {
         let level3 = create_procedural_level(20, 15, 54321);
 
         // Same seed should produce same result
-        assert_eq!(level1.tiles, level2.tiles);
+        assert_eq!(level1, level2);
 
         // Different seed should produce different result
-        assert_ne!(level1.tiles, level3.tiles);
+        assert_ne!(level1, level3);
     }
 }
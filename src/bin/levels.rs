@@ -0,0 +1,185 @@
+//! Level file tool: converts, validates, summarizes and previews levels
+//! without launching the game
+//!
+//! Run with: cargo run --bin levels -- <command>
+//!
+//! Uses the game's own loader, so a level that passes `validate` loads in
+//! the game. Files ending in `.level.ron` are read and written as
+//! `LevelData`, files ending in `.level.csv` as the CSV format, `.level.txt`
+//! as the symbol format and `.tmj` or `.level.json` as Tiled maps
+
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+use bevy_sidescroller::components::{LevelData, LevelError, LevelErrorKind};
+use bevy_sidescroller::constants::{ASSET_DIRECTORY, LEVEL_REGISTRY_PATH};
+use bevy_sidescroller::systems::level_loader::{
+    load_level_from_file, load_level_registry, save_level_to_file, tile_group_name, LevelFormat,
+};
+
+const USAGE: &str = "\
+Usage: levels <command>
+
+Commands:
+  convert <input> <output>  Converts a level between the RON, CSV, symbol (.level.txt) and
+                            Tiled (.tmj, .level.json) formats
+  validate [level...]       Checks that levels load (default: every level in the registry)
+  stats <level>             Prints the size, tile counts and placed objects
+  preview <level>           Prints the tile grid as ASCII";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args[..] {
+        ["convert", input, output] => convert(input, output),
+        ["validate", ref levels @ ..] => validate(levels),
+        ["stats", level] => stats(level),
+        ["preview", level] => preview(level),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads `input` and writes it to `output`, each in the format given by its
+/// extension
+fn convert(input: &str, output: &str) -> Result<(), String> {
+    // Checked first, so an unknown output fails before any warning
+    let format = LevelFormat::from_path(output)
+        .ok_or_else(|| LevelError::new(output, LevelErrorKind::UnknownFormat).to_string())?;
+    let level = load_level_from_file(input).map_err(|e| e.to_string())?;
+    if !format.holds_objects() && object_counts(&level).iter().any(|(_, count)| *count > 0) {
+        eprintln!(
+            "warning: the {} format only holds the tile grid, '{}' loses the placed objects",
+            format.name(),
+            output
+        );
+    }
+    save_level_to_file(output, &level).map_err(|e| e.to_string())?;
+    println!("Converted '{}' to '{}'", input, output);
+    Ok(())
+}
+
+/// Loads each level, reporting every failure rather than stopping at the
+/// first
+fn validate(levels: &[&str]) -> Result<(), String> {
+    let levels: Vec<String> = if levels.is_empty() {
+        // The registry holds asset paths
+        let registry = load_level_registry(LEVEL_REGISTRY_PATH).map_err(|e| e.to_string())?;
+        registry
+            .levels
+            .iter()
+            .map(|path| format!("{}/{}", ASSET_DIRECTORY, path))
+            .collect()
+    } else {
+        levels.iter().map(ToString::to_string).collect()
+    };
+
+    let mut failures = 0;
+    for path in &levels {
+        match load_level_from_file(path) {
+            Ok(level) => println!("ok    {} ({}x{})", path, level.width, level.height),
+            Err(e) => {
+                println!("error {}", e);
+                failures += 1;
+            }
+        }
+    }
+    match failures {
+        0 => Ok(()),
+        _ => Err(format!("{} of {} levels failed", failures, levels.len())),
+    }
+}
+
+/// Prints the level's size, how many tiles of each group it uses and how
+/// many of each object it places
+fn stats(path: &str) -> Result<(), String> {
+    let level = load_level_from_file(path).map_err(|e| e.to_string())?;
+    println!("{}: {}x{} tiles", path, level.width, level.height);
+
+    let mut groups: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, tile) in level.tiles.iter() {
+        *groups
+            .entry(tile_group_name(tile).unwrap_or("Unknown"))
+            .or_default() += 1;
+    }
+    println!("Tiles:");
+    for (group, count) in groups {
+        println!("  {:<16}{}", group, count);
+    }
+
+    println!("Objects:");
+    for (name, count) in object_counts(&level) {
+        if count > 0 {
+            println!("  {:<16}{}", name, count);
+        }
+    }
+    Ok(())
+}
+
+/// Prints one character per tile: `#` terrain, `=` platforms, `~` water,
+/// `o` coins, `*` gems, `.` empty and `?` tiles outside the known groups
+fn preview(path: &str) -> Result<(), String> {
+    let level = load_level_from_file(path).map_err(|e| e.to_string())?;
+    for row in level.tiles.rows() {
+        let line: String = row.iter().map(|&tile| tile_symbol(tile)).collect();
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn tile_symbol(tile: u32) -> char {
+    match tile_group_name(tile) {
+        Some("Wood platform" | "Stone platform" | "Metal platform") => '=',
+        Some("Water") => '~',
+        Some("Coin") => 'o',
+        Some("Gem") => '*',
+        Some("Empty") => '.',
+        Some(_) => '#',
+        None => '?',
+    }
+}
+
+/// Number of each kind of object placed off the tile grid
+fn object_counts(level: &LevelData) -> Vec<(&'static str, usize)> {
+    vec![
+        ("Camera zones", level.camera_zones.len()),
+        ("Camera path", level.intro_camera_path.len()),
+        ("Platforms", level.moving_platforms.len()),
+        ("Props", level.props.len()),
+        ("Switches", level.switches.len()),
+        ("Elevators", level.elevators.len()),
+        ("Gates", level.gates.len()),
+        ("Ropes", level.ropes.len()),
+        ("Collectibles", level.collectibles.len()),
+        ("Keys", level.keys.len()),
+        ("Doors", level.doors.len()),
+        ("Dialogues", level.dialogue_triggers.len()),
+        ("Hints", level.hints.len()),
+        ("Checkpoints", level.checkpoints.len()),
+        ("Goal", level.goal.iter().count()),
+        ("Secrets", level.secrets.len()),
+        ("Blocks", level.blocks.len()),
+        ("Objectives", level.objectives.len()),
+        ("Chests", level.chests.len()),
+        ("Hazards", level.falling_hazards.len()),
+        ("Moving hazards", level.moving_hazards.len()),
+        ("Rising liquid", level.rising_liquid.iter().count()),
+        ("Respawn zones", level.respawn_zones.len()),
+        ("Currents", level.water_currents.len()),
+        ("Boss arenas", level.boss_arenas.len()),
+        ("Sound emitters", level.sound_emitters.len()),
+        (
+            "Lights",
+            level
+                .lighting
+                .as_ref()
+                .map_or(0, |lighting| lighting.lights.len()),
+        ),
+    ]
+}
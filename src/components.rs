@@ -1,22 +1,35 @@
 //! Game components for the sidescroller game
 
+use bevy::log::tracing_subscriber::{reload, EnvFilter, Registry};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::{SocketAddr, UdpSocket};
 use std::time::Duration;
 
 /// Player velocity component wrapping a Vec2
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct PlayerVelocity(pub Vec2);
 
 /// Animation states for the player character
-#[derive(Component, PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[derive(
+    Component, PartialEq, Eq, Clone, Copy, Default, Debug, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
 pub enum AnimationState {
     #[default]
     Idle,
     Run,
+    /// Rising from an air jump
+    DoubleJump,
+    /// Plays once and holds the last frame
+    Dead,
 }
 
 /// Tracks which direction the character is facing for sprite flipping
-#[derive(Component, PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Component, Debug, PartialEq, Eq, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
 pub enum FacingDirection {
     #[default]
     Right,
@@ -24,7 +37,8 @@ pub enum FacingDirection {
 }
 
 /// Configuration for a single animation sequence
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component)]
 pub struct AnimationConfig {
     pub first_sprite_index: usize,
     pub last_sprite_index: usize,
@@ -44,31 +58,57 @@ impl AnimationConfig {
     }
 }
 
+/// Marker for characters and hazards far outside the camera view; their
+/// animation and AI systems skip them until the camera comes close again
+/// Only entities carrying `SleepsOffScreen` are ever made dormant, and the
+/// systems driving them opt in by filtering their queries with
+/// `Without<Dormant>`
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Dormant;
+
+/// Opts an entity into dormancy: `update_dormancy()` marks it `Dormant` while
+/// it's outside the camera view (plus `ACTIVATION_MARGIN`) and wakes it as the
+/// camera approaches. Add it where the entity is spawned, alongside its AI or
+/// animation component; players never sleep, even with it
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct SleepsOffScreen;
+
 /// Collection of all animation configurations for a character
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct AnimationCollection {
     pub idle: AnimationConfig,
     pub run: AnimationConfig,
+    pub double_jump: AnimationConfig,
+    pub dead: AnimationConfig,
 }
 
 /// Handles for texture and layout assets used in animations
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct AnimationHandles {
     pub idle_texture: Handle<Image>,
     pub idle_layout: Handle<TextureAtlasLayout>,
     pub run_texture: Handle<Image>,
     pub run_layout: Handle<TextureAtlasLayout>,
+    pub double_jump_texture: Handle<Image>,
+    pub double_jump_layout: Handle<TextureAtlasLayout>,
+    pub dead_texture: Handle<Image>,
+    pub dead_layout: Handle<TextureAtlasLayout>,
 }
 
 /// Component for individual tiles in the game world
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Tile {
     pub tile_type: TileType,
     pub solid: bool,
 }
 
 /// Different types of tiles available
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
 pub enum TileType {
     Ground,
     Platform,
@@ -76,39 +116,224 @@ pub enum TileType {
 }
 
 /// Component for parallax scrolling background layers
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct ParallaxLayer {
     pub speed_multiplier: f32,
+    pub vertical_speed_multiplier: f32,
+    pub y_offset: f32,
     pub repeat_width: f32,
+    pub repeat_height: f32,
     pub layer_depth: f32,
+    pub repeat: bool,
+    pub repeat_vertical: bool,
+    pub auto_scroll_speed: f32,
+    pub scroll_offset: f32,
+    /// Base tint from the config, combined with the time-of-day palette
+    pub tint: Color,
+}
+
+/// Data-driven description of the parallax backgrounds, loaded from RON
+#[derive(Clone, Debug, Resource, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct ParallaxConfig {
+    pub layers: Vec<ParallaxLayerConfig>,
+}
+
+/// Configuration for a single parallax layer
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct ParallaxLayerConfig {
+    pub texture: String,
+    pub speed_x: f32,
+    #[serde(default)]
+    pub speed_y: f32,
+    #[serde(default)]
+    pub y_offset: f32,
+    pub depth: f32,
+    #[serde(default = "default_layer_tint")]
+    pub tint: [f32; 4],
+    #[serde(default = "default_layer_repeat")]
+    pub repeat: bool,
+    /// Stack copies vertically too, for tall levels
+    #[serde(default)]
+    pub repeat_y: bool,
+    /// Constant drift in pixels per second (clouds, fog)
+    #[serde(default)]
+    pub auto_scroll: f32,
+}
+
+fn default_layer_tint() -> [f32; 4] {
+    [1.0, 1.0, 1.0, 1.0]
+}
+
+fn default_layer_repeat() -> bool {
+    true
 }
 
 /// Component to track which background instance this is (for infinite scrolling)
-#[derive(Component)]
+/// `index` is the horizontal copy, `row` the vertical one
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct BackgroundIndex {
     pub index: i32,
+    pub row: i32,
 }
 
 /// Marker component for the main camera to track for parallax
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct MainCamera;
 
 /// Component for managing tile maps
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct TileMap {
     pub width: u32,
     pub height: u32,
     pub tile_size: f32,
 }
 
+/// Smoothed state of the follow camera
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct CameraFollow {
+    /// Horizontal lookahead in the direction of travel
+    pub lookahead_offset: f32,
+    /// Player height the camera is framing vertically (last ground height)
+    pub anchor_y: Option<f32>,
+}
+
+/// Camera shake from hits and crashes, worn off over time by `shake_camera`
+/// Skipped entirely when `Settings::screen_shake` is off
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct CameraShake {
+    /// Shake strength from 0.0 to 1.0; the offset grows with its square
+    pub trauma: f32,
+    /// Offset added to the camera this frame, taken off again before the
+    /// camera moves next frame
+    pub offset: Vec2,
+}
+
+/// Scripted camera movement that takes over from the follow camera
+/// Removed automatically once the last waypoint is reached, handing control
+/// back to `update_camera_follow` which eases back to the player
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct CameraPath {
+    pub waypoints: Vec<CameraWaypoint>,
+    pub current: usize,
+    pub elapsed: f32,
+    /// Camera position at the start of the current segment
+    pub segment_start: Option<Vec2>,
+}
+
+impl CameraPath {
+    pub fn new(waypoints: Vec<CameraWaypoint>) -> Self {
+        Self {
+            waypoints,
+            current: 0,
+            elapsed: 0.0,
+            segment_start: None,
+        }
+    }
+}
+
+/// A single stop on a camera path, reached after `duration` seconds
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CameraWaypoint {
+    pub position: Vec2,
+    pub duration: f32,
+    #[serde(default)]
+    pub easing: CameraEasing,
+}
+
+/// Easing curve applied to a camera path segment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum CameraEasing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl CameraEasing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            CameraEasing::Linear => t,
+            CameraEasing::EaseIn => t * t,
+            CameraEasing::EaseOut => t * (2.0 - t),
+            CameraEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 /// Component for tracking camera position for parallax calculations
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct CameraTracker {
     pub last_position: Vec3,
 }
 
+/// Parent of the tiles in a square of the level grid, hidden as a whole
+/// while `rect` (its world bounds) is out of view
+/// Chunks sit at the origin, so tile transforms stay in world space
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TileChunk {
+    pub rect: Rect,
+}
+
+/// Tiles of the current level still to be spawned, a budget per frame
+/// Gameplay is paused while it exists
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct TileSpawnQueue {
+    pub tiles: Vec<(UVec2, TileId)>,
+    pub next: usize,
+    /// Chunks spawned so far, by chunk coordinates
+    pub chunks: std::collections::HashMap<UVec2, Entity>,
+}
+
+/// Sent once every tile of a newly loaded level is spawned and gameplay
+/// can start
+#[derive(Event, Clone, Debug)]
+pub struct LevelLoaded {
+    pub path: String,
+}
+
+/// Sent when a tile is placed after the level has loaded, so
+/// `update_tile_collisions()` gives it a collider
+#[derive(Event)]
+pub struct TilePlaced {
+    pub coords: UVec2,
+}
+
+/// Sent when a tile of the current level is despawned
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TileRemoved {
+    pub coords: UVec2,
+}
+
+/// Marker for the loading progress text shown while tiles spawn
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LevelLoadingText;
+
+/// Tile entities by level grid coordinates, for lookups without scanning
+/// every tile
+/// Rebuilt with the tilemap; despawned tiles drop out of it
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct TileGrid {
+    pub tiles: std::collections::HashMap<UVec2, Entity>,
+}
+
 /// Component for individual tiles with tileset index
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct TileIndex {
     pub index: u32,
     pub tileset_x: u32,
@@ -116,7 +341,8 @@ pub struct TileIndex {
 }
 
 /// Component for tileset information
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct TilesetInfo {
     pub tile_size: u32,
     pub tiles_per_row: u32,
@@ -125,31 +351,2688 @@ pub struct TilesetInfo {
     pub layout_handle: Handle<TextureAtlasLayout>,
 }
 
+/// Index of a tile in the tileset atlas
+pub type TileId = u32;
+
+/// Grid of tile indices stored row-major in one flat vector, row 0 at the top
+/// Serialized as a list of rows, which must all have the same length
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(try_from = "Vec<Vec<TileId>>", into = "Vec<Vec<TileId>>")]
+pub struct TileLayer {
+    width: u32,
+    height: u32,
+    tiles: Vec<TileId>,
+}
+
+impl TileLayer {
+    /// Layer of `width` × `height` tiles, all set to `tile`
+    pub fn filled(width: u32, height: u32, tile: TileId) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![tile; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn offset(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    /// Tile at grid coordinates, `None` outside the layer
+    pub fn get(&self, x: u32, y: u32) -> Option<TileId> {
+        self.offset(x, y).map(|offset| self.tiles[offset])
+    }
+
+    /// Replaces the tile at grid coordinates; returns false outside the layer
+    pub fn set(&mut self, x: u32, y: u32, tile: TileId) -> bool {
+        let Some(offset) = self.offset(x, y) else {
+            return false;
+        };
+        self.tiles[offset] = tile;
+        true
+    }
+
+    /// Every tile with its grid coordinates, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (UVec2, TileId)> + '_ {
+        let width = self.width.max(1);
+        self.tiles.iter().enumerate().map(move |(offset, &tile)| {
+            let offset = offset as u32;
+            (UVec2::new(offset % width, offset / width), tile)
+        })
+    }
+
+    /// The rows, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[TileId]> {
+        self.tiles.chunks(self.width.max(1) as usize)
+    }
+}
+
+impl TryFrom<Vec<Vec<TileId>>> for TileLayer {
+    type Error = String;
+
+    fn try_from(rows: Vec<Vec<TileId>>) -> Result<Self, Self::Error> {
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().position(|row| row.len() != width) {
+            return Err(format!(
+                "Row {} has {} tiles, expected {}",
+                row,
+                rows[row].len(),
+                width
+            ));
+        }
+        Ok(Self {
+            width: width as u32,
+            height: rows.len() as u32,
+            tiles: rows.into_iter().flatten().collect(),
+        })
+    }
+}
+
+impl From<TileLayer> for Vec<Vec<TileId>> {
+    fn from(layer: TileLayer) -> Self {
+        layer.rows().map(<[TileId]>::to_vec).collect()
+    }
+}
+
 /// Level data structure for loading from files
-#[derive(Clone, Resource)]
+#[derive(Clone, Default, Resource, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
 pub struct LevelData {
     pub width: u32,
     pub height: u32,
-    pub tiles: Vec<Vec<u32>>, // 2D array of tile indices
+    pub tiles: TileLayer,
+    #[serde(default)]
+    pub camera_zones: Vec<CameraZone>,
+    /// Overrides the global parallax configuration for this level
+    #[serde(default)]
+    pub parallax: Option<ParallaxConfig>,
+    /// Camera fly-through played when the level starts
+    #[serde(default)]
+    pub intro_camera_path: Vec<CameraWaypoint>,
+    #[serde(default)]
+    pub moving_platforms: Vec<MovingPlatformDef>,
+    #[serde(default)]
+    pub props: Vec<PropDef>,
+    #[serde(default)]
+    pub switches: Vec<SwitchDef>,
+    #[serde(default)]
+    pub elevators: Vec<ElevatorDef>,
+    #[serde(default)]
+    pub gates: Vec<GateDef>,
+    #[serde(default)]
+    pub ropes: Vec<RopeDef>,
+    #[serde(default)]
+    pub collectibles: Vec<CollectibleDef>,
+    #[serde(default)]
+    pub keys: Vec<KeyDef>,
+    #[serde(default)]
+    pub doors: Vec<DoorDef>,
+    #[serde(default)]
+    pub medals: Option<MedalTimes>,
+    #[serde(default)]
+    pub dialogue_triggers: Vec<DialogueTriggerDef>,
+    #[serde(default)]
+    pub hints: Vec<HintDef>,
+    #[serde(default)]
+    pub checkpoints: Vec<CheckpointDef>,
+    #[serde(default)]
+    pub goal: Option<GoalDef>,
+    #[serde(default)]
+    pub secrets: Vec<SecretDef>,
+    #[serde(default)]
+    pub blocks: Vec<BlockDef>,
+    #[serde(default)]
+    pub objectives: Vec<ObjectiveDef>,
+    /// Cutscene (file in `assets/cutscenes/`) played when the level starts
+    #[serde(default)]
+    pub intro_cutscene: Option<String>,
+    /// Cutscene played after reaching the goal, before the results
+    #[serde(default)]
+    pub outro_cutscene: Option<String>,
+    #[serde(default)]
+    pub chests: Vec<ChestDef>,
+    #[serde(default)]
+    pub falling_hazards: Vec<FallingHazardDef>,
+    #[serde(default)]
+    pub moving_hazards: Vec<MovingHazardDef>,
+    /// Lava or water rising through the level (escape levels)
+    #[serde(default)]
+    pub rising_liquid: Option<RisingLiquidDef>,
+    /// Pits and other areas that put the player back at a safe point
+    #[serde(default)]
+    pub respawn_zones: Vec<RespawnZoneDef>,
+    #[serde(default)]
+    pub water_currents: Vec<WaterCurrentDef>,
+    #[serde(default)]
+    pub weather: Option<WeatherDef>,
+    /// Darkness and light sources; levels without it are fully lit
+    #[serde(default)]
+    pub lighting: Option<LightingDef>,
+    #[serde(default)]
+    pub boss_arenas: Vec<BossArenaDef>,
+    #[serde(default)]
+    pub music: Option<MusicDef>,
+    #[serde(default)]
+    pub sound_emitters: Vec<SoundEmitterDef>,
+    /// Ambient loop (sound registry name) played across the level; camera
+    /// zones can override it
+    #[serde(default)]
+    pub ambient: Option<String>,
+    /// Enemies, pickups and props placed from the prefab registry
+    #[serde(default)]
+    pub entities: Vec<EntityDef>,
 }
 
-/// Component for the loaded level
-#[derive(Component)]
-pub struct Level {
-    pub data: LevelData,
-    pub tile_size: f32,
+/// Timed list of actions loaded from `assets/cutscenes/`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CutsceneScript {
+    pub actions: Vec<CutsceneAction>,
 }
 
-/// Resource for managing all tilesets
-#[derive(Resource)]
-pub struct TilesetRegistry {
-    pub tilesets: Vec<TilesetInfo>,
-    pub current_tileset: usize,
+/// Step of a cutscene; actors are entities looked up by `Name`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub enum CutsceneAction {
+    Wait(f32),
+    /// Pans the camera and holds it there until the cutscene ends
+    MoveCamera {
+        to: Vec2,
+        duration: f32,
+    },
+    MoveActor {
+        actor: String,
+        to: Vec2,
+        duration: f32,
+    },
+    PlayAnimation {
+        actor: String,
+        animation: AnimationState,
+    },
+    /// Opens a dialogue (file in `assets/dialogue/`); the cutscene resumes
+    /// once it's closed
+    ShowDialogue(String),
 }
 
-/// Resource for tile collision properties based on index
+/// Cutscene being played; player control is suppressed while it exists
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ActiveCutscene {
+    pub script: CutsceneScript,
+    pub action: usize,
+    pub elapsed: f32,
+    /// Actor position when the current `MoveActor` started
+    pub actor_start: Option<Vec2>,
+    /// Show the results screen when the cutscene ends (level outros)
+    pub show_results: bool,
+}
+
+/// Level-data definition of an objective
+/// `required` objectives must be complete before the goal ends the level
+/// (time limits can't be required); `bonus` points are awarded at the end
+/// of the level for each completed objective
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct ObjectiveDef {
+    pub kind: ObjectiveKind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub bonus: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum ObjectiveKind {
+    CollectCoins(u32),
+    CollectGems(u32),
+    /// Reach the goal within this many seconds
+    ReachGoalWithin(f32),
+    DefeatBoss,
+}
+
+/// Progress of one of the current level's objectives
+#[derive(Clone, Debug, Reflect)]
+pub struct Objective {
+    pub def: ObjectiveDef,
+    pub progress: u32,
+    pub complete: bool,
+}
+
+/// Objectives of the current level, rebuilt whenever a level is spawned
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Objectives {
+    pub entries: Vec<Objective>,
+}
+
+/// Sent by the boss fight when the boss is defeated
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BossDefeated;
+
+/// Sent when the player walks into a boss arena and it locks
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BossFightStarted {
+    pub arena: Entity,
+}
+
+/// Level-data definition of a boss arena: the tiles between `min` and `max`
+/// (grid coordinates, inclusive). Walking in walls off its left and right
+/// edges, locks the camera on it and starts `music` (file in `assets/audio/`)
+/// until the boss is defeated
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct BossArenaDef {
+    pub min: UVec2,
+    pub max: UVec2,
+    #[serde(default)]
+    pub music: Option<String>,
+    #[serde(default = "default_zone_zoom")]
+    pub zoom: f32,
+}
+
+/// Boss arena; `area` is its world-space rectangle
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BossArena {
+    pub area: Rect,
+    pub music: Option<String>,
+    pub zoom: f32,
+    pub state: BossArenaState,
+}
+
+/// Progress of a boss arena
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum BossArenaState {
+    /// Waiting for the player to enter
+    Idle,
+    /// Fight in progress: barriers up, camera locked
+    Locked,
+    /// Boss defeated; the arena stays open
+    Cleared,
+}
+
+/// Temporary wall closing a locked boss arena
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ArenaBarrier;
+
+/// Debug console (toggled with `); `log` holds the commands run and their
+/// output
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct DebugConsole {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+}
+
+/// Debug request to move the player to `position`, stopping it
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TeleportPlayer {
+    pub position: Vec2,
+}
+
+/// Debug request to save the world to, or restore it from, the snapshot file
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotRequest {
+    Save,
+    Load,
+}
+
+/// Present while the debug free-fly camera is on; the follow camera and
+/// player control are suspended until it is removed
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct FreeCamera;
+
+/// Which debug views are on, toggled from the debug window or their hotkeys
+#[derive(Resource, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct DebugSettings {
+    /// The debug window itself (Shift+F1)
+    pub window: bool,
+    pub player_panel: bool,
+    pub fps: bool,
+    pub physics_render: bool,
+    pub controller_contacts: bool,
+    pub tile_info: bool,
+    pub tile_grid: bool,
+    pub tile_collisions: bool,
+    pub tileset_info: bool,
+    pub physics_tuning: bool,
+}
+
+/// Log verbosity of the game's modules, applied to the tracing filter at
+/// runtime by `apply_log_settings()` and edited from the debug window
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct LogSettings {
+    /// Level of the game's modules without an override
+    pub level: bevy::log::Level,
+    /// Per-module levels, by module name under `systems` (e.g. "tiles")
+    pub modules: Vec<(String, bevy::log::Level)>,
+    /// Logs the `EDITOR_LOG_MODULES` at trace level regardless of `modules`
+    pub editor_verbose: bool,
+}
+
+/// Reload handle of the log filter layer installed by `log_filter_layer()`
 #[derive(Resource)]
-pub struct TileCollisionMap {
-    pub solid_tiles: std::collections::HashSet<u32>,
-    pub platform_tiles: std::collections::HashSet<u32>,
+pub struct LogFilterHandle(pub reload::Handle<EnvFilter, Registry>);
+
+/// Debug time controls: `scale` slows the game down, and while `frozen` the
+/// simulation only advances `step_frames` at a time
+#[derive(Resource, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct DebugTime {
+    pub scale: f32,
+    pub frozen: bool,
+    pub step_frames: u8,
+}
+
+/// Volume bus of a sound; the settings give each bus a volume (on top of
+/// the master volume) applied to every sound playing on it
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Ambient,
+}
+
+/// A sound's own linear volume, before its bus; systems fade sounds by
+/// changing it rather than touching the sink
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct AudioGain(pub f32);
+
+/// Sent when an animation advances to a new frame; `frame` counts from the
+/// first frame of the animation
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AnimationFrameEvent {
+    pub entity: Entity,
+    pub state: AnimationState,
+    pub frame: usize,
+}
+
+/// Request to play a sound of the registry by name; with a `position` it is
+/// spatial (panned and attenuated relative to the camera)
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SoundEvent {
+    pub name: &'static str,
+    pub position: Option<Vec2>,
+}
+
+/// Volume categories of sound effects
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+pub enum SoundCategory {
+    Player,
+    Pickup,
+    Damage,
+    World,
+}
+
+/// Sound of the registry: `file` is relative to `assets/`
+/// `variants` are alternative samples, one picked at random each time
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct SoundDef {
+    pub file: String,
+    #[serde(default)]
+    pub variants: Vec<String>,
+    pub category: SoundCategory,
+    #[serde(default = "default_sound_volume")]
+    pub volume: f32,
+}
+
+fn default_sound_volume() -> f32 {
+    1.0
+}
+
+/// Contents of `assets/config/sounds.ron`: sounds by name and the volume of
+/// each category
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+pub struct SoundRegistryConfig {
+    #[serde(default)]
+    pub categories: std::collections::HashMap<SoundCategory, f32>,
+    pub sounds: std::collections::HashMap<String, SoundDef>,
+}
+
+/// Loaded sound registry; only samples whose file is shipped have a handle
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SoundRegistry {
+    pub config: SoundRegistryConfig,
+    pub handles: std::collections::HashMap<String, Vec<Handle<AudioSource>>>,
+}
+
+/// How a looping sound fades with the distance to the listener: full volume
+/// up to `min_distance`, silent from `max_distance` (world units)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct Attenuation {
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+/// Level-data looping sound source (waterfall, lava, rushing water);
+/// `sound` is a name from the sound registry
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct SoundEmitterDef {
+    pub position: Vec2,
+    pub sound: String,
+    #[serde(default)]
+    pub attenuation: Attenuation,
+}
+
+/// Looping spatial sound played from an entity (enemies, hazards, level
+/// sound sources); volume and pan follow the listener
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct AudioEmitter {
+    pub sound: String,
+    pub attenuation: Attenuation,
+}
+
+/// Playing ambient loop; `fade` goes from 0 to 1 as it fades in
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct AmbientPlayer {
+    pub sound: String,
+    pub fade: f32,
+}
+
+/// Level-data background music: `track` is a file in `assets/audio/`, and
+/// `crossfade` the seconds it takes to blend into it from the previous track
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct MusicDef {
+    pub track: String,
+    #[serde(default = "default_music_crossfade")]
+    pub crossfade: f32,
+    /// Extra layers played in sync with the track
+    #[serde(default)]
+    pub stems: Vec<MusicStemDef>,
+}
+
+/// Layer of a level track (drums, strings, ...): `track` is a file in
+/// `assets/audio/`, audible while the `MusicIntensity` is at least
+/// `threshold`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct MusicStemDef {
+    pub track: String,
+    pub threshold: f32,
+}
+
+fn default_music_crossfade() -> f32 {
+    crate::constants::MUSIC_CROSSFADE_TIME
+}
+
+/// Which music should be playing (asset paths); the boss track overrides the
+/// level's while a boss fight is on
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MusicController {
+    pub level_track: Option<String>,
+    pub boss_track: Option<String>,
+    /// Seconds a crossfade between tracks takes
+    pub crossfade: f32,
+    /// Stems of the level track (asset paths)
+    pub stems: Vec<MusicStemDef>,
+}
+
+/// How intense the music should be, from 0.0 (calm) to 1.0 (boss fight);
+/// gameplay systems raise it and stems fade in above their threshold
+#[derive(Resource, Default, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct MusicIntensity(pub f32);
+
+/// Looping music track; `fade` ramps up to 1.0 while it is the wanted track
+/// and back down to silence (then despawns) once it isn't
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MusicPlayer {
+    pub track: String,
+    pub fade: f32,
+}
+
+/// Stem playing in sync with the `MusicPlayer` of `track`; `fade` follows
+/// the music intensity and the stem despawns along with its track
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MusicStem {
+    pub track: String,
+    pub threshold: f32,
+    pub fade: f32,
+}
+
+/// Holds the follow camera at a fixed position and zoom, overriding camera
+/// zones (boss arenas)
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct CameraLock {
+    pub position: Vec2,
+    pub zoom: f32,
+}
+
+/// Entity spawned from the current level's data, despawned when another
+/// level is loaded
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LevelEntity;
+
+/// Ordered list of the game's levels, loaded from `assets/levels/levels.ron`
+#[derive(Resource, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct LevelRegistry {
+    /// Asset paths of the level files
+    pub levels: Vec<String>,
+}
+
+/// Level-data definition of the level's goal
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct GoalDef {
+    pub position: Vec2,
+    #[serde(default)]
+    pub kind: GoalKind,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum GoalKind {
+    #[default]
+    Flag,
+    Door,
+}
+
+/// End of the level; touching it completes the level
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Goal {
+    pub area: Rect,
+    pub touching: bool,
+}
+
+/// Dialogue script loaded from `assets/dialogue/`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct DialogueScript {
+    pub lines: Vec<DialogueLine>,
+}
+
+/// One text box of a dialogue
+/// Without choices the dialogue continues with `next` (or the following
+/// line); with choices, the chosen one decides where it goes
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct DialogueLine {
+    pub speaker: String,
+    pub text: String,
+    /// Image path relative to `assets/`
+    #[serde(default)]
+    pub portrait: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    #[serde(default)]
+    pub next: Option<usize>,
+}
+
+/// Answer the player can pick; `next: None` ends the dialogue
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct DialogueChoice {
+    pub text: String,
+    #[serde(default)]
+    pub next: Option<usize>,
+}
+
+/// Level-data definition of something that starts a dialogue
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct DialogueTriggerDef {
+    /// Script file name in `assets/dialogue/`
+    pub script: String,
+    pub kind: DialogueTriggerKind,
+    /// Only trigger the first time
+    #[serde(default)]
+    pub once: bool,
+}
+
+/// How a dialogue is started
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub enum DialogueTriggerKind {
+    /// Character the player talks to with the interact key
+    Npc { position: Vec2 },
+    /// Area that starts the dialogue when the player walks in
+    Region { min: Vec2, max: Vec2 },
+}
+
+/// Runtime dialogue trigger
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DialogueTrigger {
+    pub script: String,
+    pub area: Rect,
+    /// NPCs need the interact key, regions trigger on entering
+    pub npc: bool,
+    pub once: bool,
+    /// Whether the player was inside `area` last frame (edge detection)
+    pub inside: bool,
+}
+
+/// Level-data definition of a tutorial hint shown the first time a player
+/// walks into the area between `min` and `max`
+/// `{left}`, `{right}`, `{up}`, `{down}`, `{jump}` and `{interact}` in the
+/// text are replaced by the keys they are bound to
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct HintDef {
+    /// Key the hint is remembered by in `SaveData` once shown
+    pub id: String,
+    pub text: String,
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+/// Runtime hint region; despawned once its hint has been shown
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HintTrigger {
+    pub id: String,
+    pub text: String,
+    pub area: Rect,
+}
+
+/// HUD text showing the current hint, faded out when `timer` runs out
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HintPopup {
+    pub timer: Timer,
+}
+
+/// Dialogue currently shown; present only in `GameState::Dialogue`
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ActiveDialogue {
+    pub script: DialogueScript,
+    pub line: usize,
+    /// Characters of the current line revealed so far (typewriter effect)
+    pub revealed: f32,
+    pub selected_choice: usize,
+}
+
+/// Dialogue box UI elements
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DialoguePortrait;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DialogueSpeakerText;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DialogueBodyText;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DialogueChoicesText;
+
+/// Level-data definition of a door key
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct KeyDef {
+    pub color: KeyColor,
+    pub position: Vec2,
+}
+
+/// Level-data definition of a locked door, opened by a key of the same color
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct DoorDef {
+    pub color: KeyColor,
+    pub position: Vec2,
+    #[serde(default = "default_door_size")]
+    pub size: Vec2,
+}
+
+fn default_door_size() -> Vec2 {
+    Vec2::new(16.0, 32.0)
+}
+
+/// Key/door colors; a key only opens doors of its own color
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Green,
+    Yellow,
+}
+
+/// Key lying in the level
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct KeyItem {
+    pub color: KeyColor,
+}
+
+/// Keys the player is carrying
+#[derive(Component, Default, Debug, Reflect)]
+#[reflect(Component)]
+pub struct KeyRing {
+    pub keys: Vec<KeyColor>,
+}
+
+/// Locked door blocking the way until the player bumps into it holding a
+/// matching key
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Door {
+    pub color: KeyColor,
+}
+
+/// Opening animation of an unlocked door; the door despawns when it finishes
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DoorOpening {
+    pub timer: Timer,
+}
+
+/// Path of the level currently loaded into `LevelData`
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentLevel {
+    pub path: String,
+}
+
+/// A level file loaded through the `AssetServer`
+#[derive(Asset, TypePath)]
+pub struct LevelAsset(pub LevelData);
+
+/// The level file requested from the `AssetServer`, which becomes the
+/// current level once loaded; kept loaded so changes to it on disk reload the
+/// level
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentLevelAsset(pub Handle<LevelAsset>);
+
+/// Why a level file (or the level registry) failed to load, with the file
+/// and, when the parser knows it, the line and column of the problem
+#[derive(Debug, thiserror::Error)]
+#[error("{}: {kind}", error_location(.path, .line, .column))]
+pub struct LevelError {
+    pub path: String,
+    /// 1-based
+    pub line: Option<usize>,
+    /// 1-based, in characters
+    pub column: Option<usize>,
+    pub kind: LevelErrorKind,
+}
+
+/// What went wrong in a `LevelError`
+#[derive(Debug, thiserror::Error)]
+pub enum LevelErrorKind {
+    #[error("failed to read the file: {0}")]
+    Read(std::io::Error),
+    #[error("failed to write the file: {0}")]
+    Write(std::io::Error),
+    /// Boxed, as it's several times larger than the other kinds
+    #[error("invalid RON: {0}")]
+    Ron(Box<ron::Error>),
+    #[error("the file has no header")]
+    Empty,
+    #[error("invalid header '{0}', expected 'width,height'")]
+    InvalidHeader(String),
+    #[error("invalid tile '{0}'")]
+    InvalidTile(String),
+    #[error("row has {found} tiles, expected {expected}")]
+    RowLength { found: usize, expected: usize },
+    #[error("unknown symbol '{0}', not in the legend")]
+    UnknownSymbol(char),
+    #[error("the file has no tile rows")]
+    NoRows,
+    /// JSON syntax or a map the Tiled loader can't use
+    #[error("invalid Tiled map: {0}")]
+    Tiled(String),
+    #[error("level size {width}x{height} exceeds maximum {max_width}x{max_height}")]
+    TooLarge {
+        width: u32,
+        height: u32,
+        max_width: u32,
+        max_height: u32,
+    },
+    #[error("level declares {declared} rows but contains {found}")]
+    RowCount { declared: u32, found: u32 },
+    #[error("level declares {declared} columns but its rows have {found}")]
+    ColumnCount { declared: u32, found: u32 },
+    #[error(
+        "unknown level format, expected .level.ron, .level.csv, .level.txt, .tmj or .level.json"
+    )]
+    UnknownFormat,
+    /// Loading through the `AssetServer` failed; the message includes the
+    /// parser's error. Boxed, like `Ron`
+    #[error("{0}")]
+    Asset(Box<bevy::asset::AssetLoadError>),
+}
+
+impl LevelError {
+    /// Error in the file at `path` as a whole
+    pub fn new(path: &str, kind: LevelErrorKind) -> Self {
+        Self {
+            path: path.to_string(),
+            line: None,
+            column: None,
+            kind,
+        }
+    }
+
+    /// Error at a position in the file at `path`
+    pub fn at(path: &str, line: usize, column: usize, kind: LevelErrorKind) -> Self {
+        Self {
+            path: path.to_string(),
+            line: Some(line),
+            column: Some(column),
+            kind,
+        }
+    }
+}
+
+/// `path`, `path:line` or `path:line:column`
+fn error_location(path: &str, line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path, line, column),
+        (Some(line), None) => format!("{}:{}", path, line),
+        _ => path.to_string(),
+    }
+}
+
+/// The last level load that failed, shown in the editor until a level loads
+#[derive(Resource, Debug)]
+pub struct LevelLoadError(pub LevelError);
+
+/// Level-data definition of a collectible placed off the tile grid
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CollectibleDef {
+    pub kind: CollectibleKind,
+    pub position: Vec2,
+}
+
+/// Kinds of collectibles
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum CollectibleKind {
+    Coin,
+    Gem,
+}
+
+/// Collectible entity, picked up when the player touches it
+/// `key` identifies it within its level (its rounded world position) so
+/// collection state survives restarts; loot dropped by chests has none and
+/// isn't saved
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Collectible {
+    pub kind: CollectibleKind,
+    pub key: Option<IVec2>,
+}
+
+/// Level-data definition of a treasure chest dropping loot from the `loot`
+/// table of `assets/config/loot_tables.ron`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct ChestDef {
+    pub position: Vec2,
+    pub loot: String,
+}
+
+/// Treasure chest, opened with the interact key
+/// `key` identifies it within its level so opened chests stay open
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Chest {
+    pub loot: String,
+    pub key: IVec2,
+    pub opened: bool,
+}
+
+/// Lid of a chest, swung open by the opening animation
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChestLid;
+
+/// Chest playing its opening animation; the loot drops when it finishes
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ChestOpening {
+    pub timer: Timer,
+}
+
+/// Items a loot table can drop
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum LootItem {
+    Coin,
+    Gem,
+    Heart,
+}
+
+/// One entry of a loot table: rolled once with `chance`, dropping between
+/// `min` and `max` items on success
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct LootDrop {
+    pub item: LootItem,
+    #[serde(default = "default_loot_count")]
+    pub min: u32,
+    #[serde(default = "default_loot_count")]
+    pub max: u32,
+    #[serde(default = "default_loot_chance")]
+    pub chance: f32,
+}
+
+fn default_loot_count() -> u32 {
+    1
+}
+
+fn default_loot_chance() -> f32 {
+    1.0
+}
+
+/// List of drops rolled when a chest opens
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+pub struct LootTable {
+    pub drops: Vec<LootDrop>,
+}
+
+/// Loot tables by name, loaded from `assets/config/loot_tables.ron`
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct LootTables(pub std::collections::HashMap<String, LootTable>);
+
+/// Power-up lying in the level, applied when the player touches it
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub enum PowerUp {
+    /// Restores one hit point
+    Heart,
+}
+
+/// Short-lived sprite of a particle effect, fading out over its lifetime
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    /// Downward acceleration in pixels per second squared
+    pub gravity: f32,
+    pub lifetime: Timer,
+}
+
+/// Shapes of particle burst spawned by `spawn_particles()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum ParticleEffect {
+    /// Ring of sparks from a pickup, power-up or defeated enemy
+    Sparkle,
+    /// Small puff kicked up at a running character's feet, drifting upwards
+    RunDust,
+    /// Dust spreading sideways from a hard landing
+    LandingPuff,
+    /// Chunks thrown up by a broken tile or hazard, falling back down
+    Debris,
+}
+
+/// Top-level state of the game
+/// Gameplay systems only run while `Playing`; every other state freezes the
+/// world (including physics) underneath its own UI
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum GameState {
+    #[default]
+    Menu,
+    Playing,
+    Paused,
+    GameOver,
+    Editor,
+    /// A dialogue box is open; the world is frozen until it closes
+    Dialogue,
+    /// The level was completed and its results are shown
+    Results,
+    /// Best times and scores per level, opened from the menu or the results
+    Leaderboard,
+}
+
+/// Whether the editor is open, toggled with Tab from `Playing`
+/// Editor systems only run while `Enabled`, and the entities it spawns are
+/// scoped to it; entering and leaving also moves `GameState` in and out of
+/// `Editor`
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum EditorState {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+/// Player settings, saved to `saves/settings.ron` and applied at startup
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ambient_volume: f32,
+    pub window_mode: WindowModeSetting,
+    pub key_bindings: KeyBindings,
+    pub screen_shake: bool,
+    /// Hide the HUD and other game UI in screenshots (F12)
+    pub screenshot_hide_hud: bool,
+    /// Id of the character picked in the menu
+    pub character: String,
+    /// Replay the best run of the level as a ghost
+    pub show_ghost: bool,
+    /// Show the speedrun overlay (real-time clock and checkpoint splits)
+    pub speedrun_timer: bool,
+}
+
+/// Window modes selectable from the settings screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+/// Rebindable gameplay keys; the arrow keys always work as alternates for
+/// the directions
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub jump: KeyCode,
+    pub interact: KeyCode,
+}
+
+/// Draw order of a level object, applied to its z by `apply_draw_layers()`
+/// `Sorted` objects are ordered by the height of their feet (`feet` pixels
+/// below the origin): the lower on screen, the closer to the front.
+/// `Behind` objects draw under every sorted one and `Front` objects over them
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum DrawLayer {
+    Behind,
+    Sorted { feet: f32 },
+    Front,
+}
+
+/// Smooths the drawn position of an entity moved on the fixed timestep
+/// `previous`/`current` are its positions after the last two fixed steps,
+/// `rendered` where it was last drawn; any other position means something
+/// outside the fixed step moved it (a teleport), which resets the blend
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct RenderInterpolation {
+    pub previous: Vec3,
+    pub current: Vec3,
+    pub rendered: Vec3,
+}
+
+/// Gameplay actions that can be bound to keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum InputAction {
+    Left,
+    Right,
+    Up,
+    Down,
+    Jump,
+    Interact,
+}
+
+/// Hit points of a character, shown as hearts on the HUD for the player
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+/// HUD heart showing whether the player has at least `index + 1` hit points
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudHeart {
+    pub index: u32,
+}
+
+/// HUD text showing the collectible counts
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudCollectiblesText;
+
+/// HUD text showing the level timer
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudTimerText;
+
+/// UI root hidden while a screenshot is taken without the HUD, holding the
+/// visibility to restore once it has been captured
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HiddenForScreenshot(pub Visibility);
+
+/// Minimap of the current level: an image with one pixel per tile, filled
+/// in as the player explores
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct Minimap {
+    pub image: Handle<Image>,
+    /// One flag per tile, row by row from the top
+    pub explored: Vec<bool>,
+    /// Showing the full-map overlay instead of the corner map
+    pub expanded: bool,
+}
+
+/// UI node showing the minimap image; markers are its children
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MinimapFrame;
+
+/// Dot on the minimap at level grid coordinates
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct MinimapMarker {
+    pub kind: MinimapMarkerKind,
+    pub coords: UVec2,
+}
+
+/// What a minimap marker stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum MinimapMarkerKind {
+    /// Follows the player
+    Player,
+    /// Hidden until its tile is explored
+    Checkpoint,
+    /// Hidden until its tile is explored
+    Exit,
+}
+
+/// HUD text showing the score and active combo
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudScoreText;
+
+/// HUD text showing how far below player one a rising liquid is
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudLiquidText;
+
+/// HUD text listing the level's objectives
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudObjectivesText;
+
+/// HUD text showing the lives left
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HudLivesText;
+
+/// Lives left; losing all health costs one, losing the last one ends the game
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Lives {
+    pub remaining: u32,
+}
+
+/// Sent when the player runs out of health, starting the death sequence
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerDied {
+    pub player: Entity,
+}
+
+/// Sent when the player jumps, off the ground or a rope
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerJumped {
+    pub player: Entity,
+    pub position: Vec2,
+}
+
+/// Sent when the player lands faster than `LAND_MIN_SPEED`
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerLanded {
+    pub player: Entity,
+    pub position: Vec2,
+    /// Falling speed on impact, before any bounce
+    pub speed: f32,
+}
+
+/// Sent when the player is put back at the checkpoint after dying, so
+/// hazards and other level state can re-arm
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerRespawned {
+    pub player: Entity,
+}
+
+/// Player is playing the death sequence; input is ignored until it ends
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Dying {
+    pub timer: Timer,
+}
+
+/// Full-screen black overlay of the death sequence: darkens while the player
+/// dies, then clears again after the respawn
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ScreenFade {
+    pub timer: Timer,
+    pub fade_in: bool,
+}
+
+/// Level-data definition of a checkpoint flag
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CheckpointDef {
+    pub position: Vec2,
+}
+
+/// Checkpoint flag; touching it makes its position the respawn point
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Checkpoint {
+    pub position: Vec2,
+}
+
+/// Where the player respawns after losing a life (the level start until a
+/// checkpoint is reached)
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ActiveCheckpoint {
+    pub position: Vec2,
+}
+
+/// Totals of everything the player has collected
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct PlayerStats {
+    pub coins: u32,
+    pub gems: u32,
+}
+
+/// Running score of the session
+/// `combo` counts scoring events chained within `COMBO_WINDOW` of each other
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Score {
+    pub total: u32,
+    /// Points earned in the current level, before the end-of-level tally
+    pub level_points: u32,
+    pub combo: u32,
+    pub combo_timer: f32,
+}
+
+/// Clock of the current level attempt
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct LevelTimer {
+    pub elapsed: f32,
+}
+
+/// Time-attack medal thresholds of a level, in seconds
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub struct MedalTimes {
+    pub gold: f32,
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+/// Time-attack medals, best first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+/// Personal-best speedrun splits per level path, saved to `saves/splits.ron`
+#[derive(Resource, Default, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct SpeedrunRecords(pub std::collections::HashMap<String, SplitRun>);
+
+/// Splits of one run, in the order they were reached; a finished run ends
+/// with the goal split, whose time is the run's total
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+pub struct SplitRun {
+    pub splits: Vec<Split>,
+}
+
+/// Real time (seconds since the level started) at which a segment ended
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct Split {
+    /// "Checkpoint N" (N counts the level's checkpoints from 1) or "Goal"
+    pub name: String,
+    pub time: f32,
+}
+
+/// Real-time clock and splits of the current attempt, started over when
+/// the level timer resets
+/// Unlike `LevelTimer` it ignores time scaling and keeps running through
+/// cutscenes, dialogue, the pause screen and game over
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SpeedrunTimer {
+    pub level: String,
+    pub elapsed: f32,
+    pub run: SplitRun,
+    /// Personal best when the attempt started, compared against
+    pub personal_best: Option<SplitRun>,
+    pub finished: bool,
+    /// Last level timer value seen, to notice it being reset
+    pub level_time: f32,
+}
+
+/// Speedrun overlay root, shown with the `speedrun_timer` setting
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SpeedrunOverlay;
+
+/// Speedrun overlay real-time clock
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SpeedrunTimerText;
+
+/// Speedrun overlay splits with their difference to the personal best
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SpeedrunSplitsText;
+
+/// Something worth points happened; consumed by the score system and by
+/// anything else reacting to scoring (HUD, save data)
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ScoreEvent {
+    pub source: ScoreSource,
+    /// Base points, before the combo multiplier
+    pub points: u32,
+    /// Where it happened, for the score popup (none for level-wide awards)
+    pub position: Option<Vec2>,
+}
+
+/// Hurts a character; sent by hazards and enemies
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: u32,
+}
+
+/// Sent by `apply_damage()` once a `DamageEvent` has been taken off its
+/// target's health
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub amount: u32,
+    /// Health left afterwards
+    pub remaining: u32,
+}
+
+/// Level-data definition of a hazard hanging from the ceiling that falls when
+/// the player passes underneath
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct FallingHazardDef {
+    pub position: Vec2,
+    pub kind: FallingHazardKind,
+    #[serde(default = "default_hazard_damage")]
+    pub damage: u32,
+}
+
+fn default_hazard_damage() -> u32 {
+    1
+}
+
+/// Level-data definition of a hazard that keeps moving and hurts on contact
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct MovingHazardDef {
+    pub kind: MovingHazardKind,
+    pub motion: HazardMotion,
+    #[serde(default = "default_hazard_damage")]
+    pub damage: u32,
+}
+
+/// Kinds of moving hazards
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum MovingHazardKind {
+    /// Spinning blade; oscillates smoothly
+    SawBlade,
+    /// Piston; oscillating, it slams from `from` to `to` and slowly pulls back
+    Crusher,
+}
+
+/// How a moving hazard travels
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub enum HazardMotion {
+    /// Follows world-space waypoints at `speed`, like a moving platform
+    Path {
+        waypoints: Vec<Vec2>,
+        speed: f32,
+        #[serde(default)]
+        mode: PlatformPathMode,
+    },
+    /// Goes from `from` to `to` and back every `period` seconds, starting
+    /// `phase` (0.0 to 1.0) of the way into its cycle
+    Oscillate {
+        from: Vec2,
+        to: Vec2,
+        period: f32,
+        #[serde(default)]
+        phase: f32,
+    },
+}
+
+/// Runtime moving hazard
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MovingHazard {
+    pub kind: MovingHazardKind,
+    pub motion: HazardMotion,
+    pub damage: u32,
+    /// Next waypoint of a `Path` and the direction it is walked in
+    pub target: usize,
+    pub forward: bool,
+    /// Seconds into the cycle of an `Oscillate`
+    pub elapsed: f32,
+    /// Hurts at most once per `MOVING_HAZARD_HIT_COOLDOWN`
+    pub hit_cooldown: Timer,
+}
+
+/// Level-data definition of a liquid that rises through the level and
+/// kills players it reaches; `speed` grows by `acceleration` every second,
+/// up to `max_speed`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct RisingLiquidDef {
+    pub kind: LiquidKind,
+    /// World height of the surface when the level starts
+    pub start: f32,
+    /// Pixels per second the surface starts rising at
+    pub speed: f32,
+    #[serde(default)]
+    pub acceleration: f32,
+    #[serde(default)]
+    pub max_speed: Option<f32>,
+    /// Seconds before the surface starts rising, after the start and each
+    /// respawn
+    #[serde(default)]
+    pub delay: f32,
+}
+
+/// Kinds of rising liquid: lava burns on touch, water drowns once it's over
+/// the player's head
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum LiquidKind {
+    Lava,
+    Water,
+}
+
+/// Runtime rising liquid; `surface` is the world height of its top
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct RisingLiquid {
+    pub def: RisingLiquidDef,
+    pub surface: f32,
+    pub speed: f32,
+    pub delay: Timer,
+}
+
+/// Level-data definition of an out-of-bounds area (a pit inside the level):
+/// a player entering it is moved back to `respawn` and takes `damage`
+/// instead of dying
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct RespawnZoneDef {
+    pub min: Vec2,
+    pub max: Vec2,
+    /// Safe point the player is put back at
+    pub respawn: Vec2,
+    #[serde(default = "default_hazard_damage")]
+    pub damage: u32,
+}
+
+/// Runtime respawn zone
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct RespawnZone {
+    pub area: Rect,
+    pub respawn: Vec2,
+    pub damage: u32,
+}
+
+/// Kinds of falling hazards
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum FallingHazardKind {
+    Stalactite,
+    Boulder,
+}
+
+/// Falling hazard; `origin` is where it hangs and is put back when re-armed
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FallingHazard {
+    pub kind: FallingHazardKind,
+    pub origin: Vec2,
+    pub damage: u32,
+    pub state: FallingHazardState,
+}
+
+/// Lifecycle of a falling hazard
+#[derive(Clone, Debug, Reflect)]
+pub enum FallingHazardState {
+    /// Hanging, watching for the player below
+    Armed,
+    /// Triggered; falls when the timer ends
+    Shaking(Timer),
+    /// Dynamic body falling until it hits something
+    Falling,
+    /// Shattered; hidden until the player respawns
+    Broken,
+}
+
+/// Floating world-space text (damage, points) that rises and fades out
+/// Popups are pooled (see `Pool`)
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Popup {
+    pub timer: Timer,
+}
+
+/// Recycled entities of one kind, the kind being the component `T` they
+/// carry while in use (`Particle`, `Popup`)
+/// Removing `T` from an entity returns it to the pool, hidden, for the next
+/// `acquire()`; despawning it just drops it. Up to `capacity` free entities
+/// are kept, extra ones are despawned
+#[derive(Resource)]
+pub struct Pool<T: Component> {
+    pub name: &'static str,
+    pub free: Vec<Entity>,
+    pub capacity: usize,
+    /// Entities spawned and reused over the pool's life, for the debug panel
+    pub spawned: usize,
+    pub reused: usize,
+    pub marker: std::marker::PhantomData<T>,
+}
+
+/// What a `ScoreEvent` was awarded for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum ScoreSource {
+    Pickup(CollectibleKind),
+    /// Sent by enemy systems when an enemy is defeated
+    EnemyKill,
+    /// End-of-level bonus for finishing under par time; never multiplied
+    TimeBonus,
+    /// End-of-level bonus for a completed objective; never multiplied
+    ObjectiveBonus,
+}
+
+/// Sent when the player finishes the level, triggering the score tally
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LevelCompleted;
+
+/// Best runs per level (keyed by level path), best first, saved to
+/// `saves/leaderboard.ron`
+#[derive(Resource, Default, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct Leaderboard {
+    #[serde(default)]
+    pub levels: std::collections::HashMap<String, Vec<LeaderboardEntry>>,
+}
+
+/// One leaderboard run
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct LeaderboardEntry {
+    pub initials: String,
+    pub time: f32,
+    pub score: u32,
+}
+
+/// Run that made the leaderboard, waiting for the player to type their
+/// initials on the results screen
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct PendingLeaderboardEntry {
+    pub level: String,
+    pub entry: LeaderboardEntry,
+}
+
+/// State the leaderboard screen returns to when closed
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct LeaderboardReturn(pub GameState);
+
+/// Results screen text showing the initials being typed
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LeaderboardInitialsText;
+
+/// Directory the save, settings, leaderboard, splits and snapshot files live
+/// in; `SAVE_DIRECTORY` by default, a temporary one in headless apps
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SaveDirectory(pub String);
+
+/// Player progress saved to disk, keyed by level path
+#[derive(Resource, Default, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct SaveData {
+    /// Collectibles already picked up
+    #[serde(default)]
+    pub collected: std::collections::HashMap<String, std::collections::HashSet<IVec2>>,
+    /// Best completion time in seconds
+    #[serde(default)]
+    pub best_times: std::collections::HashMap<String, f32>,
+    /// Levels finished at least once
+    #[serde(default)]
+    pub completed_levels: std::collections::HashSet<String>,
+    /// Chests already opened
+    #[serde(default)]
+    pub opened_chests: std::collections::HashMap<String, std::collections::HashSet<IVec2>>,
+    /// Recording of the best-time run, replayed as a ghost
+    #[serde(default)]
+    pub ghosts: std::collections::HashMap<String, GhostRecording>,
+    /// Ids of the tutorial hints already shown
+    #[serde(default)]
+    pub shown_hints: std::collections::HashMap<String, std::collections::HashSet<String>>,
+}
+
+/// The player's run through a level, sampled `GHOST_SAMPLE_RATE` times per
+/// second of level time
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+pub struct GhostRecording {
+    /// Id of the character the run was played with
+    pub character: String,
+    pub frames: Vec<GhostFrame>,
+}
+
+/// Where the player was and which animation frame it showed
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub struct GhostFrame {
+    pub position: Vec2,
+    pub state: AnimationState,
+    pub index: usize,
+    pub flip_x: bool,
+}
+
+/// Recording of the current attempt, started over when the level timer is
+/// reset
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct GhostRecorder {
+    /// Level being recorded
+    pub level: String,
+    pub recording: GhostRecording,
+}
+
+/// Console request to start or stop a network session
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub enum NetworkCommand {
+    /// Host on this UDP port
+    Host(u16),
+    /// Join the host at this address (`host:port`)
+    Join(String),
+    Leave,
+}
+
+/// Side of a network session: the host simulates both players and picks the
+/// level, the client predicts its own player and draws the host's
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum NetworkRole {
+    Host,
+    Client,
+}
+
+/// Open network session, from `net host` or `net join` until `net leave` or
+/// the peer goes silent
+#[derive(Resource)]
+pub struct NetworkSession {
+    pub role: NetworkRole,
+    pub socket: UdpSocket,
+    /// The other instance; the host learns it from the client's hello
+    pub peer: Option<SocketAddr>,
+    /// Client: whether the host has answered the hello
+    pub welcomed: bool,
+    /// Real time since the last packet from the peer
+    pub silence: f32,
+    pub send_timer: Timer,
+    /// Fixed steps simulated since the session started
+    pub tick: u32,
+    /// Host: last client input tick simulated; client: last one the host
+    /// acknowledged
+    pub acked_tick: u32,
+    /// Host: client input tick applied in the current fixed step, acknowledged
+    /// once the step is simulated
+    pub simulating_tick: Option<u32>,
+    /// Client: inputs the host hasn't acknowledged yet, resent until it does;
+    /// host: client inputs received but not simulated yet, one per fixed step
+    pub pending_inputs: Vec<NetInputFrame>,
+    /// Client: where its player was predicted after each unacknowledged tick
+    pub predictions: VecDeque<(u32, Vec2)>,
+}
+
+/// Input of one fixed step of the client's player
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetInputFrame {
+    pub tick: u32,
+    pub held: Vec<InputAction>,
+    pub pressed: Vec<InputAction>,
+}
+
+/// State of one player in a host snapshot: its position and animation frame
+/// (a `GhostFrame`, whose `flip_x` is the facing), velocity and character
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetPlayerState {
+    /// 0 for the host's player, 1 for the client's
+    pub id: u8,
+    pub character: String,
+    pub frame: GhostFrame,
+    pub velocity: Vec2,
+}
+
+/// Packets exchanged by the host and the client
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// Client asks to join, playing `character`
+    Hello {
+        character: String,
+    },
+    /// Host accepts the client
+    Welcome,
+    /// Client inputs the host hasn't acknowledged yet
+    Input {
+        frames: Vec<NetInputFrame>,
+    },
+    /// Host state of every player, the level being played and the last
+    /// client input tick applied
+    Snapshot {
+        level: String,
+        ack: u32,
+        players: Vec<NetPlayerState>,
+    },
+    Bye,
+}
+
+/// The other instance's player on a client, drawn between the last two
+/// snapshot positions the host sent
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct NetworkPuppet {
+    pub previous: Vec2,
+    pub latest: Vec2,
+    /// Real time since `latest` arrived, and between the last two snapshots
+    pub since_latest: f32,
+    pub interval: f32,
+}
+
+/// Translucent copy of the player replaying the level's best run
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Ghost(pub GhostRecording);
+
+/// Level-data definition of a rope hanging from a fixed anchor
+/// With `platform` set, a platform of that size hangs from the rope's end
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct RopeDef {
+    pub anchor: Vec2,
+    pub length: f32,
+    #[serde(default)]
+    pub platform: Option<Vec2>,
+}
+
+/// One link of a rope, jointed to the previous link (or the anchor)
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct RopeSegment;
+
+/// Present on the player while hanging from a rope segment
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GrabbingRope {
+    pub segment: Entity,
+}
+
+/// Level-data definition of a switch/lever toggling the entities whose ids
+/// are listed in `targets`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct SwitchDef {
+    pub position: Vec2,
+    pub targets: Vec<String>,
+}
+
+/// Level-data definition of an elevator moving between two points
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct ElevatorDef {
+    pub id: String,
+    pub from: Vec2,
+    pub to: Vec2,
+    pub speed: f32,
+    #[serde(default = "default_platform_size")]
+    pub size: Vec2,
+}
+
+/// Level-data definition of a gate: a group of tiles toggled between solid
+/// and empty (colliders disabled) by switches
+/// The group is every tile in the `min`..=`max` grid region (the whole level
+/// when omitted) whose index is listed in `tiles` (any index when empty), so
+/// doors use a region and block puzzles can tag tiles by index.
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct GateDef {
+    pub id: String,
+    #[serde(default)]
+    pub min: UVec2,
+    #[serde(default = "default_gate_max")]
+    pub max: UVec2,
+    #[serde(default)]
+    pub tiles: Vec<u32>,
+    /// Initial state; open gates start out empty
+    #[serde(default)]
+    pub open: bool,
+}
+
+fn default_gate_max() -> UVec2 {
+    UVec2::MAX
+}
+
+/// Switch entity; toggled when the player touches it
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Switch {
+    pub targets: Vec<String>,
+    pub on: bool,
+    /// Whether the player was touching the switch last frame (edge detection)
+    pub touching: bool,
+}
+
+/// Elevator linked to switches by id; moves to its `to` point while active
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Elevator {
+    pub id: String,
+}
+
+/// Gate linked to switches by id
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Gate {
+    pub id: String,
+    /// World-space area covered by the gate's tiles
+    pub area: Rect,
+    /// Tile indices belonging to the gate; empty means every tile in `area`
+    pub tiles: Vec<u32>,
+    pub open: bool,
+}
+
+/// Fades a gate tile's alpha towards `target` (solid or ghosted)
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TileFade {
+    pub target: f32,
+}
+
+/// Level-data definition of a secret area: the tiles between `min` and `max`
+/// (grid coordinates, inclusive) become a fake wall drawn over the player,
+/// which fades out while the player is inside
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct SecretDef {
+    pub min: UVec2,
+    pub max: UVec2,
+}
+
+/// Concealed area; its cover tiles are revealed while the player is inside
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SecretArea {
+    /// World-space area covered by the secret's tiles
+    pub area: Rect,
+    pub revealed: bool,
+}
+
+/// Tile of a secret area's fake wall
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SecretCover;
+
+/// Level-data definition of a block that reacts to being hit from below:
+/// the solid tile at `position` (grid coordinates) bounces and drops `drop`
+/// on each of its `hits`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct BlockDef {
+    pub position: UVec2,
+    #[serde(default)]
+    pub kind: BlockKind,
+    #[serde(default)]
+    pub drop: Option<LootItem>,
+    #[serde(default = "default_block_hits")]
+    pub hits: u32,
+}
+
+fn default_block_hits() -> u32 {
+    1
+}
+
+/// What a block becomes once its hits are used up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum BlockKind {
+    /// Question-mark block: turns into an inert used block
+    #[default]
+    Item,
+    /// Breaks apart, leaving an opening
+    Breakable,
+}
+
+/// Tile of a block that still reacts to hits
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HitBlock {
+    pub kind: BlockKind,
+    pub drop: Option<LootItem>,
+    pub hits_left: u32,
+}
+
+/// Bump of a block tile that was just hit; moves its sprite, not its collider
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BlockBounce {
+    pub timer: Timer,
+}
+
+/// Level-data placement of a prefab (see `PrefabRegistry`)
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct EntityDef {
+    pub prefab: String,
+    pub position: Vec2,
+}
+
+/// Blueprint of an entity: the components to give it, by name
+/// Everything but the sprite is optional, so one format covers enemies,
+/// pickups and props
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct Prefab {
+    pub sprite: PrefabSprite,
+    #[serde(default)]
+    pub collider: Option<PrefabCollider>,
+    #[serde(default)]
+    pub body: PrefabBody,
+    /// Pushable prop, floating in water
+    #[serde(default)]
+    pub prop: Option<PropKind>,
+    #[serde(default)]
+    pub collectible: Option<CollectibleKind>,
+    #[serde(default)]
+    pub power_up: Option<PowerUp>,
+    #[serde(default)]
+    pub enemy: Option<PrefabEnemy>,
+    /// Loot table dropped when the entity is defeated
+    #[serde(default)]
+    pub loot: Option<String>,
+}
+
+/// How a prefab is drawn: a flat color, or an image (path under `assets/`)
+/// stretched to `size`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct PrefabSprite {
+    pub size: Vec2,
+    /// sRGB
+    #[serde(default = "default_prefab_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+fn default_prefab_color() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+/// Collision shape of a prefab
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub enum PrefabCollider {
+    Cuboid { half_width: f32, half_height: f32 },
+    Ball { radius: f32 },
+}
+
+/// Physics body of a prefab with a collider
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Reflect)]
+pub enum PrefabBody {
+    /// Doesn't move (or is moved by its own system, like enemies)
+    #[default]
+    Fixed,
+    /// Detects overlaps without blocking
+    Sensor,
+    Dynamic {
+        density: f32,
+        friction: f32,
+        #[serde(default)]
+        lock_rotation: bool,
+    },
+}
+
+/// Enemy behaviour of a prefab: patrols back and forth, hurts the player on
+/// contact and is defeated by being stomped
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub struct PrefabEnemy {
+    pub damage: u32,
+    pub speed: f32,
+    /// How far it walks either side of where it's placed; 0 walks until a
+    /// wall or ledge
+    pub patrol_distance: f32,
+}
+
+/// Prefabs by id, loaded from `assets/config/prefabs.ron`
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct PrefabRegistry(pub std::collections::HashMap<String, Prefab>);
+
+/// Prefab the editor places on click
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct PrefabPalette {
+    pub selected: Option<String>,
+}
+
+/// Tile the editor paints on click, and the shape it's painted in
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TilePalette {
+    pub selected: Option<TileId>,
+    pub tool: TileTool,
+    /// Rectangle or line being dragged, committed when the button is released
+    pub drag: Option<TileDrag>,
+    /// Most tiles a single fill may change; larger regions aren't filled
+    pub max_fill_area: u32,
+}
+
+/// Shape painted by the editor's tile tools
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TileTool {
+    /// One tile per cell under the cursor, painting while the button is held
+    #[default]
+    Brush,
+    Rectangle,
+    HollowRectangle,
+    Line,
+    /// Replaces the contiguous region of the clicked tile's index
+    Fill,
+}
+
+/// Grid cells of a tile tool drag
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct TileDrag {
+    pub start: UVec2,
+    pub end: UVec2,
+    /// Dragged with the right button, clearing the cells
+    pub erase: bool,
+}
+
+/// Patrolling enemy spawned from a prefab
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Enemy {
+    pub damage: u32,
+    pub speed: f32,
+    pub origin: Vec2,
+    pub patrol_distance: f32,
+    /// 1.0 walking right, -1.0 walking left
+    pub direction: f32,
+    pub half_size: Vec2,
+    /// Running while it can't hurt the player again
+    pub hit_cooldown: Timer,
+}
+
+/// Loot table dropped when the entity is defeated
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Loot(pub String);
+
+/// Level-data definition of a dynamic prop
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct PropDef {
+    pub kind: PropKind,
+    pub position: Vec2,
+}
+
+/// Kinds of pushable physics props
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub enum PropKind {
+    Crate,
+    Barrel,
+}
+
+/// Level-data definition of a moving platform (world-space waypoints)
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct MovingPlatformDef {
+    pub waypoints: Vec<Vec2>,
+    pub speed: f32,
+    #[serde(default)]
+    pub mode: PlatformPathMode,
+    #[serde(default = "default_platform_size")]
+    pub size: Vec2,
+}
+
+fn default_platform_size() -> Vec2 {
+    Vec2::new(48.0, 8.0)
+}
+
+/// How a moving platform continues after its last waypoint
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum PlatformPathMode {
+    /// Walk the waypoints back in reverse order
+    #[default]
+    PingPong,
+    /// Jump from the last waypoint straight back to the first
+    Loop,
+    /// Stay at the current target until something (a switch) changes it
+    Manual,
+}
+
+/// Marker for platforms that can be passed through from below
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct OneWayPlatform;
+
+/// Tracks a character dropping down through one-way platforms
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct PlatformPassThrough {
+    pub drop_timer: f32,
+}
+
+/// Kinematic platform following a waypoint path
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MovingPlatform {
+    pub waypoints: Vec<Vec2>,
+    pub speed: f32,
+    pub mode: PlatformPathMode,
+    pub target: usize,
+    pub forward: bool,
+    /// Distance moved this frame, applied to anything standing on the platform
+    pub delta: Vec2,
+}
+
+/// Rectangular world-space region that overrides the follow camera while the
+/// player is inside it (boss arenas, vertical shafts, ...)
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CameraZone {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub mode: CameraZoneMode,
+    #[serde(default = "default_zone_zoom")]
+    pub zoom: f32,
+    /// Ambient loop replacing the level's while the player is inside
+    #[serde(default)]
+    pub ambient: Option<String>,
+}
+
+fn default_zone_zoom() -> f32 {
+    1.0
+}
+
+impl CameraZone {
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+}
+
+/// How a camera zone overrides the default follow behavior
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum CameraZoneMode {
+    /// Keep following the player, only the zoom changes
+    Follow,
+    /// Lock the camera to a fixed world position
+    Locked(Vec2),
+    /// Follow horizontally but hold the camera at a fixed height
+    FixedY(f32),
+}
+
+/// Component for the loaded level
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Level {
+    pub data: LevelData,
+    pub tile_size: f32,
+}
+
+/// Resource for managing all tilesets
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TilesetRegistry {
+    pub tilesets: Vec<TilesetInfo>,
+    pub current_tileset: usize,
+}
+
+/// Resource for tile collision properties based on index
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TileCollisionMap {
+    pub solid_tiles: std::collections::HashSet<u32>,
+    pub platform_tiles: std::collections::HashSet<u32>,
+    pub water_tiles: std::collections::HashSet<u32>,
+    /// Tiles that spawn as collectibles instead of terrain
+    pub collectible_tiles: std::collections::HashMap<u32, CollectibleKind>,
+    /// Physics material per tile index; tiles not listed use `TileMaterial::default()`
+    pub materials: std::collections::HashMap<u32, TileMaterial>,
+    /// Sound (sound registry name) played when a character enters or touches
+    /// the tile, e.g. the splash of water
+    pub contact_sounds: std::collections::HashMap<u32, &'static str>,
+}
+
+/// What a surface sounds like underfoot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum Surface {
+    Grass,
+    #[default]
+    Stone,
+    Wood,
+    Metal,
+}
+
+/// Surface properties of a tile group
+/// `friction`/`restitution` go on the tile colliders (affecting props), while
+/// the character controller reads `speed_factor` and `restitution` from the
+/// tile it stands on
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct TileMaterial {
+    pub friction: f32,
+    pub restitution: f32,
+    /// Multiplier applied to the player's horizontal speed
+    pub speed_factor: f32,
+    /// Picks the footstep sounds
+    pub surface: Surface,
+}
+
+impl TileMaterial {
+    /// Default physics with the given surface
+    pub const fn with_surface(surface: Surface) -> Self {
+        Self {
+            friction: 0.5,
+            restitution: 0.0,
+            speed_factor: 1.0,
+            surface,
+        }
+    }
+}
+
+impl Default for TileMaterial {
+    fn default() -> Self {
+        Self::with_surface(Surface::default())
+    }
+}
+
+/// Material of the ground under a character, updated every frame
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct GroundMaterial(pub TileMaterial);
+
+/// Sensor volume covering a rectangle of contiguous water tiles
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct WaterVolume {
+    pub rect: Rect,
+    /// Played when a character enters the volume
+    pub sound: Option<&'static str>,
+}
+
+/// Marker for characters currently inside a water volume
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct InWater;
+
+/// Level-data definition of a current: inside water, the tiles between `min`
+/// and `max` (grid coordinates, inclusive) push characters and floating
+/// bodies along `flow` (pixels per second)
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct WaterCurrentDef {
+    pub min: UVec2,
+    pub max: UVec2,
+    pub flow: Vec2,
+}
+
+/// Current zone; only acts on what is also inside a water volume
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct WaterCurrent {
+    pub area: Rect,
+    pub flow: Vec2,
+}
+
+/// Combined flow of the currents a submerged character is in
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct InCurrent(pub Vec2);
+
+/// Dynamic bodies with this component float in water volumes
+/// `buoyancy` is the upward force as a multiple of the body's weight
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Buoyant {
+    pub buoyancy: f32,
+    pub water_damping: f32,
+}
+
+/// Tunable settings of the player's character controller, loaded from
+/// `assets/config/character_physics.ron` and applied live when changed
+/// Lengths are in pixels, angles in degrees
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct CharacterPhysicsConfig {
+    /// Gap kept between the collider and obstacles
+    pub offset: f32,
+    /// Keep the character glued to the ground when going down slopes/steps up to this distance
+    pub snap_to_ground: Option<f32>,
+    pub max_slope_climb_angle: f32,
+    pub min_slope_slide_angle: f32,
+    /// Automatically climb steps up to `max_height` tall
+    pub autostep: Option<AutostepConfig>,
+    /// Slide along obstacles instead of stopping at them
+    pub slide: bool,
+}
+
+/// Step climbing settings of `CharacterPhysicsConfig`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct AutostepConfig {
+    pub max_height: f32,
+    pub min_width: f32,
+    pub include_dynamic_bodies: bool,
+}
+
+/// Playable characters, loaded from `assets/config/characters.ron`
+/// Falls back to the built-in adventurer when the file is missing
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CharacterRoster(pub Vec<CharacterDef>);
+
+/// A playable character: its sprite sheets and stats
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct CharacterDef {
+    pub id: String,
+    pub name: String,
+    /// One line shown under the name in the character select
+    #[serde(default)]
+    pub description: String,
+    pub idle: SpriteSheetDef,
+    pub run: SpriteSheetDef,
+    /// Played while rising from an air jump; the run animation when absent
+    #[serde(default)]
+    pub double_jump: Option<SpriteSheetDef>,
+    pub dead: SpriteSheetDef,
+    /// Drawn size of a frame as a multiple of its size in the sheet
+    #[serde(default = "default_character_scale")]
+    pub scale: f32,
+    /// Vertical sprite anchor lining the feet up with the collider
+    pub anchor_y: f32,
+    #[serde(default)]
+    pub stats: CharacterStats,
+}
+
+fn default_character_scale() -> f32 {
+    1.0
+}
+
+/// One animation of a character: a single row of square `frame_size` cells,
+/// played from frame `first` to `last`
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct SpriteSheetDef {
+    /// Image path relative to `assets/`
+    pub path: String,
+    pub frame_size: u32,
+    pub columns: u32,
+    pub first: usize,
+    pub last: usize,
+    pub fps: u8,
+}
+
+/// Which character of the roster the player is
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct PlayableCharacter(pub String);
+
+/// Per-character movement stats; `speed` and `jump` scale the `GameConfig`
+/// player speed and jump force, `air_jumps` is how many extra jumps the
+/// character has in the air
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct CharacterStats {
+    pub speed: f32,
+    pub jump: f32,
+    pub air_jumps: u32,
+}
+
+/// Air jumps the player has left before touching the ground again
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct AirJumps(pub u32);
+
+/// Where the player is in a jump
+/// Letting go of Jump while `Rising` or `AirJumping` cuts the rise short
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum JumpPhase {
+    #[default]
+    Grounded,
+    /// Rising from a jump off the ground (or a rope)
+    Rising,
+    /// Rising from an air jump
+    AirJumping,
+    /// In the air after the rise: falling, cut short, or thrown up by
+    /// something other than a jump
+    Airborne,
+}
+
+/// Character select line of the menu screen
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct CharacterSelectText;
+
+/// Device a player is controlled with: player one plays on the keyboard,
+/// players who join in co-op on their gamepad, and on a network host the
+/// client's player is driven by the inputs it sends
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum PlayerController {
+    Keyboard,
+    Gamepad(Entity),
+    Remote,
+}
+
+/// Player one, the one the HUD, the ghost recording and the debug panel follow
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PrimaryPlayer;
+
+/// Actions of one player, read from its `PlayerController` every frame
+/// `buffered` keeps the presses since the last fixed step, so systems on the
+/// fixed timestep neither miss a press made between steps nor see it twice
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct PlayerInput {
+    pub held: Vec<InputAction>,
+    pub just_pressed: Vec<InputAction>,
+    pub buffered: Vec<InputAction>,
+}
+
+/// Gameplay tuning loaded from `assets/config/game.ron`, reloaded when the
+/// file changes and edited live from the debug window
+/// Speeds are in pixels per second, distances in pixels; the follow and
+/// blend speeds are lerp rates per second
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GameConfig {
+    pub player_speed: f32,
+    /// Vertical acceleration of the player and of Rapier's bodies
+    pub gravity: f32,
+    pub jump_force: f32,
+    /// Share of the upward speed kept when Jump is let go of mid-rise
+    pub jump_cut: f32,
+    pub camera_follow_speed_x: f32,
+    pub camera_follow_speed_y: f32,
+    /// How far the player can leave the camera's vertical anchor mid-air
+    /// before it follows
+    pub camera_vertical_margin: f32,
+    pub camera_offset_y: f32,
+    pub camera_lookahead_distance: f32,
+    pub camera_lookahead_speed: f32,
+    pub camera_zone_blend_speed: f32,
+}
+
+/// Level-data weather settings
+/// `wind` pushes particles sideways (pixels per second), `intensity` scales
+/// how many fall, and `fog` is the opacity of the screen-space fog overlay
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct WeatherDef {
+    pub kind: WeatherKind,
+    #[serde(default)]
+    pub wind: f32,
+    #[serde(default = "default_weather_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub fog: f32,
+}
+
+fn default_weather_intensity() -> f32 {
+    1.0
+}
+
+/// Kinds of falling weather particles
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+/// Current level's weather; `enabled` is toggled at runtime and kept across
+/// levels
+#[derive(Resource, Clone, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub wind: f32,
+    pub intensity: f32,
+    pub fog: f32,
+    pub enabled: bool,
+}
+
+/// Rain drop or snowflake falling through the camera view
+/// Its z places it among the parallax layers; `depth` (0.0 far, 1.0 near)
+/// scales its size and speed to match
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct WeatherParticle {
+    pub velocity: Vec2,
+    pub depth: f32,
+    /// Offset of the snowflake sway, so flakes don't move in lockstep
+    pub phase: f32,
+}
+
+/// Screen-space fog overlay
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct WeatherFog;
+
+/// Level-data lighting: darkness over the level, lit by point lights from
+/// the player, from tiles of the listed indices and placed in the level
+/// `ambient` is how much of the level shows through the darkness (0.0 pitch
+/// black, 1.0 fully lit)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct LightingDef {
+    #[serde(default = "default_lighting_ambient")]
+    pub ambient: f32,
+    /// Color of the darkness
+    #[serde(default)]
+    pub color: (f32, f32, f32),
+    #[serde(default)]
+    pub player_light: Option<LightDef>,
+    #[serde(default)]
+    pub tile_lights: Vec<TileLightDef>,
+    #[serde(default)]
+    pub lights: Vec<PlacedLightDef>,
+}
+
+fn default_lighting_ambient() -> f32 {
+    0.2
+}
+
+/// Point light: `radius` in pixels, `flicker` (0.0 steady, 1.0 strong) for
+/// torches and lava
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct LightDef {
+    pub radius: f32,
+    #[serde(default = "default_light_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub flicker: f32,
+}
+
+fn default_light_color() -> (f32, f32, f32) {
+    (1.0, 0.9, 0.7)
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+/// Light carried by every tile of the given indices (crystals, lava, torches)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct TileLightDef {
+    pub tiles: Vec<TileId>,
+    pub light: LightDef,
+}
+
+/// Light placed at a position of the level
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct PlacedLightDef {
+    pub position: Vec2,
+    pub light: LightDef,
+}
+
+/// Point light shining from an entity (the player, a tile, a placed light)
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct LightSource(pub LightDef);
+
+/// Light spawned from the level's `lighting.lights`
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PlacedLight;
+
+/// Darkness overlay following the camera view
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LightingOverlay;
+
+/// Editor light tool: while `placing`, left click places a light copying
+/// `brush` and right click removes the nearest one
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct LightingEditor {
+    pub placing: bool,
+    pub brush: LightDef,
+}
+
+/// Resource tracking the day/night cycle
+/// `time` is the position in the cycle: 0.0 = midnight, 0.5 = noon
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TimeOfDay {
+    pub time: f32,
+    pub cycle_duration: f32,
+    pub paused: bool,
+    /// Also apply the palette to level tiles (ambient tint)
+    pub tint_tiles: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            time: 0.4,
+            cycle_duration: crate::constants::DAY_NIGHT_CYCLE_SECONDS,
+            paused: false,
+            tint_tiles: true,
+        }
+    }
 }
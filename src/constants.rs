@@ -1,24 +1,32 @@
 //! Game constants and configuration values
 
-/// Player movement constants
-pub const PLAYER_SPEED: f32 = 300.0;
-pub const GRAVITY: f32 = -981.0;
-pub const JUMP_FORCE: f32 = 300.0;
+/// Gameplay tuning (movement and camera follow, see `GameConfig`), and how
+/// often the file is checked for changes (seconds)
+pub const GAME_CONFIG_PATH: &str = "assets/config/game.ron";
+pub const GAME_CONFIG_POLL_INTERVAL: f32 = 0.5;
 
 /// Physics constants
 pub const PIXELS_PER_METER: f32 = 100.0;
-pub const GROUND_HEIGHT: f32 = -100.0;
+/// Rate of the fixed timestep that player movement and Rapier run on
+pub const FIXED_TIMESTEP_HZ: f64 = 60.0;
+pub const GROUND_HEIGHT: f32 = -148.0; // Safety floor just below the level
 pub const GROUND_WIDTH: f32 = 500.0;
 pub const GROUND_THICKNESS: f32 = 50.0;
 
-/// Animation constants
+/// Playable characters, and the one picked until the player chooses
+pub const CHARACTERS_PATH: &str = "assets/config/characters.ron";
+pub const DEFAULT_CHARACTER: &str = "adventurer";
+
+/// Animation constants of the built-in adventurer
 pub const IDLE_ANIMATION_FPS: u8 = 5;
 pub const RUN_ANIMATION_FPS: u8 = 10;
+pub const DEAD_ANIMATION_FPS: u8 = 8;
 
-/// Sprite constants
+/// Sprite constants of the built-in adventurer
 pub const SPRITE_SIZE: u32 = 96;
 pub const IDLE_FRAMES: u32 = 10;
 pub const RUN_FRAMES: u32 = 6;
+pub const DEAD_FRAMES: u32 = 4;
 
 /// Character spawn position
 pub const PLAYER_SPAWN_X: f32 = 0.0;
@@ -42,11 +50,323 @@ pub const MAX_LEVEL_WIDTH: u32 = 200;
 pub const MAX_LEVEL_HEIGHT: u32 = 50;
 pub const DEFAULT_LEVEL_WIDTH: u32 = 100;
 pub const DEFAULT_LEVEL_HEIGHT: u32 = 30;
+/// Asset path (relative to `ASSET_DIRECTORY`) of the level used when the
+/// registry can't be read
+pub const DEFAULT_LEVEL_PATH: &str = "levels/level1.level.ron";
+pub const LEVEL_REGISTRY_PATH: &str = "assets/levels/levels.ron";
+/// Directory the `AssetServer` loads from, for the tools reading level files
+/// directly
+pub const ASSET_DIRECTORY: &str = "assets";
+/// World position of the level's bottom-left corner
+pub const LEVEL_ORIGIN_X: f32 = -280.0;
+pub const LEVEL_ORIGIN_Y: f32 = -98.0;
+pub const TILE_LAYER_Z: f32 = -1.0;
+/// Tiles per side of a tile chunk, the unit of visibility culling
+pub const TILE_CHUNK_SIZE: u32 = 16;
+/// Distance beyond the camera view at which chunks are still drawn
+pub const TILE_CHUNK_CULL_MARGIN: f32 = 32.0;
+/// Tiles spawned per frame while a level loads
+pub const TILE_SPAWN_BUDGET: usize = 2000;
+/// Distance beyond the camera view at which animated and AI entities go
+/// dormant; larger than `HAZARD_TRIGGER_RANGE` so hazards can still see a
+/// player at the edge of the screen
+pub const ACTIVATION_MARGIN: f32 = 256.0;
+
+/// Hit block constants
+pub const BLOCK_BOUNCE_TIME: f32 = 0.2;
+pub const BLOCK_BOUNCE_HEIGHT: f32 = 5.0;
+/// Upward speed of the item a block drops
+pub const BLOCK_DROP_SPEED: f32 = 180.0;
+
+/// Prop constants
+pub const PLAYER_MASS: f32 = 2.0;
+pub const CRATE_SIZE: f32 = 16.0;
+pub const CRATE_DENSITY: f32 = 1.0;
+pub const BARREL_RADIUS: f32 = 8.0;
+pub const BARREL_DENSITY: f32 = 0.6;
+pub const PROP_BUOYANCY: f32 = 1.6;
+pub const PROP_WATER_DAMPING: f32 = 3.0;
+
+/// Switch constants
+pub const SWITCH_SIZE: f32 = 16.0;
+/// Alpha of the tiles of an open (empty) gate
+pub const GATE_OPEN_ALPHA: f32 = 0.2;
+pub const GATE_FADE_SPEED: f32 = 8.0;
+
+/// Secret area constants
+/// Alpha of a secret's cover tiles while the player is inside
+pub const SECRET_REVEALED_ALPHA: f32 = 0.15;
+/// Cover tiles are drawn in front of the player
+pub const SECRET_COVER_Z: f32 = 5.0;
+
+/// Directory the save, settings, leaderboard, splits and snapshot files are
+/// written to, unless `SaveDirectory` points elsewhere
+pub const SAVE_DIRECTORY: &str = "saves";
+
+/// Save file (collected items, best times)
+pub const SAVE_FILE: &str = "save.ron";
+
+/// Debug world snapshot (`snapshot save` / `snapshot load` in the console)
+pub const SNAPSHOT_FILE: &str = "snapshot.scn.ron";
+
+/// Leaderboard file and size
+pub const LEADERBOARD_FILE: &str = "leaderboard.ron";
+/// Runs kept per level
+pub const LEADERBOARD_SIZE: usize = 5;
+
+/// Personal-best speedrun splits
+pub const SPLITS_FILE: &str = "splits.ron";
+pub const LEADERBOARD_INITIALS: usize = 3;
+
+/// Settings file
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// Directory F12 screenshots are saved to
+pub const SCREENSHOTS_DIR: &str = "screenshots";
+
+/// Health and HUD constants
+pub const PLAYER_MAX_HEALTH: u32 = 3;
+pub const HUD_HEART_SIZE: f32 = 16.0;
+pub const HUD_FONT_SIZE: f32 = 18.0;
+pub const HUD_MARGIN: f32 = 10.0;
+
+/// Minimap constants: corner width (the height follows the level's aspect
+/// ratio), full-map overlay width (% of the window), how many tiles around
+/// the player are revealed and the marker size
+pub const MINIMAP_WIDTH: f32 = 200.0;
+pub const MINIMAP_EXPANDED_WIDTH: f32 = 80.0;
+pub const MINIMAP_REVEAL_RADIUS: i32 = 8;
+pub const MINIMAP_MARKER_SIZE: f32 = 6.0;
+
+/// Draw layer z: between the tiles (`TILE_LAYER_Z`) and the particles, with
+/// y-sorted objects spread over the range in between
+pub const DRAW_LAYER_BEHIND_Z: f32 = 0.1;
+pub const DRAW_LAYER_SORTED_MIN_Z: f32 = 0.2;
+pub const DRAW_LAYER_SORTED_MAX_Z: f32 = 0.8;
+pub const DRAW_LAYER_FRONT_Z: f32 = 0.9;
+
+/// Collectible constants
+pub const COLLECTIBLE_PICKUP_RADIUS: f32 = 14.0;
+pub const COLLECTIBLE_SIZE: f32 = 10.0;
+
+/// Particle constants
+pub const PICKUP_PARTICLE_COUNT: u32 = 8;
+pub const PICKUP_PARTICLE_SPEED: f32 = 80.0;
+pub const PICKUP_PARTICLE_LIFETIME: f32 = 0.4;
+pub const DUST_PARTICLE_COUNT: u32 = 3;
+pub const DUST_PARTICLE_LIFETIME: f32 = 0.35;
+pub const LANDING_PARTICLE_COUNT: u32 = 10;
+pub const LANDING_PARTICLE_LIFETIME: f32 = 0.45;
+pub const DEBRIS_PARTICLE_COUNT: u32 = 6;
+pub const DEBRIS_PARTICLE_LIFETIME: f32 = 0.9;
+pub const DEBRIS_GRAVITY: f32 = 600.0;
+/// Particles are neither emitted nor kept this far outside the camera view
+pub const PARTICLE_CULL_MARGIN: f32 = 32.0;
+/// Hidden particles kept for reuse; extra ones are despawned
+pub const PARTICLE_POOL_CAPACITY: usize = 256;
+
+/// Score constants
+pub const COIN_POINTS: u32 = 10;
+pub const GEM_POINTS: u32 = 50;
+pub const ENEMY_KILL_POINTS: u32 = 100;
+/// Seconds after a scoring event during which the next one extends the combo
+pub const COMBO_WINDOW: f32 = 2.0;
+pub const MAX_COMBO_MULTIPLIER: u32 = 5;
+pub const LEVEL_PAR_TIME: f32 = 120.0;
+pub const TIME_BONUS_PER_SECOND: u32 = 10;
+
+/// Ghost replay constants: samples recorded per second of level time
+pub const GHOST_SAMPLE_RATE: f32 = 20.0;
+
+/// Lives and checkpoint constants
+pub const PLAYER_LIVES: u32 = 3;
+pub const CHECKPOINT_WIDTH: f32 = 6.0;
+pub const CHECKPOINT_HEIGHT: f32 = 24.0;
+pub const CHECKPOINT_RADIUS: f32 = 16.0;
+/// Distance below the level's bottom edge at which a falling player dies
+pub const KILL_PLANE_MARGIN: f32 = 64.0;
+/// Seconds the death animation and screen fade-out last before the respawn
+pub const DEATH_SEQUENCE_TIME: f32 = 1.2;
+/// Seconds the screen takes to clear again after the respawn
+pub const RESPAWN_FADE_TIME: f32 = 0.4;
+
+/// Popup constants
+pub const POPUP_POOL_SIZE: usize = 16;
+pub const POPUP_LIFETIME: f32 = 0.8;
+pub const POPUP_RISE_SPEED: f32 = 30.0;
+pub const POPUP_FONT_SIZE: f32 = 10.0;
+pub const POPUP_Z: f32 = 10.0;
+
+/// Key and door constants
+pub const KEY_WIDTH: f32 = 8.0;
+pub const KEY_HEIGHT: f32 = 12.0;
+pub const KEY_PICKUP_RADIUS: f32 = 14.0;
+pub const DOOR_OPEN_TIME: f32 = 0.5;
+
+/// Chest and loot constants
+pub const LOOT_TABLES_PATH: &str = "assets/config/loot_tables.ron";
+pub const CHEST_WIDTH: f32 = 16.0;
+pub const CHEST_HEIGHT: f32 = 10.0;
+pub const CHEST_LID_HEIGHT: f32 = 4.0;
+pub const CHEST_INTERACT_RANGE: f32 = 20.0;
+pub const CHEST_OPEN_TIME: f32 = 0.4;
+/// Upward speed of loot popping out of a chest; the sideways speed is a random
+/// fraction of it
+pub const LOOT_POP_SPEED: f32 = 140.0;
+pub const POWER_UP_SIZE: f32 = 10.0;
+pub const POWER_UP_PICKUP_RADIUS: f32 = 14.0;
+
+/// Prefab and enemy constants
+pub const PREFABS_PATH: &str = "assets/config/prefabs.ron";
+/// Half extents of the player's body when checking enemy contact
+pub const PLAYER_CONTACT_HALF_WIDTH: f32 = 5.0;
+pub const PLAYER_CONTACT_HALF_HEIGHT: f32 = 15.0;
+/// Seconds an enemy waits before it can hurt the player again
+pub const ENEMY_HIT_COOLDOWN: f32 = 1.0;
+/// How far past its edges an enemy looks for walls ahead and ground below
+pub const ENEMY_PROBE_DISTANCE: f32 = 2.0;
+/// Upward speed the player bounces off with after stomping an enemy
+pub const STOMP_BOUNCE_SPEED: f32 = 250.0;
 
-/// Parallax constants
+/// Falling hazard constants
+/// How far below a hazard the player is detected
+pub const HAZARD_TRIGGER_RANGE: f32 = 200.0;
+pub const HAZARD_SHAKE_TIME: f32 = 0.5;
+pub const HAZARD_SHAKE_AMPLITUDE: f32 = 1.5;
+pub const HAZARD_SHAKE_FREQUENCY: f32 = 40.0;
+pub const STALACTITE_WIDTH: f32 = 10.0;
+pub const STALACTITE_HEIGHT: f32 = 20.0;
+pub const BOULDER_RADIUS: f32 = 8.0;
+
+/// Moving hazard constants
+pub const SAW_BLADE_RADIUS: f32 = 10.0;
+/// Radians per second a saw blade spins
+pub const SAW_BLADE_SPIN_SPEED: f32 = 12.0;
+pub const CRUSHER_WIDTH: f32 = 32.0;
+pub const CRUSHER_HEIGHT: f32 = 24.0;
+/// Part of a crusher's cycle spent slamming; the rest pulls it back
+pub const CRUSHER_SLAM_FRACTION: f32 = 0.15;
+pub const MOVING_HAZARD_HIT_COOLDOWN: f32 = 1.0;
+
+/// Rising liquid constants
+/// Distance below the respawn point the surface is lowered to on respawn
+pub const RISING_LIQUID_RESPAWN_GAP: f32 = 96.0;
+pub const RISING_LIQUID_Z: f32 = 6.0;
+
+/// Weather constants
+/// Particles spawned per second at intensity 1.0
+pub const RAIN_SPAWN_RATE: f32 = 120.0;
+pub const SNOW_SPAWN_RATE: f32 = 30.0;
+pub const RAIN_FALL_SPEED: f32 = 420.0;
+pub const SNOW_FALL_SPEED: f32 = 40.0;
+pub const SNOW_SWAY: f32 = 12.0;
+pub const WEATHER_MAX_PARTICLES: usize = 600;
+/// Z range of weather particles: the far end sits behind the nearest parallax
+/// layer, the near end in front of the level
+pub const WEATHER_MIN_Z: f32 = -15.0;
+pub const WEATHER_MAX_Z: f32 = 5.0;
+
+/// Lighting constants: lights sent to the shader per frame (the nearest to
+/// the camera), z of the darkness overlay (above the level, below the UI)
+pub const MAX_LIGHTS: usize = 32;
+pub const LIGHTING_Z: f32 = 8.0;
+pub const LIGHTING_SHADER_PATH: &str = "shaders/lighting.wgsl";
+/// Right click within this distance of a light removes it in the editor
+pub const LIGHT_PICK_RADIUS: f32 = 12.0;
+
+/// Sound effect constants
+pub const SOUND_REGISTRY_PATH: &str = "assets/config/sounds.ron";
+/// World units per audio unit: spatial sounds are positioned in pixels
+pub const AUDIO_SPATIAL_SCALE: f32 = 1.0 / 200.0;
+/// Distance between the listener's ears, in pixels
+pub const AUDIO_EAR_GAP: f32 = 160.0;
+/// Landings faster than this send `PlayerLanded` (and play the land sound)
+pub const LAND_MIN_SPEED: f32 = 150.0;
+/// Default distances (world units) over which looping sound sources fade out
+pub const EMITTER_MIN_DISTANCE: f32 = 48.0;
+pub const EMITTER_MAX_DISTANCE: f32 = 320.0;
+/// Seconds to crossfade between ambient loops
+pub const AMBIENT_CROSSFADE_TIME: f32 = 2.0;
+/// Run animation frames (counted from its first frame) where a foot touches
+/// the ground
+pub const FOOTSTEP_FRAMES: [usize; 2] = [1, 4];
+
+/// Music constants
+/// Default seconds for crossfading between music tracks
+pub const MUSIC_CROSSFADE_TIME: f32 = 1.5;
+/// Seconds for a music stem to fade in or out
+pub const MUSIC_STEM_FADE_TIME: f32 = 1.0;
+/// Music intensity during a boss fight and while the player is low on health
+pub const MUSIC_INTENSITY_COMBAT: f32 = 1.0;
+pub const MUSIC_INTENSITY_LOW_HEALTH: f32 = 0.5;
+/// Health at or below which the player counts as low on health
+pub const LOW_HEALTH: u32 = 1;
+
+/// Goal constants
+pub const GOAL_WIDTH: f32 = 16.0;
+pub const GOAL_HEIGHT: f32 = 32.0;
+
+/// Dialogue constants
+pub const DIALOGUE_DIRECTORY: &str = "assets/dialogue";
+pub const DIALOGUE_CHARS_PER_SECOND: f32 = 40.0;
+pub const DIALOGUE_PORTRAIT_SIZE: f32 = 64.0;
+pub const NPC_TALK_RANGE: f32 = 24.0;
+
+/// Tutorial hint constants: seconds a hint stays on screen, the last
+/// `HINT_FADE_TIME` of them fading out
+pub const HINT_DURATION: f32 = 4.0;
+pub const HINT_FADE_TIME: f32 = 0.8;
+pub const HINT_FONT_SIZE: f32 = 16.0;
+
+/// Cutscene constants
+pub const CUTSCENE_DIRECTORY: &str = "assets/cutscenes";
+
+/// Rope constants
+pub const ROPE_SEGMENT_LENGTH: f32 = 8.0;
+pub const ROPE_SEGMENT_WIDTH: f32 = 3.0;
+pub const ROPE_SEGMENT_MASS: f32 = 0.2;
+pub const ROPE_GRAB_RANGE: f32 = 12.0;
+pub const ROPE_SWING_IMPULSE: f32 = 40.0;
+
+/// Water constants
+pub const WATER_GRAVITY_SCALE: f32 = 0.25;
+pub const WATER_MAX_SINK_SPEED: f32 = 60.0;
+pub const WATER_SPEED_FACTOR: f32 = 0.6;
+/// Spacing of the flow arrows drawn over currents in the editor
+pub const WATER_CURRENT_ARROW_SPACING: f32 = 32.0;
+
+/// Character controller config file
+pub const CHARACTER_PHYSICS_CONFIG_PATH: &str = "assets/config/character_physics.ron";
+
+/// Tile material constants
+/// Landings slower than this don't bounce on bouncy tiles
+pub const TILE_BOUNCE_MIN_SPEED: f32 = 150.0;
+
+/// Tile palette constants: on-screen size of the editor's tile buttons and
+/// of the selected tile preview
+pub const TILE_PALETTE_BUTTON_SIZE: f32 = 24.0;
+pub const TILE_PALETTE_PREVIEW_SIZE: f32 = 64.0;
+/// Flood fill constants: default and highest area (in tiles) the editor's
+/// fill tool may change at once
+pub const TILE_FILL_DEFAULT_AREA: u32 = 512;
+pub const TILE_FILL_MAX_AREA: u32 = 16384;
+
+/// One-way platform constants
+pub const PLATFORM_DROP_TIME: f32 = 0.25;
+
+/// Parallax constants (fallback when the parallax config can't be loaded)
+pub const PARALLAX_CONFIG_PATH: &str = "assets/config/parallax.ron";
 pub const PARALLAX_BACKGROUND_0_SPEED: f32 = 0.03;
 pub const PARALLAX_BACKGROUND_1_SPEED: f32 = 0.1;
 pub const PARALLAX_BACKGROUND_2_SPEED: f32 = 0.2;
+pub const PARALLAX_BACKGROUND_0_SPEED_Y: f32 = 0.01;
+pub const PARALLAX_BACKGROUND_1_SPEED_Y: f32 = 0.05;
+pub const PARALLAX_BACKGROUND_2_SPEED_Y: f32 = 0.1;
+/// Camera movement (in pixels) below which unchanged layers aren't updated
+pub const PARALLAX_CAMERA_EPSILON: f32 = 0.01;
+
+/// Day/night cycle length in seconds
+pub const DAY_NIGHT_CYCLE_SECONDS: f32 = 240.0;
 
 /// Default window dimensions (fallback values)
 pub const DEFAULT_WINDOW_WIDTH: f32 = 1280.0;
@@ -55,6 +375,65 @@ pub const DEFAULT_WINDOW_HEIGHT: f32 = 720.0;
 /// Background layer scaling
 pub const BACKGROUND_SCALE_FACTOR: f32 = 1.0;
 
-/// Camera settings
-pub const CAMERA_FOLLOW_SPEED: f32 = 5.0;
-pub const CAMERA_OFFSET_Y: f32 = 100.0;
+/// Free-fly debug camera speed (pixels per second at zoom 1.0, Shift
+/// triples it) and zoom limits
+pub const FREE_CAMERA_SPEED: f32 = 400.0;
+pub const FREE_CAMERA_ZOOM_STEP: f32 = 0.1;
+pub const FREE_CAMERA_MIN_ZOOM: f32 = 0.25;
+pub const FREE_CAMERA_MAX_ZOOM: f32 = 6.0;
+
+/// Camera shake constants
+/// Furthest the camera strays at full trauma
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 12.0;
+/// How fast the offset swings back and forth (radians per second)
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 40.0;
+/// Trauma worn off per second
+pub const CAMERA_SHAKE_DECAY: f32 = 1.5;
+/// Trauma added when a player takes damage
+pub const CAMERA_SHAKE_DAMAGE_TRAUMA: f32 = 0.5;
+/// Trauma added when a falling hazard crashes
+pub const CAMERA_SHAKE_CRASH_TRAUMA: f32 = 0.35;
+
+/// Debug time constants
+/// Speeds cycled through by the time scale key
+pub const DEBUG_TIME_SCALES: [f32; 3] = [1.0, 0.5, 0.25];
+
+/// Logging constants
+/// Filter for noisy dependencies (Bevy's default); everything else outside
+/// the game logs at info
+pub const LOG_ENGINE_FILTER: &str = "wgpu=error,naga=warn";
+/// `systems` modules whose verbosity can be set from the debug window
+pub const LOG_MODULES: [&str; 6] = [
+    "camera",
+    "console",
+    "debug",
+    "level_loader",
+    "tiles",
+    "time_scale",
+];
+/// Editor and debug tool modules logged at trace level with the editor
+/// verbose flag
+pub const EDITOR_LOG_MODULES: [&str; 4] = ["camera", "console", "debug", "time_scale"];
+
+/// Co-op constants
+/// Players at once: player one on the keyboard plus gamepad players
+pub const MAX_PLAYERS: usize = 2;
+/// How far a gamepad stick must be pushed to count as a direction
+pub const GAMEPAD_STICK_THRESHOLD: f32 = 0.5;
+/// Space kept around the players when the camera frames them all
+pub const COOP_CAMERA_MARGIN: f32 = 96.0;
+/// Furthest the camera zooms out to keep the players in view
+pub const COOP_CAMERA_MAX_ZOOM: f32 = 2.0;
+
+/// Network constants
+/// UDP port `net host` listens on without one
+pub const NET_DEFAULT_PORT: u16 = 7777;
+/// Packets sent per second each way
+pub const NET_SEND_RATE: f32 = 30.0;
+/// Seconds without a packet from the peer before the session ends
+pub const NET_TIMEOUT: f32 = 5.0;
+/// How far the client's predicted player may drift from the host's before
+/// it is corrected
+pub const NET_CORRECTION_DISTANCE: f32 = 8.0;
+/// Unacknowledged client inputs kept for resending (about two seconds)
+pub const NET_MAX_PENDING_INPUTS: usize = 128;
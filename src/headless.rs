@@ -0,0 +1,163 @@
+//! Running the game without a window or renderer, for integration tests
+//!
+//! `headless_app()` builds the gameplay plugins on `MinimalPlugins` and
+//! advances exactly one fixed timestep per `App::update()`, so a test runs
+//! the same simulation every time; the helpers load a level, hold actions
+//! for a number of frames and read the player back
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use bevy::asset::io::AssetSourceBuilder;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::transform::TransformPlugin;
+
+use crate::components::{
+    ActiveCutscene, CurrentLevel, GameState, Health, InputAction, LevelLoadError, PlayerVelocity,
+    SaveDirectory, Settings, TileSpawnQueue,
+};
+use crate::constants::FIXED_TIMESTEP_HZ;
+use crate::plugins::{AnimationPlugin, GamePlugin, LevelPlugin, PlayerPlugin};
+use crate::systems::level_loader::switch_level;
+
+/// Frames `load_level()` waits for the level to load and its tiles to spawn
+/// before giving up
+const LEVEL_LOAD_FRAME_LIMIT: u32 = 600;
+
+/// Headless apps built so far in this process, numbering their save
+/// directories
+static HEADLESS_APPS: AtomicU32 = AtomicU32::new(0);
+
+/// Fresh directory under the system's temporary one for a headless app's
+/// save, settings and record files, so tests start from defaults and never
+/// touch the player's `saves/`
+fn temp_save_directory() -> SaveDirectory {
+    let n = HEADLESS_APPS.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("bevy_sidescroller-{}-{}", std::process::id(), n));
+    // Left over from an earlier run that reused the process id
+    let _ = std::fs::remove_dir_all(&dir);
+    SaveDirectory(dir.to_string_lossy().into_owned())
+}
+
+/// App with the game, player, animation and level plugins, without windowing,
+/// rendering, audio output, the parallax backgrounds, the editor or the
+/// debug tools
+///
+/// Assets load as in the game but images and sounds are never decoded, and
+/// the `tests://` asset source reads from `tests/` for test-only levels
+///
+/// Saves and settings go to a fresh temporary directory instead of `saves/`
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.register_asset_source("tests", AssetSourceBuilder::platform_default("tests", None))
+        .add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            InputPlugin,
+            StatesPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+        ))
+        // Asset types the game loads or creates, normally registered by the
+        // render, sprite and audio plugins
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<AudioSource>()
+        .init_asset::<Mesh>()
+        // One fixed step per update, however long the update actually took
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / FIXED_TIMESTEP_HZ,
+        )))
+        .insert_resource(temp_save_directory())
+        .add_plugins((GamePlugin, PlayerPlugin, AnimationPlugin, LevelPlugin));
+    app
+}
+
+/// Replaces the startup level with the one at the asset path `path`, waits
+/// for it to load and its tiles to spawn and starts playing, skipping its
+/// intro cutscene
+/// Panics if the level can't be loaded
+pub fn load_level(app: &mut App, path: &str) {
+    // Startup requests the registry's first level and spawns the player
+    app.update();
+    switch_level(app.world_mut(), path);
+
+    let mut frames = 0;
+    while app
+        .world()
+        .get_resource::<CurrentLevel>()
+        .is_none_or(|current| current.path != path)
+        || app.world().contains_resource::<TileSpawnQueue>()
+    {
+        if let Some(error) = app.world().get_resource::<LevelLoadError>() {
+            panic!("{}", error.0);
+        }
+        assert!(
+            frames < LEVEL_LOAD_FRAME_LIMIT,
+            "level '{}' still loading after {} frames",
+            path,
+            frames
+        );
+        app.update();
+        frames += 1;
+    }
+    app.world_mut().remove_resource::<ActiveCutscene>();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+}
+
+/// Runs `frames` updates, i.e. `frames` fixed steps
+pub fn run_frames(app: &mut App, frames: u32) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Holds the key bound to `action` until `release()`
+pub fn press(app: &mut App, action: InputAction) {
+    let key = app.world().resource::<Settings>().key_bindings.key(action);
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+}
+
+/// Lets go of the key bound to `action`
+pub fn release(app: &mut App, action: InputAction) {
+    let key = app.world().resource::<Settings>().key_bindings.key(action);
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(key);
+}
+
+/// Holds `action` for `frames` updates, then releases it
+pub fn hold(app: &mut App, action: InputAction, frames: u32) {
+    press(app, action);
+    run_frames(app, frames);
+    release(app, action);
+}
+
+/// World position of the player
+/// Panics if there isn't exactly one player
+pub fn player_position(app: &mut App) -> Vec2 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<PlayerVelocity>>()
+        .single(app.world())
+        .expect("expected exactly one player")
+        .translation
+        .truncate()
+}
+
+/// Hit points of the player
+/// Panics if there isn't exactly one player
+pub fn player_health(app: &mut App) -> Health {
+    *app.world_mut()
+        .query_filtered::<&Health, With<PlayerVelocity>>()
+        .single(app.world())
+        .expect("expected exactly one player")
+}
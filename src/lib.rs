@@ -0,0 +1,45 @@
+//! Bevy Sidescroller Game
+//!
+//! A 2D sidescroller game built with Bevy and Rapier2D physics.
+//! Features character movement, animations, and sprite flipping.
+//!
+//! `SidescrollerPlugin` registers the whole game on top of Bevy's
+//! `DefaultPlugins`; the modules are public so examples and tests can use
+//! the level, tile and component types directly, and `headless` runs the
+//! gameplay without a window for integration tests.
+
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+pub mod components;
+pub mod constants;
+pub mod headless;
+pub mod plugins;
+pub mod systems;
+
+use plugins::{
+    AnimationPlugin, DebugPlugin, EditorPlugin, GamePlugin, LevelPlugin, NetworkPlugin,
+    ParallaxPlugin, PlayerPlugin,
+};
+
+/// The whole game, as a plugin group: `GamePlugin` plus the player,
+/// animation, level, parallax, editor, debug and network plugins.
+///
+/// Add it after `DefaultPlugins` (see `main.rs` for the window, audio and log
+/// configuration the game expects); leave plugins out with e.g.
+/// `SidescrollerPlugin.build().disable::<DebugPlugin>()`.
+pub struct SidescrollerPlugin;
+
+impl PluginGroup for SidescrollerPlugin {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GamePlugin)
+            .add(PlayerPlugin)
+            .add(AnimationPlugin)
+            .add(LevelPlugin)
+            .add(ParallaxPlugin)
+            .add(EditorPlugin)
+            .add(DebugPlugin)
+            .add(NetworkPlugin)
+    }
+}
@@ -1,59 +1,41 @@
 //! Bevy Sidescroller Game
 //!
-//! A 2D sidescroller game built with Bevy and Rapier2D physics.
-//! Features character movement, animations, and sprite flipping.
+//! Runs the game from the `bevy_sidescroller` library's `SidescrollerPlugin`
 
+use bevy::audio::{AudioPlugin, SpatialScale};
+use bevy::log::{Level, LogPlugin};
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
-use bevy_egui::EguiPlugin;
 
-mod components;
-mod constants;
-mod systems;
-
-use constants::{DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH, PIXELS_PER_METER};
-use systems::{
-    debug_tile_collisions, debug_tile_grid, debug_tile_info, debug_tileset_info,
-    execute_animations, move_player, setup_graphics,
-    setup_physics, toggle_debug_render, update_animation_state, update_facing_direction,
+use bevy_sidescroller::constants::{
+    AUDIO_SPATIAL_SCALE, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH, LOG_ENGINE_FILTER,
 };
+use bevy_sidescroller::systems::log_filter_layer;
+use bevy_sidescroller::SidescrollerPlugin;
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Bevy Sidescroller".into(),
-                resolution: (DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT).into(),
-                resizable: true,
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(EguiPlugin::default())
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
-            PIXELS_PER_METER,
-        ))
-        .add_plugins(RapierDebugRenderPlugin::default())
-        .add_systems(
-            Startup,
-            (
-                setup_graphics,
-                setup_physics,
-            ),
-        )
-        .add_systems(
-            Update,
-            (
-                toggle_debug_render,
-                move_player,
-                update_facing_direction,
-                update_animation_state,
-                execute_animations,
-                debug_tile_info,
-                debug_tile_grid,
-                debug_tile_collisions,
-                debug_tileset_info,
-            ),
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Bevy Sidescroller".into(),
+                        resolution: (DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT).into(),
+                        resizable: true,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .set(AudioPlugin {
+                    default_spatial_scale: SpatialScale::new_2d(AUDIO_SPATIAL_SCALE),
+                    ..default()
+                })
+                .set(LogPlugin {
+                    // `log_filter_layer` filters the game's logs per `LogSettings`
+                    level: Level::TRACE,
+                    filter: LOG_ENGINE_FILTER.to_string(),
+                    custom_layer: log_filter_layer,
+                }),
         )
+        .add_plugins(SidescrollerPlugin)
         .run();
 }
@@ -0,0 +1,830 @@
+//! The game's plugins, composed by `SidescrollerPlugin`
+//!
+//! `GamePlugin` is the core the others build on; the player, animation,
+//! level, parallax, editor, debug and network plugins each register their
+//! own resources, events and systems and can be left out of the group
+
+use bevy::ecs::schedule::InternedSystemSet;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::sprite::Material2dPlugin;
+use bevy_egui::{EguiPlugin, EguiPrimaryContextPass};
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    ActiveCheckpoint, ActiveCutscene, AnimationFrameEvent, BossDefeated, BossFightStarted,
+    CameraShake, DamageDealt, DamageEvent, DebugConsole, DebugSettings, DebugTime, EditorState,
+    FreeCamera, GameState, GhostRecorder, LevelAsset, LevelCompleted, LevelLoadError, LevelLoaded,
+    LevelTimer, LightingEditor, Lives, LogSettings, Minimap, MusicController, MusicIntensity,
+    NetworkCommand, NetworkSession, Particle, PlayerDied, PlayerJumped, PlayerLanded,
+    PlayerRespawned, PlayerStats, Pool, Popup, PrefabPalette, SaveDirectory, Score, ScoreEvent,
+    SnapshotRequest, SoundEvent, SpeedrunTimer, TeleportPlayer, TilePalette, TilePlaced,
+    TileRemoved, TileSpawnQueue, TimeOfDay,
+};
+use crate::constants::{FIXED_TIMESTEP_HZ, PARTICLE_POOL_CAPACITY, PIXELS_PER_METER};
+use crate::systems::{
+    activate_checkpoints, advance_dialogue, advance_from_results, animate_block_bounces,
+    animate_chests, animate_doors, apply_audio_buses, apply_buoyancy,
+    apply_character_physics_config, apply_damage, apply_day_night_tint, apply_draw_layers,
+    apply_game_config, apply_gate_states, apply_level_asset, apply_log_settings,
+    apply_network_input, apply_respawn_zones, apply_score_events, apply_settings,
+    attach_tile_lights, auto_scroll_parallax, award_objective_bonuses, capture_console_keyboard,
+    capture_network_input, carry_on_platforms, character_physics_debug_ui, check_dialogue_triggers,
+    clear_buffered_input, click_to_teleport, close_leaderboard, collect_keys, collect_pickups,
+    collect_power_ups, crossfade_ambient, crossfade_music, cull_tile_chunks, debug_console_ui,
+    debug_controller_contacts, debug_fps, debug_hotkeys, debug_settings_ui, debug_tile_collisions,
+    debug_tile_grid, debug_tile_info, debug_tileset_info, debug_time_controls,
+    despawn_network_players, detect_fall_death, detect_player_death, draw_moving_hazards,
+    draw_placed_lights, draw_tile_tool_preview, draw_water_currents, emit_landing_dust,
+    emit_run_dust, emit_tile_debris, enemy_contact, enter_boss_arenas, enter_editor,
+    enter_leaderboard_initials, execute_animations, exit_editor, fade_gate_tiles,
+    finish_death_sequence, finish_debug_step, finish_speedrun, fly_free_camera, grab_rope,
+    handle_game_over_input, handle_network_commands, handle_snapshot_requests, hit_blocks,
+    inspector_plugin, interpolate_transforms, join_coop_players, level_error_ui,
+    lighting_editor_ui, load_character_physics, load_characters, load_game_config,
+    load_leaderboard, load_level, load_loot_tables, load_prefabs, load_save, load_settings,
+    load_sound_registry, load_speedrun_records, lock_boss_arena, move_hazards, move_platforms,
+    move_player, moving_hazard_contact, open_chests, open_doors, open_leaderboard, paint_tiles,
+    patrol_enemies, place_lights, place_prefabs, play_damage_sounds, play_footsteps,
+    play_movement_sounds, play_sound_events, player_debug_ui, prefab_palette_ui,
+    prepare_leaderboard_entry, prune_tile_grid, raise_liquid, reach_goal, read_player_input,
+    rearm_falling_hazards, receive_network_messages, record_ghost, record_level_time,
+    record_network_prediction, record_physics_transforms, record_speedrun_splits, register_types,
+    release_boss_arenas, reload_game_config, replay_ghost, reset_rising_liquid,
+    restore_physics_transforms, return_to_pool, reveal_minimap, reveal_secrets,
+    rising_liquid_contact, run_cutscene, select_character, select_tile_tool, send_network_messages,
+    settings_ui, settle_camera_shake, setup_graphics, setup_hint_popup, setup_hud,
+    setup_level_music, setup_lighting, setup_minimap, setup_objectives, setup_parallax_backgrounds,
+    setup_physics, setup_popup_pool, setup_speedrun_overlay, setup_tilemap, setup_weather,
+    shake_camera, shake_camera_on_damage, show_hints, simulation_running, spawn_blocks,
+    spawn_boss_arenas, spawn_checkpoints, spawn_chests, spawn_collectibles, spawn_dialogue_box,
+    spawn_dialogue_triggers, spawn_falling_hazards, spawn_game_over_screen, spawn_ghost,
+    spawn_goal, spawn_hints, spawn_initials_prompt, spawn_keys_and_doors, spawn_leaderboard_screen,
+    spawn_level_entities, spawn_menu_screen, spawn_moving_hazards, spawn_moving_platforms,
+    spawn_pause_screen, spawn_popups, spawn_props, spawn_queued_tiles, spawn_respawn_zones,
+    spawn_results_screen, spawn_rising_liquid, spawn_ropes, spawn_secrets, spawn_selected_player,
+    spawn_sound_emitters, spawn_speedrun_summary, spawn_switches, spawn_water_currents,
+    spawn_water_volumes, spawn_weather_particles, start_audio_emitters, start_death_sequence,
+    start_game, start_intro_cutscene, start_level_intro, swap_player_character, swing_on_rope,
+    sync_debug_render, sync_world_pause, take_screenshot, tally_level_score, teleport_player,
+    tile_collisions_dirty, tile_palette_ui, toggle_editor, toggle_free_camera, toggle_minimap,
+    toggle_pause, toggle_weather, track_objectives, trigger_falling_hazards,
+    update_animation_state, update_audio_emitters, update_background_size_on_resize,
+    update_camera_follow, update_camera_path, update_dialogue_box, update_dormancy,
+    update_facing_direction, update_falling_hazards, update_ground_material, update_hint_popup,
+    update_hud_collectibles, update_hud_hearts, update_hud_liquid, update_hud_lives,
+    update_hud_objectives, update_hud_score, update_hud_timer, update_initials_text,
+    update_level_timer, update_lighting, update_minimap_markers, update_music_intensity,
+    update_network_puppets, update_one_way_platforms, update_parallax, update_particles,
+    update_popups, update_score_timers, update_screen_fade, update_speedrun_overlay,
+    update_speedrun_timer, update_switches, update_tile_collisions, update_time_of_day,
+    update_water_state, update_weather_fog, update_weather_particles, LevelAssetLoader,
+    LightingMaterial, OneWayPlatformHook, SpawnLevel,
+};
+
+/// Stages of a frame, chained in this order in `Update` and `FixedUpdate`
+/// (where the player moves and the physics objects react) by `GamePlugin`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    /// Keyboard and mouse handling: state changes, toggles and hotkeys
+    Input,
+    /// Moving things: the player, cutscene actors, teleports, who's asleep
+    Movement,
+    /// Reacting to where things ended up: platforms, pickups, triggers,
+    /// damage and score
+    Physics,
+    /// Facing direction, animation state and frames
+    Animation,
+    /// Camera follow and everything placed from the camera view
+    Camera,
+    /// Editor and debug overlays, drawn over the final positions
+    Editor,
+}
+
+/// Core of the game, required by the other plugins: Egui (when rendering) and
+/// Rapier (on the fixed timestep), the game states and their screens, the resources and
+/// events the plugins share, and the camera, cutscenes, dialogue, score, HUD
+/// and audio
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        // Egui needs the renderer, which headless apps leave out
+        if app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        app
+            // Rapier steps on the fixed timestep, like player movement
+            .add_plugins(
+                RapierPhysicsPlugin::<OneWayPlatformHook>::pixels_per_meter(PIXELS_PER_METER)
+                    .in_fixed_schedule(),
+            )
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .insert_resource(TimestepMode::Fixed {
+                dt: (1.0 / FIXED_TIMESTEP_HZ) as f32,
+                substeps: 1,
+            })
+            // Reflection for the world inspector and scene snapshots
+            .add_plugins(register_types)
+            .init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .init_resource::<SaveDirectory>()
+            .init_resource::<PlayerStats>()
+            .init_resource::<Score>()
+            .init_resource::<LevelTimer>()
+            .init_resource::<GhostRecorder>()
+            .init_resource::<SpeedrunTimer>()
+            .init_resource::<Lives>()
+            .init_resource::<ActiveCheckpoint>()
+            .init_resource::<MusicController>()
+            .init_resource::<MusicIntensity>()
+            .init_resource::<CameraShake>()
+            // Read by `simulation_running()`, so it lives here rather than in
+            // `DebugPlugin`
+            .init_resource::<DebugTime>()
+            // Pickups, enemies and hazards here emit particles too, so the
+            // pool lives here rather than in `AnimationPlugin`
+            .insert_resource(Pool::<Particle>::new("Particle", PARTICLE_POOL_CAPACITY))
+            .add_event::<ScoreEvent>()
+            .add_event::<LevelCompleted>()
+            .add_event::<PlayerDied>()
+            .add_event::<PlayerRespawned>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageDealt>()
+            .add_event::<PlayerJumped>()
+            .add_event::<PlayerLanded>()
+            .add_event::<BossDefeated>()
+            .add_event::<BossFightStarted>()
+            .add_event::<SoundEvent>()
+            .configure_sets(Update, game_sets())
+            .configure_sets(FixedUpdate, game_sets())
+            .add_systems(
+                Startup,
+                (
+                    load_settings,
+                    load_game_config,
+                    setup_graphics,
+                    setup_hint_popup,
+                    setup_hud,
+                    setup_popup_pool,
+                    load_save,
+                    load_leaderboard,
+                    load_sound_registry,
+                    load_speedrun_records,
+                    setup_speedrun_overlay,
+                ),
+            )
+            .add_systems(
+                SpawnLevel,
+                (
+                    setup_weather,
+                    setup_level_music,
+                    start_level_intro,
+                    spawn_sound_emitters,
+                    setup_objectives,
+                    start_intro_cutscene,
+                    spawn_ghost,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Menu), spawn_menu_screen)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_screen)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(OnEnter(GameState::Dialogue), spawn_dialogue_box)
+            .add_systems(
+                OnEnter(GameState::Results),
+                (
+                    spawn_results_screen,
+                    spawn_initials_prompt,
+                    spawn_speedrun_summary,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Leaderboard), spawn_leaderboard_screen)
+            .add_systems(
+                Update,
+                (
+                    // Game state transitions
+                    (
+                        start_game.run_if(in_state(GameState::Menu)),
+                        toggle_pause,
+                        sync_world_pause.after(debug_time_controls),
+                        handle_game_over_input.run_if(in_state(GameState::GameOver)),
+                        (enter_leaderboard_initials, update_initials_text)
+                            .chain()
+                            .before(advance_from_results)
+                            .run_if(in_state(GameState::Results)),
+                        advance_from_results.run_if(in_state(GameState::Results)),
+                        open_leaderboard
+                            .run_if(in_state(GameState::Menu).or(in_state(GameState::Results))),
+                        close_leaderboard.run_if(in_state(GameState::Leaderboard)),
+                    )
+                        .in_set(GameSet::Input),
+                    // Cutscenes (player control is suppressed while one plays)
+                    run_cutscene
+                        .in_set(GameSet::Movement)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    // Dialogue
+                    (
+                        check_dialogue_triggers
+                            .run_if(in_state(GameState::Playing))
+                            .run_if(simulation_running)
+                            .run_if(not(resource_exists::<ActiveCutscene>)),
+                        (advance_dialogue, update_dialogue_box)
+                            .chain()
+                            .run_if(in_state(GameState::Dialogue)),
+                    )
+                        .in_set(GameSet::Input),
+                    // Score
+                    (
+                        update_score_timers,
+                        update_level_timer.run_if(not(resource_exists::<ActiveCutscene>)),
+                        tally_level_score.after(reach_goal),
+                        record_level_time.after(reach_goal),
+                        apply_score_events
+                            .after(collect_pickups)
+                            .after(tally_level_score),
+                        track_objectives.after(collect_pickups),
+                        prepare_leaderboard_entry.after(apply_score_events),
+                        award_objective_bonuses
+                            .after(reach_goal)
+                            .before(apply_score_events),
+                        spawn_popups
+                            .after(collect_pickups)
+                            .after(apply_damage)
+                            .before(apply_score_events),
+                        (update_popups, return_to_pool::<Popup>).chain(),
+                    )
+                        .in_set(GameSet::Physics)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    // Speedrun clock and splits (real time, so it also runs paused,
+                    // in dialogue and on the game over screen)
+                    (
+                        update_speedrun_timer,
+                        record_speedrun_splits.after(activate_checkpoints),
+                        finish_speedrun.after(reach_goal),
+                    )
+                        .chain()
+                        .in_set(GameSet::Physics)
+                        .run_if(
+                            in_state(GameState::Playing)
+                                .or(in_state(GameState::Paused))
+                                .or(in_state(GameState::Dialogue))
+                                .or(in_state(GameState::GameOver)),
+                        ),
+                    // Ghost replay of the best run (records the pose this
+                    // frame's animations settled on)
+                    (record_ghost.after(execute_animations), replay_ghost)
+                        .in_set(GameSet::Animation)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    // Camera and weather
+                    (
+                        (
+                            settle_camera_shake
+                                .before(update_camera_follow)
+                                .before(update_camera_path),
+                            update_camera_follow,
+                            update_camera_path,
+                            shake_camera
+                                .after(update_camera_follow)
+                                .after(update_camera_path),
+                        )
+                            .run_if(not(resource_exists::<FreeCamera>)),
+                        (
+                            toggle_weather,
+                            update_weather_fog,
+                            spawn_weather_particles,
+                            update_weather_particles,
+                        )
+                            .chain()
+                            .after(update_camera_follow),
+                    )
+                        .in_set(GameSet::Camera)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    // Tuning (all states)
+                    (reload_game_config, apply_game_config).chain(),
+                    take_screenshot,
+                    // HUD and audio (all states)
+                    (
+                        update_hud_hearts,
+                        update_hud_lives,
+                        update_hud_objectives,
+                        update_hud_collectibles,
+                        update_hud_timer.after(update_level_timer),
+                        update_hud_score.after(apply_score_events),
+                        update_hud_liquid,
+                        update_speedrun_overlay.after(finish_speedrun),
+                        apply_settings,
+                        crossfade_music,
+                        play_damage_sounds.after(apply_damage),
+                        play_movement_sounds,
+                        play_sound_events
+                            .after(play_damage_sounds)
+                            .after(play_movement_sounds),
+                        (start_audio_emitters, update_audio_emitters)
+                            .chain()
+                            .after(update_camera_follow),
+                        crossfade_ambient,
+                        apply_audio_buses
+                            .after(crossfade_music)
+                            .after(crossfade_ambient)
+                            .after(update_audio_emitters),
+                    ),
+                ),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                (
+                    settings_ui.run_if(in_state(GameState::Menu).or(in_state(GameState::Paused))),
+                    // A failed load returns to the menu, a failed hot reload keeps playing
+                    level_error_ui
+                        .run_if(resource_exists::<LevelLoadError>)
+                        .run_if(
+                            in_state(GameState::Menu)
+                                .or(in_state(GameState::Playing))
+                                .or(in_state(GameState::Editor)),
+                        ),
+                ),
+            );
+    }
+}
+
+/// The player: spawning it as the character picked in the menu, gamepad
+/// players joining in co-op, each player's input and movement and the
+/// physics objects they interact with on the fixed timestep (drawn
+/// interpolated between steps), and death and respawn
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            (
+                setup_physics,
+                load_character_physics,
+                load_characters,
+                spawn_selected_player
+                    .after(load_settings)
+                    .after(load_character_physics)
+                    .after(load_characters),
+            ),
+        )
+        .add_systems(
+            RunFixedMainLoop,
+            (
+                read_player_input
+                    .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(simulation_running),
+                interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            ),
+        )
+        .add_systems(FixedFirst, restore_physics_transforms)
+        // Rapier steps right after, in `FixedPostUpdate`
+        .add_systems(
+            FixedUpdate,
+            (
+                (
+                    (
+                        update_water_state,
+                        (grab_rope, swing_on_rope)
+                            .chain()
+                            .before(move_player)
+                            .run_if(not(resource_exists::<ActiveCutscene>))
+                            .run_if(not(resource_exists::<FreeCamera>)),
+                        move_player
+                            .after(update_water_state)
+                            .run_if(not(resource_exists::<ActiveCutscene>))
+                            .run_if(not(resource_exists::<FreeCamera>)),
+                        update_ground_material.before(move_player),
+                        apply_character_physics_config.before(move_player),
+                    )
+                        .in_set(GameSet::Movement),
+                    (
+                        update_one_way_platforms,
+                        apply_buoyancy,
+                        move_platforms,
+                        carry_on_platforms.after(move_platforms),
+                    )
+                        .in_set(GameSet::Physics),
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(simulation_running),
+                clear_buffered_input.after(GameSet::Physics),
+            ),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            record_physics_transforms.after(PhysicsSet::Writeback),
+        )
+        .add_systems(
+            Update,
+            (select_character, swap_player_character)
+                .chain()
+                .in_set(GameSet::Input)
+                .run_if(in_state(GameState::Menu)),
+        )
+        .add_systems(
+            Update,
+            join_coop_players
+                .in_set(GameSet::Input)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                apply_damage,
+                shake_camera_on_damage,
+                detect_player_death.after(apply_damage),
+                detect_fall_death,
+                start_death_sequence
+                    .after(detect_player_death)
+                    .after(detect_fall_death),
+                finish_death_sequence.after(start_death_sequence),
+                update_screen_fade.after(finish_death_sequence),
+            )
+                .in_set(GameSet::Physics)
+                .run_if(in_state(GameState::Playing))
+                .run_if(simulation_running),
+        );
+    }
+}
+
+/// Sprite animations, facing direction, footsteps and dust on animation
+/// frames, particle effects, draw order by y, and putting far off-screen
+/// animated entities to sleep
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationFrameEvent>().add_systems(
+            Update,
+            (
+                (
+                    update_facing_direction,
+                    update_animation_state,
+                    execute_animations,
+                    play_footsteps.before(play_sound_events),
+                )
+                    .chain()
+                    .in_set(GameSet::Animation),
+                apply_draw_layers.in_set(GameSet::Animation),
+                // Wakes and sleeps from last frame's camera view, ahead of the
+                // hazard triggers and animations it gates
+                update_dormancy.in_set(GameSet::Movement),
+                // Particle effects from this frame's footsteps, landings and
+                // removed tiles
+                (
+                    emit_run_dust.after(execute_animations),
+                    emit_landing_dust,
+                    emit_tile_debris.after(prune_tile_grid),
+                    (update_particles, return_to_pool::<Particle>).chain(),
+                )
+                    .in_set(GameSet::Animation),
+            )
+                .run_if(in_state(GameState::Playing))
+                .run_if(simulation_running),
+        );
+    }
+}
+
+/// Loading and spawning levels: tiles (spawned over several frames, culled
+/// by chunk), their colliders, the level's interactables, lighting and the
+/// day/night cycle
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        // Headless apps have no renderer to draw the lighting with
+        if app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(Material2dPlugin::<LightingMaterial>::default());
+        } else {
+            app.init_asset::<LightingMaterial>();
+        }
+        app.init_resource::<TimeOfDay>()
+            .init_resource::<Minimap>()
+            .init_asset::<LevelAsset>()
+            .init_asset_loader::<LevelAssetLoader>()
+            .add_event::<LevelLoaded>()
+            .add_event::<TilePlaced>()
+            .add_event::<TileRemoved>()
+            .add_systems(Startup, (load_level, load_loot_tables, load_prefabs))
+            // Everything spawned from the level data; rerun when the level changes
+            .add_systems(
+                SpawnLevel,
+                (
+                    spawn_moving_platforms,
+                    setup_tilemap,
+                    spawn_water_volumes.after(setup_tilemap),
+                    spawn_water_currents,
+                    spawn_props,
+                    spawn_switches,
+                    spawn_ropes,
+                    spawn_keys_and_doors,
+                    spawn_dialogue_triggers,
+                    spawn_hints,
+                    spawn_checkpoints,
+                    spawn_goal,
+                    spawn_chests,
+                    (
+                        spawn_falling_hazards,
+                        spawn_moving_hazards,
+                        spawn_respawn_zones,
+                        spawn_rising_liquid,
+                    ),
+                    spawn_boss_arenas,
+                    spawn_level_entities,
+                    setup_minimap,
+                    setup_lighting,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Interactables (the player moved in this frame's fixed steps)
+                    (
+                        collect_pickups,
+                        (update_switches, apply_gate_states).chain(),
+                        fade_gate_tiles.after(apply_gate_states),
+                        reveal_secrets.before(fade_gate_tiles),
+                        collect_keys,
+                        open_doors.after(collect_keys),
+                        animate_doors,
+                        open_chests,
+                        animate_chests.after(open_chests),
+                        (show_hints, update_hint_popup).chain(),
+                        (hit_blocks, animate_block_bounces).chain(),
+                        collect_power_ups,
+                        (
+                            activate_checkpoints,
+                            apply_respawn_zones.before(apply_damage),
+                        ),
+                        (
+                            trigger_falling_hazards,
+                            update_falling_hazards
+                                .after(trigger_falling_hazards)
+                                .before(apply_damage),
+                            rearm_falling_hazards.after(finish_death_sequence),
+                            move_hazards,
+                            moving_hazard_contact
+                                .after(move_hazards)
+                                .before(apply_damage),
+                        ),
+                        (
+                            reset_rising_liquid.after(finish_death_sequence),
+                            raise_liquid,
+                            rising_liquid_contact
+                                .after(raise_liquid)
+                                .before(apply_damage),
+                        ),
+                        (
+                            patrol_enemies,
+                            enemy_contact.after(patrol_enemies).before(apply_damage),
+                        ),
+                        (
+                            enter_boss_arenas,
+                            lock_boss_arena.after(enter_boss_arenas),
+                            update_music_intensity
+                                .after(lock_boss_arena)
+                                .after(release_boss_arenas)
+                                .after(apply_damage),
+                            release_boss_arenas
+                                .after(lock_boss_arena)
+                                .after(finish_death_sequence),
+                        ),
+                        reach_goal.run_if(not(resource_exists::<ActiveCutscene>)),
+                        (update_time_of_day, apply_day_night_tint).chain(),
+                    )
+                        .in_set(GameSet::Physics)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    // Tile loading and upkeep (all states)
+                    (
+                        spawn_queued_tiles.run_if(resource_exists::<TileSpawnQueue>),
+                        attach_tile_lights,
+                        (spawn_collectibles, spawn_secrets, spawn_blocks)
+                            .run_if(on_event::<LevelLoaded>),
+                        update_tile_collisions.run_if(tile_collisions_dirty),
+                    )
+                        .chain(),
+                    cull_tile_chunks
+                        .in_set(GameSet::Camera)
+                        .after(update_camera_follow)
+                        .after(update_camera_path)
+                        .after(shake_camera)
+                        .after(fly_free_camera),
+                    prune_tile_grid,
+                    update_lighting
+                        .in_set(GameSet::Camera)
+                        .after(update_camera_follow)
+                        .after(update_camera_path)
+                        .after(shake_camera)
+                        .after(fly_free_camera),
+                    // The requested level once loaded, and again when its file changes;
+                    // before the tile spawner, so it doesn't finish the previous level's
+                    // queue after the new level is in place
+                    apply_level_asset.before(spawn_queued_tiles),
+                    // Minimap, after the player's tile is known for the frame
+                    (
+                        toggle_minimap,
+                        (reveal_minimap, update_minimap_markers).chain(),
+                    )
+                        .in_set(GameSet::Camera)
+                        .run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
+
+/// Parallax backgrounds, rebuilt for each level and resized with the window
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(SpawnLevel, setup_parallax_backgrounds)
+            .add_systems(
+                Update,
+                (
+                    (
+                        auto_scroll_parallax.before(update_parallax),
+                        update_parallax,
+                    )
+                        .in_set(GameSet::Camera)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(simulation_running),
+                    update_background_size_on_resize,
+                ),
+            );
+    }
+}
+
+/// The editor (Tab) and its overlays, which only run while it's open
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<EditorState>()
+            .enable_state_scoped_entities::<EditorState>()
+            .init_resource::<PrefabPalette>()
+            .init_resource::<TilePalette>()
+            .init_resource::<LightingEditor>()
+            .add_systems(OnEnter(EditorState::Enabled), enter_editor)
+            .add_systems(OnExit(EditorState::Enabled), exit_editor)
+            .add_systems(
+                Update,
+                (
+                    toggle_editor
+                        .in_set(GameSet::Input)
+                        .run_if(in_state(GameState::Playing).or(in_state(EditorState::Enabled))),
+                    (
+                        draw_water_currents,
+                        draw_moving_hazards,
+                        place_prefabs,
+                        place_lights,
+                        (select_tile_tool, paint_tiles).chain(),
+                        draw_tile_tool_preview,
+                        draw_placed_lights,
+                    )
+                        .in_set(GameSet::Editor)
+                        .run_if(in_state(EditorState::Enabled)),
+                ),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                (
+                    prefab_palette_ui,
+                    tile_palette_ui,
+                    lighting_editor_ui,
+                )
+                    .run_if(in_state(EditorState::Enabled)),
+            );
+    }
+}
+
+/// Development tools: physics debug render, the debug window and overlays,
+/// log verbosity, time controls, the free camera, the console and the world
+/// inspector (with the `inspector` feature).
+///
+/// The console's `net` commands are sent as `NetworkCommand`, registered here
+/// as well as by the `NetworkPlugin` so the console works without it.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RapierDebugRenderPlugin::default())
+            .add_plugins(inspector_plugin)
+            .init_resource::<DebugConsole>()
+            .init_resource::<DebugSettings>()
+            .init_resource::<LogSettings>()
+            .add_event::<TeleportPlayer>()
+            .add_event::<SnapshotRequest>()
+            .add_event::<NetworkCommand>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        debug_hotkeys,
+                        sync_debug_render.after(debug_hotkeys),
+                        apply_log_settings,
+                        debug_time_controls,
+                        toggle_free_camera,
+                        click_to_teleport.run_if(in_state(GameState::Playing)),
+                    )
+                        .in_set(GameSet::Input),
+                    teleport_player
+                        .in_set(GameSet::Movement)
+                        .run_if(in_state(GameState::Playing)),
+                    handle_snapshot_requests
+                        .in_set(GameSet::Movement)
+                        .run_if(on_event::<SnapshotRequest>),
+                    fly_free_camera
+                        .in_set(GameSet::Camera)
+                        .run_if(resource_exists::<FreeCamera>)
+                        .run_if(in_state(GameState::Playing)),
+                    (
+                        debug_fps,
+                        debug_tile_grid,
+                        debug_tile_collisions,
+                        debug_controller_contacts,
+                    )
+                        .in_set(GameSet::Editor),
+                ),
+            )
+            // The console keeps the keyboard from gameplay while it's open
+            .add_systems(PreUpdate, capture_console_keyboard.after(InputSystem))
+            .add_systems(Last, finish_debug_step)
+            .add_systems(
+                EguiPrimaryContextPass,
+                (
+                    debug_settings_ui,
+                    character_physics_debug_ui,
+                    debug_tileset_info,
+                    debug_tile_info,
+                    debug_console_ui,
+                    player_debug_ui,
+                ),
+            );
+    }
+}
+
+/// Optional networked play between two instances, idle until the console
+/// starts a session (`net host`, `net join`): the host simulates both
+/// players, the client predicts its own and draws the host's from snapshots
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NetworkCommand>()
+            .add_systems(
+                RunFixedMainLoop,
+                receive_network_messages
+                    .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+                    .after(read_player_input)
+                    .run_if(resource_exists::<NetworkSession>),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_network_input.before(GameSet::Movement),
+                    capture_network_input
+                        .after(GameSet::Movement)
+                        .before(clear_buffered_input),
+                )
+                    .run_if(resource_exists::<NetworkSession>),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                record_network_prediction
+                    .after(PhysicsSet::Writeback)
+                    .run_if(resource_exists::<NetworkSession>),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_network_commands.in_set(GameSet::Input),
+                    update_network_puppets.in_set(GameSet::Animation),
+                    despawn_network_players.run_if(resource_removed::<NetworkSession>),
+                ),
+            )
+            .add_systems(
+                Last,
+                send_network_messages.run_if(resource_exists::<NetworkSession>),
+            );
+    }
+}
+
+/// `GameSet`s in frame order, chained
+fn game_sets() -> impl IntoScheduleConfigs<InternedSystemSet, ()> {
+    (
+        GameSet::Input,
+        GameSet::Movement,
+        GameSet::Physics,
+        GameSet::Animation,
+        GameSet::Camera,
+        GameSet::Editor,
+    )
+        .chain()
+}
@@ -0,0 +1,43 @@
+//! Activation: animated and AI entities far off screen go dormant
+
+use bevy::prelude::*;
+
+use crate::components::{Dormant, MainCamera, PlayerVelocity, SleepsOffScreen};
+use crate::constants::*;
+use crate::systems::camera::camera_view;
+
+/// Entities that opted into dormancy
+type DormancyCandidates<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static GlobalTransform, Has<Dormant>),
+    (With<SleepsOffScreen>, Without<PlayerVelocity>),
+>;
+
+/// Marks `SleepsOffScreen` entities (enemies, falling and moving hazards)
+/// outside the camera view (plus `ACTIVATION_MARGIN`) as `Dormant`, waking
+/// them as the camera approaches
+/// The player never sleeps, even with the free camera away from it
+pub fn update_dormancy(
+    mut commands: Commands,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    entities: DormancyCandidates,
+) {
+    let Some(view) = cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection))
+    else {
+        return;
+    };
+    let active_area = view.inflate(ACTIVATION_MARGIN);
+
+    for (entity, transform, dormant) in entities.iter() {
+        let active = active_area.contains(transform.translation().truncate());
+        if active && dormant {
+            commands.entity(entity).remove::<Dormant>();
+        } else if !active && !dormant {
+            commands.entity(entity).insert(Dormant);
+        }
+    }
+}
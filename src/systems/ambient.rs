@@ -0,0 +1,79 @@
+//! Ambient loops (wind, cave drips, birds) per level and camera zone,
+//! crossfaded as the player moves between zones
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::components::{
+    AmbientPlayer, AudioBus, AudioGain, LevelData, PrimaryPlayer, SoundRegistry,
+};
+use crate::constants::*;
+
+/// Ambient loop for a position: the first camera zone with one containing it,
+/// otherwise the level's
+fn ambient_at(level: &LevelData, position: Vec2) -> Option<&str> {
+    level
+        .camera_zones
+        .iter()
+        .filter(|zone| zone.ambient.is_some())
+        .find(|zone| zone.contains(position))
+        .and_then(|zone| zone.ambient.as_deref())
+        .or(level.ambient.as_deref())
+}
+
+/// Starts the loop wanted where the player is and crossfades it in while
+/// every other loop fades out and despawns
+/// Uses real time like the music; loops play on the ambient bus
+pub fn crossfade_ambient(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    level: Option<Res<LevelData>>,
+    registry: Option<Res<SoundRegistry>>,
+    players: Query<&Transform, With<PrimaryPlayer>>,
+    mut ambients: Query<(Entity, &mut AmbientPlayer, &mut AudioGain)>,
+) {
+    let wanted = match (&level, players.single()) {
+        (Some(level), Ok(transform)) => ambient_at(level, transform.translation.truncate()),
+        _ => None,
+    };
+
+    if let (Some(sound), Some(registry)) = (wanted, &registry) {
+        let playing = ambients
+            .iter()
+            .any(|(_, ambient, _)| ambient.sound == sound);
+        let handle = (!playing)
+            .then(|| registry.sample(sound, &mut rand::thread_rng()))
+            .flatten();
+        if let Some(handle) = handle {
+            commands.spawn((
+                Name::new(format!("Ambient {}", sound)),
+                AudioPlayer::new(handle),
+                PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+                AudioBus::Ambient,
+                AudioGain(0.0),
+                AmbientPlayer {
+                    sound: sound.to_string(),
+                    fade: 0.0,
+                },
+            ));
+        }
+    }
+
+    let step = time.delta_secs() / AMBIENT_CROSSFADE_TIME;
+    for (entity, mut ambient, mut gain) in ambients.iter_mut() {
+        if wanted == Some(ambient.sound.as_str()) {
+            ambient.fade = (ambient.fade + step).min(1.0);
+        } else {
+            ambient.fade -= step;
+            if ambient.fade <= 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        }
+        let volume = registry
+            .as_ref()
+            .and_then(|registry| registry.volume(&ambient.sound))
+            .unwrap_or(1.0);
+        gain.0 = volume * ambient.fade;
+    }
+}
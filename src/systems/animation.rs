@@ -3,15 +3,32 @@
 use bevy::prelude::*;
 
 use crate::components::{
-    AnimationCollection, AnimationHandles, AnimationState, FacingDirection, PlayerVelocity,
+    AnimationCollection, AnimationFrameEvent, AnimationHandles, AnimationState, Dormant, Dying,
+    FacingDirection, JumpPhase, PlayerVelocity,
 };
 
+/// Characters whose velocity or jump phase changed, unless dying
+type ChangedAnimationDrivers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static PlayerVelocity,
+        Option<&'static JumpPhase>,
+        &'static mut AnimationState,
+    ),
+    (
+        Or<(Changed<PlayerVelocity>, Changed<JumpPhase>)>,
+        Without<Dying>,
+    ),
+>;
+
 /// Updates animation state based on player movement
-pub fn update_animation_state(
-    mut query: Query<(&PlayerVelocity, &mut AnimationState), Changed<PlayerVelocity>>,
-) {
-    for (velocity, mut state) in query.iter_mut() {
-        let new_state = if velocity.0.x.abs() > 0.0 {
+/// The death sequence owns the animation state while it runs
+pub fn update_animation_state(mut query: ChangedAnimationDrivers) {
+    for (velocity, phase, mut state) in query.iter_mut() {
+        let new_state = if phase == Some(&JumpPhase::AirJumping) {
+            AnimationState::DoubleJump
+        } else if velocity.0.x.abs() > 0.0 {
             AnimationState::Run
         } else {
             AnimationState::Idle
@@ -23,21 +40,37 @@ pub fn update_animation_state(
     }
 }
 
+/// Awake sprites playing an animation
+type AnimatedSprites<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Sprite,
+        &'static mut AnimationCollection,
+        &'static AnimationHandles,
+        &'static AnimationState,
+        &'static FacingDirection,
+    ),
+    Without<Dormant>,
+>;
+
 /// Executes sprite animations, handles texture switching, and applies sprite flipping
+/// Every frame change sends an `AnimationFrameEvent` (footsteps hook into it)
+/// `Dormant` characters far off screen aren't animated
 pub fn execute_animations(
     time: Res<Time>,
-    mut query: Query<(
-        &mut Sprite,
-        &mut AnimationCollection,
-        &AnimationHandles,
-        &AnimationState,
-        &FacingDirection,
-    )>,
+    mut query: AnimatedSprites,
+    mut frame_events: EventWriter<AnimationFrameEvent>,
 ) {
-    for (mut sprite, mut collection, handles, state, facing_direction) in query.iter_mut() {
+    for (entity, mut sprite, mut collection, handles, state, facing_direction) in query.iter_mut() {
         let (target_image, target_layout) = match *state {
             AnimationState::Idle => (&handles.idle_texture, &handles.idle_layout),
             AnimationState::Run => (&handles.run_texture, &handles.run_layout),
+            AnimationState::DoubleJump => {
+                (&handles.double_jump_texture, &handles.double_jump_layout)
+            }
+            AnimationState::Dead => (&handles.dead_texture, &handles.dead_layout),
         };
 
         // Check if we need to change the texture atlas
@@ -59,10 +92,13 @@ pub fn execute_animations(
                 atlas.index = match *state {
                     AnimationState::Idle => collection.idle.first_sprite_index,
                     AnimationState::Run => collection.run.first_sprite_index,
+                    AnimationState::DoubleJump => collection.double_jump.first_sprite_index,
+                    AnimationState::Dead => collection.dead.first_sprite_index,
                 };
             }
 
             // Handle timer and animation logic
+            let previous_index = atlas.index;
             match *state {
                 AnimationState::Idle => {
                     collection.idle.frame_timer.tick(time.delta());
@@ -84,6 +120,38 @@ pub fn execute_animations(
                         };
                     }
                 }
+                AnimationState::DoubleJump => {
+                    collection.double_jump.frame_timer.tick(time.delta());
+                    if collection.double_jump.frame_timer.just_finished() {
+                        atlas.index = if atlas.index >= collection.double_jump.last_sprite_index {
+                            collection.double_jump.first_sprite_index
+                        } else {
+                            atlas.index + 1
+                        };
+                    }
+                }
+                AnimationState::Dead => {
+                    collection.dead.frame_timer.tick(time.delta());
+                    if collection.dead.frame_timer.just_finished()
+                        && atlas.index < collection.dead.last_sprite_index
+                    {
+                        atlas.index += 1;
+                    }
+                }
+            }
+
+            if atlas.index != previous_index {
+                let first = match *state {
+                    AnimationState::Idle => collection.idle.first_sprite_index,
+                    AnimationState::Run => collection.run.first_sprite_index,
+                    AnimationState::DoubleJump => collection.double_jump.first_sprite_index,
+                    AnimationState::Dead => collection.dead.first_sprite_index,
+                };
+                frame_events.write(AnimationFrameEvent {
+                    entity,
+                    state: *state,
+                    frame: atlas.index - first,
+                });
             }
         }
 
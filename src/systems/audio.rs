@@ -0,0 +1,26 @@
+//! Audio buses: the master, music, SFX and ambient volumes of the settings,
+//! applied to every playing sound
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::components::{AudioBus, AudioGain, Settings};
+
+/// Sets the volume of every playing sound to its bus volume times its own
+/// gain, so settings changes and fades reach sounds already playing
+pub fn apply_audio_buses(
+    settings: Res<Settings>,
+    mut sinks: Query<(&AudioBus, &AudioGain, &mut AudioSink)>,
+    mut spatial_sinks: Query<(&AudioBus, &AudioGain, &mut SpatialAudioSink)>,
+) {
+    for (bus, gain, mut sink) in sinks.iter_mut() {
+        sink.set_volume(Volume::Linear(
+            settings.bus_volume(*bus).to_linear() * gain.0,
+        ));
+    }
+    for (bus, gain, mut sink) in spatial_sinks.iter_mut() {
+        sink.set_volume(Volume::Linear(
+            settings.bus_volume(*bus).to_linear() * gain.0,
+        ));
+    }
+}
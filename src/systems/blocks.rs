@@ -0,0 +1,141 @@
+//! Hit blocks: question-mark and breakable block tiles that bounce and drop
+//! an item when a player bumps them from below
+
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    BlockBounce, BlockKind, HitBlock, LevelData, PlayerVelocity, SoundEvent, Tile, TileGrid,
+    TileIndex,
+};
+use crate::constants::*;
+use crate::systems::chests::spawn_loot_item;
+use crate::systems::level_loader::USED_BLOCK_TILE;
+
+/// Turns the solid tiles listed in the level's `blocks` into hit blocks
+/// Runs after the tilemap is spawned
+pub fn spawn_blocks(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    grid: Option<Res<TileGrid>>,
+    tiles: Query<&Tile>,
+) {
+    let (Some(level), Some(grid)) = (level, grid) else {
+        return;
+    };
+
+    for block in &level.blocks {
+        let solid_tile = grid
+            .get(block.position)
+            .filter(|&entity| tiles.get(entity).is_ok_and(|tile| tile.solid));
+        let Some(entity) = solid_tile else {
+            warn!(
+                "Block at {} is not on a solid tile, skipping",
+                block.position
+            );
+            continue;
+        };
+        commands.entity(entity).insert(HitBlock {
+            kind: block.kind,
+            drop: block.drop,
+            hits_left: block.hits.max(1),
+        });
+    }
+}
+
+/// Bumps hit blocks a player's head ran into this frame: the block bounces
+/// and drops its item, then becomes a used block or breaks on its last hit
+pub fn hit_blocks(
+    mut commands: Commands,
+    players: Query<&KinematicCharacterControllerOutput, With<PlayerVelocity>>,
+    mut blocks: Query<
+        (
+            Entity,
+            &mut HitBlock,
+            &mut TileIndex,
+            &mut Sprite,
+            &Transform,
+        ),
+        Without<BlockBounce>,
+    >,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for output in players.iter() {
+        for collision in &output.collisions {
+            // Normals point out of the tile that was hit: down for a ceiling
+            if collision
+                .hit
+                .details
+                .is_none_or(|details| details.normal1.y > -0.7)
+            {
+                continue;
+            }
+            let Ok((entity, mut block, mut tile_index, mut sprite, transform)) =
+                blocks.get_mut(collision.entity)
+            else {
+                continue;
+            };
+            let position = transform.translation.truncate();
+
+            commands.entity(entity).insert(BlockBounce {
+                timer: Timer::from_seconds(BLOCK_BOUNCE_TIME, TimerMode::Once),
+            });
+            if let Some(item) = block.drop {
+                spawn_loot_item(
+                    &mut commands,
+                    item,
+                    position + Vec2::Y * TILE_SIZE_16,
+                    Vec2::Y * BLOCK_DROP_SPEED,
+                );
+            }
+            sound_events.write(SoundEvent {
+                name: "block_bump",
+                position: Some(position),
+            });
+
+            block.hits_left -= 1;
+            if block.hits_left > 0 {
+                continue;
+            }
+            commands.entity(entity).remove::<HitBlock>();
+            match block.kind {
+                BlockKind::Item => {
+                    *tile_index = TileIndex {
+                        index: USED_BLOCK_TILE,
+                        tileset_x: USED_BLOCK_TILE % TILES_PER_ROW,
+                        tileset_y: USED_BLOCK_TILE / TILES_PER_ROW,
+                    };
+                    if let Some(atlas) = &mut sprite.texture_atlas {
+                        atlas.index = USED_BLOCK_TILE as usize;
+                    }
+                }
+                // Despawning the tile sends `TileRemoved`, which throws debris
+                BlockKind::Breakable => {
+                    commands.entity(entity).despawn();
+                    sound_events.write(SoundEvent {
+                        name: "block_break",
+                        position: Some(position),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Raises and lowers the sprite of bumped blocks; the collider stays put
+pub fn animate_block_bounces(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut blocks: Query<(Entity, &mut BlockBounce, &mut Sprite)>,
+) {
+    for (entity, mut bounce, mut sprite) in blocks.iter_mut() {
+        bounce.timer.tick(time.delta());
+        if bounce.timer.finished() {
+            sprite.anchor = Anchor::Center;
+            commands.entity(entity).remove::<BlockBounce>();
+            continue;
+        }
+        let height = (bounce.timer.fraction() * std::f32::consts::PI).sin() * BLOCK_BOUNCE_HEIGHT;
+        sprite.anchor = Anchor::Custom(Vec2::new(0.0, -height / TILE_SIZE_16));
+    }
+}
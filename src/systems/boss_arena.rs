@@ -0,0 +1,142 @@
+//! Boss arenas: walls, camera lock and music for the length of a boss fight
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    ArenaBarrier, BossArena, BossArenaState, BossDefeated, BossFightStarted, CameraLock, LevelData,
+    LevelEntity, MainCamera, MusicController, PlayerRespawned, PlayerVelocity,
+};
+use crate::constants::*;
+use crate::systems::music::music_asset;
+use crate::systems::tiles::tile_to_world;
+
+const BARRIER_COLOR: Color = Color::srgb(0.45, 0.2, 0.25);
+
+/// Spawns the boss arenas defined in the level data
+pub fn spawn_boss_arenas(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    let last_tile = UVec2::new(level.width, level.height).saturating_sub(UVec2::ONE);
+    for (index, arena) in level.boss_arenas.iter().enumerate() {
+        let max = arena.max.min(last_tile);
+        let top_left = tile_to_world(&level, arena.min.x, arena.min.y);
+        let bottom_right = tile_to_world(&level, max.x, max.y);
+        commands.spawn((
+            Name::new(format!("Boss arena {}", index)),
+            LevelEntity,
+            BossArena {
+                area: Rect::from_corners(top_left - half_tile, bottom_right + half_tile),
+                music: arena.music.clone(),
+                zoom: arena.zoom,
+                state: BossArenaState::Idle,
+            },
+        ));
+    }
+}
+
+/// Locks an idle arena once a player is fully inside it (clear of the
+/// edge tiles the barriers go on) and announces the fight
+pub fn enter_boss_arenas(
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut arenas: Query<(Entity, &mut BossArena)>,
+    mut fight_started: EventWriter<BossFightStarted>,
+) {
+    for (entity, mut arena) in arenas.iter_mut() {
+        let inner = arena.area.inflate(-TILE_SIZE_16);
+        let entered = players
+            .iter()
+            .any(|transform| inner.contains(transform.translation.truncate()));
+        if arena.state == BossArenaState::Idle && entered {
+            arena.state = BossArenaState::Locked;
+            fight_started.write(BossFightStarted { arena: entity });
+        }
+    }
+}
+
+/// Raises the barriers on both edges of the arena, locks the camera on it and
+/// crossfades to the boss music
+pub fn lock_boss_arena(
+    mut commands: Commands,
+    mut fight_started: EventReader<BossFightStarted>,
+    arenas: Query<&BossArena>,
+    cameras: Query<Entity, With<MainCamera>>,
+    mut music: ResMut<MusicController>,
+) {
+    for event in fight_started.read() {
+        let Ok(arena) = arenas.get(event.arena) else {
+            continue;
+        };
+
+        let size = Vec2::new(TILE_SIZE_16, arena.area.height());
+        for x in [
+            arena.area.min.x + TILE_SIZE_16 / 2.0,
+            arena.area.max.x - TILE_SIZE_16 / 2.0,
+        ] {
+            commands.spawn((
+                Name::new("Arena barrier"),
+                LevelEntity,
+                ArenaBarrier,
+                Collider::cuboid(size.x / 2.0, size.y / 2.0),
+                Sprite::from_color(BARRIER_COLOR, size),
+                Transform::from_xyz(x, arena.area.center().y, 0.5),
+            ));
+        }
+
+        if let Ok(camera) = cameras.single() {
+            commands.entity(camera).insert(CameraLock {
+                position: arena.area.center(),
+                zoom: arena.zoom,
+            });
+        }
+
+        if let Some(track) = &arena.music {
+            music.boss_track = music_asset(track);
+        }
+        info!("Boss fight started");
+    }
+}
+
+/// Opens locked arenas again: for good when the boss is defeated, or back to
+/// idle when the player respawns so the fight restarts on re-entry
+pub fn release_boss_arenas(
+    mut commands: Commands,
+    mut boss_defeated: EventReader<BossDefeated>,
+    mut respawned: EventReader<PlayerRespawned>,
+    mut arenas: Query<&mut BossArena>,
+    barriers: Query<Entity, With<ArenaBarrier>>,
+    cameras: Query<Entity, With<MainCamera>>,
+    mut music: ResMut<MusicController>,
+) {
+    let defeated = boss_defeated.read().count() > 0;
+    let respawned = respawned.read().count() > 0;
+    let next_state = if defeated {
+        BossArenaState::Cleared
+    } else if respawned {
+        BossArenaState::Idle
+    } else {
+        return;
+    };
+
+    let mut released = false;
+    for mut arena in arenas.iter_mut() {
+        if arena.state == BossArenaState::Locked {
+            arena.state = next_state;
+            released = true;
+        }
+    }
+    if !released {
+        return;
+    }
+
+    for entity in barriers.iter() {
+        commands.entity(entity).despawn();
+    }
+    if let Ok(camera) = cameras.single() {
+        commands.entity(camera).remove::<CameraLock>();
+    }
+    music.boss_track = None;
+}
@@ -0,0 +1,415 @@
+//! Camera systems for following the player
+
+use bevy::input::mouse::AccumulatedMouseScroll;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    CameraFollow, CameraLock, CameraPath, CameraShake, CameraZoneMode, DamageEvent,
+    FacingDirection, FreeCamera, GameConfig, LevelData, MainCamera, PlayerVelocity, PrimaryPlayer,
+    Settings,
+};
+use crate::constants::*;
+
+/// World-space rectangle seen by the camera
+pub(crate) fn camera_view(transform: &Transform, projection: &Projection) -> Option<Rect> {
+    let Projection::Orthographic(ortho) = projection else {
+        return None;
+    };
+    Some(Rect::from_center_size(
+        transform.translation.truncate() + ortho.area.center(),
+        ortho.area.size(),
+    ))
+}
+
+/// The main camera, unless it's flying a camera path
+type FollowCameras<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Projection,
+        &'static mut CameraFollow,
+        Option<&'static CameraLock>,
+    ),
+    (With<MainCamera>, Without<CameraPath>),
+>;
+
+/// Players the camera follows
+type FollowedPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static PlayerVelocity,
+        &'static FacingDirection,
+        &'static KinematicCharacterControllerOutput,
+        Has<PrimaryPlayer>,
+    ),
+    Without<MainCamera>,
+>;
+
+/// Smoothly follows the player, leading the view in the direction of travel
+/// The lookahead offset grows with horizontal speed and eases back to the
+/// player when they stop, so the camera never snaps when turning around.
+/// Vertically the camera only re-anchors when the player lands on a new
+/// ground height or leaves the vertical margin (falling down a pit, being
+/// launched upwards), so jumps don't bob the view. Camera zones from the
+/// level data override the target and zoom while the player is inside them;
+/// the per-axis lerps below blend between zones. A `CameraLock` on the camera
+/// overrides both.
+/// In co-op the camera aims between the players instead, zooming out (up to
+/// `COOP_CAMERA_MAX_ZOOM`) to keep them all in view as they separate; zones
+/// and the lookahead still follow player one.
+pub fn update_camera_follow(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    level: Option<Res<LevelData>>,
+    player_query: FollowedPlayers,
+    mut camera_query: FollowCameras,
+) {
+    let Some((player_transform, velocity, facing_direction, output, _)) = player_query
+        .iter()
+        .find(|(.., primary)| *primary)
+        .or_else(|| player_query.iter().next())
+    else {
+        return;
+    };
+    let Ok((mut camera_transform, mut projection, mut follow, lock)) = camera_query.single_mut()
+    else {
+        return;
+    };
+
+    let dt = time.delta_secs();
+
+    // Lookahead: direction from facing, magnitude from how fast we're moving
+    let direction = match facing_direction {
+        FacingDirection::Right => 1.0,
+        FacingDirection::Left => -1.0,
+    };
+    let speed_factor = (velocity.0.x.abs() / config.player_speed).min(1.0);
+    let target_offset = direction * config.camera_lookahead_distance * speed_factor;
+    let lookahead_t = (config.camera_lookahead_speed * dt).min(1.0);
+    follow.lookahead_offset += (target_offset - follow.lookahead_offset) * lookahead_t;
+
+    let player_pos = player_transform.translation.truncate();
+
+    // Platform snap: only move the vertical anchor on landing or leaving the margin
+    let mut anchor_y = follow.anchor_y.unwrap_or(player_pos.y);
+    let offset_y = player_pos.y - anchor_y;
+    if output.grounded {
+        anchor_y = player_pos.y;
+    } else if offset_y.abs() > config.camera_vertical_margin {
+        // Drag the anchor so the player stays on the margin edge
+        anchor_y = player_pos.y - config.camera_vertical_margin * offset_y.signum();
+    }
+    follow.anchor_y = Some(anchor_y);
+
+    let mut follow_target = Vec2::new(
+        player_pos.x + follow.lookahead_offset,
+        anchor_y + config.camera_offset_y,
+    );
+
+    // Co-op: frame the box around every player, zooming out to fit it
+    let mut fit_zoom = 1.0;
+    if player_query.iter().count() > 1 {
+        let bounds = player_query.iter().fold(
+            Rect::from_center_size(player_pos, Vec2::ZERO),
+            |bounds, player| bounds.union_point(player.0.translation.truncate()),
+        );
+        follow_target = bounds.center() + Vec2::Y * config.camera_offset_y;
+        // The projection area is the view at the current scale (empty
+        // before the first frame)
+        let view = match projection.as_ref() {
+            Projection::Orthographic(ortho) => ortho.area.size() / ortho.scale,
+            _ => Vec2::ZERO,
+        };
+        if view.min_element() > 0.0 {
+            let needed = (bounds.size() + Vec2::splat(COOP_CAMERA_MARGIN * 2.0)) / view;
+            fit_zoom = needed.max_element().clamp(1.0, COOP_CAMERA_MAX_ZOOM);
+        }
+    }
+
+    // First zone containing the player wins
+    let active_zone = level.as_ref().and_then(|level| {
+        level
+            .camera_zones
+            .iter()
+            .find(|zone| zone.contains(player_pos))
+    });
+
+    let (target, target_zoom) = match (lock, active_zone) {
+        (Some(lock), _) => (lock.position, lock.zoom),
+        (None, Some(zone)) => {
+            let target = match zone.mode {
+                CameraZoneMode::Follow => follow_target,
+                CameraZoneMode::Locked(position) => position,
+                CameraZoneMode::FixedY(y) => Vec2::new(follow_target.x, y),
+            };
+            (target, zone.zoom.max(fit_zoom))
+        }
+        (None, None) => (follow_target, fit_zoom),
+    };
+
+    let follow_t = Vec2::new(
+        (config.camera_follow_speed_x * dt).min(1.0),
+        (config.camera_follow_speed_y * dt).min(1.0),
+    );
+    let current = camera_transform.translation.truncate();
+    let position = current + (target - current) * follow_t;
+    camera_transform.translation.x = position.x;
+    camera_transform.translation.y = position.y;
+
+    if let Projection::Orthographic(ortho) = projection.as_mut() {
+        let zoom_t = (config.camera_zone_blend_speed * dt).min(1.0);
+        ortho.scale += (target_zoom - ortho.scale) * zoom_t;
+    }
+}
+
+/// Starts the level intro fly-through if the level defines one
+pub fn start_level_intro(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    camera_query: Query<Entity, With<MainCamera>>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    if level.intro_camera_path.is_empty() {
+        return;
+    }
+    if let Ok(camera) = camera_query.single() {
+        commands
+            .entity(camera)
+            .insert(CameraPath::new(level.intro_camera_path.clone()));
+    }
+}
+
+/// Drives cameras along their `CameraPath`, segment by segment
+/// Enter skips the remaining path
+pub fn update_camera_path(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut camera_query: Query<(Entity, &mut Transform, &mut CameraPath), With<MainCamera>>,
+) {
+    for (entity, mut transform, mut path) in camera_query.iter_mut() {
+        if keyboard.just_pressed(KeyCode::Enter) || path.current >= path.waypoints.len() {
+            commands.entity(entity).remove::<CameraPath>();
+            continue;
+        }
+
+        let start = *path
+            .segment_start
+            .get_or_insert(transform.translation.truncate());
+        path.elapsed += time.delta_secs();
+
+        let waypoint = &path.waypoints[path.current];
+        let progress = if waypoint.duration > 0.0 {
+            path.elapsed / waypoint.duration
+        } else {
+            1.0
+        };
+        let position = start.lerp(waypoint.position, waypoint.easing.apply(progress));
+        transform.translation = position.extend(transform.translation.z);
+
+        if progress >= 1.0 {
+            path.current += 1;
+            path.elapsed = 0.0;
+            path.segment_start = Some(position);
+        }
+    }
+}
+
+impl CameraShake {
+    /// Adds trauma, capped at full strength
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+}
+
+/// Camera offset for `trauma` at `elapsed` seconds
+/// Two sines out of step sweep the view around, scaled by the square of the
+/// trauma so light hits barely move it
+fn shake_offset(trauma: f32, elapsed: f32) -> Vec2 {
+    let phase = elapsed * CAMERA_SHAKE_FREQUENCY;
+    let strength = trauma * trauma * CAMERA_SHAKE_MAX_OFFSET;
+    Vec2::new(phase.sin(), (phase * 1.3 + 1.7).sin()) * strength
+}
+
+/// Shakes the camera when a player takes damage
+pub fn shake_camera_on_damage(
+    mut events: EventReader<DamageEvent>,
+    players: Query<(), With<PlayerVelocity>>,
+    mut shake: ResMut<CameraShake>,
+) {
+    for event in events.read() {
+        if players.contains(event.target) {
+            shake.add_trauma(CAMERA_SHAKE_DAMAGE_TRAUMA);
+        }
+    }
+}
+
+/// Takes last frame's shake off the camera, so the follow camera and camera
+/// paths move from where the camera really is
+pub fn settle_camera_shake(
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    let offset = std::mem::take(&mut shake.offset);
+    if offset == Vec2::ZERO {
+        return;
+    }
+    if let Ok(mut transform) = camera_query.single_mut() {
+        transform.translation -= offset.extend(0.0);
+    }
+}
+
+/// Offsets the camera by the current shake and wears the trauma off
+/// With screen shake turned off in the settings the trauma is dropped instead
+pub fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !settings.screen_shake {
+        shake.trauma = 0.0;
+        return;
+    }
+    if shake.trauma <= 0.0 {
+        return;
+    }
+    let Ok(mut transform) = camera_query.single_mut() else {
+        return;
+    };
+    shake.offset = shake_offset(shake.trauma, time.elapsed_secs());
+    transform.translation += shake.offset.extend(0.0);
+    shake.trauma = (shake.trauma - CAMERA_SHAKE_DECAY * time.delta_secs()).max(0.0);
+}
+
+/// Home toggles the free-fly debug camera; turning it off hands the view back
+/// to the follow camera, which eases back onto the player
+pub fn toggle_free_camera(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    free_camera: Option<Res<FreeCamera>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Home) {
+        return;
+    }
+    if free_camera.is_some() {
+        commands.remove_resource::<FreeCamera>();
+    } else {
+        commands.insert_resource(FreeCamera);
+    }
+    debug!(
+        "Free camera: {}",
+        if free_camera.is_some() { "OFF" } else { "ON" }
+    );
+}
+
+/// Zoom steps of the free camera this frame: one per wheel direction
+/// scrolled and per -/= press, positive zooming out
+fn free_camera_zoom_steps(scroll_y: f32, keyboard: &ButtonInput<KeyCode>) -> f32 {
+    // `signum()` of 0.0 is 1.0, so a frame without scrolling is checked first
+    let mut steps = if scroll_y == 0.0 {
+        0.0
+    } else {
+        -scroll_y.signum()
+    };
+    if keyboard.just_pressed(KeyCode::Minus) {
+        steps += 1.0;
+    }
+    if keyboard.just_pressed(KeyCode::Equal) {
+        steps -= 1.0;
+    }
+    steps
+}
+
+/// Flies the free camera with WASD/arrows (Shift for speed) and zooms with the
+/// mouse wheel or -/=
+/// Uses real time so it also works while the simulation is frozen
+pub fn fly_free_camera(
+    time: Res<Time<bevy::time::Real>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let Ok((mut transform, mut projection)) = camera_query.single_mut() else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = projection.as_mut() else {
+        return;
+    };
+
+    let zoom_steps = free_camera_zoom_steps(scroll.delta.y, &keyboard);
+    ortho.scale = (ortho.scale * (1.0 + FREE_CAMERA_ZOOM_STEP).powf(zoom_steps))
+        .clamp(FREE_CAMERA_MIN_ZOOM, FREE_CAMERA_MAX_ZOOM);
+
+    let mut direction = Vec2::ZERO;
+    if keyboard.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+        direction.x -= 1.0;
+    }
+    if keyboard.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+        direction.x += 1.0;
+    }
+    if keyboard.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+        direction.y -= 1.0;
+    }
+    if keyboard.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        direction.y += 1.0;
+    }
+    let boost = if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        3.0
+    } else {
+        1.0
+    };
+    let velocity = direction.normalize_or_zero() * FREE_CAMERA_SPEED * boost * ortho.scale;
+    transform.translation += (velocity * time.delta_secs()).extend(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_scroll_keeps_the_zoom() {
+        let keyboard = ButtonInput::<KeyCode>::default();
+        assert_eq!(free_camera_zoom_steps(0.0, &keyboard), 0.0);
+        assert_eq!(free_camera_zoom_steps(-0.0, &keyboard), 0.0);
+    }
+
+    #[test]
+    fn scrolling_and_keys_zoom_one_step() {
+        let mut keyboard = ButtonInput::<KeyCode>::default();
+        assert_eq!(free_camera_zoom_steps(3.0, &keyboard), -1.0);
+        assert_eq!(free_camera_zoom_steps(-0.5, &keyboard), 1.0);
+
+        keyboard.press(KeyCode::Minus);
+        assert_eq!(free_camera_zoom_steps(0.0, &keyboard), 1.0);
+        // The wheel and the key cancel out
+        assert_eq!(free_camera_zoom_steps(1.0, &keyboard), 0.0);
+    }
+
+    #[test]
+    fn shake_grows_with_trauma_and_stays_in_bounds() {
+        assert_eq!(shake_offset(0.0, 1.25), Vec2::ZERO);
+        for step in 0..100 {
+            let elapsed = step as f32 * 0.013;
+            let light = shake_offset(0.3, elapsed);
+            let full = shake_offset(1.0, elapsed);
+            assert!(full.x.abs() <= CAMERA_SHAKE_MAX_OFFSET);
+            assert!(full.y.abs() <= CAMERA_SHAKE_MAX_OFFSET);
+            assert!(light.length() <= full.length());
+        }
+    }
+
+    #[test]
+    fn trauma_is_capped() {
+        let mut shake = CameraShake::default();
+        shake.add_trauma(0.8);
+        shake.add_trauma(0.8);
+        assert_eq!(shake.trauma, 1.0);
+    }
+}
@@ -0,0 +1,77 @@
+//! Character controller tuning loaded from a config file
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{CharacterPhysicsConfig, PlayerVelocity};
+use crate::constants::CHARACTER_PHYSICS_CONFIG_PATH;
+
+impl Default for CharacterPhysicsConfig {
+    fn default() -> Self {
+        Self {
+            offset: 0.01,
+            snap_to_ground: Some(2.0),
+            max_slope_climb_angle: 45.0,
+            min_slope_slide_angle: 30.0,
+            autostep: None,
+            slide: true,
+        }
+    }
+}
+
+impl CharacterPhysicsConfig {
+    /// Copies the settings onto a character controller, leaving the rest untouched
+    pub fn apply(&self, controller: &mut KinematicCharacterController) {
+        controller.offset = CharacterLength::Absolute(self.offset);
+        controller.snap_to_ground = self.snap_to_ground.map(CharacterLength::Absolute);
+        controller.max_slope_climb_angle = self.max_slope_climb_angle.to_radians();
+        controller.min_slope_slide_angle = self.min_slope_slide_angle.to_radians();
+        controller.autostep = self.autostep.map(|autostep| CharacterAutostep {
+            max_height: CharacterLength::Absolute(autostep.max_height),
+            min_width: CharacterLength::Absolute(autostep.min_width),
+            include_dynamic_bodies: autostep.include_dynamic_bodies,
+        });
+        controller.slide = self.slide;
+    }
+}
+
+/// Loads the character controller settings from a RON file
+pub fn load_character_physics_config(path: &str) -> Result<CharacterPhysicsConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read character physics config '{}': {}", path, e))?;
+    ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse character physics config '{}': {}", path, e))
+}
+
+/// Saves the character controller settings (used by the debug UI)
+pub fn save_character_physics_config(
+    path: &str,
+    config: &CharacterPhysicsConfig,
+) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize character physics config: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write character physics config '{}': {}", path, e))
+}
+
+/// Inserts the `CharacterPhysicsConfig` resource, falling back to the defaults
+pub fn load_character_physics(mut commands: Commands) {
+    let config = load_character_physics_config(CHARACTER_PHYSICS_CONFIG_PATH).unwrap_or_else(|e| {
+        warn!("{}, using default character physics", e);
+        CharacterPhysicsConfig::default()
+    });
+    commands.insert_resource(config);
+}
+
+/// Re-applies the config to the player's controller whenever it changes
+pub fn apply_character_physics_config(
+    config: Res<CharacterPhysicsConfig>,
+    mut controllers: Query<&mut KinematicCharacterController, With<PlayerVelocity>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for mut controller in controllers.iter_mut() {
+        config.apply(&mut controller);
+    }
+}
@@ -0,0 +1,311 @@
+//! Playable characters: the roster, picking one in the menu and spawning the
+//! player as it
+
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    AirJumps, AnimationCollection, AnimationConfig, AnimationHandles, AnimationState, CharacterDef,
+    CharacterPhysicsConfig, CharacterRoster, CharacterSelectText, CharacterStats, DrawLayer,
+    FacingDirection, GroundMaterial, Health, InputAction, JumpPhase, KeyRing, PlatformPassThrough,
+    PlayableCharacter, PlayerController, PlayerInput, PlayerVelocity, PrimaryPlayer,
+    RenderInterpolation, SaveDirectory, Settings, SpriteSheetDef,
+};
+use crate::constants::*;
+use crate::systems::settings::save_settings_file;
+
+/// Collision group of the players; character controllers filter it out so
+/// co-op players walk through each other
+pub const PLAYER_GROUP: Group = Group::GROUP_3;
+
+impl Default for CharacterStats {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            jump: 1.0,
+            air_jumps: 0,
+        }
+    }
+}
+
+impl Default for CharacterRoster {
+    /// Just the adventurer, from the sprite constants
+    fn default() -> Self {
+        let sheet = |path: &str, frames: u32, fps: u8| SpriteSheetDef {
+            path: path.to_string(),
+            frame_size: SPRITE_SIZE,
+            columns: frames,
+            first: 0,
+            last: (frames - 1) as usize,
+            fps,
+        };
+        Self(vec![CharacterDef {
+            id: DEFAULT_CHARACTER.to_string(),
+            name: "Adventurer".to_string(),
+            description: String::new(),
+            idle: sheet("character/IDLE.png", IDLE_FRAMES, IDLE_ANIMATION_FPS),
+            run: sheet("character/RUN.png", RUN_FRAMES, RUN_ANIMATION_FPS),
+            double_jump: None,
+            dead: sheet("character/HURT.png", DEAD_FRAMES, DEAD_ANIMATION_FPS),
+            scale: 1.0,
+            anchor_y: -0.2,
+            stats: CharacterStats::default(),
+        }])
+    }
+}
+
+impl CharacterRoster {
+    /// The character `id`, or the first one when there is no such character
+    pub fn get(&self, id: &str) -> &CharacterDef {
+        self.0
+            .iter()
+            .find(|character| character.id == id)
+            .unwrap_or(&self.0[0])
+    }
+
+    /// Id of the character `step` places after `id`, wrapping around
+    pub(crate) fn cycle(&self, id: &str, step: isize) -> &str {
+        let index = self
+            .0
+            .iter()
+            .position(|character| character.id == id)
+            .unwrap_or(0);
+        let next = (index as isize + step).rem_euclid(self.0.len() as isize) as usize;
+        &self.0[next].id
+    }
+}
+
+impl CharacterDef {
+    /// Loads the character's sprite sheets, with their animations
+    pub(crate) fn animations(
+        &self,
+        asset_server: &AssetServer,
+        layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> (AnimationCollection, AnimationHandles) {
+        let mut load_sheet = |sheet: &SpriteSheetDef| {
+            let layout = TextureAtlasLayout::from_grid(
+                UVec2::splat(sheet.frame_size),
+                sheet.columns,
+                1,
+                None,
+                None,
+            );
+            (
+                asset_server.load::<Image>(sheet.path.clone()),
+                layouts.add(layout),
+            )
+        };
+        let (idle_texture, idle_layout) = load_sheet(&self.idle);
+        let (run_texture, run_layout) = load_sheet(&self.run);
+        let double_jump = self.double_jump.as_ref().unwrap_or(&self.run);
+        let (double_jump_texture, double_jump_layout) = load_sheet(double_jump);
+        let (dead_texture, dead_layout) = load_sheet(&self.dead);
+
+        let animation_config =
+            |sheet: &SpriteSheetDef| AnimationConfig::new(sheet.first, sheet.last, sheet.fps);
+        let collection = AnimationCollection {
+            idle: animation_config(&self.idle),
+            run: animation_config(&self.run),
+            double_jump: animation_config(double_jump),
+            dead: animation_config(&self.dead),
+        };
+        let handles = AnimationHandles {
+            idle_texture,
+            idle_layout,
+            run_texture,
+            run_layout,
+            double_jump_texture,
+            double_jump_layout,
+            dead_texture,
+            dead_layout,
+        };
+        (collection, handles)
+    }
+
+    /// Sprite on the first idle frame, sized and anchored so the feet line
+    /// up with the bottom of the player collider
+    pub(crate) fn sprite(&self, handles: &AnimationHandles) -> Sprite {
+        Sprite {
+            image: handles.idle_texture.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: handles.idle_layout.clone(),
+                index: self.idle.first,
+            }),
+            custom_size: (self.scale != 1.0)
+                .then(|| Vec2::splat(self.idle.frame_size as f32 * self.scale)),
+            anchor: Anchor::Custom(Vec2::new(0.0, self.anchor_y)),
+            ..default()
+        }
+    }
+}
+
+/// Loads the character roster from a RON file
+pub fn load_character_roster_file(path: &str) -> Result<CharacterRoster, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read characters '{}': {}", path, e))?;
+    let characters: Vec<CharacterDef> = ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse characters '{}': {}", path, e))?;
+    if characters.is_empty() {
+        return Err(format!("No characters in '{}'", path));
+    }
+    Ok(CharacterRoster(characters))
+}
+
+/// Inserts the `CharacterRoster` resource, falling back to the adventurer
+pub fn load_characters(mut commands: Commands) {
+    let roster = load_character_roster_file(CHARACTERS_PATH).unwrap_or_else(|e| {
+        warn!("{}, only the adventurer is playable", e);
+        CharacterRoster::default()
+    });
+    commands.insert_resource(roster);
+}
+
+/// Spawns a player as the character `character_id` at `position`, played
+/// with `controller`; the keyboard player is player one
+/// Unknown ids spawn the first character of the roster
+pub fn spawn_player(
+    world: &mut World,
+    character_id: &str,
+    position: Vec2,
+    controller: PlayerController,
+) -> Entity {
+    let character = world
+        .get_resource::<CharacterRoster>()
+        .map(|roster| roster.get(character_id).clone())
+        .unwrap_or_else(|| CharacterRoster::default().0[0].clone());
+    if character.id != character_id {
+        warn!(
+            "Unknown character '{}', playing as '{}'",
+            character_id, character.id
+        );
+    }
+
+    let asset_server = world.resource::<AssetServer>().clone();
+    let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+    let (animation_collection, animation_handles) =
+        character.animations(&asset_server, &mut layouts);
+    let sprite = character.sprite(&animation_handles);
+
+    // Offset, slopes, snapping and autostep come from `CharacterPhysicsConfig`
+    let mut character_controller = KinematicCharacterController {
+        // Push crates and barrels by walking into them
+        apply_impulse_to_dynamic_bodies: true,
+        custom_mass: Some(PLAYER_MASS),
+        ..default()
+    };
+    if let Some(config) = world.get_resource::<CharacterPhysicsConfig>() {
+        config.apply(&mut character_controller);
+    }
+
+    let mut player = world.spawn((
+        Name::new(format!("Player ({})", character.name)),
+        // Physics components, shared by every character
+        character_controller,
+        Collider::capsule(Vec2::new(0.0, -10.0), Vec2::new(0.0, 10.0), 5.0),
+        CollisionGroups::new(PLAYER_GROUP, Group::ALL),
+        KinematicCharacterControllerOutput::default(),
+        // Visual components, anchored so the feet sit on the ground
+        sprite,
+        Transform::from_translation(position.extend(0.0)),
+        DrawLayer::Sorted {
+            feet: PLAYER_CONTACT_HALF_HEIGHT,
+        },
+        RenderInterpolation::default(),
+        PlayableCharacter(character.id.clone()),
+        character.stats,
+        AirJumps(character.stats.air_jumps),
+        // Game logic components (nested: bundles take at most 15 items)
+        (
+            PlayerVelocity::default(),
+            JumpPhase::default(),
+            PlatformPassThrough::default(),
+            GroundMaterial::default(),
+            Health::new(PLAYER_MAX_HEALTH),
+            KeyRing::default(),
+            AnimationState::default(),
+            FacingDirection::default(),
+            animation_collection,
+            animation_handles,
+        ),
+        // Input, from the keyboard or a gamepad
+        (controller, PlayerInput::default()),
+    ));
+    if controller == PlayerController::Keyboard {
+        player.insert(PrimaryPlayer);
+    }
+    player.id()
+}
+
+/// Spawns the player at the spawn point as the character picked in the settings
+pub fn spawn_selected_player(world: &mut World) {
+    let character_id = world
+        .get_resource::<Settings>()
+        .map_or(DEFAULT_CHARACTER.to_string(), |settings| {
+            settings.character.clone()
+        });
+    spawn_player(
+        world,
+        &character_id,
+        Vec2::new(PLAYER_SPAWN_X, PLAYER_SPAWN_Y),
+        PlayerController::Keyboard,
+    );
+}
+
+/// Left and right pick the character on the menu screen; the choice is
+/// saved with the settings right away
+pub fn select_character(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    roster: Res<CharacterRoster>,
+    mut settings: ResMut<Settings>,
+    saves: Res<SaveDirectory>,
+    mut texts: Query<&mut Text, With<CharacterSelectText>>,
+) {
+    let keys = &settings.key_bindings;
+    let mut step = 0;
+    if keys.just_pressed(&keyboard, InputAction::Left) {
+        step -= 1;
+    }
+    if keys.just_pressed(&keyboard, InputAction::Right) {
+        step += 1;
+    }
+    if step != 0 {
+        settings.character = roster.cycle(&settings.character, step).to_string();
+        if let Err(e) = save_settings_file(&saves.file(SETTINGS_FILE), &settings) {
+            error!("{}", e);
+        }
+    }
+
+    let character = roster.get(&settings.character);
+    let label = if character.description.is_empty() {
+        format!("< {} >", character.name)
+    } else {
+        format!("< {} >\n{}", character.name, character.description)
+    };
+    for mut text in texts.iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
+
+/// Swaps player one for the newly picked character, in place
+pub fn swap_player_character(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    players: Query<(Entity, &PlayableCharacter, &Transform), With<PrimaryPlayer>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (entity, character, transform) in players.iter() {
+        if character.0 == settings.character {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        let character_id = settings.character.clone();
+        let position = transform.translation.truncate();
+        commands.queue(move |world: &mut World| {
+            spawn_player(world, &character_id, position, PlayerController::Keyboard);
+        });
+    }
+}
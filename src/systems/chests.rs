@@ -0,0 +1,244 @@
+//! Treasure chests dropping loot from data-driven loot tables
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::components::{
+    Chest, ChestLid, ChestOpening, Collectible, CollectibleKind, CurrentLevel, DrawLayer, Health,
+    InputAction, LevelData, LevelEntity, LootItem, LootTable, LootTables, Particle, ParticleEffect,
+    PlayerInput, PlayerVelocity, Pool, PowerUp, SaveData, SaveDirectory,
+};
+use crate::constants::*;
+use crate::systems::particles::spawn_particles;
+use crate::systems::save::write_save_file;
+
+const CHEST_COLOR: Color = Color::srgb(0.55, 0.35, 0.15);
+const CHEST_LID_COLOR: Color = Color::srgb(0.45, 0.28, 0.12);
+const HEART_COLOR: Color = Color::srgb(0.95, 0.25, 0.35);
+/// Angle (radians) the lid swings back to when fully open
+const CHEST_LID_OPEN_ANGLE: f32 = 1.9;
+
+/// Loads the loot tables from a RON file
+pub fn load_loot_table_file(path: &str) -> Result<LootTables, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read loot tables '{}': {}", path, e))?;
+    let tables: HashMap<String, LootTable> = ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse loot tables '{}': {}", path, e))?;
+    Ok(LootTables(tables))
+}
+
+/// Inserts the `LootTables` resource; chests drop nothing without it
+pub fn load_loot_tables(mut commands: Commands) {
+    let tables = load_loot_table_file(LOOT_TABLES_PATH).unwrap_or_else(|e| {
+        warn!("{}, chests will be empty", e);
+        LootTables::default()
+    });
+    commands.insert_resource(tables);
+}
+
+/// Where the lid sits for an opening progress of `t` (0.0 closed, 1.0 open):
+/// it swings around its back hinge
+fn lid_transform(t: f32) -> Transform {
+    let hinge = Vec2::new(-CHEST_WIDTH / 2.0, CHEST_HEIGHT / 2.0);
+    let rotation = Quat::from_rotation_z(t * CHEST_LID_OPEN_ANGLE);
+    let offset = rotation * Vec3::new(CHEST_WIDTH / 2.0, CHEST_LID_HEIGHT / 2.0, 0.0);
+    Transform::from_translation(hinge.extend(0.1) + offset).with_rotation(rotation)
+}
+
+/// Spawns the chests defined in the level data; chests already opened in
+/// this level start open and empty
+pub fn spawn_chests(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    save_data: Res<SaveData>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    let opened_chests = current_level
+        .as_ref()
+        .and_then(|current| save_data.opened_chests.get(&current.path));
+
+    for (index, chest) in level.chests.iter().enumerate() {
+        let key = chest.position.round().as_ivec2();
+        let opened = opened_chests.is_some_and(|keys| keys.contains(&key));
+        commands
+            .spawn((
+                Name::new(format!("Chest {}", index)),
+                LevelEntity,
+                Sprite::from_color(CHEST_COLOR, Vec2::new(CHEST_WIDTH, CHEST_HEIGHT)),
+                Transform::from_translation(chest.position.extend(0.0)),
+                DrawLayer::standing(Vec2::new(CHEST_WIDTH, CHEST_HEIGHT)),
+                Chest {
+                    loot: chest.loot.clone(),
+                    key,
+                    opened,
+                },
+            ))
+            .with_child((
+                ChestLid,
+                Sprite::from_color(CHEST_LID_COLOR, Vec2::new(CHEST_WIDTH, CHEST_LID_HEIGHT)),
+                lid_transform(if opened { 1.0 } else { 0.0 }),
+            ));
+    }
+}
+
+/// Opens a closed chest when a player presses interact next to it,
+/// recording it in `SaveData`
+pub fn open_chests(
+    mut commands: Commands,
+    players: Query<(&Transform, &PlayerInput), With<PlayerVelocity>>,
+    mut chests: Query<(Entity, &mut Chest, &Transform), Without<PlayerVelocity>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut save_data: ResMut<SaveData>,
+    saves: Res<SaveDirectory>,
+) {
+    for (entity, mut chest, transform) in chests.iter_mut() {
+        let chest_pos = transform.translation.truncate();
+        let opened_by_player = players.iter().any(|(player_transform, input)| {
+            input.just_pressed(InputAction::Interact)
+                && player_transform.translation.truncate().distance(chest_pos)
+                    <= CHEST_INTERACT_RANGE
+        });
+        if chest.opened || !opened_by_player {
+            continue;
+        }
+
+        chest.opened = true;
+        commands.entity(entity).insert(ChestOpening {
+            timer: Timer::from_seconds(CHEST_OPEN_TIME, TimerMode::Once),
+        });
+        info!("Opened chest ({})", chest.loot);
+
+        if let Some(current) = &current_level {
+            save_data
+                .opened_chests
+                .entry(current.path.clone())
+                .or_default()
+                .insert(chest.key);
+            if let Err(e) = write_save_file(&saves.file(SAVE_FILE), &save_data) {
+                error!("{}", e);
+            }
+        }
+    }
+}
+
+/// Swings the lids of opening chests and drops their loot once fully open
+pub fn animate_chests(
+    mut commands: Commands,
+    time: Res<Time>,
+    loot_tables: Option<Res<LootTables>>,
+    mut chests: Query<(Entity, &Chest, &mut ChestOpening, &Transform, &Children)>,
+    mut lids: Query<&mut Transform, (With<ChestLid>, Without<Chest>)>,
+) {
+    for (entity, chest, mut opening, transform, children) in chests.iter_mut() {
+        opening.timer.tick(time.delta());
+        for child in children.iter() {
+            if let Ok(mut lid) = lids.get_mut(child) {
+                *lid = lid_transform(opening.timer.fraction());
+            }
+        }
+        if !opening.timer.finished() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<ChestOpening>();
+        match loot_tables
+            .as_ref()
+            .and_then(|tables| tables.0.get(&chest.loot))
+        {
+            Some(table) => spawn_loot(&mut commands, table, transform.translation.truncate()),
+            None => warn!("Unknown loot table '{}'", chest.loot),
+        }
+    }
+}
+
+/// Rolls every drop of `table` and spawns the resulting items popping out of
+/// `position` with a random sideways impulse
+pub(crate) fn spawn_loot(commands: &mut Commands, table: &LootTable, position: Vec2) {
+    let mut rng = rand::thread_rng();
+    for drop in &table.drops {
+        if rng.gen::<f32>() >= drop.chance {
+            continue;
+        }
+        let count = rng.gen_range(drop.min..=drop.max.max(drop.min));
+        for _ in 0..count {
+            let velocity = Vec2::new(
+                rng.gen_range(-0.5..=0.5) * LOOT_POP_SPEED,
+                LOOT_POP_SPEED * rng.gen_range(0.8..=1.2),
+            );
+            spawn_loot_item(commands, drop.item, position, velocity);
+        }
+    }
+}
+
+/// Spawns one loot item at `position`, thrown with `velocity`
+pub(crate) fn spawn_loot_item(
+    commands: &mut Commands,
+    item: LootItem,
+    position: Vec2,
+    velocity: Vec2,
+) {
+    let mut entity = commands.spawn((
+        Name::new(format!("{:?} loot", item)),
+        LevelEntity,
+        Transform::from_translation(position.extend(0.5)),
+        RigidBody::Dynamic,
+        LockedAxes::ROTATION_LOCKED,
+        Velocity::linear(velocity),
+    ));
+    match item {
+        LootItem::Coin | LootItem::Gem => {
+            let kind = if item == LootItem::Coin {
+                CollectibleKind::Coin
+            } else {
+                CollectibleKind::Gem
+            };
+            entity.insert((
+                Sprite::from_color(kind.color(), Vec2::splat(COLLECTIBLE_SIZE)),
+                Collider::ball(COLLECTIBLE_SIZE / 2.0),
+                Collectible { kind, key: None },
+            ));
+        }
+        LootItem::Heart => {
+            entity.insert((
+                Sprite::from_color(HEART_COLOR, Vec2::splat(POWER_UP_SIZE)),
+                Collider::ball(POWER_UP_SIZE / 2.0),
+                PowerUp::Heart,
+            ));
+        }
+    }
+}
+
+/// Applies power-ups the player touches
+pub fn collect_power_ups(
+    mut commands: Commands,
+    mut players: Query<(&Transform, &mut Health), With<PlayerVelocity>>,
+    power_ups: Query<(Entity, &PowerUp, &Transform), Without<PlayerVelocity>>,
+    mut particle_pool: ResMut<Pool<Particle>>,
+) {
+    for (player_transform, mut health) in players.iter_mut() {
+        let player_pos = player_transform.translation.truncate();
+        for (entity, power_up, transform) in power_ups.iter() {
+            let position = transform.translation.truncate();
+            if position.distance(player_pos) > POWER_UP_PICKUP_RADIUS {
+                continue;
+            }
+
+            commands.entity(entity).despawn();
+            match power_up {
+                PowerUp::Heart => health.current = (health.current + 1).min(health.max),
+            }
+            spawn_particles(
+                &mut commands,
+                &mut particle_pool,
+                ParticleEffect::Sparkle,
+                position,
+                HEART_COLOR,
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Collectible coins and gems
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    Collectible, CollectibleKind, CurrentLevel, DrawLayer, LevelData, LevelEntity, Particle,
+    ParticleEffect, PlayerStats, PlayerVelocity, Pool, SaveData, SaveDirectory, ScoreEvent,
+    SoundEvent, TileCollisionMap, TileIndex,
+};
+use crate::constants::*;
+use crate::systems::particles::spawn_particles;
+use crate::systems::save::write_save_file;
+
+impl CollectibleKind {
+    pub(crate) fn color(self) -> Color {
+        match self {
+            CollectibleKind::Coin => Color::srgb(1.0, 0.85, 0.2),
+            CollectibleKind::Gem => Color::srgb(0.3, 0.9, 1.0),
+        }
+    }
+
+    /// Name of the pickup sound in the sound registry
+    fn sound(self) -> &'static str {
+        match self {
+            CollectibleKind::Coin => "coin",
+            CollectibleKind::Gem => "gem",
+        }
+    }
+}
+
+/// Turns collectible tiles into collectibles and spawns the level's placed
+/// collectibles, skipping the ones already collected in this level
+pub fn spawn_collectibles(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    save_data: Res<SaveData>,
+    tiles: Query<(Entity, &TileIndex, &Transform)>,
+) {
+    let collected = current_level
+        .as_ref()
+        .and_then(|current| save_data.collected.get(&current.path));
+    let is_collected = |key: IVec2| collected.is_some_and(|keys| keys.contains(&key));
+
+    if let Some(collision_map) = collision_map {
+        for (entity, tile_index, transform) in tiles.iter() {
+            let Some(&kind) = collision_map.collectible_tiles.get(&tile_index.index) else {
+                continue;
+            };
+            let key = transform.translation.truncate().round().as_ivec2();
+            if is_collected(key) {
+                commands.entity(entity).despawn();
+            } else {
+                commands.entity(entity).insert((
+                    Collectible {
+                        kind,
+                        key: Some(key),
+                    },
+                    Sensor,
+                    Collider::ball(COLLECTIBLE_SIZE / 2.0),
+                ));
+            }
+        }
+    }
+
+    let Some(level) = level else {
+        return;
+    };
+    for (index, collectible) in level.collectibles.iter().enumerate() {
+        let key = collectible.position.round().as_ivec2();
+        if is_collected(key) {
+            continue;
+        }
+        commands.spawn((
+            Name::new(format!("{:?} {}", collectible.kind, index)),
+            LevelEntity,
+            Sprite::from_color(collectible.kind.color(), Vec2::splat(COLLECTIBLE_SIZE)),
+            Transform::from_translation(collectible.position.extend(0.0)),
+            DrawLayer::Front,
+            Collectible {
+                kind: collectible.kind,
+                key: Some(key),
+            },
+            Sensor,
+            Collider::ball(COLLECTIBLE_SIZE / 2.0),
+        ));
+    }
+}
+
+/// Picks up collectibles the player touches: despawns them, updates
+/// `PlayerStats`, sends a `ScoreEvent`, records and saves them in
+/// `SaveData`, and plays the pickup effects
+#[allow(clippy::too_many_arguments)]
+pub fn collect_pickups(
+    mut commands: Commands,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    collectibles: Query<(Entity, &Collectible, &Transform), Without<PlayerVelocity>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut stats: ResMut<PlayerStats>,
+    mut save_data: ResMut<SaveData>,
+    saves: Res<SaveDirectory>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut sound_events: EventWriter<SoundEvent>,
+    mut particle_pool: ResMut<Pool<Particle>>,
+) {
+    let mut save_changed = false;
+
+    for player_transform in players.iter() {
+        let player_pos = player_transform.translation.truncate();
+        for (entity, collectible, transform) in collectibles.iter() {
+            let position = transform.translation.truncate();
+            if position.distance(player_pos) > COLLECTIBLE_PICKUP_RADIUS {
+                continue;
+            }
+
+            commands.entity(entity).despawn();
+            match collectible.kind {
+                CollectibleKind::Coin => stats.coins += 1,
+                CollectibleKind::Gem => stats.gems += 1,
+            }
+            score_events.write(ScoreEvent::pickup(collectible.kind, position));
+            if let (Some(current), Some(key)) = (&current_level, collectible.key) {
+                save_data
+                    .collected
+                    .entry(current.path.clone())
+                    .or_default()
+                    .insert(key);
+                save_changed = true;
+            }
+
+            spawn_particles(
+                &mut commands,
+                &mut particle_pool,
+                ParticleEffect::Sparkle,
+                position,
+                collectible.kind.color(),
+            );
+            sound_events.write(SoundEvent {
+                name: collectible.kind.sound(),
+                position: Some(position),
+            });
+        }
+    }
+
+    if save_changed {
+        if let Err(e) = write_save_file(&saves.file(SAVE_FILE), &save_data) {
+            error!("{}", e);
+        }
+    }
+}
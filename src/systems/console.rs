@@ -0,0 +1,175 @@
+//! Debug console (`) with typed commands, and Ctrl+click teleporting
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::components::{
+    DebugConsole, MainCamera, NetworkCommand, PlayerVelocity, SaveDirectory, SnapshotRequest,
+    TeleportPlayer,
+};
+use crate::constants::{NET_DEFAULT_PORT, SNAPSHOT_FILE};
+
+/// Opens and closes the console; while it's open the keyboard is kept from
+/// gameplay so typing doesn't move the player
+/// Runs in `PreUpdate` right after input handling
+pub fn capture_console_keyboard(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut console: ResMut<DebugConsole>,
+) {
+    if keyboard.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+    if console.open {
+        keyboard.reset_all();
+    }
+}
+
+/// Runs a console command line, returning its output
+fn run_console_command(
+    line: &str,
+    snapshot_path: &str,
+    teleports: &mut EventWriter<TeleportPlayer>,
+    snapshots: &mut EventWriter<SnapshotRequest>,
+    network: &mut EventWriter<NetworkCommand>,
+) -> String {
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some("tp") => {
+            let coords: Vec<f32> = words.filter_map(|word| word.parse().ok()).collect();
+            let [x, y] = coords[..] else {
+                return "Usage: tp <x> <y>".to_string();
+            };
+            teleports.write(TeleportPlayer {
+                position: Vec2::new(x, y),
+            });
+            format!("Teleported to ({}, {})", x, y)
+        }
+        Some("snapshot") => match words.next() {
+            Some("save") => {
+                snapshots.write(SnapshotRequest::Save);
+                format!("Saving snapshot to '{}'", snapshot_path)
+            }
+            Some("load") => {
+                snapshots.write(SnapshotRequest::Load);
+                format!("Restoring snapshot from '{}'", snapshot_path)
+            }
+            _ => "Usage: snapshot <save|load>".to_string(),
+        },
+        Some("net") => match (words.next(), words.next()) {
+            (Some("host"), port) => match port.map_or(Ok(NET_DEFAULT_PORT), str::parse) {
+                Ok(port) => {
+                    network.write(NetworkCommand::Host(port));
+                    format!("Hosting on port {}", port)
+                }
+                Err(_) => "Usage: net host [port]".to_string(),
+            },
+            (Some("join"), Some(address)) => {
+                network.write(NetworkCommand::Join(address.to_string()));
+                format!("Joining {}", address)
+            }
+            (Some("leave"), None) => {
+                network.write(NetworkCommand::Leave);
+                "Leaving the network session".to_string()
+            }
+            _ => "Usage: net host [port] | net join <address> | net leave".to_string(),
+        },
+        Some("help") => {
+            "Commands: tp <x> <y>, snapshot <save|load>, net <host|join|leave>, help".to_string()
+        }
+        Some(command) => format!("Unknown command '{}'", command),
+        None => String::new(),
+    }
+}
+
+/// Console window: Enter runs the typed command
+pub fn debug_console_ui(
+    mut contexts: EguiContexts,
+    mut console: ResMut<DebugConsole>,
+    saves: Res<SaveDirectory>,
+    mut teleports: EventWriter<TeleportPlayer>,
+    mut snapshots: EventWriter<SnapshotRequest>,
+    mut network: EventWriter<NetworkCommand>,
+) {
+    if !console.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let console = console.as_mut();
+    // The toggle key also reaches the text field
+    console.input.retain(|c| c != '`');
+    let mut submitted = false;
+    egui::Window::new("Console")
+        .anchor(egui::Align2::LEFT_TOP, [10.0, 10.0])
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &console.log {
+                        ui.monospace(line);
+                    }
+                });
+            let response = ui.text_edit_singleline(&mut console.input);
+            submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            response.request_focus();
+        });
+
+    if submitted {
+        let line = std::mem::take(&mut console.input);
+        trace!("Console command: {}", line);
+        let output = run_console_command(
+            &line,
+            &saves.file(SNAPSHOT_FILE),
+            &mut teleports,
+            &mut snapshots,
+            &mut network,
+        );
+        console.log.push(format!("> {}", line));
+        if !output.is_empty() {
+            console.log.push(output);
+        }
+    }
+}
+
+/// Ctrl+click teleports the player to the clicked world position
+pub fn click_to_teleport(
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut teleports: EventWriter<TeleportPlayer>,
+) {
+    if !mouse.just_pressed(MouseButton::Left)
+        || !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+    teleports.write(TeleportPlayer { position });
+}
+
+/// Moves the player to teleport destinations, stopping it
+pub fn teleport_player(
+    mut teleports: EventReader<TeleportPlayer>,
+    mut players: Query<(&mut Transform, &mut PlayerVelocity)>,
+) {
+    let Some(teleport) = teleports.read().last() else {
+        return;
+    };
+    for (mut transform, mut velocity) in players.iter_mut() {
+        transform.translation = teleport.position.extend(transform.translation.z);
+        velocity.0 = Vec2::ZERO;
+        debug!("Teleported player to {:?}", teleport.position);
+    }
+}
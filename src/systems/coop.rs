@@ -0,0 +1,66 @@
+//! Local co-op: a second player joins on a gamepad next to player one
+//!
+//! Start on a connected gamepad joins as the next character of the roster,
+//! Select (or unplugging the gamepad) leaves. The shared camera lives in
+//! `update_camera_follow()` and respawning next to the partner in the death
+//! sequence
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveCheckpoint, CharacterRoster, PlayerController, PlayerVelocity, PrimaryPlayer, Settings,
+};
+use crate::constants::*;
+use crate::systems::characters::spawn_player;
+
+/// Spawns a player for a gamepad pressing Start, up to `MAX_PLAYERS`, and
+/// despawns gamepad players whose gamepad pressed Select or went away
+pub fn join_coop_players(
+    mut commands: Commands,
+    gamepads: Query<(Entity, &Gamepad)>,
+    players: Query<
+        (Entity, &PlayerController, &Transform, Has<PrimaryPlayer>),
+        With<PlayerVelocity>,
+    >,
+    roster: Res<CharacterRoster>,
+    settings: Res<Settings>,
+    checkpoint: Res<ActiveCheckpoint>,
+) {
+    let mut count = players.iter().count();
+    for (player, controller, _, _) in players.iter() {
+        let PlayerController::Gamepad(gamepad) = *controller else {
+            continue;
+        };
+        let left = match gamepads.get(gamepad) {
+            Ok((_, gamepad)) => gamepad.just_pressed(GamepadButton::Select),
+            // Unplugged
+            Err(_) => true,
+        };
+        if left {
+            commands.entity(player).despawn();
+            count -= 1;
+            info!("Gamepad player left");
+        }
+    }
+
+    let position = players
+        .iter()
+        .find(|(.., primary)| *primary)
+        .map_or(checkpoint.position, |(_, _, transform, _)| {
+            transform.translation.truncate()
+        });
+    for (gamepad, buttons) in gamepads.iter() {
+        let controller = PlayerController::Gamepad(gamepad);
+        let joined = players.iter().any(|(_, other, ..)| *other == controller);
+        if joined || count >= MAX_PLAYERS || !buttons.just_pressed(GamepadButton::Start) {
+            continue;
+        }
+        count += 1;
+        info!("Player {} joined on a gamepad", count);
+        // A different character from player one, so the players tell apart
+        let character_id = roster.cycle(&settings.character, 1).to_string();
+        commands.queue(move |world: &mut World| {
+            spawn_player(world, &character_id, position, controller);
+        });
+    }
+}
@@ -0,0 +1,156 @@
+//! Scripted cutscenes for level intros and endings: a sequence of timed
+//! camera, actor, animation and dialogue actions
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveCutscene, ActiveDialogue, AnimationState, CameraEasing, CameraPath, CameraWaypoint,
+    CutsceneAction, CutsceneScript, GameState, LevelData, MainCamera,
+};
+use crate::constants::*;
+use crate::systems::dialogue::load_dialogue_script;
+
+/// Loads a cutscene from `assets/cutscenes/`
+pub fn load_cutscene(name: &str) -> Result<CutsceneScript, String> {
+    let path = format!("{}/{}", CUTSCENE_DIRECTORY, name);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read cutscene '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse cutscene '{}': {}", path, e))
+}
+
+/// Starts the cutscene `name`; returns whether it could be loaded
+pub(crate) fn start_cutscene(commands: &mut Commands, name: &str, show_results: bool) -> bool {
+    match load_cutscene(name) {
+        Ok(script) => {
+            commands.insert_resource(ActiveCutscene {
+                script,
+                action: 0,
+                elapsed: 0.0,
+                actor_start: None,
+                show_results,
+            });
+            true
+        }
+        Err(e) => {
+            error!("{}", e);
+            false
+        }
+    }
+}
+
+/// Starts the level's intro cutscene, dropping any cutscene left over from
+/// the previous level
+pub fn start_intro_cutscene(mut commands: Commands, level: Option<Res<LevelData>>) {
+    commands.remove_resource::<ActiveCutscene>();
+    if let Some(name) = level
+        .as_ref()
+        .and_then(|level| level.intro_cutscene.as_ref())
+    {
+        start_cutscene(&mut commands, name, false);
+    }
+}
+
+/// Plays the active cutscene one action at a time
+/// Only runs while `Playing`, so a dialogue opened by the cutscene pauses it
+/// until the dialogue is closed
+pub fn run_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    cutscene: Option<ResMut<ActiveCutscene>>,
+    mut actors: Query<(&Name, &mut Transform, Option<&mut AnimationState>), Without<MainCamera>>,
+    cameras: Query<Entity, With<MainCamera>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut cutscene) = cutscene else {
+        return;
+    };
+    let Some(action) = cutscene.script.actions.get(cutscene.action).cloned() else {
+        commands.remove_resource::<ActiveCutscene>();
+        for camera in cameras.iter() {
+            commands.entity(camera).remove::<CameraPath>();
+        }
+        if cutscene.show_results {
+            next_state.set(GameState::Results);
+        }
+        return;
+    };
+
+    let first_frame = cutscene.elapsed == 0.0;
+    cutscene.elapsed += time.delta_secs();
+    let done = match action {
+        CutsceneAction::Wait(seconds) => cutscene.elapsed >= seconds,
+        CutsceneAction::MoveCamera { to, duration } => {
+            if first_frame {
+                // The second waypoint holds the camera until the cutscene ends
+                let waypoints = vec![
+                    CameraWaypoint {
+                        position: to,
+                        duration,
+                        easing: CameraEasing::default(),
+                    },
+                    CameraWaypoint {
+                        position: to,
+                        duration: f32::MAX,
+                        easing: CameraEasing::Linear,
+                    },
+                ];
+                for camera in cameras.iter() {
+                    commands
+                        .entity(camera)
+                        .insert(CameraPath::new(waypoints.clone()));
+                }
+            }
+            cutscene.elapsed >= duration
+        }
+        CutsceneAction::MoveActor {
+            actor,
+            to,
+            duration,
+        } => {
+            match actors.iter_mut().find(|(name, ..)| name.as_str() == actor) {
+                Some((_, mut transform, _)) => {
+                    let start = *cutscene
+                        .actor_start
+                        .get_or_insert(transform.translation.truncate());
+                    let t = if duration > 0.0 {
+                        (cutscene.elapsed / duration).min(1.0)
+                    } else {
+                        1.0
+                    };
+                    transform.translation = start.lerp(to, t).extend(transform.translation.z);
+                }
+                None => warn!("Cutscene actor '{}' not found", actor),
+            }
+            cutscene.elapsed >= duration
+        }
+        CutsceneAction::PlayAnimation { actor, animation } => {
+            match actors.iter_mut().find(|(name, ..)| name.as_str() == actor) {
+                Some((_, _, Some(mut state))) => *state = animation,
+                _ => warn!("Cutscene actor '{}' has no animations", actor),
+            }
+            true
+        }
+        CutsceneAction::ShowDialogue(name) => {
+            match load_dialogue_script(&name) {
+                Ok(script) if !script.lines.is_empty() => {
+                    commands.insert_resource(ActiveDialogue {
+                        script,
+                        line: 0,
+                        revealed: 0.0,
+                        selected_choice: 0,
+                    });
+                    next_state.set(GameState::Dialogue);
+                }
+                Ok(_) => warn!("Dialogue '{}' has no lines", name),
+                Err(e) => error!("{}", e),
+            }
+            true
+        }
+    };
+
+    if done {
+        cutscene.action += 1;
+        cutscene.elapsed = 0.0;
+        cutscene.actor_start = None;
+    }
+}
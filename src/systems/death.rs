@@ -0,0 +1,168 @@
+//! Death and respawn sequence, driven by `PlayerDied`: death animation,
+//! screen fade and the respawn at the active checkpoint
+//!
+//! In co-op a player who dies while their partner is alive respawns next to
+//! the partner instead, without the screen fade; lives are shared
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveCheckpoint, AnimationState, Dying, GameState, Health, Lives, PlayerDied, PlayerRespawned,
+    PlayerVelocity, ScreenFade,
+};
+use crate::constants::*;
+use crate::systems::lives::respawn_player;
+
+/// Players not already dying whose health just changed
+type DamagedPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Health),
+    (With<PlayerVelocity>, Changed<Health>, Without<Dying>),
+>;
+
+/// Sends `PlayerDied` when the player's health reaches zero
+pub fn detect_player_death(players: DamagedPlayers, mut died: EventWriter<PlayerDied>) {
+    for (player, health) in players.iter() {
+        if health.current == 0 {
+            died.write(PlayerDied { player });
+        }
+    }
+}
+
+/// Players not already dying
+type LivingPlayers<'w, 's> =
+    Query<'w, 's, (Entity, &'static Transform), (With<PlayerVelocity>, Without<Dying>)>;
+
+/// Sends `PlayerDied` when a player falls past the kill plane below the
+/// level; pits inside the level use respawn zones instead
+pub fn detect_fall_death(players: LivingPlayers, mut died: EventWriter<PlayerDied>) {
+    for (player, transform) in players.iter() {
+        if transform.translation.y < LEVEL_ORIGIN_Y - KILL_PLANE_MARGIN {
+            died.write(PlayerDied { player });
+        }
+    }
+}
+
+/// Starts the death sequence: costs a life, stops the player, plays the
+/// death animation and darkens the screen unless a partner is still alive
+pub fn start_death_sequence(
+    mut commands: Commands,
+    mut died: EventReader<PlayerDied>,
+    mut players: Query<(&mut AnimationState, &mut PlayerVelocity), Without<Dying>>,
+    mut lives: ResMut<Lives>,
+) {
+    let mut alive = players.iter().count();
+    for event in died.read() {
+        let Ok((mut animation, mut velocity)) = players.get_mut(event.player) else {
+            continue;
+        };
+        lives.remaining = lives.remaining.saturating_sub(1);
+        *animation = AnimationState::Dead;
+        velocity.0 = Vec2::ZERO;
+        alive = alive.saturating_sub(1);
+
+        commands.entity(event.player).insert(Dying {
+            timer: Timer::from_seconds(DEATH_SEQUENCE_TIME, TimerMode::Once),
+        });
+        if alive > 0 {
+            continue;
+        }
+        commands.spawn((
+            Name::new("Screen fade"),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            GlobalZIndex(10),
+            ScreenFade {
+                timer: Timer::from_seconds(DEATH_SEQUENCE_TIME, TimerMode::Once),
+                fade_in: false,
+            },
+        ));
+    }
+}
+
+/// Ends the death sequence: respawns the player next to a living partner or
+/// at the active checkpoint, or goes to the game over screen after the last
+/// life
+#[allow(clippy::too_many_arguments)]
+pub fn finish_death_sequence(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(
+        Entity,
+        &mut Dying,
+        &mut Health,
+        &mut Transform,
+        &mut PlayerVelocity,
+        &mut AnimationState,
+    )>,
+    partners: Query<&Transform, (With<PlayerVelocity>, Without<Dying>)>,
+    mut fades: Query<(Entity, &mut ScreenFade)>,
+    lives: Res<Lives>,
+    checkpoint: Res<ActiveCheckpoint>,
+    mut respawned: EventWriter<PlayerRespawned>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (player, mut dying, mut health, mut transform, mut velocity, mut animation) in
+        players.iter_mut()
+    {
+        if !dying.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        commands.entity(player).remove::<Dying>();
+
+        if lives.remaining > 0 {
+            let position = partners
+                .iter()
+                .next()
+                .map_or(checkpoint.position, |partner| {
+                    partner.translation.truncate()
+                });
+            respawn_player(
+                &mut health,
+                &mut transform,
+                &mut velocity,
+                &mut animation,
+                position,
+            );
+            respawned.write(PlayerRespawned { player });
+            for (_, mut fade) in fades.iter_mut() {
+                *fade = ScreenFade {
+                    timer: Timer::from_seconds(RESPAWN_FADE_TIME, TimerMode::Once),
+                    fade_in: true,
+                };
+            }
+        } else {
+            for (entity, _) in fades.iter() {
+                commands.entity(entity).despawn();
+            }
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
+
+/// Darkens or clears the death-sequence overlay, removing it once clear
+pub fn update_screen_fade(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fades: Query<(Entity, &mut ScreenFade, &mut BackgroundColor)>,
+) {
+    for (entity, mut fade, mut color) in fades.iter_mut() {
+        fade.timer.tick(time.delta());
+        let progress = fade.timer.fraction();
+        let alpha = if fade.fade_in {
+            1.0 - progress
+        } else {
+            progress
+        };
+        color.0 = Color::BLACK.with_alpha(alpha);
+        if fade.fade_in && fade.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,62 +1,303 @@
 //! Debug systems for development and testing
 
+use bevy::log::Level;
 use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
 use bevy_rapier2d::prelude::*;
-use crate::components::{TileIndex, TilesetRegistry};
+use crate::components::{
+    AnimationState, AutostepConfig, CharacterPhysicsConfig, DebugSettings, FacingDirection,
+    GameConfig, GroundMaterial, InWater, LevelData, LogSettings, MainCamera, Particle, PlayerVelocity, Pool, Popup, PrimaryPlayer, TileCollisionMap, TileIndex,
+    TilesetRegistry,
+};
+use crate::constants::{CHARACTER_PHYSICS_CONFIG_PATH, GAME_CONFIG_PATH};
+use crate::systems::character::save_character_physics_config;
+use crate::systems::game_config::save_game_config_file;
+use crate::systems::level_loader::tile_group_name;
+use crate::systems::tiles::{collision_type, world_to_tile};
 
-/// Toggles the Rapier physics debug rendering on/off with F3 key
-pub fn toggle_debug_render(
+impl Default for DebugSettings {
+    fn default() -> Self {
+        Self {
+            window: false,
+            player_panel: false,
+            fps: false,
+            // Matches the Rapier debug render plugin, which starts enabled
+            physics_render: true,
+            controller_contacts: false,
+            tile_info: false,
+            tile_grid: false,
+            tile_collisions: false,
+            tileset_info: false,
+            physics_tuning: false,
+        }
+    }
+}
+
+/// Flips a debug setting and logs its new state
+fn toggle(setting: &mut bool, name: &str) {
+    *setting = !*setting;
+    debug!("{}: {}", name, if *setting { "ON" } else { "OFF" });
+}
+
+/// Hotkeys for the debug views, shortcuts for the debug window's checkboxes
+/// Shift picks the alternate view of F1 (the debug window) and F3 (controller
+/// contacts)
+pub fn debug_hotkeys(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<DebugSettings>) {
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    // Only mark the settings changed when a hotkey actually toggles one
+    let mut toggled = false;
+    let s = settings.bypass_change_detection();
+    let hotkeys: [(KeyCode, bool, &mut bool, &str); 10] = [
+        (KeyCode::F1, true, &mut s.window, "Debug Window"),
+        (KeyCode::F1, false, &mut s.player_panel, "Player Panel"),
+        (KeyCode::F2, false, &mut s.fps, "FPS Debug"),
+        (KeyCode::F3, false, &mut s.physics_render, "Rapier debug rendering"),
+        (KeyCode::F3, true, &mut s.controller_contacts, "Controller Contacts"),
+        (KeyCode::F4, false, &mut s.tile_info, "Tile Debug"),
+        (KeyCode::F5, false, &mut s.tile_grid, "Tile Grid"),
+        (KeyCode::F6, false, &mut s.tile_collisions, "Tile Collisions"),
+        (KeyCode::F7, false, &mut s.tileset_info, "Tileset Info"),
+        (KeyCode::F8, false, &mut s.physics_tuning, "Character Physics"),
+    ];
+    for (key, with_shift, setting, name) in hotkeys {
+        if keyboard.just_pressed(key) && shift == with_shift {
+            toggle(setting, name);
+            toggled = true;
+        }
+    }
+    if toggled {
+        settings.set_changed();
+    }
+}
+
+/// Applies the physics render setting to the Rapier debug renderer
+pub fn sync_debug_render(
+    settings: Res<DebugSettings>,
     mut debug_context: ResMut<DebugRenderContext>,
-    keyboard: Res<ButtonInput<KeyCode>>,
 ) {
-    if keyboard.just_pressed(KeyCode::F3) {
-        debug_context.enabled = !debug_context.enabled;
-        info!(
-            "Rapier debug rendering toggled: {}",
-            if debug_context.enabled { "ON" } else { "OFF" }
-        );
+    if settings.is_changed() && debug_context.enabled != settings.physics_render {
+        debug_context.enabled = settings.physics_render;
     }
 }
 
-/// Debug system to display player information in console
-/// This can be enabled/disabled for debugging purposes
-#[allow(dead_code)]
-pub fn debug_player_info(
-    query: Query<(
-        &Transform,
-        &crate::components::PlayerVelocity,
-        &crate::components::AnimationState,
-    )>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+/// Debug window with a checkbox for every debug view
+/// Levels offered by the debug window's log verbosity combo boxes
+const LOG_LEVELS: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Combo box picking a log level
+fn log_level_combo(ui: &mut egui::Ui, label: &str, level: &mut Level) {
+    egui::ComboBox::from_label(label)
+        .selected_text(level.to_string())
+        .show_ui(ui, |ui| {
+            for option in LOG_LEVELS {
+                ui.selectable_value(level, option, option.to_string());
+            }
+        });
+}
+
+pub fn debug_settings_ui(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<DebugSettings>,
+    mut log_settings: ResMut<LogSettings>,
+    mut game_config: ResMut<GameConfig>,
+    particle_pool: Option<Res<Pool<Particle>>>,
+    popup_pool: Option<Res<Pool<Popup>>>,
 ) {
-    if keyboard.just_pressed(KeyCode::F1) {
-        for (transform, velocity, animation_state) in query.iter() {
-            info!(
-                "Player Debug Info - Position: ({:.2}, {:.2}), Velocity: ({:.2}, {:.2}), Animation: {:?}",
-                transform.translation.x,
-                transform.translation.y,
-                velocity.0.x,
-                velocity.0.y,
-                animation_state
-            );
-        }
+    if !settings.window {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    // Edit a copy so change detection only fires on real edits
+    let mut edited = settings.clone();
+    let mut edited_log = log_settings.clone();
+    let mut edited_config = game_config.clone();
+    egui::Window::new("Debug")
+        .open(&mut edited.window)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut edited.player_panel, "Player panel (F1)");
+            ui.checkbox(&mut edited.fps, "FPS (F2)");
+            ui.checkbox(&mut edited.physics_render, "Physics render (F3)");
+            ui.checkbox(&mut edited.controller_contacts, "Controller contacts (Shift+F3)");
+            ui.checkbox(&mut edited.tile_info, "Hovered tile (F4)");
+            ui.checkbox(&mut edited.tile_grid, "Tile grid (F5)");
+            ui.checkbox(&mut edited.tile_collisions, "Tile collisions (F6)");
+            ui.checkbox(&mut edited.tileset_info, "Tileset info (F7)");
+            ui.checkbox(&mut edited.physics_tuning, "Character physics (F8)");
+
+            ui.collapsing("Logging", |ui| {
+                log_level_combo(ui, "Game", &mut edited_log.level);
+                for (module, level) in edited_log.modules.iter_mut() {
+                    log_level_combo(ui, module, level);
+                }
+                ui.checkbox(&mut edited_log.editor_verbose, "Verbose editor and debug tools");
+            });
+
+            ui.collapsing("Tuning", |ui| {
+                game_config_sliders(ui, &mut edited_config);
+                if ui.button("Save").clicked() {
+                    match save_game_config_file(GAME_CONFIG_PATH, &edited_config) {
+                        Ok(()) => info!("Saved game config to '{}'", GAME_CONFIG_PATH),
+                        Err(e) => error!("{}", e),
+                    }
+                }
+            });
+
+            ui.collapsing("Pools", |ui| {
+                if let Some(pool) = &particle_pool {
+                    pool_stats(ui, pool);
+                }
+                if let Some(pool) = &popup_pool {
+                    pool_stats(ui, pool);
+                }
+            });
+        });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+    if edited_log != *log_settings {
+        *log_settings = edited_log;
+    }
+    if edited_config != *game_config {
+        *game_config = edited_config;
     }
 }
 
-/// Debug system to display FPS information
-/// Toggle with F2 key
-pub fn debug_fps(
-    time: Res<Time>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut show_fps: Local<bool>,
-    mut timer: Local<f32>,
+/// One line of entity pool counters
+fn pool_stats<T: Component>(ui: &mut egui::Ui, pool: &Pool<T>) {
+    ui.label(format!(
+        "{}: {} spawned, {} reused, {}/{} free",
+        pool.name,
+        pool.spawned,
+        pool.reused,
+        pool.free.len(),
+        pool.capacity
+    ));
+}
+
+/// Sliders for the movement and camera follow values of `GameConfig`
+fn game_config_sliders(ui: &mut egui::Ui, config: &mut GameConfig) {
+    ui.add(egui::Slider::new(&mut config.player_speed, 0.0..=800.0).text("Player speed"));
+    ui.add(egui::Slider::new(&mut config.gravity, -3000.0..=0.0).text("Gravity"));
+    ui.add(egui::Slider::new(&mut config.jump_force, 0.0..=1000.0).text("Jump force"));
+    ui.add(egui::Slider::new(&mut config.jump_cut, 0.0..=1.0).text("Jump cut"));
+    ui.separator();
+    ui.add(
+        egui::Slider::new(&mut config.camera_follow_speed_x, 0.0..=20.0).text("Camera follow X"),
+    );
+    ui.add(
+        egui::Slider::new(&mut config.camera_follow_speed_y, 0.0..=20.0).text("Camera follow Y"),
+    );
+    ui.add(
+        egui::Slider::new(&mut config.camera_vertical_margin, 0.0..=400.0)
+            .text("Camera vertical margin"),
+    );
+    ui.add(egui::Slider::new(&mut config.camera_offset_y, -200.0..=300.0).text("Camera offset Y"));
+    ui.add(
+        egui::Slider::new(&mut config.camera_lookahead_distance, 0.0..=400.0)
+            .text("Lookahead distance"),
+    );
+    ui.add(
+        egui::Slider::new(&mut config.camera_lookahead_speed, 0.0..=10.0).text("Lookahead speed"),
+    );
+    ui.add(
+        egui::Slider::new(&mut config.camera_zone_blend_speed, 0.0..=10.0).text("Zone blend speed"),
+    );
+}
+
+/// The primary player's physics state shown in the debug window
+type DebuggedPlayer<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static PlayerVelocity,
+        &'static KinematicCharacterControllerOutput,
+        &'static AnimationState,
+        &'static FacingDirection,
+        &'static GroundMaterial,
+        Has<InWater>,
+    ),
+    With<PrimaryPlayer>,
+>;
+
+/// Debug panel with player one's live state
+/// Shown with the player panel setting (F1); shows the controller output of
+/// the last move and the tile the player stands on
+pub fn player_debug_ui(
+    mut contexts: EguiContexts,
+    players: DebuggedPlayer,
+    tiles: Query<(&TileIndex, &Transform)>,
+    settings: Res<DebugSettings>,
 ) {
-    if keyboard.just_pressed(KeyCode::F2) {
-        *show_fps = !*show_fps;
-        info!("FPS Debug: {}", if *show_fps { "ON" } else { "OFF" });
+    if !settings.player_panel {
+        return;
     }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let Ok((transform, velocity, output, animation, facing, ground, in_water)) = players.single()
+    else {
+        return;
+    };
+
+    // Same pick as `update_ground_material()`: a hit tile below the player
+    let underfoot = output.collisions.iter().find_map(|collision| {
+        tiles
+            .get(collision.entity)
+            .ok()
+            .filter(|(_, tile_transform)| tile_transform.translation.y < transform.translation.y)
+            .map(|(tile, _)| tile.index)
+    });
+
+    egui::Window::new("Player")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .show(ctx, |ui| {
+            egui::Grid::new("player_state").show(ui, |ui| {
+                ui.label("Position");
+                ui.monospace(format!(
+                    "({:.1}, {:.1})",
+                    transform.translation.x, transform.translation.y
+                ));
+                ui.end_row();
+                ui.label("Velocity");
+                ui.monospace(format!("({:.1}, {:.1})", velocity.0.x, velocity.0.y));
+                ui.end_row();
+                ui.label("Grounded");
+                ui.monospace(output.grounded.to_string());
+                ui.end_row();
+                ui.label("In water");
+                ui.monospace(in_water.to_string());
+                ui.end_row();
+                ui.label("Animation");
+                ui.monospace(format!("{:?}", animation));
+                ui.end_row();
+                ui.label("Facing");
+                ui.monospace(format!("{:?}", facing));
+                ui.end_row();
+                ui.label("Tile underfoot");
+                ui.monospace(underfoot.map_or("-".to_string(), |index| index.to_string()));
+                ui.end_row();
+                ui.label("Surface");
+                ui.monospace(format!(
+                    "{:?} (speed x{:.2}, bounce {:.2})",
+                    ground.0.surface, ground.0.speed_factor, ground.0.restitution
+                ));
+                ui.end_row();
+                ui.label("Contacts");
+                ui.monospace(output.collisions.len().to_string());
+                ui.end_row();
+            });
+        });
+}
 
-    if *show_fps {
+/// Debug system to display FPS information
+/// Shown with the FPS setting (F2)
+pub fn debug_fps(time: Res<Time>, settings: Res<DebugSettings>, mut timer: Local<f32>) {
+    if settings.fps {
         *timer += time.delta_secs();
         if *timer >= 1.0 {
             let fps = 1.0 / time.delta_secs();
@@ -66,64 +307,69 @@ pub fn debug_fps(
     }
 }
 
-/// Debug system to display tile information
+/// Overlay next to the cursor describing the hovered tile
+/// Shown with the tile info setting (F4); the cursor goes through the camera
+/// projection so it stays accurate when the camera moves or zooms
 pub fn debug_tile_info(
-    _camera_query: Query<&GlobalTransform, With<crate::components::MainCamera>>,
-    tile_query: Query<(&Transform, &TileIndex)>,
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     windows: Query<&Window>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut show_tile_debug: Local<bool>,
+    level: Option<Res<LevelData>>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    settings: Res<DebugSettings>,
 ) {
-    // Toggle tile debug with F4
-    if keyboard.just_pressed(KeyCode::F4) {
-        *show_tile_debug = !*show_tile_debug;
-        info!("Tile Debug: {}", if *show_tile_debug { "ON" } else { "OFF" });
-    }
-
-    if !*show_tile_debug {
-        return;
-    }
-
-    // Get mouse position (simplified - you'd need proper screen-to-world conversion)
-    if let Ok(window) = windows.single() {
-        if let Some(cursor_pos) = window.cursor_position() {
-            // Convert screen coordinates to world coordinates (simplified)
-            let world_x = cursor_pos.x - window.width() / 2.0;
-            let world_y = -(cursor_pos.y - window.height() / 2.0);
-
-            // Find tiles near cursor
-            for (transform, tile_index) in tile_query.iter() {
-                let distance = transform.translation.truncate().distance(Vec2::new(world_x, world_y));
-                if distance < 32.0 {
-                    info!(
-                        "Tile at ({:.1}, {:.1}): Index {}, Tileset pos ({}, {})",
-                        transform.translation.x,
-                        transform.translation.y,
-                        tile_index.index,
-                        tile_index.tileset_x,
-                        tile_index.tileset_y
-                    );
-                    break;
-                }
-            }
-        }
+    if !settings.tile_info {
+        return;
     }
+    let (Some(level), Some(collision_map)) = (level, collision_map) else {
+        return;
+    };
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Some(tile) = camera
+        .viewport_to_world_2d(camera_transform, cursor)
+        .ok()
+        .and_then(|position| world_to_tile(&level, position))
+    else {
+        return;
+    };
+    let Some(index) = level.tiles.get(tile.x, tile.y) else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("hovered_tile"))
+        .fixed_pos([cursor.x + 16.0, cursor.y + 16.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.monospace(format!("Tile ({}, {})", tile.x, tile.y));
+                ui.monospace(format!("Index: {}", index));
+                ui.monospace(format!(
+                    "Group: {}",
+                    tile_group_name(index).unwrap_or("Unknown")
+                ));
+                ui.monospace(format!(
+                    "Collision: {}",
+                    collision_type(&collision_map, index)
+                ));
+            });
+        });
 }
 
 /// Debug system to show tile grid overlay
 pub fn debug_tile_grid(
     mut gizmos: Gizmos,
-    camera_query: Query<&Transform, With<crate::components::MainCamera>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut show_grid: Local<bool>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    settings: Res<DebugSettings>,
 ) {
-    // Toggle grid with F5
-    if keyboard.just_pressed(KeyCode::F5) {
-        *show_grid = !*show_grid;
-        info!("Tile Grid: {}", if *show_grid { "ON" } else { "OFF" });
-    }
-
-    if !*show_grid {
+    // Grid is toggled with F5
+    if !settings.tile_grid {
         return;
     }
 
@@ -153,16 +399,10 @@ pub fn debug_tile_grid(
 pub fn debug_tile_collisions(
     mut gizmos: Gizmos,
     tile_query: Query<&Transform, (With<TileIndex>, With<Collider>)>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut show_collisions: Local<bool>,
+    settings: Res<DebugSettings>,
 ) {
-    // Toggle collision debug with F6
-    if keyboard.just_pressed(KeyCode::F6) {
-        *show_collisions = !*show_collisions;
-        info!("Tile Collisions: {}", if *show_collisions { "ON" } else { "OFF" });
-    }
-
-    if !*show_collisions {
+    // Collision debug is toggled with F6
+    if !settings.tile_collisions {
         return;
     }
 
@@ -176,26 +416,162 @@ pub fn debug_tile_collisions(
     }
 }
 
-/// Debug system to show tileset information
+/// Debug system to draw the character controller's contacts from its last move
+/// Shown with Shift+F3: the player's marker is green when grounded, and each
+/// contact point gets its normal, green for ground, red for walls and yellow
+/// for anything else (ceilings, tile seams)
+pub fn debug_controller_contacts(
+    mut gizmos: Gizmos,
+    characters: Query<
+        (&Transform, &KinematicCharacterControllerOutput),
+        With<PlayerVelocity>,
+    >,
+    colliders: Query<&GlobalTransform>,
+    settings: Res<DebugSettings>,
+) {
+    if !settings.controller_contacts {
+        return;
+    }
+
+    for (transform, output) in characters.iter() {
+        let position = transform.translation.truncate();
+        let grounded_color = if output.grounded {
+            Color::srgb(0.0, 1.0, 0.0)
+        } else {
+            Color::srgb(1.0, 0.5, 0.0)
+        };
+        gizmos.circle_2d(position, 3.0, grounded_color);
+        gizmos.arrow_2d(
+            position,
+            position + output.effective_translation * 10.0,
+            Color::srgb(0.3, 0.6, 1.0),
+        );
+
+        for collision in &output.collisions {
+            // Contact details are local to the collider that was hit
+            let (Some(details), Ok(collider_transform)) =
+                (collision.hit.details, colliders.get(collision.entity))
+            else {
+                continue;
+            };
+            let point = collider_transform
+                .transform_point(details.witness1.extend(0.0))
+                .truncate();
+            let normal = collider_transform
+                .affine()
+                .transform_vector3(details.normal1.extend(0.0))
+                .truncate()
+                .normalize_or_zero();
+
+            let color = if normal.y > 0.7 {
+                Color::srgb(0.0, 1.0, 0.0)
+            } else if normal.x.abs() > 0.7 {
+                Color::srgb(1.0, 0.0, 0.0)
+            } else {
+                Color::srgb(1.0, 1.0, 0.0)
+            };
+            gizmos.circle_2d(point, 1.5, color);
+            gizmos.arrow_2d(point, point + normal * 8.0, color);
+        }
+    }
+}
+
+/// Debug window with the loaded tilesets
+/// Shown with the tileset info setting (F7)
 pub fn debug_tileset_info(
+    mut contexts: EguiContexts,
     tileset_registry: Option<Res<TilesetRegistry>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<DebugSettings>,
 ) {
-    if keyboard.just_pressed(KeyCode::F7) {
-        if let Some(registry) = tileset_registry {
-            info!("=== Tileset Information ===");
-            for (i, tileset) in registry.tilesets.iter().enumerate() {
-                info!(
-                    "Tileset {}: {}x{} tiles, tile size: {}px",
-                    i,
-                    tileset.tiles_per_row,
-                    tileset.tiles_per_column,
-                    tileset.tile_size
-                );
+    if !settings.tileset_info {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    egui::Window::new("Tilesets").show(ctx, |ui| {
+        let Some(registry) = tileset_registry else {
+            ui.label("No tileset registry found");
+            return;
+        };
+        for (i, tileset) in registry.tilesets.iter().enumerate() {
+            ui.monospace(format!(
+                "Tileset {}: {}x{} tiles, tile size: {}px",
+                i, tileset.tiles_per_row, tileset.tiles_per_column, tileset.tile_size
+            ));
+        }
+        ui.label(format!("Current tileset: {}", registry.current_tileset));
+    });
+}
+
+/// Debug window for tuning the character controller live
+/// Shown with the character physics setting (F8); changes apply immediately and "Save" writes
+/// them back to the config file
+pub fn character_physics_debug_ui(
+    mut contexts: EguiContexts,
+    mut config: ResMut<CharacterPhysicsConfig>,
+    settings: Res<DebugSettings>,
+) {
+    if !settings.physics_tuning {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    // Edit a copy so change detection only fires on real edits
+    let mut edited = config.clone();
+    egui::Window::new("Character Physics").show(ctx, |ui| {
+        ui.add(egui::Slider::new(&mut edited.offset, 0.0..=2.0).text("Offset"));
+
+        let mut snap = edited.snap_to_ground.is_some();
+        ui.checkbox(&mut snap, "Snap to ground");
+        let mut snap_distance = edited.snap_to_ground.unwrap_or(2.0);
+        if snap {
+            ui.add(egui::Slider::new(&mut snap_distance, 0.0..=16.0).text("Snap distance"));
+        }
+        edited.snap_to_ground = snap.then_some(snap_distance);
+
+        ui.add(
+            egui::Slider::new(&mut edited.max_slope_climb_angle, 0.0..=90.0)
+                .text("Max climb angle"),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.min_slope_slide_angle, 0.0..=90.0)
+                .text("Min slide angle"),
+        );
+        ui.checkbox(&mut edited.slide, "Slide along obstacles");
+
+        let mut autostep_enabled = edited.autostep.is_some();
+        ui.checkbox(&mut autostep_enabled, "Autostep");
+        let mut autostep = edited.autostep.unwrap_or(AutostepConfig {
+            max_height: 4.0,
+            min_width: 2.0,
+            include_dynamic_bodies: false,
+        });
+        if autostep_enabled {
+            ui.add(egui::Slider::new(&mut autostep.max_height, 0.0..=16.0).text("Step height"));
+            ui.add(egui::Slider::new(&mut autostep.min_width, 0.0..=16.0).text("Step width"));
+            ui.checkbox(
+                &mut autostep.include_dynamic_bodies,
+                "Step on dynamic bodies",
+            );
+        }
+        edited.autostep = autostep_enabled.then_some(autostep);
+
+        if ui.button("Save").clicked() {
+            match save_character_physics_config(CHARACTER_PHYSICS_CONFIG_PATH, &edited) {
+                Ok(()) => info!(
+                    "Saved character physics to '{}'",
+                    CHARACTER_PHYSICS_CONFIG_PATH
+                ),
+                Err(e) => error!("{}", e),
             }
-            info!("Current tileset: {}", registry.current_tileset);
-        } else {
-            info!("No tileset registry found");
         }
+    });
+
+    if edited != *config {
+        *config = edited;
     }
 }
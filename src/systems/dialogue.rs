@@ -0,0 +1,316 @@
+//! Dialogue: scripts loaded from `assets/dialogue/`, NPC and region
+//! triggers, and the typewriter text box
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveDialogue, DialogueBodyText, DialogueChoicesText, DialoguePortrait, DialogueScript,
+    DialogueSpeakerText, DialogueTrigger, DialogueTriggerKind, DrawLayer, GameState, InputAction,
+    LevelData, LevelEntity, PlayerInput, PlayerVelocity, Settings,
+};
+use crate::constants::*;
+
+/// Loads a dialogue script from `assets/dialogue/`
+pub fn load_dialogue_script(name: &str) -> Result<DialogueScript, String> {
+    let path = format!("{}/{}", DIALOGUE_DIRECTORY, name);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read dialogue '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse dialogue '{}': {}", path, e))
+}
+
+/// Spawns the level's dialogue triggers; NPCs also get a placeholder sprite
+pub fn spawn_dialogue_triggers(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for trigger in &level.dialogue_triggers {
+        match trigger.kind {
+            DialogueTriggerKind::Npc { position } => {
+                commands.spawn((
+                    Name::new(format!("NPC ({})", trigger.script)),
+                    LevelEntity,
+                    Sprite::from_color(Color::srgb(0.7, 0.5, 0.9), Vec2::new(14.0, 24.0)),
+                    Transform::from_translation(position.extend(0.0)),
+                    DrawLayer::standing(Vec2::new(14.0, 24.0)),
+                    DialogueTrigger {
+                        script: trigger.script.clone(),
+                        area: Rect::from_center_size(position, Vec2::splat(NPC_TALK_RANGE * 2.0)),
+                        npc: true,
+                        once: trigger.once,
+                        inside: false,
+                    },
+                ));
+            }
+            DialogueTriggerKind::Region { min, max } => {
+                commands.spawn((
+                    Name::new(format!("Dialogue region ({})", trigger.script)),
+                    LevelEntity,
+                    DialogueTrigger {
+                        script: trigger.script.clone(),
+                        area: Rect::from_corners(min, max),
+                        npc: false,
+                        once: trigger.once,
+                        inside: false,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Starts a dialogue when a player talks to an NPC (interact) or walks
+/// into a dialogue region
+pub fn check_dialogue_triggers(
+    mut commands: Commands,
+    players: Query<(&Transform, &PlayerInput), With<PlayerVelocity>>,
+    mut triggers: Query<(Entity, &mut DialogueTrigger)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (entity, mut trigger) in triggers.iter_mut() {
+        let mut inside = false;
+        let mut interact = false;
+        for (transform, input) in players.iter() {
+            if trigger.area.contains(transform.translation.truncate()) {
+                inside = true;
+                interact |= input.just_pressed(InputAction::Interact);
+            }
+        }
+        let start = if trigger.npc {
+            interact
+        } else {
+            inside && !trigger.inside
+        };
+        trigger.inside = inside;
+        if !start {
+            continue;
+        }
+
+        match load_dialogue_script(&trigger.script) {
+            Ok(script) if !script.lines.is_empty() => {
+                commands.insert_resource(ActiveDialogue {
+                    script,
+                    line: 0,
+                    revealed: 0.0,
+                    selected_choice: 0,
+                });
+                next_state.set(GameState::Dialogue);
+                if trigger.once {
+                    commands.entity(entity).remove::<DialogueTrigger>();
+                }
+                return;
+            }
+            Ok(_) => warn!("Dialogue '{}' has no lines", trigger.script),
+            Err(e) => error!("{}", e),
+        }
+    }
+}
+
+/// Spawns the dialogue box at the bottom of the screen
+pub fn spawn_dialogue_box(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Dialogue box"),
+            StateScoped(GameState::Dialogue),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(10.0),
+                right: Val::Percent(10.0),
+                bottom: Val::Px(HUD_MARGIN * 2.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                column_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+            BorderRadius::all(Val::Px(6.0)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Node {
+                    width: Val::Px(DIALOGUE_PORTRAIT_SIZE),
+                    height: Val::Px(DIALOGUE_PORTRAIT_SIZE),
+                    display: Display::None,
+                    ..default()
+                },
+                ImageNode::default(),
+                DialoguePortrait,
+            ));
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    flex_grow: 1.0,
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn((
+                        Text::default(),
+                        TextFont {
+                            font_size: HUD_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 0.85, 0.4)),
+                        DialogueSpeakerText,
+                    ));
+                    column.spawn((
+                        Text::default(),
+                        TextFont {
+                            font_size: HUD_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        DialogueBodyText,
+                    ));
+                    column.spawn((
+                        Text::default(),
+                        TextFont {
+                            font_size: HUD_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.7, 0.85, 1.0)),
+                        DialogueChoicesText,
+                    ));
+                });
+        });
+}
+
+/// Reveals the current line and handles input: confirm (interact, jump or
+/// Enter) completes the line or moves on, Up/Down pick a choice
+/// Co-op gamepads drive it too, with the d-pad and the jump or interact button
+/// Uses real time since virtual time is paused during dialogues
+pub fn advance_dialogue(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    settings: Res<Settings>,
+    dialogue: Option<ResMut<ActiveDialogue>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut dialogue) = dialogue else {
+        next_state.set(GameState::Playing);
+        return;
+    };
+    let Some(line) = dialogue.script.lines.get(dialogue.line).cloned() else {
+        commands.remove_resource::<ActiveDialogue>();
+        next_state.set(GameState::Playing);
+        return;
+    };
+
+    let keys = &settings.key_bindings;
+    let gamepad_pressed = |button| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+    let length = line.text.chars().count() as f32;
+    dialogue.revealed =
+        (dialogue.revealed + DIALOGUE_CHARS_PER_SECOND * time.delta_secs()).min(length);
+
+    let choice_count = line.choices.len();
+    if choice_count > 0 {
+        if keys.just_pressed(&keyboard, InputAction::Up) || gamepad_pressed(GamepadButton::DPadUp) {
+            dialogue.selected_choice = (dialogue.selected_choice + choice_count - 1) % choice_count;
+        }
+        if keys.just_pressed(&keyboard, InputAction::Down)
+            || gamepad_pressed(GamepadButton::DPadDown)
+        {
+            dialogue.selected_choice = (dialogue.selected_choice + 1) % choice_count;
+        }
+    }
+
+    let confirm = keys.just_pressed(&keyboard, InputAction::Interact)
+        || keys.just_pressed(&keyboard, InputAction::Jump)
+        || keyboard.just_pressed(KeyCode::Enter)
+        || gamepad_pressed(GamepadButton::South)
+        || gamepad_pressed(GamepadButton::West);
+    if !confirm {
+        return;
+    }
+    if dialogue.revealed < length {
+        dialogue.revealed = length;
+        return;
+    }
+
+    let next = if choice_count > 0 {
+        line.choices[dialogue.selected_choice].next
+    } else {
+        Some(line.next.unwrap_or(dialogue.line + 1))
+    };
+    match next.filter(|&next| next < dialogue.script.lines.len()) {
+        Some(next) => {
+            dialogue.line = next;
+            dialogue.revealed = 0.0;
+            dialogue.selected_choice = 0;
+        }
+        None => {
+            commands.remove_resource::<ActiveDialogue>();
+            next_state.set(GameState::Playing);
+        }
+    }
+}
+
+/// Speaker, body and choice texts of the dialogue box
+type DialogueTexts<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Text,
+        AnyOf<(
+            &'static DialogueSpeakerText,
+            &'static DialogueBodyText,
+            &'static DialogueChoicesText,
+        )>,
+    ),
+>;
+
+/// Fills the dialogue box from the active dialogue
+pub fn update_dialogue_box(
+    asset_server: Res<AssetServer>,
+    dialogue: Option<Res<ActiveDialogue>>,
+    mut texts: DialogueTexts,
+    mut portraits: Query<(&mut ImageNode, &mut Node), With<DialoguePortrait>>,
+) {
+    let Some(dialogue) = dialogue else {
+        return;
+    };
+    if !dialogue.is_changed() {
+        return;
+    }
+    let Some(line) = dialogue.script.lines.get(dialogue.line) else {
+        return;
+    };
+
+    for (mut text, (speaker, body, choices)) in texts.iter_mut() {
+        if speaker.is_some() {
+            text.0 = line.speaker.clone();
+        } else if body.is_some() {
+            text.0 = line.text.chars().take(dialogue.revealed as usize).collect();
+        } else if choices.is_some() {
+            let fully_revealed = dialogue.revealed as usize >= line.text.chars().count();
+            text.0 = if fully_revealed {
+                line.choices
+                    .iter()
+                    .enumerate()
+                    .map(|(index, choice)| {
+                        let marker = if index == dialogue.selected_choice {
+                            ">"
+                        } else {
+                            " "
+                        };
+                        format!("{} {}", marker, choice.text)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                String::new()
+            };
+        }
+    }
+
+    for (mut image, mut node) in portraits.iter_mut() {
+        match &line.portrait {
+            Some(portrait) => {
+                image.image = asset_server.load(portrait.clone());
+                node.display = Display::Flex;
+            }
+            None => node.display = Display::None,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Locked doors and the keys that open them
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    Door, DoorOpening, DrawLayer, KeyColor, KeyItem, KeyRing, LevelData, LevelEntity,
+    PlayerVelocity,
+};
+use crate::constants::*;
+
+impl KeyColor {
+    pub fn color(self) -> Color {
+        match self {
+            KeyColor::Red => Color::srgb(0.9, 0.2, 0.2),
+            KeyColor::Blue => Color::srgb(0.2, 0.4, 0.95),
+            KeyColor::Green => Color::srgb(0.2, 0.8, 0.3),
+            KeyColor::Yellow => Color::srgb(0.95, 0.85, 0.2),
+        }
+    }
+}
+
+/// Spawns the keys and doors defined in the level data
+pub fn spawn_keys_and_doors(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, key) in level.keys.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("{:?} key {}", key.color, index)),
+            LevelEntity,
+            Sprite::from_color(key.color.color(), Vec2::new(KEY_WIDTH, KEY_HEIGHT)),
+            Transform::from_translation(key.position.extend(0.0)),
+            DrawLayer::Front,
+            KeyItem { color: key.color },
+        ));
+    }
+
+    for (index, door) in level.doors.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("{:?} door {}", door.color, index)),
+            LevelEntity,
+            Collider::cuboid(door.size.x / 2.0, door.size.y / 2.0),
+            Sprite::from_color(door.color.color().darker(0.2), door.size),
+            Transform::from_translation(door.position.extend(0.0)),
+            DrawLayer::standing(door.size),
+            Door { color: door.color },
+        ));
+    }
+}
+
+/// Adds keys the player touches to their key ring
+pub fn collect_keys(
+    mut commands: Commands,
+    mut players: Query<(&Transform, &mut KeyRing), With<PlayerVelocity>>,
+    keys: Query<(Entity, &KeyItem, &Transform), Without<PlayerVelocity>>,
+) {
+    for (player_transform, mut key_ring) in players.iter_mut() {
+        let player_pos = player_transform.translation.truncate();
+        for (entity, key, transform) in keys.iter() {
+            if transform.translation.truncate().distance(player_pos) <= KEY_PICKUP_RADIUS {
+                key_ring.keys.push(key.color);
+                commands.entity(entity).despawn();
+                info!("Picked up {:?} key", key.color);
+            }
+        }
+    }
+}
+
+/// Opens doors the player bumps into while holding a matching key
+/// The key is used up and the door loses its collider right away; the
+/// opening animation plays out afterwards
+pub fn open_doors(
+    mut commands: Commands,
+    mut players: Query<(&KinematicCharacterControllerOutput, &mut KeyRing)>,
+    doors: Query<&Door, Without<DoorOpening>>,
+) {
+    for (output, mut key_ring) in players.iter_mut() {
+        for collision in &output.collisions {
+            let Ok(door) = doors.get(collision.entity) else {
+                continue;
+            };
+            let Some(key_index) = key_ring.keys.iter().position(|&key| key == door.color) else {
+                continue;
+            };
+
+            key_ring.keys.remove(key_index);
+            commands
+                .entity(collision.entity)
+                .remove::<Collider>()
+                .insert(DoorOpening {
+                    timer: Timer::from_seconds(DOOR_OPEN_TIME, TimerMode::Once),
+                });
+            info!("Opened {:?} door", door.color);
+        }
+    }
+}
+
+/// Slides opening doors up into the ceiling while fading them out
+pub fn animate_doors(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut doors: Query<(Entity, &mut DoorOpening, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut opening, mut transform, mut sprite) in doors.iter_mut() {
+        opening.timer.tick(time.delta());
+        if opening.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let remaining = opening.timer.fraction_remaining();
+        transform.scale.y = remaining;
+        sprite.color.set_alpha(remaining);
+    }
+}
@@ -0,0 +1,49 @@
+//! Draw order of level objects: characters, NPCs and decorations sorted by
+//! the height of their feet, and fixed layers behind or in front of them
+
+use bevy::prelude::*;
+
+use crate::components::{DrawLayer, LevelData};
+use crate::constants::*;
+
+impl DrawLayer {
+    /// Sorted by the bottom edge of a sprite of `size` centered on its origin
+    pub fn standing(size: Vec2) -> Self {
+        DrawLayer::Sorted { feet: size.y / 2.0 }
+    }
+}
+
+/// Layered objects that moved or changed layer
+type MovedLayeredObjects<'w, 's> = Query<
+    'w,
+    's,
+    (&'static DrawLayer, &'static mut Transform),
+    Or<(Changed<Transform>, Changed<DrawLayer>)>,
+>;
+
+/// Sets the z of objects with a `DrawLayer` from their layer, spreading
+/// sorted objects over the sorted range by where their feet are in the
+/// level: the lower, the closer to the front
+/// Only objects that moved or changed layer are updated
+pub fn apply_draw_layers(level: Option<Res<LevelData>>, mut objects: MovedLayeredObjects) {
+    let Some(level) = level else {
+        return;
+    };
+    let height = (level.height as f32 * TILE_SIZE_16).max(1.0);
+
+    for (layer, mut transform) in objects.iter_mut() {
+        let z = match *layer {
+            DrawLayer::Behind => DRAW_LAYER_BEHIND_Z,
+            DrawLayer::Front => DRAW_LAYER_FRONT_Z,
+            DrawLayer::Sorted { feet } => {
+                let t =
+                    ((transform.translation.y - feet - LEVEL_ORIGIN_Y) / height).clamp(0.0, 1.0);
+                DRAW_LAYER_SORTED_MAX_Z - t * (DRAW_LAYER_SORTED_MAX_Z - DRAW_LAYER_SORTED_MIN_Z)
+            }
+        };
+        // Writing only on a real change keeps `Changed<Transform>` quiet
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Enemies spawned from prefabs: patrolling between walls and ledges,
+//! contact damage and stomping
+
+use bevy::prelude::*;
+
+use crate::components::{
+    DamageEvent, Dormant, Enemy, LevelData, Loot, LootTables, Particle, ParticleEffect,
+    PlayerVelocity, Pool, ScoreEvent, ScoreSource, Tile, TileGrid, TileType,
+};
+use crate::constants::*;
+use crate::systems::chests::spawn_loot;
+use crate::systems::particles::spawn_particles;
+use crate::systems::tiles::world_to_tile;
+
+const ENEMY_DEFEAT_COLOR: Color = Color::srgb(0.6, 0.3, 0.7);
+
+/// Whether enemies can walk on a tile
+fn is_floor(tile: &Tile) -> bool {
+    tile.solid || tile.tile_type == TileType::Platform
+}
+
+/// Walks enemies back and forth, facing the way they walk; they turn around
+/// at the end of their patrol range (if they have one), at walls, and at
+/// ledges when they stand on the ground
+/// `Dormant` enemies stand still until the camera comes close
+pub fn patrol_enemies(
+    time: Res<Time>,
+    level: Option<Res<LevelData>>,
+    grid: Option<Res<TileGrid>>,
+    tiles: Query<&Tile>,
+    mut enemies: Query<(&mut Enemy, &mut Transform, &mut Sprite), Without<Dormant>>,
+) {
+    let tile_at = |position: Vec2| {
+        let (level, grid) = (level.as_ref()?, grid.as_ref()?);
+        let entity = grid.get(world_to_tile(level, position)?)?;
+        tiles.get(entity).ok()
+    };
+    // Outside the level counts as a wall
+    let solid_at = |position: Vec2| {
+        level
+            .as_ref()
+            .is_some_and(|level| world_to_tile(level, position).is_none())
+            || tile_at(position).is_some_and(|tile| tile.solid)
+    };
+    let floor_at = |position: Vec2| tile_at(position).is_some_and(is_floor);
+
+    for (mut enemy, mut transform, mut sprite) in enemies.iter_mut() {
+        enemy.hit_cooldown.tick(time.delta());
+        if enemy.speed <= 0.0 {
+            continue;
+        }
+
+        transform.translation.x += enemy.direction * enemy.speed * time.delta_secs();
+        let position = transform.translation.truncate();
+        let offset = position.x - enemy.origin.x;
+        let mut turn = enemy.patrol_distance > 0.0
+            && offset.abs() >= enemy.patrol_distance
+            && offset.signum() == enemy.direction;
+        if turn {
+            transform.translation.x = enemy.origin.x + enemy.patrol_distance * enemy.direction;
+        }
+
+        let ahead = enemy.half_size.x + ENEMY_PROBE_DISTANCE;
+        let feet = position - Vec2::Y * (enemy.half_size.y + ENEMY_PROBE_DISTANCE);
+        let wall = solid_at(position + Vec2::X * ahead * enemy.direction);
+        let ledge = floor_at(feet) && !floor_at(feet + Vec2::X * ahead * enemy.direction);
+        turn |= wall || ledge;
+
+        if turn {
+            enemy.direction = -enemy.direction;
+        }
+        sprite.flip_x = enemy.direction < 0.0;
+    }
+}
+
+/// Checks the player against each enemy: landing on top defeats the enemy,
+/// bouncing the player off and dropping its loot, while any other touch
+/// hurts the player (at most once per `ENEMY_HIT_COOLDOWN`)
+pub fn enemy_contact(
+    mut commands: Commands,
+    loot_tables: Option<Res<LootTables>>,
+    mut players: Query<(Entity, &Transform, &mut PlayerVelocity)>,
+    mut enemies: Query<
+        (Entity, &mut Enemy, &Transform, Option<&Loot>),
+        (Without<PlayerVelocity>, Without<Dormant>),
+    >,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut particle_pool: ResMut<Pool<Particle>>,
+) {
+    // Enemies stomped this frame, so two players landing on the same one
+    // defeat it (and drop its loot) only once
+    let mut defeated = std::collections::HashSet::new();
+    for (player, player_transform, mut velocity) in players.iter_mut() {
+        let player_pos = player_transform.translation.truncate();
+        for (entity, mut enemy, transform, loot) in enemies.iter_mut() {
+            if defeated.contains(&entity) {
+                continue;
+            }
+            let position = transform.translation.truncate();
+            let reach =
+                enemy.half_size + Vec2::new(PLAYER_CONTACT_HALF_WIDTH, PLAYER_CONTACT_HALF_HEIGHT);
+            let delta = player_pos - position;
+            if delta.x.abs() > reach.x || delta.y.abs() > reach.y {
+                continue;
+            }
+
+            // Falling onto it from above
+            if velocity.0.y < 0.0 && delta.y > enemy.half_size.y {
+                defeated.insert(entity);
+                commands.entity(entity).try_despawn();
+                velocity.0.y = STOMP_BOUNCE_SPEED;
+                score_events.write(ScoreEvent {
+                    source: ScoreSource::EnemyKill,
+                    points: ENEMY_KILL_POINTS,
+                    position: Some(position),
+                });
+                spawn_particles(
+                    &mut commands,
+                    &mut particle_pool,
+                    ParticleEffect::Sparkle,
+                    position,
+                    ENEMY_DEFEAT_COLOR,
+                );
+                if let Some(Loot(table)) = loot {
+                    match loot_tables.as_ref().and_then(|tables| tables.0.get(table)) {
+                        Some(table) => spawn_loot(&mut commands, table, position),
+                        None => warn!("Unknown loot table '{}'", table),
+                    }
+                }
+                debug!("Enemy stomped at {}", position);
+            } else if enemy.hit_cooldown.finished() {
+                damage_events.write(DamageEvent {
+                    target: player,
+                    amount: enemy.damage,
+                });
+                enemy.hit_cooldown.reset();
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Fixed timestep support: render interpolation between steps (the input
+//! the fixed-step systems read is buffered by `player_input`)
+
+use bevy::prelude::*;
+
+use crate::components::RenderInterpolation;
+
+/// Takes a position set outside the fixed step (teleports, respawns,
+/// cutscenes) as the new physics position, with nothing to blend from
+/// Depth isn't simulated, so z (set by the draw layers) is taken as it is
+fn adopt_external_move(transform: &Transform, interpolation: &mut RenderInterpolation) {
+    if transform.translation.truncate() != interpolation.rendered.truncate() {
+        interpolation.previous = transform.translation;
+        interpolation.current = transform.translation;
+    }
+    interpolation.previous.z = transform.translation.z;
+    interpolation.current.z = transform.translation.z;
+}
+
+/// Puts interpolated entities back at their physics position before a fixed
+/// step, so movement and Rapier never see the blended one
+pub fn restore_physics_transforms(mut query: Query<(&mut Transform, &mut RenderInterpolation)>) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        adopt_external_move(&transform, &mut interpolation);
+        interpolation.previous = interpolation.current;
+        transform.translation = interpolation.current;
+        interpolation.rendered = interpolation.current;
+    }
+}
+
+/// Records where the fixed step (and Rapier's writeback) left each entity
+pub fn record_physics_transforms(mut query: Query<(&Transform, &mut RenderInterpolation)>) {
+    for (transform, mut interpolation) in query.iter_mut() {
+        interpolation.current = transform.translation;
+        interpolation.rendered = transform.translation;
+    }
+}
+
+/// Draws interpolated entities between their last two physics positions by
+/// how far the frame is into the next fixed step
+/// Runs after the fixed main loop, so `Update` systems (like the camera) see
+/// the same smoothed positions that get drawn
+pub fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut RenderInterpolation)>,
+) {
+    let blend = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        adopt_external_move(&transform, &mut interpolation);
+        let translation = interpolation.previous.lerp(interpolation.current, blend);
+        transform.translation = translation;
+        interpolation.rendered = translation;
+    }
+}
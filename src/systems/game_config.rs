@@ -0,0 +1,101 @@
+//! Gameplay tuning loaded from a config file and reloaded when it changes
+
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::GameConfig;
+use crate::constants::{GAME_CONFIG_PATH, GAME_CONFIG_POLL_INTERVAL};
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            player_speed: 300.0,
+            gravity: -981.0,
+            jump_force: 300.0,
+            jump_cut: 0.5,
+            camera_follow_speed_x: 5.0,
+            camera_follow_speed_y: 3.0,
+            camera_vertical_margin: 150.0,
+            camera_offset_y: 100.0,
+            camera_lookahead_distance: 120.0,
+            camera_lookahead_speed: 2.0,
+            camera_zone_blend_speed: 3.0,
+        }
+    }
+}
+
+/// Loads the gameplay tuning from a RON file
+pub fn load_game_config_file(path: &str) -> Result<GameConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read game config '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse game config '{}': {}", path, e))
+}
+
+/// Saves the gameplay tuning (used by the debug window)
+pub fn save_game_config_file(path: &str, config: &GameConfig) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize game config: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write game config '{}': {}", path, e))
+}
+
+/// Inserts the `GameConfig` resource, falling back to the defaults
+pub fn load_game_config(mut commands: Commands) {
+    let config = load_game_config_file(GAME_CONFIG_PATH).unwrap_or_else(|e| {
+        warn!("{}, using the default game config", e);
+        GameConfig::default()
+    });
+    commands.insert_resource(config);
+}
+
+/// Reloads the config whenever the file's modification time changes
+/// Checks every `GAME_CONFIG_POLL_INTERVAL` seconds; a file that fails to
+/// parse (e.g. mid-edit) is reported and the current values are kept
+pub fn reload_game_config(
+    time: Res<Time<bevy::time::Real>>,
+    mut config: ResMut<GameConfig>,
+    mut poll: Local<Option<Timer>>,
+    mut last_modified: Local<Option<SystemTime>>,
+) {
+    let poll = poll.get_or_insert_with(|| {
+        Timer::from_seconds(GAME_CONFIG_POLL_INTERVAL, TimerMode::Repeating)
+    });
+    if !poll.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Ok(modified) = std::fs::metadata(GAME_CONFIG_PATH).and_then(|meta| meta.modified()) else {
+        return;
+    };
+    // The first check only records the time of the file loaded at startup
+    let Some(previous) = last_modified.replace(modified) else {
+        return;
+    };
+    if previous == modified {
+        return;
+    }
+
+    match load_game_config_file(GAME_CONFIG_PATH) {
+        Ok(reloaded) => {
+            if reloaded != *config {
+                info!("Reloaded game config from '{}'", GAME_CONFIG_PATH);
+                *config = reloaded;
+            }
+        }
+        Err(e) => warn!("{}, keeping the current values", e),
+    }
+}
+
+/// Gives Rapier the config's gravity, on startup and whenever it changes
+pub fn apply_game_config(
+    config: Res<GameConfig>,
+    mut rapier_configs: Query<&mut RapierConfiguration>,
+) {
+    for mut rapier_config in rapier_configs.iter_mut() {
+        if config.is_changed() || rapier_config.is_added() {
+            rapier_config.gravity = Vec2::new(0.0, config.gravity);
+        }
+    }
+}
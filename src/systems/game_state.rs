@@ -0,0 +1,228 @@
+//! Game state transitions and the screens shown for each state
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    CharacterSelectText, DebugTime, EditorState, GameState, LevelLoadError, TileSpawnQueue,
+};
+use crate::constants::HUD_FONT_SIZE;
+
+/// Spawns a full-screen dimmed overlay with a title and a hint line, removed
+/// automatically when `state` is left
+pub(crate) fn spawn_state_screen(
+    commands: &mut Commands,
+    state: GameState,
+    title: &str,
+    hint: &str,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(format!("{:?} screen", state)),
+            StateScoped(state),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 3.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent.spawn((
+                Text::new(hint),
+                TextFont {
+                    font_size: HUD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        })
+        .id()
+}
+
+/// Menu screen, with the character select line kept up to date by
+/// `select_character()`
+pub fn spawn_menu_screen(mut commands: Commands) {
+    let screen = spawn_state_screen(
+        &mut commands,
+        GameState::Menu,
+        "Bevy Sidescroller",
+        "Press Enter to start - Left/Right to pick a character - L for the leaderboard - Settings below",
+    );
+    commands.entity(screen).with_child((
+        Text::default(),
+        TextFont {
+            font_size: HUD_FONT_SIZE * 1.5,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        CharacterSelectText,
+    ));
+}
+
+pub fn spawn_pause_screen(mut commands: Commands) {
+    spawn_state_screen(
+        &mut commands,
+        GameState::Paused,
+        "Paused",
+        "Press Escape to resume - Settings below",
+    );
+}
+
+pub fn spawn_game_over_screen(mut commands: Commands) {
+    spawn_state_screen(
+        &mut commands,
+        GameState::GameOver,
+        "Game Over",
+        "Press Enter to continue from the checkpoint - R to restart the level",
+    );
+}
+
+/// Starts the game from the menu with Enter
+pub fn start_game(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Escape pauses and resumes gameplay
+pub fn toggle_pause(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    match state.get() {
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Playing),
+        _ => {}
+    }
+}
+
+/// Tab opens the editor and closes it again
+/// Only runs while `Playing` or in the editor
+pub fn toggle_editor(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    editor_state: Res<State<EditorState>>,
+    mut next_editor_state: ResMut<NextState<EditorState>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    next_editor_state.set(match editor_state.get() {
+        EditorState::Disabled => EditorState::Enabled,
+        EditorState::Enabled => EditorState::Disabled,
+    });
+}
+
+/// Freezes gameplay under the editor and shows its banner, removed
+/// automatically when the editor closes
+pub fn enter_editor(mut commands: Commands, mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Editor);
+
+    commands.spawn((
+        Name::new("Editor banner"),
+        StateScoped(EditorState::Enabled),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        GlobalZIndex(2),
+        Text::new("Editor - Tab to return"),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+    ));
+}
+
+/// Resumes gameplay when the editor closes
+pub fn exit_editor(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+/// Window with the last level load that failed: the file, where in it the
+/// problem is and what it is; shown in the menu, while playing and in the
+/// editor, and closing it dismisses the error
+pub fn level_error_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    error: Res<LevelLoadError>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let error = &error.0;
+    let mut open = true;
+    egui::Window::new("Level error")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(ctx, |ui| {
+            ui.label(format!("File: {}", error.path));
+            if let Some(line) = error.line {
+                match error.column {
+                    Some(column) => ui.label(format!("Line {}, column {}", line, column)),
+                    None => ui.label(format!("Line {}", line)),
+                };
+            }
+            ui.colored_label(egui::Color32::LIGHT_RED, error.kind.to_string());
+        });
+    if !open {
+        commands.remove_resource::<LevelLoadError>();
+    }
+}
+
+/// Freezes virtual time and the physics pipeline outside of `Playing` (or
+/// while the debug time controls freeze the simulation), and applies the
+/// debug time scale
+/// Runs every frame but only acts when the state, debug time or level loading
+/// changes (including the initial state, once the Rapier context exists)
+pub fn sync_world_pause(
+    state: Res<State<GameState>>,
+    debug_time: Res<DebugTime>,
+    loading: Option<Res<TileSpawnQueue>>,
+    mut was_loading: Local<bool>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: Query<&mut RapierConfiguration>,
+) {
+    let loading = loading.is_some();
+    if !state.is_changed() && !debug_time.is_changed() && loading == *was_loading {
+        return;
+    }
+    *was_loading = loading;
+    time.set_relative_speed(debug_time.scale);
+    let playing = *state.get() == GameState::Playing && debug_time.running() && !loading;
+    if playing {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+    for mut config in physics.iter_mut() {
+        config.physics_pipeline_active = playing;
+    }
+}
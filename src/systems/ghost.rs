@@ -0,0 +1,152 @@
+//! Ghost replay of the best run of a level, for time-attack practice
+//!
+//! Every attempt is recorded into `GhostRecorder` against the level timer;
+//! `record_level_time()` keeps the recording in the save file when it sets a
+//! new best time, and later attempts show it as a translucent ghost
+
+use bevy::prelude::*;
+
+use crate::components::{
+    AnimationHandles, AnimationState, CharacterRoster, CurrentLevel, DrawLayer, Ghost, GhostFrame,
+    GhostRecorder, GhostRecording, LevelEntity, LevelTimer, PlayableCharacter, PrimaryPlayer,
+    SaveData, Settings,
+};
+use crate::constants::*;
+
+/// Tint of the ghost, alpha included
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.4);
+
+impl GhostFrame {
+    /// The player's current position and animation frame
+    pub(crate) fn capture(transform: &Transform, sprite: &Sprite, state: AnimationState) -> Self {
+        Self {
+            position: transform.translation.truncate(),
+            state,
+            index: sprite.texture_atlas.as_ref().map_or(0, |atlas| atlas.index),
+            flip_x: sprite.flip_x,
+        }
+    }
+
+    /// Shows the frame's animation frame on `sprite`, from the character's
+    /// sprite sheets
+    pub(crate) fn show(&self, sprite: &mut Sprite, handles: &AnimationHandles) {
+        let (image, layout) = match self.state {
+            AnimationState::Idle => (&handles.idle_texture, &handles.idle_layout),
+            AnimationState::Run => (&handles.run_texture, &handles.run_layout),
+            AnimationState::DoubleJump => {
+                (&handles.double_jump_texture, &handles.double_jump_layout)
+            }
+            AnimationState::Dead => (&handles.dead_texture, &handles.dead_layout),
+        };
+        if sprite.image != *image {
+            sprite.image = image.clone();
+        }
+        sprite.flip_x = self.flip_x;
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.layout = layout.clone();
+            atlas.index = self.index;
+        }
+    }
+}
+
+/// Samples player one into the `GhostRecorder` at `GHOST_SAMPLE_RATE` per
+/// second of level time; a new level or a reset timer starts a new recording
+pub fn record_ghost(
+    timer: Res<LevelTimer>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut recorder: ResMut<GhostRecorder>,
+    players: Query<(&PlayableCharacter, &Transform, &Sprite, &AnimationState), With<PrimaryPlayer>>,
+) {
+    let Some(current_level) = current_level else {
+        return;
+    };
+    let Ok((character, transform, sprite, state)) = players.single() else {
+        return;
+    };
+
+    let recorded_time = recorder.recording.frames.len() as f32 / GHOST_SAMPLE_RATE;
+    if recorder.level != current_level.path
+        || timer.elapsed + 2.0 / GHOST_SAMPLE_RATE < recorded_time
+    {
+        *recorder = GhostRecorder {
+            level: current_level.path.clone(),
+            recording: GhostRecording {
+                character: character.0.clone(),
+                frames: Vec::new(),
+            },
+        };
+    }
+
+    let frame = GhostFrame::capture(transform, sprite, *state);
+    // Repeat the sample over any samples a long frame skipped
+    while recorder.recording.frames.len() as f32 / GHOST_SAMPLE_RATE <= timer.elapsed {
+        recorder.recording.frames.push(frame);
+    }
+}
+
+/// Spawns the ghost of the level's best run, if one was recorded
+pub fn spawn_ghost(
+    mut commands: Commands,
+    current_level: Option<Res<CurrentLevel>>,
+    save_data: Res<SaveData>,
+    roster: Option<Res<CharacterRoster>>,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let Some(recording) = current_level
+        .as_ref()
+        .and_then(|current| save_data.ghosts.get(&current.path))
+    else {
+        return;
+    };
+    let Some(first) = recording.frames.first() else {
+        return;
+    };
+
+    let roster = roster.map_or_else(CharacterRoster::default, |roster| roster.clone());
+    let character = roster.get(&recording.character);
+    let (_, handles) = character.animations(&asset_server, &mut layouts);
+    let mut sprite = character.sprite(&handles);
+    sprite.color = GHOST_COLOR;
+    commands.spawn((
+        Name::new("Ghost"),
+        LevelEntity,
+        sprite,
+        Transform::from_translation(first.position.extend(0.0)),
+        DrawLayer::Sorted {
+            feet: PLAYER_CONTACT_HALF_HEIGHT,
+        },
+        Visibility::Hidden,
+        handles,
+        Ghost(recording.clone()),
+    ));
+}
+
+/// Moves the ghost along its recording by the level timer; it is hidden once
+/// the recording ends and while ghosts are turned off in the settings
+pub fn replay_ghost(
+    timer: Res<LevelTimer>,
+    settings: Res<Settings>,
+    mut ghosts: Query<(
+        &Ghost,
+        &AnimationHandles,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let sample = timer.elapsed * GHOST_SAMPLE_RATE;
+    for (ghost, handles, mut transform, mut sprite, mut visibility) in ghosts.iter_mut() {
+        let frames = &ghost.0.frames;
+        let Some(frame) = frames.get(sample as usize).filter(|_| settings.show_ghost) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let next = frames.get(sample as usize + 1).unwrap_or(frame);
+        let position = frame.position.lerp(next.position, sample.fract());
+        transform.translation = position.extend(transform.translation.z);
+        frame.show(&mut sprite, handles);
+    }
+}
@@ -0,0 +1,145 @@
+//! Level goal, results screen and advancing to the next level
+
+use bevy::prelude::*;
+
+use crate::components::{
+    CurrentLevel, DrawLayer, GameState, Goal, GoalKind, LevelCompleted, LevelData, LevelEntity,
+    LevelRegistry, LevelTimer, Objectives, PendingLeaderboardEntry, PlayerStats, PlayerVelocity,
+    SaveData, SaveDirectory, Score,
+};
+use crate::constants::*;
+use crate::systems::cutscene::start_cutscene;
+use crate::systems::game_state::spawn_state_screen;
+use crate::systems::level_loader::switch_level;
+use crate::systems::save::write_save_file;
+use crate::systems::time_attack::format_time;
+
+/// Spawns the level's goal flag or exit door
+pub fn spawn_goal(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(goal) = level.as_ref().and_then(|level| level.goal.clone()) else {
+        return;
+    };
+
+    let size = Vec2::new(GOAL_WIDTH, GOAL_HEIGHT);
+    let (sprite_size, color) = match goal.kind {
+        GoalKind::Flag => (Vec2::new(4.0, GOAL_HEIGHT), Color::srgb(1.0, 0.9, 0.3)),
+        GoalKind::Door => (size, Color::srgb(0.35, 0.6, 0.9)),
+    };
+    commands.spawn((
+        Name::new(format!("Goal {:?}", goal.kind)),
+        LevelEntity,
+        Sprite::from_color(color, sprite_size),
+        Transform::from_translation(goal.position.extend(0.0)),
+        DrawLayer::standing(sprite_size),
+        Goal {
+            area: Rect::from_center_size(goal.position, size),
+            touching: false,
+        },
+    ));
+}
+
+/// Completes the level when a player touches the goal: marks it as
+/// completed in the save file, sends `LevelCompleted` for the score tally
+/// and best time, and shows the results
+/// The goal stays locked while required objectives are pending, and the
+/// level's outro cutscene plays before the results
+#[allow(clippy::too_many_arguments)]
+pub fn reach_goal(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut goals: Query<&mut Goal>,
+    objectives: Option<Res<Objectives>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut save_data: ResMut<SaveData>,
+    saves: Res<SaveDirectory>,
+    mut completed: EventWriter<LevelCompleted>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut touched = false;
+    let mut just_touched = false;
+    for mut goal in goals.iter_mut() {
+        let touching = players
+            .iter()
+            .any(|transform| goal.area.contains(transform.translation.truncate()));
+        touched |= touching;
+        just_touched |= touching && !goal.touching;
+        goal.touching = touching;
+    }
+    if !touched {
+        return;
+    }
+    if objectives.is_some_and(|objectives| objectives.required_pending()) {
+        if just_touched {
+            info!("Complete the required objectives to finish the level");
+        }
+        return;
+    }
+
+    if let Some(current_level) = current_level {
+        save_data
+            .completed_levels
+            .insert(current_level.path.clone());
+        if let Err(e) = write_save_file(&saves.file(SAVE_FILE), &save_data) {
+            error!("{}", e);
+        }
+    }
+    completed.write(LevelCompleted);
+    let outro = level
+        .as_ref()
+        .and_then(|level| level.outro_cutscene.as_ref());
+    if !outro.is_some_and(|name| start_cutscene(&mut commands, name, true)) {
+        next_state.set(GameState::Results);
+    }
+}
+
+/// Shows the level's time (and medal), collectibles and score
+pub fn spawn_results_screen(
+    mut commands: Commands,
+    timer: Res<LevelTimer>,
+    stats: Res<PlayerStats>,
+    score: Res<Score>,
+    level: Option<Res<LevelData>>,
+) {
+    let medal = level
+        .as_ref()
+        .and_then(|level| level.medals)
+        .and_then(|medals| medals.medal_for(timer.elapsed));
+    let time = match medal {
+        Some(medal) => format!("Time: {} ({:?} medal)", format_time(timer.elapsed), medal),
+        None => format!("Time: {}", format_time(timer.elapsed)),
+    };
+    let summary = format!(
+        "{}\nCoins: {}  Gems: {}\nLevel score: {}  Total: {}\n\nPress Enter to continue - L for the leaderboard",
+        time, stats.coins, stats.gems, score.level_points, score.total
+    );
+    spawn_state_screen(
+        &mut commands,
+        GameState::Results,
+        "Level Complete",
+        &summary,
+    );
+}
+
+/// Enter on the results screen loads the next level of the registry, or
+/// returns to the menu with the first level loaded after the last one
+/// Waits while the player is typing initials for the leaderboard
+pub fn advance_from_results(world: &mut World) {
+    if !world
+        .resource::<ButtonInput<KeyCode>>()
+        .just_pressed(KeyCode::Enter)
+        || world.contains_resource::<PendingLeaderboardEntry>()
+    {
+        return;
+    }
+
+    let registry = world.resource::<LevelRegistry>();
+    let current = world.get_resource::<CurrentLevel>();
+    let (path, state) = match current.and_then(|current| registry.next_after(&current.path)) {
+        Some(next) => (next.to_string(), GameState::Playing),
+        None => (registry.first().to_string(), GameState::Menu),
+    };
+
+    switch_level(world, &path);
+    world.resource_mut::<NextState<GameState>>().set(state);
+}
@@ -0,0 +1,398 @@
+//! Falling hazards (stalactites, boulders) that drop on the player, and
+//! moving hazards (saw blades, crushers) that hurt on contact
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    CameraShake, DamageEvent, Dormant, FallingHazard, FallingHazardKind, FallingHazardState,
+    HazardMotion, LevelData, LevelEntity, MovingHazard, MovingHazardKind, Particle, ParticleEffect,
+    PlatformPathMode, PlayerRespawned, PlayerVelocity, Pool, SleepsOffScreen, SoundEvent,
+};
+use crate::constants::*;
+use crate::systems::particles::spawn_particles;
+use crate::systems::platforms::next_waypoint;
+
+impl FallingHazardKind {
+    fn color(self) -> Color {
+        match self {
+            FallingHazardKind::Stalactite => Color::srgb(0.55, 0.5, 0.45),
+            FallingHazardKind::Boulder => Color::srgb(0.45, 0.42, 0.4),
+        }
+    }
+}
+
+/// Spawns the falling hazards defined in the level data, hanging in place
+pub fn spawn_falling_hazards(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, hazard) in level.falling_hazards.iter().enumerate() {
+        let (collider, size) = match hazard.kind {
+            FallingHazardKind::Stalactite => (
+                Collider::cuboid(STALACTITE_WIDTH / 2.0, STALACTITE_HEIGHT / 2.0),
+                Vec2::new(STALACTITE_WIDTH, STALACTITE_HEIGHT),
+            ),
+            FallingHazardKind::Boulder => (
+                Collider::ball(BOULDER_RADIUS),
+                Vec2::splat(BOULDER_RADIUS * 2.0),
+            ),
+        };
+
+        commands.spawn((
+            Name::new(format!("{:?} {}", hazard.kind, index)),
+            LevelEntity,
+            RigidBody::Fixed,
+            collider,
+            Sprite::from_color(hazard.kind.color(), size),
+            Transform::from_translation(hazard.position.extend(0.0)),
+            FallingHazard {
+                kind: hazard.kind,
+                origin: hazard.position,
+                damage: hazard.damage,
+                state: FallingHazardState::Armed,
+            },
+            SleepsOffScreen,
+        ));
+    }
+}
+
+/// Starts shaking (and rumbling) armed hazards when a downward ray from them
+/// hits the player (walls and platforms in between block it)
+/// `Dormant` hazards far off screen don't cast their ray
+pub fn trigger_falling_hazards(
+    rapier_context: ReadRapierContext,
+    players: Query<(), With<PlayerVelocity>>,
+    mut hazards: Query<(Entity, &mut FallingHazard, &Transform), Without<Dormant>>,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+
+    for (entity, mut hazard, transform) in hazards.iter_mut() {
+        if !matches!(hazard.state, FallingHazardState::Armed) {
+            continue;
+        }
+        let filter = QueryFilter::default()
+            .exclude_sensors()
+            .exclude_collider(entity);
+        let hit = context.cast_ray(
+            transform.translation.truncate(),
+            Vec2::NEG_Y,
+            HAZARD_TRIGGER_RANGE,
+            true,
+            filter,
+        );
+        if hit.is_some_and(|(hit_entity, _)| players.contains(hit_entity)) {
+            hazard.state = FallingHazardState::Shaking(Timer::from_seconds(
+                HAZARD_SHAKE_TIME,
+                TimerMode::Once,
+            ));
+            sound_events.write(SoundEvent {
+                name: "hazard_rumble",
+                position: Some(transform.translation.truncate()),
+            });
+        }
+    }
+}
+
+/// Shakes triggered hazards, then drops them as dynamic bodies; a falling
+/// hazard damages the player on contact and breaks on whatever it hits,
+/// shaking the camera
+#[allow(clippy::too_many_arguments)]
+pub fn update_falling_hazards(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    players: Query<(), With<PlayerVelocity>>,
+    mut hazards: Query<(Entity, &mut FallingHazard, &mut Transform, &mut Visibility)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut sound_events: EventWriter<SoundEvent>,
+    mut particle_pool: ResMut<Pool<Particle>>,
+    mut shake: ResMut<CameraShake>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+
+    for (entity, mut hazard, mut transform, mut visibility) in hazards.iter_mut() {
+        let origin = hazard.origin;
+        match &mut hazard.state {
+            FallingHazardState::Shaking(timer) => {
+                timer.tick(time.delta());
+                if timer.finished() {
+                    transform.translation.x = origin.x;
+                    commands.entity(entity).insert((
+                        RigidBody::Dynamic,
+                        Velocity::zero(),
+                        LockedAxes::ROTATION_LOCKED,
+                    ));
+                    hazard.state = FallingHazardState::Falling;
+                } else {
+                    let phase = timer.elapsed_secs() * HAZARD_SHAKE_FREQUENCY;
+                    transform.translation.x = origin.x + phase.sin() * HAZARD_SHAKE_AMPLITUDE;
+                }
+            }
+            FallingHazardState::Falling => {
+                let mut hit = false;
+                for pair in context.contact_pairs_with(entity) {
+                    if !pair.has_any_active_contact() {
+                        continue;
+                    }
+                    let (Some(collider1), Some(collider2)) = (pair.collider1(), pair.collider2())
+                    else {
+                        continue;
+                    };
+                    let other = if collider1 == entity {
+                        collider2
+                    } else {
+                        collider1
+                    };
+                    if players.contains(other) {
+                        damage_events.write(DamageEvent {
+                            target: other,
+                            amount: hazard.damage,
+                        });
+                    }
+                    hit = true;
+                }
+                if !hit {
+                    continue;
+                }
+
+                commands
+                    .entity(entity)
+                    .remove::<(Velocity, LockedAxes)>()
+                    .insert((RigidBody::Fixed, ColliderDisabled));
+                *visibility = Visibility::Hidden;
+                spawn_particles(
+                    &mut commands,
+                    &mut particle_pool,
+                    ParticleEffect::Debris,
+                    transform.translation.truncate(),
+                    hazard.kind.color(),
+                );
+                sound_events.write(SoundEvent {
+                    name: "hazard_crash",
+                    position: Some(transform.translation.truncate()),
+                });
+                shake.add_trauma(CAMERA_SHAKE_CRASH_TRAUMA);
+                hazard.state = FallingHazardState::Broken;
+            }
+            FallingHazardState::Armed | FallingHazardState::Broken => {}
+        }
+    }
+}
+
+/// Hangs every triggered or broken hazard back in place when the player
+/// respawns
+pub fn rearm_falling_hazards(
+    mut commands: Commands,
+    mut respawned: EventReader<PlayerRespawned>,
+    mut hazards: Query<(Entity, &mut FallingHazard, &mut Transform, &mut Visibility)>,
+) {
+    if respawned.read().count() == 0 {
+        return;
+    }
+
+    for (entity, mut hazard, mut transform, mut visibility) in hazards.iter_mut() {
+        if matches!(hazard.state, FallingHazardState::Armed) {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .remove::<(Velocity, LockedAxes, ColliderDisabled)>()
+            .insert(RigidBody::Fixed);
+        *transform = Transform::from_translation(hazard.origin.extend(0.0));
+        *visibility = Visibility::Inherited;
+        hazard.state = FallingHazardState::Armed;
+    }
+}
+
+impl MovingHazardKind {
+    fn color(self) -> Color {
+        match self {
+            MovingHazardKind::SawBlade => Color::srgb(0.75, 0.75, 0.8),
+            MovingHazardKind::Crusher => Color::srgb(0.4, 0.38, 0.42),
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            MovingHazardKind::SawBlade => Vec2::splat(SAW_BLADE_RADIUS * 2.0),
+            MovingHazardKind::Crusher => Vec2::new(CRUSHER_WIDTH, CRUSHER_HEIGHT),
+        }
+    }
+
+    /// How far from `from` towards `to` an oscillating hazard is, `cycle`
+    /// (0.0 to 1.0) of the way into its period
+    fn oscillation(self, cycle: f32) -> f32 {
+        match self {
+            MovingHazardKind::SawBlade => 0.5 - 0.5 * (cycle * std::f32::consts::TAU).cos(),
+            MovingHazardKind::Crusher => {
+                if cycle < CRUSHER_SLAM_FRACTION {
+                    (cycle / CRUSHER_SLAM_FRACTION).powi(2)
+                } else {
+                    1.0 - (cycle - CRUSHER_SLAM_FRACTION) / (1.0 - CRUSHER_SLAM_FRACTION)
+                }
+            }
+        }
+    }
+}
+
+impl HazardMotion {
+    /// Where the hazard starts
+    fn start(&self, kind: MovingHazardKind) -> Option<Vec2> {
+        match self {
+            HazardMotion::Path { waypoints, .. } => waypoints.first().copied(),
+            HazardMotion::Oscillate {
+                from, to, phase, ..
+            } => Some(from.lerp(*to, kind.oscillation(phase.rem_euclid(1.0)))),
+        }
+    }
+}
+
+/// Spawns the moving hazards defined in the level data
+pub fn spawn_moving_hazards(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, hazard) in level.moving_hazards.iter().enumerate() {
+        let Some(start) = hazard.motion.start(hazard.kind) else {
+            warn!("Moving hazard {} has no waypoints, skipping", index);
+            continue;
+        };
+        let (target, elapsed) = match &hazard.motion {
+            HazardMotion::Path { waypoints, .. } => (usize::min(1, waypoints.len() - 1), 0.0),
+            HazardMotion::Oscillate { period, phase, .. } => (0, phase.rem_euclid(1.0) * period),
+        };
+
+        commands.spawn((
+            Name::new(format!("{:?} {}", hazard.kind, index)),
+            LevelEntity,
+            Sprite::from_color(hazard.kind.color(), hazard.kind.size()),
+            Transform::from_translation(start.extend(0.0)),
+            MovingHazard {
+                kind: hazard.kind,
+                motion: hazard.motion.clone(),
+                damage: hazard.damage,
+                target,
+                forward: true,
+                elapsed,
+                hit_cooldown: Timer::from_seconds(MOVING_HAZARD_HIT_COOLDOWN, TimerMode::Once),
+            },
+            SleepsOffScreen,
+        ));
+    }
+}
+
+/// Moves hazards along their path or oscillation and spins saw blades
+/// `Dormant` hazards far off screen hold still
+pub fn move_hazards(
+    time: Res<Time>,
+    mut hazards: Query<(&mut MovingHazard, &mut Transform), Without<Dormant>>,
+) {
+    for (mut hazard, mut transform) in hazards.iter_mut() {
+        let hazard = &mut *hazard;
+        hazard.hit_cooldown.tick(time.delta());
+        hazard.elapsed += time.delta_secs();
+
+        let position = match &hazard.motion {
+            HazardMotion::Path {
+                waypoints,
+                speed,
+                mode,
+            } => {
+                let position = transform.translation.truncate();
+                let Some(&target) = waypoints.get(hazard.target) else {
+                    continue;
+                };
+                let step = speed * time.delta_secs();
+                let to_target = target - position;
+                if to_target.length() <= step {
+                    next_waypoint(
+                        &mut hazard.target,
+                        &mut hazard.forward,
+                        *mode,
+                        waypoints.len(),
+                    );
+                    target
+                } else {
+                    position + to_target.normalize() * step
+                }
+            }
+            HazardMotion::Oscillate {
+                from, to, period, ..
+            } => {
+                let cycle = (hazard.elapsed / period.max(f32::EPSILON)).fract();
+                from.lerp(*to, hazard.kind.oscillation(cycle))
+            }
+        };
+        transform.translation = position.extend(transform.translation.z);
+
+        if hazard.kind == MovingHazardKind::SawBlade {
+            transform.rotate_z(-SAW_BLADE_SPIN_SPEED * time.delta_secs());
+        }
+    }
+}
+
+/// Hurts players touching a moving hazard, at most once per
+/// `MOVING_HAZARD_HIT_COOLDOWN` for each hazard
+pub fn moving_hazard_contact(
+    players: Query<(Entity, &Transform), With<PlayerVelocity>>,
+    mut hazards: Query<
+        (&mut MovingHazard, &Transform),
+        (Without<PlayerVelocity>, Without<Dormant>),
+    >,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let player_half_size = Vec2::new(PLAYER_CONTACT_HALF_WIDTH, PLAYER_CONTACT_HALF_HEIGHT);
+    for (mut hazard, transform) in hazards.iter_mut() {
+        if !hazard.hit_cooldown.finished() {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let reach = hazard.kind.size() / 2.0 + player_half_size;
+        for (player, player_transform) in players.iter() {
+            let delta = player_transform.translation.truncate() - position;
+            if delta.x.abs() > reach.x || delta.y.abs() > reach.y {
+                continue;
+            }
+            damage_events.write(DamageEvent {
+                target: player,
+                amount: hazard.damage,
+            });
+            hazard.hit_cooldown.reset();
+        }
+    }
+}
+
+/// Draws where moving hazards travel in the editor: their path, or the line
+/// they oscillate along
+pub fn draw_moving_hazards(mut gizmos: Gizmos, hazards: Query<&MovingHazard>) {
+    let color = Color::srgba(1.0, 0.35, 0.3, 0.8);
+    for hazard in hazards.iter() {
+        let half_size = hazard.kind.size() / 2.0;
+        match &hazard.motion {
+            HazardMotion::Path {
+                waypoints, mode, ..
+            } => {
+                gizmos.linestrip_2d(waypoints.iter().copied(), color);
+                if *mode == PlatformPathMode::Loop && waypoints.len() > 2 {
+                    gizmos.line_2d(waypoints[waypoints.len() - 1], waypoints[0], color);
+                }
+                for &waypoint in waypoints {
+                    gizmos.rect_2d(waypoint, half_size * 2.0, color.with_alpha(0.3));
+                }
+            }
+            HazardMotion::Oscillate { from, to, .. } => {
+                gizmos.line_2d(*from, *to, color);
+                gizmos.rect_2d(*from, half_size * 2.0, color.with_alpha(0.3));
+                gizmos.rect_2d(*to, half_size * 2.0, color);
+            }
+        }
+    }
+}
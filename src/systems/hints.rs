@@ -0,0 +1,172 @@
+//! Tutorial hints: control tips shown once when a player first walks into a
+//! hint region, remembered in the save file so they don't repeat
+
+use bevy::prelude::*;
+
+use crate::components::{
+    CurrentLevel, HintPopup, HintTrigger, InputAction, LevelData, LevelEntity, PlayerVelocity,
+    SaveData, SaveDirectory, Settings,
+};
+use crate::constants::*;
+use crate::systems::save::write_save_file;
+
+const HINT_TEXT_COLOR: Color = Color::WHITE;
+const HINT_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// Spawns the hidden hint text above the bottom edge of the screen
+pub fn setup_hint_popup(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Hint"),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Percent(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_child((
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(HINT_BACKGROUND_COLOR),
+            Text::default(),
+            TextFont {
+                font_size: HINT_FONT_SIZE,
+                ..default()
+            },
+            TextColor(HINT_TEXT_COLOR),
+            Visibility::Hidden,
+            HintPopup {
+                timer: Timer::from_seconds(HINT_DURATION, TimerMode::Once),
+            },
+        ));
+}
+
+/// Spawns the level's hint regions, skipping hints already shown
+pub fn spawn_hints(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    save_data: Res<SaveData>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    let shown_hints = current_level
+        .as_ref()
+        .and_then(|current| save_data.shown_hints.get(&current.path));
+
+    for hint in &level.hints {
+        if shown_hints.is_some_and(|ids| ids.contains(&hint.id)) {
+            continue;
+        }
+        commands.spawn((
+            Name::new(format!("Hint region ({})", hint.id)),
+            LevelEntity,
+            HintTrigger {
+                id: hint.id.clone(),
+                text: hint.text.clone(),
+                area: Rect::from_corners(hint.min, hint.max),
+            },
+        ));
+    }
+}
+
+/// Name of a key for hint text: `KeyA` becomes `A`, `Digit1` becomes `1`
+fn key_label(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// Replaces the `{action}` placeholders of a hint with the bound keys
+fn fill_hint_text(text: &str, settings: &Settings) -> String {
+    InputAction::ALL
+        .into_iter()
+        .fold(text.to_string(), |text, action| {
+            let placeholder = format!("{{{:?}}}", action).to_lowercase();
+            text.replace(&placeholder, &key_label(settings.key_bindings.key(action)))
+        })
+}
+
+/// Shows a hint when a player enters its region, recording it in `SaveData`
+/// and removing the region
+#[allow(clippy::too_many_arguments)]
+pub fn show_hints(
+    mut commands: Commands,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    triggers: Query<(Entity, &HintTrigger)>,
+    mut popups: Query<(
+        &mut Text,
+        &mut TextColor,
+        &mut BackgroundColor,
+        &mut Visibility,
+        &mut HintPopup,
+    )>,
+    settings: Res<Settings>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut save_data: ResMut<SaveData>,
+    saves: Res<SaveDirectory>,
+) {
+    for (entity, trigger) in triggers.iter() {
+        let entered = players
+            .iter()
+            .any(|transform| trigger.area.contains(transform.translation.truncate()));
+        if !entered {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        for (mut text, mut color, mut background, mut visibility, mut popup) in popups.iter_mut() {
+            text.0 = fill_hint_text(&trigger.text, &settings);
+            color.0 = HINT_TEXT_COLOR;
+            background.0 = HINT_BACKGROUND_COLOR;
+            *visibility = Visibility::Inherited;
+            popup.timer.reset();
+        }
+
+        if let Some(current) = &current_level {
+            save_data
+                .shown_hints
+                .entry(current.path.clone())
+                .or_default()
+                .insert(trigger.id.clone());
+            if let Err(e) = write_save_file(&saves.file(SAVE_FILE), &save_data) {
+                error!("{}", e);
+            }
+        }
+        // One hint per frame; a newer hint replaces the one on screen
+        return;
+    }
+}
+
+/// Fades the shown hint out over its last `HINT_FADE_TIME` seconds
+pub fn update_hint_popup(
+    time: Res<Time>,
+    mut popups: Query<(
+        &mut TextColor,
+        &mut BackgroundColor,
+        &mut Visibility,
+        &mut HintPopup,
+    )>,
+) {
+    for (mut color, mut background, mut visibility, mut popup) in popups.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        popup.timer.tick(time.delta());
+        let alpha = (popup.timer.remaining_secs() / HINT_FADE_TIME).min(1.0);
+        color.0.set_alpha(alpha);
+        background
+            .0
+            .set_alpha(HINT_BACKGROUND_COLOR.alpha() * alpha);
+        if popup.timer.finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
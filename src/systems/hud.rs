@@ -0,0 +1,229 @@
+//! On-screen HUD: health hearts, lives, collectible counts, objectives, level timer,
+//! score and the distance to a rising liquid
+
+use bevy::prelude::*;
+
+use crate::components::{
+    CurrentLevel, Health, HudCollectiblesText, HudHeart, HudLiquidText, HudLivesText,
+    HudObjectivesText, HudScoreText, HudTimerText, LevelTimer, Lives, Objective, Objectives,
+    PlayerStats, PrimaryPlayer, RisingLiquid, SaveData, Score,
+};
+use crate::constants::*;
+use crate::systems::time_attack::format_time;
+
+const HEART_FULL_COLOR: Color = Color::srgb(0.9, 0.15, 0.2);
+const HEART_EMPTY_COLOR: Color = Color::srgba(0.2, 0.2, 0.2, 0.6);
+
+/// Spawns the HUD: hearts and collectible count in the top-left corner,
+/// timer, score and rising liquid distance in the top-right corner
+pub fn setup_hud(mut commands: Commands) {
+    let text_font = TextFont {
+        font_size: HUD_FONT_SIZE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Name::new("HUD left"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HUD_MARGIN),
+                left: Val::Px(HUD_MARGIN),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                })
+                .with_children(|hearts| {
+                    for index in 0..PLAYER_MAX_HEALTH {
+                        hearts.spawn((
+                            Node {
+                                width: Val::Px(HUD_HEART_SIZE),
+                                height: Val::Px(HUD_HEART_SIZE),
+                                ..default()
+                            },
+                            BackgroundColor(HEART_FULL_COLOR),
+                            HudHeart { index },
+                        ));
+                    }
+                });
+            parent.spawn((
+                Text::new(format!("Lives: {}", PLAYER_LIVES)),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+                HudLivesText,
+            ));
+            parent.spawn((
+                Text::new("Coins: 0  Gems: 0"),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+                HudCollectiblesText,
+            ));
+            parent.spawn((
+                Text::default(),
+                text_font.clone(),
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                HudObjectivesText,
+            ));
+        });
+
+    commands
+        .spawn((
+            Name::new("HUD right"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HUD_MARGIN),
+                right: Val::Px(HUD_MARGIN),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("00:00.0"),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+                HudTimerText,
+            ));
+            parent.spawn((
+                Text::new("Score: 0"),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+                HudScoreText,
+            ));
+            parent.spawn((
+                Text::default(),
+                text_font,
+                TextColor(Color::srgb(1.0, 0.6, 0.3)),
+                HudLiquidText,
+            ));
+        });
+}
+
+/// Fills or empties the hearts when player one's health changes
+pub fn update_hud_hearts(
+    players: Query<&Health, (With<PrimaryPlayer>, Changed<Health>)>,
+    mut hearts: Query<(&HudHeart, &mut BackgroundColor, &mut Visibility)>,
+) {
+    let Ok(health) = players.single() else {
+        return;
+    };
+    for (heart, mut color, mut visibility) in hearts.iter_mut() {
+        *visibility = if heart.index < health.max {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        color.0 = if heart.index < health.current {
+            HEART_FULL_COLOR
+        } else {
+            HEART_EMPTY_COLOR
+        };
+    }
+}
+
+/// Refreshes the lives count when `Lives` changes
+pub fn update_hud_lives(lives: Res<Lives>, mut texts: Query<&mut Text, With<HudLivesText>>) {
+    if !lives.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = format!("Lives: {}", lives.remaining);
+    }
+}
+
+/// Refreshes the collectible counts when `PlayerStats` changes
+pub fn update_hud_collectibles(
+    stats: Res<PlayerStats>,
+    mut texts: Query<&mut Text, With<HudCollectiblesText>>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = format!("Coins: {}  Gems: {}", stats.coins, stats.gems);
+    }
+}
+
+/// Lists the objectives when their progress changes
+pub fn update_hud_objectives(
+    objectives: Option<Res<Objectives>>,
+    mut texts: Query<&mut Text, With<HudObjectivesText>>,
+) {
+    let Some(objectives) = objectives else {
+        return;
+    };
+    if !objectives.is_changed() {
+        return;
+    }
+    let lines: Vec<String> = objectives.entries.iter().map(Objective::label).collect();
+    for mut text in texts.iter_mut() {
+        text.0 = lines.join("\n");
+    }
+}
+
+/// Shows the attempt timer, plus the level's best time once there is one
+pub fn update_hud_timer(
+    timer: Res<LevelTimer>,
+    save_data: Res<SaveData>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut texts: Query<&mut Text, With<HudTimerText>>,
+) {
+    let best = current_level
+        .as_ref()
+        .and_then(|current| save_data.best_times.get(&current.path));
+    for mut text in texts.iter_mut() {
+        text.0 = match best {
+            Some(&best) => format!(
+                "{}  (best {})",
+                format_time(timer.elapsed),
+                format_time(best)
+            ),
+            None => format_time(timer.elapsed),
+        };
+    }
+}
+
+/// Shows the score, plus the multiplier the next pickup would get while a combo is running
+pub fn update_hud_score(score: Res<Score>, mut texts: Query<&mut Text, With<HudScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = if score.combo > 0 {
+            format!("Score: {}  x{}", score.total, score.multiplier())
+        } else {
+            format!("Score: {}", score.total)
+        };
+    }
+}
+
+/// Shows how far below player one the rising liquid's surface is, in tiles;
+/// empty in levels without one
+pub fn update_hud_liquid(
+    players: Query<&Transform, With<PrimaryPlayer>>,
+    liquids: Query<&RisingLiquid>,
+    mut texts: Query<&mut Text, With<HudLiquidText>>,
+) {
+    let label = match (players.single(), liquids.single()) {
+        (Ok(transform), Ok(liquid)) => {
+            let feet = transform.translation.y - PLAYER_CONTACT_HALF_HEIGHT;
+            let distance = ((feet - liquid.surface) / TILE_SIZE_16).max(0.0);
+            format!("{:?}: {:.1} tiles below", liquid.def.kind, distance)
+        }
+        _ => String::new(),
+    };
+    for mut text in texts.iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
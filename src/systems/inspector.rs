@@ -0,0 +1,19 @@
+//! World inspector for viewing and editing components and resources live
+//!
+//! The inspector window itself is only built with the `inspector` feature
+//! (`cargo run --features inspector`); the types it shows are registered by
+//! `GamePlugin` (see `type_registry`)
+
+#[cfg(feature = "inspector")]
+use bevy::input::common_conditions::input_toggle_active;
+use bevy::prelude::*;
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+
+/// With the `inspector` feature, adds the inspector window (hidden until F10
+/// is pressed)
+#[cfg_attr(not(feature = "inspector"), allow(unused_variables))]
+pub fn inspector_plugin(app: &mut App) {
+    #[cfg(feature = "inspector")]
+    app.add_plugins(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F10)));
+}
@@ -0,0 +1,262 @@
+//! Local leaderboard of the best runs per level, with initials typed on the
+//! results screen
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::components::{
+    CurrentLevel, GameState, Leaderboard, LeaderboardEntry, LeaderboardInitialsText,
+    LeaderboardReturn, LevelCompleted, LevelRegistry, LevelTimer, PendingLeaderboardEntry,
+    SaveDirectory, Score,
+};
+use crate::constants::*;
+use crate::systems::game_state::spawn_state_screen;
+use crate::systems::time_attack::format_time;
+
+impl Leaderboard {
+    /// Whether a run with `score` and `time` would make the level's board
+    /// Higher scores rank first, faster times break ties
+    pub fn qualifies(&self, level: &str, score: u32, time: f32) -> bool {
+        let entries = self
+            .levels
+            .get(level)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        entries.len() < LEADERBOARD_SIZE
+            || entries
+                .last()
+                .is_some_and(|last| score > last.score || (score == last.score && time < last.time))
+    }
+
+    /// Adds a run to the level's board, keeping it sorted and trimmed
+    pub fn insert(&mut self, level: &str, entry: LeaderboardEntry) {
+        let entries = self.levels.entry(level.to_string()).or_default();
+        entries.push(entry);
+        entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.time.total_cmp(&b.time)));
+        entries.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+/// Loads the leaderboard from a RON file
+pub fn load_leaderboard_file(path: &str) -> Result<Leaderboard, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read leaderboard '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse leaderboard '{}': {}", path, e))
+}
+
+/// Writes the leaderboard to a RON file, creating its directory if needed
+pub fn write_leaderboard_file(path: &str, leaderboard: &Leaderboard) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create save directory '{}': {}", dir.display(), e))?;
+    }
+    let contents = ron::ser::to_string_pretty(leaderboard, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize leaderboard: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write leaderboard '{}': {}", path, e))
+}
+
+/// Inserts the `Leaderboard` resource from its file (empty on first run)
+pub fn load_leaderboard(mut commands: Commands, saves: Res<SaveDirectory>) {
+    let path = saves.file(LEADERBOARD_FILE);
+    let leaderboard = if Path::new(&path).exists() {
+        load_leaderboard_file(&path).unwrap_or_else(|e| {
+            warn!("{}, starting a new leaderboard", e);
+            Leaderboard::default()
+        })
+    } else {
+        Leaderboard::default()
+    };
+    commands.insert_resource(leaderboard);
+}
+
+/// When a level is completed with a run good enough for its board, asks for
+/// the player's initials on the results screen
+/// Runs after the score tally so the level score is final
+pub fn prepare_leaderboard_entry(
+    mut commands: Commands,
+    mut completed: EventReader<LevelCompleted>,
+    leaderboard: Res<Leaderboard>,
+    current_level: Option<Res<CurrentLevel>>,
+    timer: Res<LevelTimer>,
+    score: Res<Score>,
+) {
+    if completed.read().last().is_none() {
+        return;
+    }
+    let Some(current_level) = current_level else {
+        return;
+    };
+
+    if leaderboard.qualifies(&current_level.path, score.level_points, timer.elapsed) {
+        commands.insert_resource(PendingLeaderboardEntry {
+            level: current_level.path.clone(),
+            entry: LeaderboardEntry {
+                initials: String::new(),
+                time: timer.elapsed,
+                score: score.level_points,
+            },
+        });
+    }
+}
+
+/// Adds the initials prompt to the results screen while a run is pending
+pub fn spawn_initials_prompt(
+    mut commands: Commands,
+    pending: Option<Res<PendingLeaderboardEntry>>,
+) {
+    if pending.is_none() {
+        return;
+    }
+    commands.spawn((
+        Name::new("Leaderboard initials"),
+        StateScoped(GameState::Results),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(15.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        GlobalZIndex(1),
+        Text::new(""),
+        TextFont {
+            font_size: HUD_FONT_SIZE * 1.5,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+        TextLayout::new_with_justify(JustifyText::Center),
+        LeaderboardInitialsText,
+    ));
+}
+
+/// Types the initials of a pending run (letters and digits, Backspace to
+/// erase); Enter saves it to the leaderboard
+/// The Enter press is consumed so it doesn't also leave the results screen
+pub fn enter_leaderboard_initials(
+    mut commands: Commands,
+    mut key_events: EventReader<KeyboardInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    pending: Option<ResMut<PendingLeaderboardEntry>>,
+    mut leaderboard: ResMut<Leaderboard>,
+    saves: Res<SaveDirectory>,
+) {
+    let Some(mut pending) = pending else {
+        key_events.clear();
+        return;
+    };
+
+    for event in key_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        let initials = &mut pending.entry.initials;
+        match &event.logical_key {
+            Key::Character(text) => {
+                for c in text.chars().filter(char::is_ascii_alphanumeric) {
+                    if initials.len() < LEADERBOARD_INITIALS {
+                        initials.push(c.to_ascii_uppercase());
+                    }
+                }
+            }
+            Key::Backspace => {
+                initials.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Enter) && !pending.entry.initials.is_empty() {
+        keyboard.clear_just_pressed(KeyCode::Enter);
+        leaderboard.insert(&pending.level, pending.entry.clone());
+        if let Err(e) = write_leaderboard_file(&saves.file(LEADERBOARD_FILE), &leaderboard) {
+            error!("{}", e);
+        }
+        commands.remove_resource::<PendingLeaderboardEntry>();
+    }
+}
+
+/// Shows the initials typed so far, or a confirmation once saved
+pub fn update_initials_text(
+    pending: Option<Res<PendingLeaderboardEntry>>,
+    mut texts: Query<&mut Text, With<LeaderboardInitialsText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = match &pending {
+            Some(pending) => format!(
+                "New high score! Your initials: {:_<width$}  (Enter to save)",
+                pending.entry.initials,
+                width = LEADERBOARD_INITIALS
+            ),
+            None => "Saved to the leaderboard".to_string(),
+        };
+    }
+}
+
+/// L opens the leaderboard from the menu or the results screen (once the
+/// initials are saved)
+pub fn open_leaderboard(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    pending: Option<Res<PendingLeaderboardEntry>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if pending.is_some() || !keyboard.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+    commands.insert_resource(LeaderboardReturn(*state.get()));
+    next_state.set(GameState::Leaderboard);
+}
+
+/// Lists every level of the registry with its best runs
+pub fn spawn_leaderboard_screen(
+    mut commands: Commands,
+    registry: Res<LevelRegistry>,
+    leaderboard: Res<Leaderboard>,
+) {
+    let mut lines = Vec::new();
+    for level in &registry.levels {
+        let name = Path::new(level)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| level.clone());
+        lines.push(name);
+        match leaderboard.levels.get(level) {
+            Some(entries) if !entries.is_empty() => {
+                for (rank, entry) in entries.iter().enumerate() {
+                    lines.push(format!(
+                        "{}. {:<width$}  {:>6}  {}",
+                        rank + 1,
+                        entry.initials,
+                        entry.score,
+                        format_time(entry.time),
+                        width = LEADERBOARD_INITIALS
+                    ));
+                }
+            }
+            _ => lines.push("No runs yet".to_string()),
+        }
+        lines.push(String::new());
+    }
+    lines.push("Press Escape or L to go back".to_string());
+
+    spawn_state_screen(
+        &mut commands,
+        GameState::Leaderboard,
+        "Leaderboard",
+        &lines.join("\n"),
+    );
+}
+
+/// Escape or L closes the leaderboard, back to where it was opened from
+pub fn close_leaderboard(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    origin: Option<Res<LeaderboardReturn>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.any_just_pressed([KeyCode::Escape, KeyCode::KeyL]) {
+        next_state.set(origin.map_or(GameState::Menu, |origin| origin.0));
+    }
+}
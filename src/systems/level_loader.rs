@@ -0,0 +1,1201 @@
+//! Level loading through the `AssetServer`, the level registry, switching
+//! between levels and reloading the current one when its file changes
+//!
+//! Four formats are supported, told apart by the file extension:
+//! - `.level.ron`: the full `LevelData` structure, including camera zones
+//!   and parallax overrides
+//! - `.level.csv`: the CSV format (`width,height` header followed by one
+//!   comma-separated row of tile indices per line)
+//! - `.level.txt`: the symbol format (one character per tile, see
+//!   `assets/levels/sample_level.level.txt`), with a `//` comment legend
+//! - `.tmj` or `.level.json`: a map saved by the Tiled editor in its JSON
+//!   format, drawn with `assets/scene/tileset.png`
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoadFailedEvent, AssetLoader, LoadContext};
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use std::collections::{HashMap, HashSet};
+
+use crate::components::{
+    ActiveCheckpoint, AnimationState, CameraLock, CollectibleKind, CurrentLevel, CurrentLevelAsset,
+    GameState, Health, KeyRing, LevelAsset, LevelData, LevelEntity, LevelError, LevelErrorKind,
+    LevelLoadError, LevelRegistry, LevelTimer, PlayerVelocity, Score, Surface, TileCollisionMap,
+    TileId, TileLayer, TileMaterial,
+};
+use crate::constants::{
+    DEFAULT_LEVEL_PATH, LEVEL_REGISTRY_PATH, MAX_LEVEL_HEIGHT, MAX_LEVEL_WIDTH, TILESET_HEIGHT,
+    TILESET_TILE_SIZE, TILESET_WIDTH, TILES_PER_COLUMN, TILES_PER_ROW,
+};
+use crate::systems::lives::respawn_player;
+
+/// Tile index used for empty/air cells
+pub const EMPTY_TILE: TileId = 255;
+
+/// Extension of level files in the RON format
+pub const LEVEL_RON_EXTENSION: &str = "level.ron";
+/// Extension of level files in the CSV format
+pub const LEVEL_CSV_EXTENSION: &str = "level.csv";
+/// Extension of level files in the symbol format
+pub const LEVEL_SYMBOL_EXTENSION: &str = "level.txt";
+/// Extension of Tiled JSON maps
+pub const LEVEL_TILED_EXTENSION: &str = "tmj";
+/// Extension of Tiled maps exported as plain `.json` (Tiled's format before
+/// `.tmj`), renamed so other JSON files aren't taken for levels
+pub const LEVEL_TILED_JSON_EXTENSION: &str = "level.json";
+
+/// Tiled's flip and rotation flags in the top bits of a tile gid
+const TILED_FLIP_FLAGS: u32 = 0xF000_0000;
+
+// Tile groups (see TILE_CONSTANTS.md). The last entry of each group is the
+// tile used by the symbol format and the editor hotkeys.
+
+// Terrain tiles (solid)
+const GRASS_TILES: [u32; 5] = [0, 1, 2, 3, 180];
+const STONE_TILES: [u32; 5] = [16, 17, 18, 19, 176];
+const BRICK_TILES: [u32; 5] = [20, 21, 22, 23, 184];
+const ROCK_TILES: [u32; 4] = [4, 5, 6, 7];
+const MUSHROOM_TILES: [u32; 4] = [144, 145, 146, 147];
+const MUD_TILES: [u32; 4] = [160, 161, 162, 163];
+
+// Platform tiles (jump-through)
+const WOOD_PLATFORMS: [u32; 5] = [32, 33, 34, 35, 182];
+const STONE_PLATFORMS: [u32; 5] = [48, 49, 50, 51, 181];
+const METAL_PLATFORMS: [u32; 4] = [64, 65, 66, 67];
+
+// Liquid tiles (no collision, grouped into water volumes)
+const WATER_TILES: [u32; 5] = [96, 97, 98, 99, 189];
+
+// Collectible tiles (replaced by collectible entities)
+const COIN_TILES: [u32; 1] = [186];
+const GEM_TILES: [u32; 1] = [190];
+
+/// Symbols of the symbol format, unless the file's legend gives them another
+/// tile; where symbols share a tile, the first one is written
+const SYMBOL_TILES: [(char, TileId); 14] = [
+    ('.', EMPTY_TILE),
+    ('G', GRASS_TILES[4]),
+    ('S', STONE_TILES[4]),
+    ('B', BRICK_TILES[4]),
+    ('P', STONE_PLATFORMS[4]),
+    ('W', WOOD_PLATFORMS[4]),
+    ('F', 183),
+    ('T', 185),
+    ('C', 187),
+    ('#', STONE_TILES[4]),
+    ('=', STONE_PLATFORMS[4]),
+    ('^', 188),
+    ('~', WATER_TILES[4]),
+    ('*', 187),
+];
+
+/// Tile an item block turns into once used up (a rock tile, so still solid)
+pub const USED_BLOCK_TILE: TileId = 7;
+
+// Surface materials of the special terrain groups
+const MUSHROOM_MATERIAL: TileMaterial = TileMaterial {
+    friction: 0.8,
+    restitution: 0.85,
+    speed_factor: 1.0,
+    surface: Surface::Grass,
+};
+const MUD_MATERIAL: TileMaterial = TileMaterial {
+    friction: 2.0,
+    restitution: 0.0,
+    speed_factor: 0.45,
+    surface: Surface::Grass,
+};
+const GRASS_MATERIAL: TileMaterial = TileMaterial::with_surface(Surface::Grass);
+const WOOD_MATERIAL: TileMaterial = TileMaterial::with_surface(Surface::Wood);
+const METAL_MATERIAL: TileMaterial = TileMaterial::with_surface(Surface::Metal);
+
+/// Name of the tile group a tile index belongs to
+pub fn tile_group_name(index: u32) -> Option<&'static str> {
+    let groups: [(&str, &[u32]); 13] = [
+        ("Grass", &GRASS_TILES),
+        ("Stone", &STONE_TILES),
+        ("Brick", &BRICK_TILES),
+        ("Rock", &ROCK_TILES),
+        ("Mushroom", &MUSHROOM_TILES),
+        ("Mud", &MUD_TILES),
+        ("Wood platform", &WOOD_PLATFORMS),
+        ("Stone platform", &STONE_PLATFORMS),
+        ("Metal platform", &METAL_PLATFORMS),
+        ("Water", &WATER_TILES),
+        ("Coin", &COIN_TILES),
+        ("Gem", &GEM_TILES),
+        ("Empty", &[EMPTY_TILE]),
+    ];
+    groups
+        .into_iter()
+        .find(|(_, tiles)| tiles.contains(&index))
+        .map(|(name, _)| name)
+}
+
+/// Builds the collision map from the tile groups
+pub fn create_collision_map() -> TileCollisionMap {
+    let mut solid_tiles = HashSet::new();
+    let mut platform_tiles = HashSet::new();
+    let water_tiles = WATER_TILES.iter().copied().collect();
+    let mut materials = HashMap::new();
+    let mut collectible_tiles = HashMap::new();
+    let mut contact_sounds = HashMap::new();
+
+    for &tile in GRASS_TILES
+        .iter()
+        .chain(&STONE_TILES)
+        .chain(&BRICK_TILES)
+        .chain(&ROCK_TILES)
+        .chain(&MUSHROOM_TILES)
+        .chain(&MUD_TILES)
+    {
+        solid_tiles.insert(tile);
+    }
+    // Stone, brick and rock keep the default material (stone surface)
+    for &tile in &GRASS_TILES {
+        materials.insert(tile, GRASS_MATERIAL);
+    }
+    for &tile in &WOOD_PLATFORMS {
+        materials.insert(tile, WOOD_MATERIAL);
+    }
+    for &tile in &METAL_PLATFORMS {
+        materials.insert(tile, METAL_MATERIAL);
+    }
+    for &tile in &MUSHROOM_TILES {
+        materials.insert(tile, MUSHROOM_MATERIAL);
+    }
+    for &tile in &MUD_TILES {
+        materials.insert(tile, MUD_MATERIAL);
+    }
+    for &tile in &WATER_TILES {
+        contact_sounds.insert(tile, "splash");
+    }
+    for &tile in &COIN_TILES {
+        collectible_tiles.insert(tile, CollectibleKind::Coin);
+    }
+    for &tile in &GEM_TILES {
+        collectible_tiles.insert(tile, CollectibleKind::Gem);
+    }
+    for &tile in WOOD_PLATFORMS
+        .iter()
+        .chain(&STONE_PLATFORMS)
+        .chain(&METAL_PLATFORMS)
+    {
+        platform_tiles.insert(tile);
+    }
+
+    TileCollisionMap {
+        solid_tiles,
+        platform_tiles,
+        water_tiles,
+        collectible_tiles,
+        materials,
+        contact_sounds,
+    }
+}
+
+/// Loads a level straight from disk, picking the parser from the file
+/// extension; the game itself loads levels through `LevelAssetLoader`
+pub fn load_level_from_file(path: &str) -> Result<LevelData, LevelError> {
+    let _span = info_span!("load_level_file", path).entered();
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| LevelError::new(path, LevelErrorKind::Read(e)))?;
+    parse_level(path, &contents)
+}
+
+/// Level file format, given by the file extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelFormat {
+    Ron,
+    Csv,
+    Symbol,
+    Tiled,
+}
+
+impl LevelFormat {
+    /// Format of the file at `path`, if its extension is a level format's
+    pub fn from_path(path: &str) -> Option<Self> {
+        if has_extension(path, LEVEL_RON_EXTENSION) {
+            Some(Self::Ron)
+        } else if has_extension(path, LEVEL_CSV_EXTENSION) {
+            Some(Self::Csv)
+        } else if has_extension(path, LEVEL_SYMBOL_EXTENSION) {
+            Some(Self::Symbol)
+        } else if has_extension(path, LEVEL_TILED_EXTENSION)
+            || has_extension(path, LEVEL_TILED_JSON_EXTENSION)
+        {
+            Some(Self::Tiled)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the format keeps the whole level rather than only its tile
+    /// grid
+    pub fn holds_objects(self) -> bool {
+        self == Self::Ron
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ron => "RON",
+            Self::Csv => "CSV",
+            Self::Symbol => "symbol",
+            Self::Tiled => "Tiled",
+        }
+    }
+}
+
+/// Parses and checks a level file's contents, picking the parser from the
+/// extension of `path`
+fn parse_level(path: &str, contents: &str) -> Result<LevelData, LevelError> {
+    let level = match LevelFormat::from_path(path) {
+        Some(LevelFormat::Ron) => parse_ron(path, contents)?,
+        Some(LevelFormat::Csv) => parse_csv_level(path, contents)?,
+        Some(LevelFormat::Symbol) => parse_symbol_level(path, contents)?,
+        Some(LevelFormat::Tiled) => parse_tiled_level(path, contents)?,
+        None => return Err(LevelError::new(path, LevelErrorKind::UnknownFormat)),
+    };
+
+    validate_level(path, &level)?;
+    Ok(level)
+}
+
+/// Whether the file name in `path` ends in `.extension`
+fn has_extension(path: &str, extension: &str) -> bool {
+    path.strip_suffix(extension)
+        .is_some_and(|stem| stem.ends_with('.'))
+}
+
+/// Loads `.level.ron`, `.level.csv`, `.level.txt` and Tiled map files through the
+/// `AssetServer`, with the same parsers and checks as `load_level_from_file`
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = LevelError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<LevelAsset, LevelError> {
+        let path = load_context.path().display().to_string();
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| LevelError::new(&path, LevelErrorKind::Read(e)))?;
+        let contents = String::from_utf8(bytes).map_err(|e| {
+            let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+            LevelError::new(&path, LevelErrorKind::Read(e))
+        })?;
+        parse_level(&path, &contents).map(LevelAsset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[
+            LEVEL_RON_EXTENSION,
+            LEVEL_CSV_EXTENSION,
+            LEVEL_SYMBOL_EXTENSION,
+            LEVEL_TILED_EXTENSION,
+            LEVEL_TILED_JSON_EXTENSION,
+        ]
+    }
+}
+
+/// Deserializes a RON file, keeping the parser's position in the error
+fn parse_ron<T: serde::de::DeserializeOwned>(path: &str, contents: &str) -> Result<T, LevelError> {
+    ron::from_str(contents).map_err(|e| {
+        LevelError::at(
+            path,
+            e.position.line,
+            e.position.col,
+            LevelErrorKind::Ron(Box::new(e.code)),
+        )
+    })
+}
+
+/// Parses the CSV level format described in TILE_MAPPING.md
+/// `path` is only used in the errors
+pub fn parse_csv_level(path: &str, contents: &str) -> Result<LevelData, LevelError> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#') && !line.starts_with("//")
+        });
+
+    let (header_line, header) = lines
+        .next()
+        .ok_or_else(|| LevelError::new(path, LevelErrorKind::Empty))?;
+    let mut dimensions = header.split(',').map(|v| v.trim().parse::<u32>());
+    let (width, height) = match (dimensions.next(), dimensions.next()) {
+        (Some(Ok(width)), Some(Ok(height))) => (width, height),
+        _ => {
+            return Err(LevelError::at(
+                path,
+                header_line,
+                1,
+                LevelErrorKind::InvalidHeader(header.trim().to_string()),
+            ))
+        }
+    };
+
+    let mut rows: Vec<Vec<u32>> = Vec::with_capacity(height as usize);
+    for (line_number, line) in lines {
+        let mut row = Vec::new();
+        // Character column where the current field starts
+        let mut column = 1;
+        for field in line.split(',') {
+            let value = field.trim();
+            let tile = value.parse::<u32>().map_err(|_| {
+                let leading = field.chars().take_while(|c| c.is_whitespace()).count();
+                LevelError::at(
+                    path,
+                    line_number,
+                    column + leading,
+                    LevelErrorKind::InvalidTile(value.to_string()),
+                )
+            })?;
+            row.push(tile);
+            column += field.chars().count() + 1;
+        }
+        if let Some(expected) = rows.first().map(Vec::len) {
+            if row.len() != expected {
+                return Err(LevelError::at(
+                    path,
+                    line_number,
+                    1,
+                    LevelErrorKind::RowLength {
+                        found: row.len(),
+                        expected,
+                    },
+                ));
+            }
+        }
+        rows.push(row);
+    }
+    // Every row was checked against the first, so this can't fail
+    let tiles = TileLayer::try_from(rows).expect("CSV rows have equal lengths");
+
+    Ok(LevelData {
+        width,
+        height,
+        tiles,
+        ..Default::default()
+    })
+}
+
+/// Parses the symbol format: one character per tile and one row per line,
+/// with `//` comment lines; a legend comment `// X = Name (position N)` makes
+/// `X` stand for tile `N`, other symbols are those of `SYMBOL_TILES`
+/// Rows shorter than the longest are padded with empty tiles
+/// `path` is only used in the errors
+pub fn parse_symbol_level(path: &str, contents: &str) -> Result<LevelData, LevelError> {
+    let mut symbols: HashMap<char, TileId> = SYMBOL_TILES.into_iter().collect();
+    // The legend applies to the whole file, wherever it is
+    symbols.extend(
+        contents
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix("//"))
+            .filter_map(parse_legend_entry),
+    );
+
+    let mut rows: Vec<Vec<TileId>> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || line.trim_start().starts_with("//") {
+            continue;
+        }
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(column, symbol)| {
+                symbols.get(&symbol).copied().ok_or_else(|| {
+                    LevelError::at(
+                        path,
+                        index + 1,
+                        column + 1,
+                        LevelErrorKind::UnknownSymbol(symbol),
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        rows.push(row);
+    }
+    if rows.is_empty() {
+        return Err(LevelError::new(path, LevelErrorKind::NoRows));
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+    for row in &mut rows {
+        row.resize(width, EMPTY_TILE);
+    }
+    let height = rows.len() as u32;
+    let tiles = TileLayer::try_from(rows).expect("symbol rows are padded to equal lengths");
+
+    Ok(LevelData {
+        width: width as u32,
+        height,
+        tiles,
+        ..Default::default()
+    })
+}
+
+/// Symbol and tile of a legend comment (`X = Name (position N)`, after the
+/// `//`); entries without a position keep the symbol's usual tile
+fn parse_legend_entry(comment: &str) -> Option<(char, TileId)> {
+    let mut chars = comment.trim_start().chars();
+    let symbol = chars.next()?;
+    let description = chars.as_str().strip_prefix(" = ")?;
+    let (_, position) = description.rsplit_once("position ")?;
+    let digits: String = position.chars().take_while(char::is_ascii_digit).collect();
+    Some((symbol, digits.parse().ok()?))
+}
+
+/// Tiled JSON map, as far as the level needs it
+#[derive(serde::Deserialize)]
+struct TiledMap {
+    width: u32,
+    height: u32,
+    /// Only checked: maps that aren't `orthogonal` fail to deserialize
+    #[serde(rename = "orientation")]
+    _orientation: TiledOrientation,
+    layers: Vec<TiledLayer>,
+    #[serde(default)]
+    tilesets: Vec<TiledTileset>,
+}
+
+/// The only orientation a tile grid can come from
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TiledOrientation {
+    Orthogonal,
+}
+
+#[derive(serde::Deserialize)]
+struct TiledTileset {
+    firstgid: u32,
+}
+
+/// Layer of a Tiled map, checked while it's deserialized so its errors point
+/// into the layer
+#[derive(serde::Deserialize)]
+#[serde(try_from = "RawTiledLayer")]
+enum TiledLayer {
+    /// Row-major tile gids, 0 for an empty cell
+    Tiles {
+        width: u32,
+        data: Vec<u32>,
+    },
+    Group(Vec<TiledLayer>),
+    /// Object and image layers, which the level ignores
+    Other,
+}
+
+/// Every layer type's fields, as Tiled writes them
+#[derive(serde::Deserialize)]
+struct RawTiledLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    data: Option<serde_json::Value>,
+    encoding: Option<String>,
+    #[serde(default)]
+    layers: Vec<TiledLayer>,
+}
+
+impl TryFrom<RawTiledLayer> for TiledLayer {
+    type Error = String;
+
+    fn try_from(layer: RawTiledLayer) -> Result<Self, Self::Error> {
+        match layer.kind.as_str() {
+            "tilelayer" => {}
+            "group" => return Ok(Self::Group(layer.layers)),
+            _ => return Ok(Self::Other),
+        }
+        if layer
+            .encoding
+            .as_deref()
+            .is_some_and(|encoding| encoding != "csv")
+        {
+            return Err(format!(
+                "layer '{}' is encoded, save the map with the CSV tile layer format",
+                layer.name
+            ));
+        }
+        let Some(data) = layer.data else {
+            return Err(format!(
+                "layer '{}' has no tile data, infinite maps aren't supported",
+                layer.name
+            ));
+        };
+        let data: Vec<u32> = serde_json::from_value(data)
+            .map_err(|e| format!("layer '{}' has invalid tile data: {}", layer.name, e))?;
+        let expected = layer.width as usize * layer.height as usize;
+        if data.len() != expected {
+            return Err(format!(
+                "layer '{}' has {} tiles, expected {}x{}",
+                layer.name,
+                data.len(),
+                layer.width,
+                layer.height
+            ));
+        }
+        Ok(Self::Tiles {
+            width: layer.width,
+            data,
+        })
+    }
+}
+
+/// Parses a Tiled JSON map: game tile `n` is tile `n` of the map's first
+/// tileset, and the tile layers are stacked in order, each non-empty cell
+/// covering the ones below; object and image layers are ignored
+/// `path` is only used in the errors
+pub fn parse_tiled_level(path: &str, contents: &str) -> Result<LevelData, LevelError> {
+    let map: TiledMap = serde_json::from_str(contents).map_err(|e| {
+        // The message ends with the position, which the error shows itself
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        let message = e.to_string();
+        let message = message.strip_suffix(&position).unwrap_or(&message);
+        LevelError::at(
+            path,
+            e.line(),
+            e.column(),
+            LevelErrorKind::Tiled(message.to_string()),
+        )
+    })?;
+    check_level_size(path, map.width, map.height)?;
+
+    let first_gid = map.tilesets.first().map_or(1, |tileset| tileset.firstgid);
+    let mut tiles = TileLayer::filled(map.width, map.height, EMPTY_TILE);
+    stack_tiled_layers(&map.layers, first_gid, &mut tiles)
+        .map_err(|gid| LevelError::new(path, LevelErrorKind::InvalidTile(gid.to_string())))?;
+
+    Ok(LevelData {
+        width: map.width,
+        height: map.height,
+        tiles,
+        ..Default::default()
+    })
+}
+
+/// Draws the tile layers onto `tiles` in order, going into groups; fails with
+/// a gid that's before the first tileset
+fn stack_tiled_layers(
+    layers: &[TiledLayer],
+    first_gid: u32,
+    tiles: &mut TileLayer,
+) -> Result<(), u32> {
+    for layer in layers {
+        match layer {
+            TiledLayer::Tiles { width, data } => {
+                for (offset, &gid) in data.iter().enumerate() {
+                    let gid = gid & !TILED_FLIP_FLAGS;
+                    if gid == 0 {
+                        continue;
+                    }
+                    let tile = gid.checked_sub(first_gid).ok_or(gid)?;
+                    let offset = offset as u32;
+                    tiles.set(offset % width, offset / width, tile);
+                }
+            }
+            TiledLayer::Group(children) => stack_tiled_layers(children, first_gid, tiles)?,
+            TiledLayer::Other => {}
+        }
+    }
+    Ok(())
+}
+
+/// Writes a level to disk in the format given by the file extension
+/// Only the RON format holds more than the tile grid (see
+/// `LevelFormat::holds_objects`); the others drop everything else
+pub fn save_level_to_file(path: &str, level: &LevelData) -> Result<(), LevelError> {
+    let contents = match LevelFormat::from_path(path) {
+        Some(LevelFormat::Ron) => {
+            ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::default())
+                .map_err(|e| LevelError::new(path, LevelErrorKind::Ron(Box::new(e))))?
+        }
+        Some(LevelFormat::Csv) => write_csv_level(level),
+        Some(LevelFormat::Symbol) => write_symbol_level(level),
+        Some(LevelFormat::Tiled) => write_tiled_level(level),
+        None => return Err(LevelError::new(path, LevelErrorKind::UnknownFormat)),
+    };
+    std::fs::write(path, contents).map_err(|e| LevelError::new(path, LevelErrorKind::Write(e)))
+}
+
+/// Writes the tile grid in the CSV level format
+pub fn write_csv_level(level: &LevelData) -> String {
+    let mut csv = format!("{},{}\n", level.width, level.height);
+    for row in level.tiles.rows() {
+        let row: Vec<String> = row.iter().map(u32::to_string).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Writes the tile grid in the symbol format, with a legend of every symbol
+/// it uses; tiles without a symbol in `SYMBOL_TILES` get a spare character
+pub fn write_symbol_level(level: &LevelData) -> String {
+    let mut spare = ('!'..='~')
+        .chain('\u{c0}'..=char::MAX)
+        .filter(|c| *c != '/' && !SYMBOL_TILES.iter().any(|(symbol, _)| symbol == c));
+    let mut legend: Vec<(char, TileId)> = Vec::new();
+    let mut grid = String::new();
+    for row in level.tiles.rows() {
+        for &tile in row {
+            let symbol = match legend.iter().find(|(_, used)| *used == tile) {
+                Some(&(symbol, _)) => symbol,
+                None => {
+                    let symbol = SYMBOL_TILES
+                        .iter()
+                        .find(|(_, known)| *known == tile)
+                        .map(|&(symbol, _)| symbol)
+                        .or_else(|| spare.next())
+                        .expect("a spare symbol for every tile");
+                    legend.push((symbol, tile));
+                    symbol
+                }
+            };
+            grid.push(symbol);
+        }
+        grid.push('\n');
+    }
+
+    let mut text = String::from("// Symbol Legend:\n");
+    for (symbol, tile) in legend {
+        let name = tile_group_name(tile).unwrap_or("Tile");
+        text.push_str(&format!("// {} = {} (position {})\n", symbol, name, tile));
+    }
+    text.push('\n');
+    text.push_str(&grid);
+    text
+}
+
+/// Writes the tile grid as a Tiled JSON map with one tile layer, drawn with
+/// the game's tileset as seen from `assets/levels/`
+pub fn write_tiled_level(level: &LevelData) -> String {
+    // Tiled's gid 0 is an empty cell, so the tileset starts at 1
+    let data: Vec<u32> = level
+        .tiles
+        .iter()
+        .map(|(_, tile)| if tile == EMPTY_TILE { 0 } else { tile + 1 })
+        .collect();
+    serde_json::json!({
+        "type": "map",
+        "version": "1.10",
+        "orientation": "orthogonal",
+        "renderorder": "right-down",
+        "infinite": false,
+        "width": level.width,
+        "height": level.height,
+        "tilewidth": TILESET_TILE_SIZE,
+        "tileheight": TILESET_TILE_SIZE,
+        "nextlayerid": 2,
+        "nextobjectid": 1,
+        "layers": [{
+            "id": 1,
+            "name": "tiles",
+            "type": "tilelayer",
+            "x": 0,
+            "y": 0,
+            "width": level.width,
+            "height": level.height,
+            "opacity": 1,
+            "visible": true,
+            "data": data,
+        }],
+        "tilesets": [{
+            "firstgid": 1,
+            "name": "tileset",
+            "image": "../scene/tileset.png",
+            "imagewidth": TILESET_WIDTH,
+            "imageheight": TILESET_HEIGHT,
+            "tilewidth": TILESET_TILE_SIZE,
+            "tileheight": TILESET_TILE_SIZE,
+            "columns": TILES_PER_ROW,
+            "tilecount": TILES_PER_ROW * TILES_PER_COLUMN,
+            "margin": 0,
+            "spacing": 0,
+        }],
+    })
+    .to_string()
+}
+
+/// Checks that the declared dimensions are within the maximum level size
+fn check_level_size(path: &str, width: u32, height: u32) -> Result<(), LevelError> {
+    if width <= MAX_LEVEL_WIDTH && height <= MAX_LEVEL_HEIGHT {
+        return Ok(());
+    }
+    Err(LevelError::new(
+        path,
+        LevelErrorKind::TooLarge {
+            width,
+            height,
+            max_width: MAX_LEVEL_WIDTH,
+            max_height: MAX_LEVEL_HEIGHT,
+        },
+    ))
+}
+
+/// Checks that the tile grid matches the declared dimensions
+fn validate_level(path: &str, level: &LevelData) -> Result<(), LevelError> {
+    check_level_size(path, level.width, level.height)?;
+    let kind = if level.tiles.height() != level.height {
+        LevelErrorKind::RowCount {
+            declared: level.height,
+            found: level.tiles.height(),
+        }
+    } else if level.tiles.width() != level.width {
+        LevelErrorKind::ColumnCount {
+            declared: level.width,
+            found: level.tiles.width(),
+        }
+    } else {
+        return Ok(());
+    };
+    Err(LevelError::new(path, kind))
+}
+
+impl Default for LevelRegistry {
+    fn default() -> Self {
+        Self {
+            levels: vec![DEFAULT_LEVEL_PATH.to_string()],
+        }
+    }
+}
+
+impl LevelRegistry {
+    /// Path of the first level
+    pub fn first(&self) -> &str {
+        self.levels
+            .first()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_LEVEL_PATH)
+    }
+
+    /// Path of the level after `path`, if there is one
+    pub fn next_after(&self, path: &str) -> Option<&str> {
+        let index = self.levels.iter().position(|level| level == path)?;
+        self.levels.get(index + 1).map(String::as_str)
+    }
+}
+
+/// Loads the level registry from a RON file
+pub fn load_level_registry(path: &str) -> Result<LevelRegistry, LevelError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| LevelError::new(path, LevelErrorKind::Read(e)))?;
+    parse_ron(path, &contents)
+}
+
+/// Schedule with every system that spawns entities from `LevelData`
+/// Runs once at startup and again each time another level is loaded
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpawnLevel;
+
+/// Loads the level registry as the `LevelRegistry` resource and requests its
+/// first level from the `AssetServer`; `apply_level_asset` makes it the
+/// current level once it's loaded
+pub fn load_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let registry = load_level_registry(LEVEL_REGISTRY_PATH).unwrap_or_else(|e| {
+        warn!("{}, using the default level only", e);
+        LevelRegistry::default()
+    });
+    let path = registry.first().to_string();
+    commands.insert_resource(registry);
+
+    info!("Loading level '{}'", path);
+    commands.insert_resource(CurrentLevelAsset(asset_server.load(path)));
+}
+
+/// Replaces the current level with the one at the asset path `path`: right
+/// away if the `AssetServer` already has it, otherwise once it's loaded (see
+/// `apply_level_asset`)
+/// If it fails to load the current level is left as it was
+pub fn switch_level(world: &mut World, path: &str) {
+    let _span = info_span!("switch_level", path).entered();
+    info!("Switching to level '{}'", path);
+    let handle: Handle<LevelAsset> = world.resource::<AssetServer>().load(path.to_string());
+    let loaded = world
+        .resource::<Assets<LevelAsset>>()
+        .get(&handle)
+        .map(|LevelAsset(level)| level.clone());
+    world.insert_resource(CurrentLevelAsset(handle));
+    if let Some(level) = loaded {
+        replace_level(world, path, level);
+    }
+}
+
+/// Makes `level`, read from `path`, the current level: despawns every
+/// `LevelEntity`, spawns the new level and puts the player back at its start
+/// with the per-level state (timer, level points, checkpoint, keys) reset
+pub fn replace_level(world: &mut World, path: &str, level: LevelData) {
+    {
+        let _span = info_span!("despawn_level").entered();
+        let entities: Vec<Entity> = world
+            .query_filtered::<Entity, With<LevelEntity>>()
+            .iter(world)
+            .collect();
+        for entity in entities {
+            world.despawn(entity);
+        }
+        let cameras: Vec<Entity> = world
+            .query_filtered::<Entity, With<CameraLock>>()
+            .iter(world)
+            .collect();
+        for camera in cameras {
+            world.entity_mut(camera).remove::<CameraLock>();
+        }
+    }
+
+    world.remove_resource::<LevelLoadError>();
+    world.insert_resource(level);
+    world.insert_resource(CurrentLevel {
+        path: path.to_string(),
+    });
+    world.insert_resource(ActiveCheckpoint::default());
+    world.insert_resource(LevelTimer::default());
+    let mut score = world.resource_mut::<Score>();
+    score.level_points = 0;
+    score.combo = 0;
+
+    let spawn = world.resource::<ActiveCheckpoint>().position;
+    let mut players = world.query::<(
+        &mut Health,
+        &mut Transform,
+        &mut PlayerVelocity,
+        &mut AnimationState,
+        &mut KeyRing,
+    )>();
+    for (mut health, mut transform, mut velocity, mut animation, mut key_ring) in
+        players.iter_mut(world)
+    {
+        respawn_player(
+            &mut health,
+            &mut transform,
+            &mut velocity,
+            &mut animation,
+            spawn,
+        );
+        key_ring.keys.clear();
+    }
+
+    let _span = info_span!("spawn_level").entered();
+    world.run_schedule(SpawnLevel);
+}
+
+/// Makes the level in `CurrentLevelAsset` the current level once the
+/// `AssetServer` has loaded it, and again whenever its file changes on disk
+/// (watched with the `hot_reload` feature)
+/// A level that fails to load leaves the current one as it was and its error
+/// is shown as `LevelLoadError`; unless it was a reload of the current level,
+/// the game also returns to the menu
+pub fn apply_level_asset(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<LevelAsset>>,
+    mut failures: EventReader<AssetLoadFailedEvent<LevelAsset>>,
+    levels: Res<Assets<LevelAsset>>,
+    current_asset: Option<Res<CurrentLevelAsset>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(current_asset) = current_asset else {
+        events.clear();
+        failures.clear();
+        return;
+    };
+    let id = current_asset.0.id();
+    let path = current_asset
+        .0
+        .path()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    let reloading = current_level
+        .as_ref()
+        .is_some_and(|current| current.path == path);
+
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(id) {
+            continue;
+        }
+        let Some(LevelAsset(level)) = levels.get(id) else {
+            continue;
+        };
+        if reloading {
+            info!("Level file '{}' changed, reloading", path);
+        } else {
+            info!(
+                "Loaded level '{}' ({}x{}, {} camera zones)",
+                path,
+                level.width,
+                level.height,
+                level.camera_zones.len()
+            );
+        }
+        let path = path.clone();
+        let level = level.clone();
+        commands.queue(move |world: &mut World| replace_level(world, &path, level));
+    }
+
+    for failure in failures.read() {
+        if failure.id != id {
+            continue;
+        }
+        let e = LevelError::new(
+            &path,
+            LevelErrorKind::Asset(Box::new(failure.error.clone())),
+        );
+        if reloading {
+            warn!("{}, keeping the level as it is", e);
+        } else {
+            error!("{}", e);
+            next_state.set(GameState::Menu);
+        }
+        commands.insert_resource(LevelLoadError(e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+# Two rows of three tiles
+3,2
+255,255,32
+16,16,16
+";
+
+    #[test]
+    fn parses_the_csv_format() {
+        let level = parse_csv_level("test.level.csv", CSV).unwrap();
+        assert_eq!((level.width, level.height), (3, 2));
+        assert_eq!(level.tiles.get(2, 0), Some(32));
+        assert_eq!(level.tiles.get(0, 1), Some(16));
+        assert!(level.camera_zones.is_empty());
+        assert!(level.goal.is_none());
+    }
+
+    #[test]
+    fn picks_the_parser_from_the_extension() {
+        let level = parse_level("levels/test.level.csv", CSV).unwrap();
+        assert_eq!(level.tiles.width(), 3);
+
+        let ron = ron::ser::to_string(&level).unwrap();
+        let level = parse_level("levels/test.level.ron", &ron).unwrap();
+        assert_eq!(level.tiles.rows().count(), 2);
+    }
+
+    #[test]
+    fn reports_csv_errors_at_their_position() {
+        let Err(e) = parse_csv_level("test.level.csv", "# header\n3,x\n") else {
+            panic!("invalid header parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::InvalidHeader(_)));
+        assert_eq!((e.line, e.column), (Some(2), Some(1)));
+
+        let Err(e) = parse_csv_level("test.level.csv", "2,2\n1, 2\n3,  oops\n") else {
+            panic!("invalid tile parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::InvalidTile(ref tile) if tile == "oops"));
+        assert_eq!((e.line, e.column), (Some(3), Some(5)));
+        assert_eq!(e.to_string(), "test.level.csv:3:5: invalid tile 'oops'");
+
+        let Err(e) = parse_csv_level("test.level.csv", "2,2\n1,2\n3\n") else {
+            panic!("short row parsed");
+        };
+        assert!(matches!(
+            e.kind,
+            LevelErrorKind::RowLength {
+                found: 1,
+                expected: 2
+            }
+        ));
+        assert_eq!(e.line, Some(3));
+
+        let Err(e) = parse_csv_level("test.level.csv", "# only a comment\n") else {
+            panic!("empty file parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::Empty));
+        assert_eq!(e.to_string(), "test.level.csv: the file has no header");
+    }
+
+    #[test]
+    fn reports_ron_errors_at_their_position() {
+        let Err(e) = parse_level("test.level.ron", "(\n    width: 3,\n    height: ,\n)") else {
+            panic!("invalid RON parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::Ron(_)));
+        assert_eq!(e.line, Some(3));
+    }
+
+    #[test]
+    fn checks_the_declared_size() {
+        let Err(e) = parse_level("test.level.csv", "3,3\n1,2,3\n4,5,6\n") else {
+            panic!("missing row parsed");
+        };
+        assert!(matches!(
+            e.kind,
+            LevelErrorKind::RowCount {
+                declared: 3,
+                found: 2
+            }
+        ));
+        assert_eq!((e.line, e.column), (None, None));
+
+        let Err(e) = parse_level("test.level.csv", "999,1\n1\n") else {
+            panic!("oversized level parsed");
+        };
+        assert!(matches!(
+            e.kind,
+            LevelErrorKind::TooLarge { width: 999, .. }
+        ));
+    }
+
+    #[test]
+    fn parses_the_symbol_format() {
+        let path = "levels/sample_level.level.txt";
+        let sample = include_str!("../../assets/levels/sample_level.level.txt");
+        let level = parse_level(path, sample).unwrap();
+        // Short rows are padded to the longest
+        assert_eq!((level.width, level.height), (35, 17));
+        assert_eq!(level.tiles.get(5, 0), Some(185));
+        assert_eq!(level.tiles.get(34, 0), Some(EMPTY_TILE));
+        assert_eq!(level.tiles.get(3, 6), Some(176));
+        assert_eq!(level.tiles.get(0, 14), Some(180));
+
+        // The legend gives symbols their tile
+        let level =
+            parse_symbol_level(path, "// G = Grass (position 3)\n// x = Stone\n\nGG\n").unwrap();
+        assert_eq!(level.tiles.get(1, 0), Some(3));
+    }
+
+    #[test]
+    fn reports_symbol_errors_at_their_position() {
+        let Err(e) = parse_symbol_level("test.level.txt", "// x = Stone\n...\n.Gx\n") else {
+            panic!("unknown symbol parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::UnknownSymbol('x')));
+        assert_eq!(
+            e.to_string(),
+            "test.level.txt:3:3: unknown symbol 'x', not in the legend"
+        );
+
+        let Err(e) = parse_symbol_level("test.level.txt", "// only a comment\n\n") else {
+            panic!("empty file parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::NoRows));
+    }
+
+    #[test]
+    fn writes_symbols_that_read_back() {
+        let rows = vec![vec![EMPTY_TILE, 180, 7], vec![176, 176, 186]];
+        let level = LevelData {
+            width: 3,
+            height: 2,
+            tiles: TileLayer::try_from(rows.clone()).unwrap(),
+            ..Default::default()
+        };
+        let text = write_symbol_level(&level);
+        assert!(text.contains("// G = Grass (position 180)"));
+        let read = parse_symbol_level("test.level.txt", &text).unwrap();
+        assert_eq!(Vec::<Vec<TileId>>::from(read.tiles), rows);
+    }
+
+    const TILED: &str = r#"{
+  "width": 3, "height": 2, "orientation": "orthogonal", "tilewidth": 16, "tileheight": 16,
+  "tilesets": [{ "firstgid": 1, "source": "tileset.tsj" }],
+  "layers": [
+    { "type": "tilelayer", "name": "ground", "width": 3, "height": 2, "data": [0, 0, 33, 17, 17, 17] },
+    { "type": "group", "name": "details", "layers": [
+      { "type": "tilelayer", "name": "coins", "width": 3, "height": 2, "data": [187, 0, 0, 0, 0, 2147483665] }
+    ] },
+    { "type": "objectgroup", "name": "spawns", "objects": [] }
+  ]
+}"#;
+
+    #[test]
+    fn parses_tiled_maps() {
+        let level = parse_level("levels/test.tmj", TILED).unwrap();
+        assert_eq!((level.width, level.height), (3, 2));
+        assert_eq!(level.tiles.get(1, 0), Some(EMPTY_TILE));
+        assert_eq!(level.tiles.get(2, 0), Some(32));
+        // Later layers cover earlier ones, without their flip flags
+        assert_eq!(level.tiles.get(0, 0), Some(186));
+        assert_eq!(level.tiles.get(2, 1), Some(16));
+
+        let level = parse_level("levels/test.level.json", TILED).unwrap();
+        assert_eq!(level.tiles.get(0, 1), Some(16));
+    }
+
+    #[test]
+    fn writes_tiled_maps_that_read_back() {
+        let rows = vec![vec![EMPTY_TILE, 180, 0], vec![176, 176, 186]];
+        let level = LevelData {
+            width: 3,
+            height: 2,
+            tiles: TileLayer::try_from(rows.clone()).unwrap(),
+            ..Default::default()
+        };
+        let read = parse_level("test.tmj", &write_tiled_level(&level)).unwrap();
+        assert_eq!(Vec::<Vec<TileId>>::from(read.tiles), rows);
+    }
+
+    #[test]
+    fn reports_tiled_errors_at_their_position() {
+        let Err(e) = parse_tiled_level("test.tmj", "{\n  \"width\": 3,\n  \"height\": x\n}") else {
+            panic!("invalid JSON parsed");
+        };
+        assert!(matches!(e.kind, LevelErrorKind::Tiled(_)));
+        assert_eq!((e.line, e.column), (Some(3), Some(13)));
+
+        let isometric = TILED.replace("orthogonal", "isometric");
+        let Err(e) = parse_tiled_level("test.tmj", &isometric) else {
+            panic!("isometric map parsed");
+        };
+        assert_eq!(e.line, Some(2));
+        assert_eq!(
+            e.to_string(),
+            "test.tmj:2:53: invalid Tiled map: unknown variant `isometric`, expected `orthogonal`"
+        );
+
+        // serde_json reports where the layer ends, the message names it
+        let short_layer = TILED.replace("[0, 0, 33, 17, 17, 17]", "[0, 0, 33]");
+        let Err(e) = parse_tiled_level("test.tmj", &short_layer) else {
+            panic!("short layer parsed");
+        };
+        assert!(e.line.is_some());
+        assert!(matches!(
+            e.kind,
+            LevelErrorKind::Tiled(ref message) if message == "layer 'ground' has 3 tiles, expected 3x2"
+        ));
+    }
+
+    #[test]
+    fn rejects_other_extensions() {
+        for path in [
+            "levels/test.level.toml",
+            "levels/test.csv",
+            "levels/testlevel.csv",
+            "levels/test.txt",
+            "levels/test.json",
+        ] {
+            let Err(e) = parse_level(path, CSV) else {
+                panic!("{} parsed", path);
+            };
+            assert!(matches!(e.kind, LevelErrorKind::UnknownFormat), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! 2D lighting: a darkness overlay for levels with a `lighting` entry, drawn
+//! by a shader that lights it up around point lights on the player, on
+//! tiles of the listed indices and placed in the level (or in the editor)
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::sprite::{AlphaMode2d, Material2d};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::components::{
+    LevelData, LevelEntity, LightDef, LightSource, LightingDef, LightingEditor, LightingOverlay,
+    MainCamera, PlacedLight, PlacedLightDef, PlayerVelocity, PrefabPalette, TileIndex,
+};
+use crate::constants::*;
+use crate::systems::camera::camera_view;
+
+/// Material of the darkness overlay, see `assets/shaders/lighting.wgsl`
+#[derive(Asset, TypePath, AsBindGroup, Clone, Default)]
+pub struct LightingMaterial {
+    #[uniform(0)]
+    pub lighting: LightingUniform,
+}
+
+pub use uniform::{GpuLight, LightingUniform};
+
+mod uniform {
+    // The `ShaderType` derive emits per-field layout `check` functions that
+    // are never called
+    #![allow(dead_code)]
+
+    use bevy::prelude::*;
+    use bevy::render::render_resource::ShaderType;
+
+    use crate::constants::MAX_LIGHTS;
+
+    /// Ambient and lights as laid out in the shader
+    #[derive(ShaderType, Clone, Copy, Default)]
+    pub struct LightingUniform {
+        /// Darkness color, with the ambient in alpha
+        pub ambient: Vec4,
+        pub light_count: u32,
+        pub lights: [GpuLight; MAX_LIGHTS],
+    }
+
+    #[derive(ShaderType, Clone, Copy, Default)]
+    pub struct GpuLight {
+        /// World position, then radius
+        pub position_radius: Vec4,
+        /// Color scaled by intensity
+        pub color: Vec4,
+    }
+}
+
+impl Material2d for LightingMaterial {
+    fn fragment_shader() -> ShaderRef {
+        LIGHTING_SHADER_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl Default for LightingDef {
+    fn default() -> Self {
+        Self {
+            ambient: 0.2,
+            color: (0.02, 0.02, 0.06),
+            player_light: Some(LightDef::default()),
+            tile_lights: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+}
+
+impl Default for LightDef {
+    fn default() -> Self {
+        Self {
+            radius: 96.0,
+            color: (1.0, 0.9, 0.7),
+            intensity: 1.0,
+            flicker: 0.0,
+        }
+    }
+}
+
+impl Default for LightingEditor {
+    fn default() -> Self {
+        Self {
+            placing: false,
+            brush: LightDef {
+                radius: 64.0,
+                color: (1.0, 0.6, 0.3),
+                intensity: 1.0,
+                flicker: 0.3,
+            },
+        }
+    }
+}
+
+fn spawn_placed_light(commands: &mut Commands, placed: &PlacedLightDef) {
+    commands.spawn((
+        Name::new("Light"),
+        LevelEntity,
+        PlacedLight,
+        LightSource(placed.light),
+        Transform::from_translation(placed.position.extend(0.0)),
+    ));
+}
+
+/// Spawns the darkness overlay (hidden while the level has no `lighting`)
+/// and the level's placed lights
+pub fn setup_lighting(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+    level: Option<Res<LevelData>>,
+) {
+    commands.spawn((
+        Name::new("Lighting"),
+        LevelEntity,
+        LightingOverlay,
+        Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
+        MeshMaterial2d(materials.add(LightingMaterial::default())),
+        Transform::from_xyz(0.0, 0.0, LIGHTING_Z),
+        Visibility::Hidden,
+    ));
+
+    let Some(lighting) = level.as_ref().and_then(|level| level.lighting.as_ref()) else {
+        return;
+    };
+    for placed in &lighting.lights {
+        spawn_placed_light(&mut commands, placed);
+    }
+}
+
+/// Gives newly spawned tiles the light of their index, if the level's
+/// `tile_lights` list one
+pub fn attach_tile_lights(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    tiles: Query<(Entity, &TileIndex), Added<TileIndex>>,
+) {
+    let Some(lighting) = level.as_ref().and_then(|level| level.lighting.as_ref()) else {
+        return;
+    };
+    if lighting.tile_lights.is_empty() {
+        return;
+    }
+    for (entity, tile) in tiles.iter() {
+        if let Some(def) = lighting
+            .tile_lights
+            .iter()
+            .find(|def| def.tiles.contains(&tile.index))
+        {
+            commands.entity(entity).insert(LightSource(def.light));
+        }
+    }
+}
+
+/// Light color scaled by its intensity, dimmed over time by its flicker
+fn light_color(light: &LightDef, position: Vec2, time: f32) -> Vec4 {
+    // Two out of phase waves, offset by position so torches don't flicker
+    // in unison
+    let phase = position.x * 0.37 + position.y * 0.11;
+    let wave = (time * 11.0 + phase).sin() * (time * 7.3 + phase * 1.7).sin();
+    let intensity = light.intensity * (1.0 - light.flicker.clamp(0.0, 1.0) * 0.3 * (wave + 1.0));
+    let (r, g, b) = light.color;
+    Vec4::new(r, g, b, 1.0) * intensity.max(0.0)
+}
+
+/// The darkness overlay
+type LightingOverlays<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Visibility,
+        &'static MeshMaterial2d<LightingMaterial>,
+    ),
+    (
+        With<LightingOverlay>,
+        Without<MainCamera>,
+        Without<PlayerVelocity>,
+    ),
+>;
+
+/// Keeps the overlay over the camera view and sends the ambient and the
+/// lights nearest to the camera (up to `MAX_LIGHTS`) to its material
+pub fn update_lighting(
+    time: Res<Time>,
+    level: Option<Res<LevelData>>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    sources: Query<(&LightSource, &GlobalTransform)>,
+    mut overlays: LightingOverlays,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+) {
+    let lighting = level.as_ref().and_then(|level| level.lighting.as_ref());
+    let view = cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection));
+
+    for (mut transform, mut visibility, material) in overlays.iter_mut() {
+        let (Some(lighting), Some(view)) = (lighting, view) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        let view = view.inflate(TILE_SIZE_16);
+        transform.translation = view.center().extend(LIGHTING_Z);
+        transform.scale = view.size().extend(1.0);
+
+        let player_light = lighting.player_light.and_then(|light| {
+            players
+                .single()
+                .ok()
+                .map(|player| (player.translation.truncate(), light))
+        });
+        let mut lights: Vec<(Vec2, LightDef)> = player_light
+            .into_iter()
+            .chain(
+                sources
+                    .iter()
+                    .map(|(source, transform)| (transform.translation().truncate(), source.0)),
+            )
+            .filter(|(position, light)| view.inflate(light.radius).contains(*position))
+            .collect();
+        let center = view.center();
+        lights.sort_by(|(a, _), (b, _)| {
+            a.distance_squared(center)
+                .total_cmp(&b.distance_squared(center))
+        });
+        lights.truncate(MAX_LIGHTS);
+
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        let (r, g, b) = lighting.color;
+        let uniform = &mut material.lighting;
+        uniform.ambient = Vec4::new(r, g, b, lighting.ambient);
+        uniform.light_count = lights.len() as u32;
+        for (gpu, (position, light)) in uniform.lights.iter_mut().zip(&lights) {
+            gpu.position_radius = Vec4::new(position.x, position.y, light.radius, 0.0);
+            gpu.color = light_color(light, *position, time.elapsed_secs());
+        }
+    }
+}
+
+fn color_edit(ui: &mut egui::Ui, color: &mut (f32, f32, f32)) {
+    let mut rgb = [color.0, color.1, color.2];
+    if ui.color_edit_button_rgb(&mut rgb).changed() {
+        *color = (rgb[0], rgb[1], rgb[2]);
+    }
+}
+
+fn light_controls(ui: &mut egui::Ui, light: &mut LightDef) {
+    ui.add(egui::Slider::new(&mut light.radius, 8.0..=400.0).text("Radius"));
+    ui.add(egui::Slider::new(&mut light.intensity, 0.0..=2.0).text("Intensity"));
+    ui.add(egui::Slider::new(&mut light.flicker, 0.0..=1.0).text("Flicker"));
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        color_edit(ui, &mut light.color);
+    });
+}
+
+/// Editor window for the level's darkness and player light, and the light
+/// tool's settings for new lights
+pub fn lighting_editor_ui(
+    mut contexts: EguiContexts,
+    level: Option<ResMut<LevelData>>,
+    mut editor: ResMut<LightingEditor>,
+    mut palette: ResMut<PrefabPalette>,
+) {
+    let (Ok(ctx), Some(mut level)) = (contexts.ctx_mut(), level) else {
+        return;
+    };
+
+    // Edit copies so change detection only fires on real edits
+    let mut lighting = level.lighting.clone();
+    let mut placing = editor.placing;
+    let mut brush = editor.brush;
+    egui::Window::new("Lighting")
+        .default_open(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .show(ctx, |ui| {
+            let mut enabled = lighting.is_some();
+            if ui.checkbox(&mut enabled, "Darkness").changed() {
+                lighting = enabled.then(LightingDef::default);
+            }
+            let Some(lighting) = &mut lighting else {
+                return;
+            };
+            ui.add(egui::Slider::new(&mut lighting.ambient, 0.0..=1.0).text("Ambient"));
+            ui.horizontal(|ui| {
+                ui.label("Darkness color");
+                color_edit(ui, &mut lighting.color);
+            });
+
+            let mut player_light = lighting.player_light.is_some();
+            if ui.checkbox(&mut player_light, "Player light").changed() {
+                lighting.player_light = player_light.then(LightDef::default);
+            }
+            if let Some(light) = &mut lighting.player_light {
+                light_controls(ui, light);
+            }
+
+            ui.separator();
+            ui.label(format!("{} placed lights", lighting.lights.len()));
+            ui.checkbox(&mut placing, "Place lights")
+                .on_hover_text("Left click adds a light, right click removes the nearest");
+            light_controls(ui, &mut brush);
+        });
+
+    if placing && !editor.placing {
+        // One click tool at a time
+        palette.selected = None;
+    }
+    if placing != editor.placing || brush != editor.brush {
+        editor.placing = placing;
+        editor.brush = brush;
+    }
+    if lighting != level.lighting {
+        level.lighting = lighting;
+    }
+}
+
+/// With the light tool on, left click places a light at the cursor and right
+/// click removes the nearest one, keeping the level's `lighting.lights` in
+/// step
+#[allow(clippy::too_many_arguments)]
+pub fn place_lights(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    editor: Res<LightingEditor>,
+    palette: Res<PrefabPalette>,
+    level: Option<ResMut<LevelData>>,
+    placed: Query<(Entity, &Transform), With<PlacedLight>>,
+) {
+    if !editor.placing || palette.selected.is_some() {
+        return;
+    }
+    let Some(mut level) = level else {
+        return;
+    };
+    let add = mouse.just_pressed(MouseButton::Left);
+    let remove = mouse.just_pressed(MouseButton::Right);
+    if !add && !remove || level.lighting.is_none() {
+        return;
+    }
+    // Clicks on the editor windows
+    if contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.is_pointer_over_area())
+    {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+    let Some(lighting) = level.lighting.as_mut() else {
+        return;
+    };
+
+    if add {
+        let light = PlacedLightDef {
+            position,
+            light: editor.brush,
+        };
+        spawn_placed_light(&mut commands, &light);
+        lighting.lights.push(light);
+        debug!("Placed light at {}", position);
+        return;
+    }
+
+    let nearest = lighting
+        .lights
+        .iter()
+        .enumerate()
+        .map(|(index, light)| (index, light.position.distance(position)))
+        .filter(|&(_, distance)| distance <= LIGHT_PICK_RADIUS)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((index, _)) = nearest {
+        let removed = lighting.lights.remove(index);
+        if let Some((entity, _)) = placed
+            .iter()
+            .find(|(_, transform)| transform.translation.truncate() == removed.position)
+        {
+            commands.entity(entity).despawn();
+        }
+        debug!("Removed light at {}", removed.position);
+    }
+}
+
+/// Marks the placed lights and their reach in the editor
+pub fn draw_placed_lights(
+    mut gizmos: Gizmos,
+    lights: Query<(&Transform, &LightSource), With<PlacedLight>>,
+) {
+    for (transform, source) in lights.iter() {
+        let (r, g, b) = source.0.color;
+        let color = Color::srgb(r, g, b);
+        let position = transform.translation.truncate();
+        gizmos.circle_2d(position, LIGHT_PICK_RADIUS, color);
+        gizmos.circle_2d(position, source.0.radius, color.with_alpha(0.3));
+    }
+}
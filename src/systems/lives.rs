@@ -0,0 +1,204 @@
+//! Lives, damage, checkpoints, respawn zones and the game over choices
+//! (continue or restart)
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveCheckpoint, AnimationState, Checkpoint, DamageDealt, DamageEvent, DrawLayer, Dying,
+    GameState, Health, LevelData, LevelEntity, LevelTimer, Lives, PlayerRespawned, PlayerVelocity,
+    RespawnZone, Score,
+};
+use crate::constants::*;
+
+const CHECKPOINT_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+const CHECKPOINT_ACTIVE_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self {
+            remaining: PLAYER_LIVES,
+        }
+    }
+}
+
+impl Default for ActiveCheckpoint {
+    fn default() -> Self {
+        Self {
+            position: Vec2::new(PLAYER_SPAWN_X, PLAYER_SPAWN_Y),
+        }
+    }
+}
+
+/// Puts the player back at `position` with full health and no momentum
+pub(crate) fn respawn_player(
+    health: &mut Health,
+    transform: &mut Transform,
+    velocity: &mut PlayerVelocity,
+    animation: &mut AnimationState,
+    position: Vec2,
+) {
+    health.current = health.max;
+    transform.translation = position.extend(transform.translation.z);
+    velocity.0 = Vec2::ZERO;
+    *animation = AnimationState::Idle;
+}
+
+/// Takes damage off the health of its target and sends `DamageDealt`;
+/// running out of health starts the death sequence
+pub fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut targets: Query<&mut Health>,
+    mut dealt: EventWriter<DamageDealt>,
+) {
+    for event in events.read() {
+        if let Ok(mut health) = targets.get_mut(event.target) {
+            health.current = health.current.saturating_sub(event.amount);
+            dealt.write(DamageDealt {
+                target: event.target,
+                amount: event.amount,
+                remaining: health.current,
+            });
+        }
+    }
+}
+
+/// Spawns the checkpoint flags from the level's `checkpoints` list
+pub fn spawn_checkpoints(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for checkpoint in &level.checkpoints {
+        commands.spawn((
+            Name::new("Checkpoint"),
+            LevelEntity,
+            Sprite::from_color(
+                CHECKPOINT_COLOR,
+                Vec2::new(CHECKPOINT_WIDTH, CHECKPOINT_HEIGHT),
+            ),
+            Transform::from_translation(checkpoint.position.extend(0.0)),
+            DrawLayer::standing(Vec2::new(CHECKPOINT_WIDTH, CHECKPOINT_HEIGHT)),
+            Checkpoint {
+                position: checkpoint.position,
+            },
+        ));
+    }
+}
+
+/// Makes a checkpoint the respawn point when a player touches it, and
+/// highlights the active flag
+pub fn activate_checkpoints(
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut checkpoints: Query<(&Checkpoint, &mut Sprite)>,
+    mut active: ResMut<ActiveCheckpoint>,
+) {
+    for player_transform in players.iter() {
+        let player_pos = player_transform.translation.truncate();
+        for (checkpoint, _) in checkpoints.iter() {
+            if checkpoint.position != active.position
+                && player_pos.distance(checkpoint.position) < CHECKPOINT_RADIUS
+            {
+                active.position = checkpoint.position;
+                info!("Checkpoint reached at {}", checkpoint.position);
+            }
+        }
+    }
+
+    if active.is_changed() {
+        for (checkpoint, mut sprite) in checkpoints.iter_mut() {
+            sprite.color = if checkpoint.position == active.position {
+                CHECKPOINT_ACTIVE_COLOR
+            } else {
+                CHECKPOINT_COLOR
+            };
+        }
+    }
+}
+
+/// Spawns the level's respawn zones
+pub fn spawn_respawn_zones(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, zone) in level.respawn_zones.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Respawn zone {}", index)),
+            LevelEntity,
+            RespawnZone {
+                area: Rect::from_corners(zone.min, zone.max),
+                respawn: zone.respawn,
+                damage: zone.damage,
+            },
+        ));
+    }
+}
+
+/// Puts a player who falls into a respawn zone back at the zone's safe
+/// point; the zone's damage replaces losing a life, unless it empties the
+/// player's health
+pub fn apply_respawn_zones(
+    mut players: Query<(Entity, &mut Transform, &mut PlayerVelocity), Without<Dying>>,
+    zones: Query<&RespawnZone>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (player, mut transform, mut velocity) in players.iter_mut() {
+        let position = transform.translation.truncate();
+        let Some(zone) = zones.iter().find(|zone| zone.area.contains(position)) else {
+            continue;
+        };
+        transform.translation = zone.respawn.extend(transform.translation.z);
+        velocity.0 = Vec2::ZERO;
+        if zone.damage > 0 {
+            damage.write(DamageEvent {
+                target: player,
+                amount: zone.damage,
+            });
+        }
+    }
+}
+
+/// Game over choices: Enter continues from the active checkpoint with fresh
+/// lives, R restarts the level (checkpoint, timer and level points reset)
+#[allow(clippy::too_many_arguments)]
+pub fn handle_game_over_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut players: Query<(
+        Entity,
+        &mut Health,
+        &mut Transform,
+        &mut PlayerVelocity,
+        &mut AnimationState,
+    )>,
+    mut lives: ResMut<Lives>,
+    mut checkpoint: ResMut<ActiveCheckpoint>,
+    mut timer: ResMut<LevelTimer>,
+    mut score: ResMut<Score>,
+    mut respawned: EventWriter<PlayerRespawned>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let restart = keyboard.just_pressed(KeyCode::KeyR);
+    if !restart && !keyboard.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    if restart {
+        *checkpoint = ActiveCheckpoint::default();
+        timer.elapsed = 0.0;
+        score.total -= score.level_points;
+        score.level_points = 0;
+        score.combo = 0;
+    }
+    *lives = Lives::default();
+    for (player, mut health, mut transform, mut velocity, mut animation) in players.iter_mut() {
+        respawn_player(
+            &mut health,
+            &mut transform,
+            &mut velocity,
+            &mut animation,
+            checkpoint.position,
+        );
+        respawned.write(PlayerRespawned { player });
+    }
+    next_state.set(GameState::Playing);
+}
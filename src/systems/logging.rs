@@ -0,0 +1,64 @@
+//! Runtime log verbosity: a reloadable tracing filter driven by `LogSettings`
+
+use bevy::log::tracing_subscriber::{reload, EnvFilter};
+use bevy::log::{BoxedLayer, Level};
+use bevy::prelude::*;
+
+use crate::components::{LogFilterHandle, LogSettings};
+use crate::constants::{EDITOR_LOG_MODULES, LOG_ENGINE_FILTER, LOG_MODULES};
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            modules: LOG_MODULES
+                .iter()
+                .map(|module| (module.to_string(), Level::INFO))
+                .collect(),
+            editor_verbose: false,
+        }
+    }
+}
+
+impl LogSettings {
+    /// Filter directives for these settings, on top of `LOG_ENGINE_FILTER`
+    pub fn filter(&self) -> String {
+        let root = env!("CARGO_CRATE_NAME");
+        let mut filter = format!("info,{},{}={}", LOG_ENGINE_FILTER, root, self.level);
+        for (module, level) in &self.modules {
+            if self.editor_verbose && EDITOR_LOG_MODULES.contains(&module.as_str()) {
+                continue;
+            }
+            filter.push_str(&format!(",{}::systems::{}={}", root, module, level));
+        }
+        if self.editor_verbose {
+            for module in EDITOR_LOG_MODULES {
+                filter.push_str(&format!(",{}::systems::{}=trace", root, module));
+            }
+        }
+        filter
+    }
+}
+
+/// `LogPlugin` custom layer: a filter that `apply_log_settings()` can swap at
+/// runtime, its handle stored as the `LogFilterHandle` resource
+/// The plugin's own filter lets everything through up to trace, so this
+/// layer decides what actually gets logged
+pub fn log_filter_layer(app: &mut App) -> Option<BoxedLayer> {
+    let (layer, handle) = reload::Layer::new(EnvFilter::new(LogSettings::default().filter()));
+    app.insert_resource(LogFilterHandle(handle));
+    Some(Box::new(layer))
+}
+
+/// Reloads the log filter when the `LogSettings` change
+pub fn apply_log_settings(settings: Res<LogSettings>, handle: Option<Res<LogFilterHandle>>) {
+    let Some(handle) = handle else {
+        return;
+    };
+    if !settings.is_changed() {
+        return;
+    }
+    if let Err(e) = handle.0.reload(EnvFilter::new(settings.filter())) {
+        warn!("Failed to apply the log settings: {}", e);
+    }
+}
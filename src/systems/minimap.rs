@@ -0,0 +1,269 @@
+//! Minimap: the level layout in a corner of the screen, revealed around the
+//! player as they explore, with markers for the player, checkpoints and the
+//! exit; `M` switches to a full-map overlay
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::{
+    LevelData, LevelEntity, Minimap, MinimapFrame, MinimapMarker, MinimapMarkerKind,
+    PlayerVelocity, PrimaryPlayer, TileCollisionMap,
+};
+use crate::constants::*;
+use crate::systems::level_loader::EMPTY_TILE;
+use crate::systems::tiles::world_to_tile;
+
+const FOG_COLOR: Color = Color::srgba(0.05, 0.05, 0.08, 0.85);
+const AIR_COLOR: Color = Color::srgba(0.15, 0.2, 0.3, 0.6);
+const SOLID_COLOR: Color = Color::srgb(0.6, 0.6, 0.55);
+const PLATFORM_COLOR: Color = Color::srgb(0.65, 0.45, 0.25);
+const WATER_COLOR: Color = Color::srgb(0.2, 0.45, 0.9);
+
+impl MinimapMarkerKind {
+    fn color(self) -> Color {
+        match self {
+            MinimapMarkerKind::Player => Color::srgb(1.0, 0.9, 0.2),
+            MinimapMarkerKind::Checkpoint => Color::srgb(0.3, 0.9, 0.4),
+            MinimapMarkerKind::Exit => Color::srgb(1.0, 0.3, 0.3),
+        }
+    }
+}
+
+/// Layout of the minimap frame: the corner map, or the full-map overlay
+/// across the middle of the screen, both keeping the level's aspect ratio
+fn frame_node(level: &LevelData, expanded: bool) -> Node {
+    let aspect_ratio = Some(level.width.max(1) as f32 / level.height.max(1) as f32);
+    if expanded {
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent((100.0 - MINIMAP_EXPANDED_WIDTH) / 2.0),
+            top: Val::Percent(10.0),
+            width: Val::Percent(MINIMAP_EXPANDED_WIDTH),
+            max_height: Val::Percent(80.0),
+            aspect_ratio,
+            ..default()
+        }
+    } else {
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(HUD_MARGIN),
+            bottom: Val::Px(HUD_MARGIN),
+            width: Val::Px(MINIMAP_WIDTH),
+            aspect_ratio,
+            ..default()
+        }
+    }
+}
+
+/// Places a marker node over the center of tile `coords`
+fn place_marker(node: &mut Node, level: &LevelData, coords: UVec2) {
+    node.left = Val::Percent((coords.x as f32 + 0.5) / level.width.max(1) as f32 * 100.0);
+    node.top = Val::Percent((coords.y as f32 + 0.5) / level.height.max(1) as f32 * 100.0);
+}
+
+/// Creates the level's minimap image, all fog, and spawns the frame with
+/// the player, checkpoint and exit markers
+pub fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut minimap: ResMut<Minimap>,
+    level: Option<Res<LevelData>>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: level.width.max(1),
+            height: level.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &FOG_COLOR.to_srgba().to_u8_array(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // One pixel per tile, kept sharp when scaled up
+    image.sampler = ImageSampler::nearest();
+    minimap.image = images.add(image);
+    minimap.explored = vec![false; (level.width * level.height) as usize];
+
+    let mut markers = vec![(MinimapMarkerKind::Player, UVec2::ZERO)];
+    markers.extend(
+        level
+            .checkpoints
+            .iter()
+            .filter_map(|checkpoint| world_to_tile(&level, checkpoint.position))
+            .map(|coords| (MinimapMarkerKind::Checkpoint, coords)),
+    );
+    markers.extend(
+        level
+            .goal
+            .as_ref()
+            .and_then(|goal| world_to_tile(&level, goal.position))
+            .map(|coords| (MinimapMarkerKind::Exit, coords)),
+    );
+
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            LevelEntity,
+            MinimapFrame,
+            frame_node(&level, minimap.expanded),
+            ImageNode::new(minimap.image.clone()),
+            GlobalZIndex(1),
+        ))
+        .with_children(|frame| {
+            for (kind, coords) in markers {
+                let mut node = Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(MINIMAP_MARKER_SIZE),
+                    height: Val::Px(MINIMAP_MARKER_SIZE),
+                    margin: UiRect {
+                        left: Val::Px(-MINIMAP_MARKER_SIZE / 2.0),
+                        top: Val::Px(-MINIMAP_MARKER_SIZE / 2.0),
+                        ..default()
+                    },
+                    ..default()
+                };
+                place_marker(&mut node, &level, coords);
+                frame.spawn((
+                    node,
+                    BackgroundColor(kind.color()),
+                    MinimapMarker { kind, coords },
+                    Visibility::Hidden,
+                ));
+            }
+        });
+}
+
+/// Minimap color of a revealed tile
+fn tile_color(index: u32, collision_map: Option<&TileCollisionMap>) -> Color {
+    let Some(map) = collision_map.filter(|_| index != EMPTY_TILE) else {
+        return AIR_COLOR;
+    };
+    if map.solid_tiles.contains(&index) {
+        SOLID_COLOR
+    } else if map.platform_tiles.contains(&index) {
+        PLATFORM_COLOR
+    } else if map.water_tiles.contains(&index) {
+        WATER_COLOR
+    } else {
+        AIR_COLOR
+    }
+}
+
+/// Reveals the tiles within `MINIMAP_REVEAL_RADIUS` of every player, drawing
+/// them into the minimap image
+pub fn reveal_minimap(
+    players: Query<&Transform, With<PlayerVelocity>>,
+    level: Option<Res<LevelData>>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    if minimap.explored.len() != (level.width * level.height) as usize {
+        return;
+    }
+
+    let radius = MINIMAP_REVEAL_RADIUS;
+    let mut revealed = Vec::new();
+    for player in players.iter() {
+        let Some(center) = world_to_tile(&level, player.translation.truncate()) else {
+            continue;
+        };
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+                let x = center.x as i32 + dx;
+                let y = center.y as i32 + dy;
+                if x < 0 || y < 0 || x >= level.width as i32 || y >= level.height as i32 {
+                    continue;
+                }
+                let index = (y as u32 * level.width + x as u32) as usize;
+                if !minimap.explored[index] {
+                    minimap.explored[index] = true;
+                    revealed.push(UVec2::new(x as u32, y as u32));
+                }
+            }
+        }
+    }
+    // Only touch the image (and re-upload it) when something new was seen
+    if revealed.is_empty() {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+    for coords in revealed {
+        let index = level.tiles.get(coords.x, coords.y).unwrap_or(EMPTY_TILE);
+        let color = tile_color(index, collision_map.as_deref());
+        if let Err(e) = image.set_color_at(coords.x, coords.y, color) {
+            warn!("Failed to draw minimap tile {}: {}", coords, e);
+            return;
+        }
+    }
+}
+
+/// Moves the player marker and shows the other markers once their tile has
+/// been explored
+pub fn update_minimap_markers(
+    players: Query<&Transform, With<PrimaryPlayer>>,
+    level: Option<Res<LevelData>>,
+    minimap: Res<Minimap>,
+    mut markers: Query<(&mut MinimapMarker, &mut Node, &mut Visibility)>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    let player_coords = players
+        .single()
+        .ok()
+        .and_then(|player| world_to_tile(&level, player.translation.truncate()));
+
+    for (mut marker, mut node, mut visibility) in markers.iter_mut() {
+        let visible = if marker.kind == MinimapMarkerKind::Player {
+            if let Some(coords) = player_coords.filter(|&coords| coords != marker.coords) {
+                marker.coords = coords;
+                place_marker(&mut node, &level, coords);
+            }
+            player_coords.is_some()
+        } else {
+            let index = (marker.coords.y * level.width + marker.coords.x) as usize;
+            minimap.explored.get(index).copied().unwrap_or(false)
+        };
+        visibility.set_if_neq(if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+/// `M` switches between the corner minimap and the full-map overlay
+pub fn toggle_minimap(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    level: Option<Res<LevelData>>,
+    mut minimap: ResMut<Minimap>,
+    mut frames: Query<&mut Node, With<MinimapFrame>>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    if !keyboard.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+    minimap.expanded = !minimap.expanded;
+    debug!("Minimap expanded: {}", minimap.expanded);
+    for mut node in frames.iter_mut() {
+        *node = frame_node(&level, minimap.expanded);
+    }
+}
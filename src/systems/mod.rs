@@ -1,18 +1,261 @@
 //! Systems module for the sidescroller game
 //!
 //! This module organiza todos os sistemas do jogo em grupos lógicos:
+//! - Activation: Entidades animadas e perigos longe da câmera ficam dormentes
+//! - Score: Pontuação com eventos, combos e contagem no fim do level
+//! - Settings: Configurações do jogador (volume, janela, teclas) salvas em arquivo
+//! - Save: Arquivo de progresso do jogador (coletáveis, melhores tempos)
 //! - Setup: Sistemas para inicialização do mundo do jogo
 //! - Movement: Sistemas para movimentação do jogador e física
+//! - Fixed step: Interpolação das posições na renderização entre os passos fixos
+//! - Player input: Entrada de cada jogador (teclado ou gamepad) acumulada para o passo fixo
+//! - Co-op: Segundo jogador no gamepad (Start entra, Select sai), câmera compartilhada e respawn no parceiro
+//! - Character: Ajustes do controlador do personagem carregados de arquivo
+//! - Characters: Personagens jogáveis definidos em arquivo (sprites, velocidade, pulo, pulos no ar) escolhidos no menu
+//! - Game config: Ajustes de jogabilidade e câmera carregados de arquivo e recarregados ao mudar
+//! - Collectibles: Moedas e gemas coletáveis com progresso salvo
+//! - Blocks: Blocos de interrogação e quebráveis que soltam itens quando atingidos por baixo
+//! - Boss arena: Arena do chefe com barreiras, câmera travada e música
+//! - Camera: Sistemas para acompanhamento da câmera e câmera livre de depuração
+//! - Chests: Baús com animação de abertura e tabelas de loot
+//! - Cutscene: Sequências roteirizadas para introduções e finais de level
+//! - Death: Sequência de morte e respawn no checkpoint
+//! - Dialogue: Diálogos com caixa de texto, escolhas e gatilhos
+//! - Doors: Portas trancadas e chaves coloridas
+//! - Draw layers: Ordenação por Y (pés) de personagens e decorações, e camadas fixas atrás ou à frente
+//! - Enemies: Inimigos que patrulham, causam dano ao toque e são derrotados ao pular em cima
+//! - Game state: Estados do jogo (menu, jogando, pausa, game over, editor)
+//! - Ghost: Fantasma translúcido repetindo a melhor corrida do level (prática de time attack)
+//! - Goal: Objetivo do level, tela de resultados e avanço para o próximo level
+//! - Hazards: Estalactites e pedregulhos que caem quando o jogador passa por baixo, serras e prensas móveis
+//! - Hints: Dicas de controle exibidas uma única vez ao entrar em regiões do level
+//! - HUD: Interface na tela com corações, vidas, coletáveis e cronômetro
+//! - Leaderboard: Melhores tempos e pontuações por level com iniciais do jogador
+//! - Lives: Vidas, checkpoints, zonas de respawn e opções de game over
+//! - Minimap: Minimapa no canto com névoa de guerra e mapa completo (M)
+//! - Level loader: Carregamento de levels a partir de arquivos e troca de level
+//! - Lighting: Escuridão por level com luzes pontuais (jogador, tiles, posicionadas no editor) via shader
+//! - Music: Música de fundo por level com crossfade e camadas conforme a intensidade
+//! - Network: Jogo em rede opcional entre duas instâncias (UDP) com predição do jogador local
+//! - Ambient: Sons ambientes em loop por level e zona de câmera
+//! - Objectives: Objetivos do level, progresso e bônus
+//! - Platforms: Plataformas móveis e plataformas atravessáveis (one-way)
+//! - Rising liquid: Lava ou água que sobe pelo level em fases de fuga
+//! - Ropes: Cordas e correntes com juntas físicas
+//! - Secrets: Áreas secretas atrás de paredes falsas
+//! - SFX: Efeitos sonoros espaciais, passos por superfície e fontes de som em loop
+//! - Speedrun: Cronômetro em tempo real com splits por checkpoint comparados ao recorde pessoal
+//! - Switches: Alavancas que acionam elevadores, portões e grupos de tiles
+//! - Tiles: Criação dos tiles do level e suas colisões
+//! - Tile palette: Paleta com o tileset no editor para pintar e apagar tiles com o mouse (pincel, retângulos e linhas)
+//! - Weather: Chuva e neve com vento, e névoa sobre a tela
+//! - Water: Volumes de água com empuxo e correntezas
+//! - Popups: Números flutuantes de dano e pontos
+//! - Pool: Reaproveitamento de entidades criadas com frequência (partículas, popups)
+//! - Props: Caixas e barris empurráveis
+//! - Prefabs: Modelos de entidades (sprite, colisor, IA, loot) carregados de arquivo e criados por id
+//! - Parallax: Fundos com rolagem parallax
+//! - Particles: Partículas de poeira ao correr e aterrissar, destroços e brilhos de coleta
+//! - Time attack: Cronômetro do level, melhores tempos e medalhas
+//! - Time scale: Câmera lenta, congelamento e avanço quadro a quadro para depuração
+//! - Time of day: Ciclo dia/noite com tonalização dos fundos
+//! - Audio: Canais de volume (master, música, efeitos, ambiente) aplicados a todos os sons
 //! - Animation: Sistemas para animações de sprites e efeitos visuais
+//! - Inspector: Inspetor do mundo (F10) para editar componentes ao vivo (feature `inspector`)
+//! - Console: Console de depuração com comandos e teleporte com Ctrl+clique
+//! - Screenshot: Captura de tela (F12) em PNG com data e hora, opcionalmente sem o HUD
+//! - Snapshot: Salvar e restaurar o estado do mundo como cena (comando `snapshot` do console)
+//! - Type registry: Registro de reflexão dos componentes, recursos e estados do jogo
+//! - Logging: Verbosidade dos logs por módulo, ajustável em tempo de execução
 //! - Debug: Sistemas para depuração e ferramentas de desenvolvimento
 
+pub mod activation;
+pub mod ambient;
 pub mod animation;
+pub mod audio;
+pub mod blocks;
+pub mod boss_arena;
+pub mod camera;
+pub mod character;
+pub mod characters;
+pub mod chests;
+pub mod collectibles;
+pub mod console;
+pub mod coop;
+pub mod cutscene;
+pub mod death;
 pub mod debug;
+pub mod dialogue;
+pub mod doors;
+pub mod draw_layers;
+pub mod enemies;
+pub mod fixed_step;
+pub mod game_config;
+pub mod game_state;
+pub mod ghost;
+pub mod goal;
+pub mod hazards;
+pub mod hints;
+pub mod hud;
+pub mod inspector;
+pub mod leaderboard;
+pub mod level_loader;
+pub mod lighting;
+pub mod lives;
+pub mod logging;
+pub mod minimap;
 pub mod movement;
+pub mod music;
+pub mod network;
+pub mod objectives;
+pub mod parallax;
+pub mod particles;
+pub mod platforms;
+pub mod player_input;
+pub mod pool;
+pub mod popups;
+pub mod prefabs;
+pub mod props;
+pub mod rising_liquid;
+pub mod ropes;
+pub mod save;
+pub mod score;
+pub mod screenshot;
+pub mod secrets;
+pub mod settings;
 pub mod setup;
+pub mod sfx;
+pub mod snapshot;
+pub mod speedrun;
+pub mod switches;
+pub mod tile_palette;
+pub mod tiles;
+pub mod time_attack;
+pub mod time_of_day;
+pub mod time_scale;
+pub mod type_registry;
+pub mod water;
+pub mod weather;
 
 // Re-export commonly used systems for easier importing
+pub use activation::update_dormancy;
+pub use ambient::crossfade_ambient;
 pub use animation::{execute_animations, update_animation_state};
-pub use debug::{debug_tile_collisions, debug_tile_grid, debug_tile_info, debug_tileset_info, toggle_debug_render};
+pub use audio::apply_audio_buses;
+pub use blocks::{animate_block_bounces, hit_blocks, spawn_blocks};
+pub use boss_arena::{enter_boss_arenas, lock_boss_arena, release_boss_arenas, spawn_boss_arenas};
+pub use camera::{
+    fly_free_camera, settle_camera_shake, shake_camera, shake_camera_on_damage, start_level_intro,
+    toggle_free_camera, update_camera_follow, update_camera_path,
+};
+pub use character::{apply_character_physics_config, load_character_physics};
+pub use characters::{
+    load_characters, select_character, spawn_player, spawn_selected_player, swap_player_character,
+};
+pub use chests::{animate_chests, collect_power_ups, load_loot_tables, open_chests, spawn_chests};
+pub use collectibles::{collect_pickups, spawn_collectibles};
+pub use console::{capture_console_keyboard, click_to_teleport, debug_console_ui, teleport_player};
+pub use coop::join_coop_players;
+pub use debug::{character_physics_debug_ui, debug_controller_contacts, debug_fps, debug_hotkeys, debug_settings_ui, debug_tile_collisions, debug_tile_grid, debug_tile_info, debug_tileset_info, player_debug_ui, sync_debug_render};
+pub use cutscene::{run_cutscene, start_intro_cutscene};
+pub use death::{
+    detect_fall_death, detect_player_death, finish_death_sequence, start_death_sequence, update_screen_fade,
+};
+pub use dialogue::{
+    advance_dialogue, check_dialogue_triggers, spawn_dialogue_box, spawn_dialogue_triggers,
+    update_dialogue_box,
+};
+pub use doors::{animate_doors, collect_keys, open_doors, spawn_keys_and_doors};
+pub use draw_layers::apply_draw_layers;
+pub use enemies::{enemy_contact, patrol_enemies};
+pub use fixed_step::{interpolate_transforms, record_physics_transforms, restore_physics_transforms};
+pub use game_config::{apply_game_config, load_game_config, reload_game_config};
+pub use game_state::{
+    enter_editor, exit_editor, level_error_ui, spawn_game_over_screen, spawn_menu_screen,
+    spawn_pause_screen, start_game, sync_world_pause, toggle_editor, toggle_pause,
+};
+pub use ghost::{record_ghost, replay_ghost, spawn_ghost};
+pub use goal::{advance_from_results, reach_goal, spawn_goal, spawn_results_screen};
+pub use hazards::{
+    draw_moving_hazards, move_hazards, moving_hazard_contact, rearm_falling_hazards,
+    spawn_falling_hazards, spawn_moving_hazards, trigger_falling_hazards, update_falling_hazards,
+};
+pub use hints::{setup_hint_popup, show_hints, spawn_hints, update_hint_popup};
+pub use hud::{
+    setup_hud, update_hud_collectibles, update_hud_hearts, update_hud_lives, update_hud_objectives,
+    update_hud_score, update_hud_timer, update_hud_liquid,
+};
+pub use inspector::inspector_plugin;
+pub use leaderboard::{
+    close_leaderboard, enter_leaderboard_initials, load_leaderboard, open_leaderboard,
+    prepare_leaderboard_entry, spawn_initials_prompt, spawn_leaderboard_screen,
+    update_initials_text,
+};
+pub use level_loader::{apply_level_asset, load_level, LevelAssetLoader, SpawnLevel};
+pub use lighting::{
+    attach_tile_lights, draw_placed_lights, lighting_editor_ui, place_lights, setup_lighting,
+    update_lighting, LightingMaterial,
+};
+pub use lives::{
+    activate_checkpoints, apply_damage, apply_respawn_zones, handle_game_over_input,
+    spawn_checkpoints, spawn_respawn_zones,
+};
+pub use logging::{apply_log_settings, log_filter_layer};
+pub use minimap::{reveal_minimap, setup_minimap, toggle_minimap, update_minimap_markers};
 pub use movement::{move_player, update_facing_direction};
+pub use music::{crossfade_music, setup_level_music, update_music_intensity};
+pub use network::{
+    apply_network_input, capture_network_input, despawn_network_players, handle_network_commands,
+    receive_network_messages, record_network_prediction, send_network_messages,
+    update_network_puppets,
+};
+pub use objectives::{award_objective_bonuses, setup_objectives, track_objectives};
+pub use parallax::{
+    auto_scroll_parallax, setup_parallax_backgrounds, update_background_size_on_resize,
+    update_parallax,
+};
+pub use particles::{emit_landing_dust, emit_run_dust, emit_tile_debris, update_particles};
+pub use platforms::{
+    carry_on_platforms, move_platforms, spawn_moving_platforms, update_one_way_platforms,
+    OneWayPlatformHook,
+};
+pub use player_input::{clear_buffered_input, read_player_input};
+pub use pool::return_to_pool;
+pub use popups::{setup_popup_pool, spawn_popups, update_popups};
+pub use prefabs::{load_prefabs, place_prefabs, prefab_palette_ui, spawn_level_entities};
+pub use props::spawn_props;
+pub use rising_liquid::{
+    raise_liquid, reset_rising_liquid, rising_liquid_contact, spawn_rising_liquid,
+};
+pub use ropes::{grab_rope, spawn_ropes, swing_on_rope};
+pub use save::load_save;
+pub use score::{apply_score_events, tally_level_score, update_score_timers};
+pub use screenshot::take_screenshot;
+pub use secrets::{reveal_secrets, spawn_secrets};
+pub use settings::{apply_settings, load_settings, settings_ui};
 pub use setup::{setup_graphics, setup_physics};
+pub use sfx::{
+    load_sound_registry, play_damage_sounds, play_footsteps, play_movement_sounds,
+    play_sound_events,
+    spawn_sound_emitters, start_audio_emitters, update_audio_emitters,
+};
+pub use snapshot::handle_snapshot_requests;
+pub use speedrun::{
+    finish_speedrun, load_speedrun_records, record_speedrun_splits, setup_speedrun_overlay,
+    spawn_speedrun_summary, update_speedrun_overlay, update_speedrun_timer,
+};
+pub use switches::{apply_gate_states, fade_gate_tiles, spawn_switches, update_switches};
+pub use tile_palette::{draw_tile_tool_preview, paint_tiles, select_tile_tool, tile_palette_ui};
+pub use tiles::{cull_tile_chunks, prune_tile_grid, setup_tilemap, spawn_queued_tiles, tile_collisions_dirty, update_ground_material, update_tile_collisions};
+pub use time_attack::{record_level_time, update_level_timer};
+pub use time_of_day::{apply_day_night_tint, update_time_of_day};
+pub use time_scale::{debug_time_controls, finish_debug_step, simulation_running};
+pub use type_registry::register_types;
+pub use weather::{
+    setup_weather, spawn_weather_particles, toggle_weather, update_weather_fog,
+    update_weather_particles,
+};
+pub use water::{
+    apply_buoyancy, draw_water_currents, spawn_water_currents, spawn_water_volumes,
+    update_water_state,
+};
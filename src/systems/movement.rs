@@ -3,44 +3,151 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::components::{FacingDirection, PlayerVelocity};
+use crate::components::{
+    AirJumps, CharacterStats, Dying, FacingDirection, GameConfig, GrabbingRope, GroundMaterial,
+    InCurrent, InWater, InputAction, JumpPhase, PlayerInput, PlayerJumped, PlayerLanded,
+    PlayerVelocity,
+};
 use crate::constants::*;
 
+/// Player characters driven by their input
+type PlayerControllers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut KinematicCharacterController,
+        &'static mut PlayerVelocity,
+        &'static KinematicCharacterControllerOutput,
+        &'static Transform,
+        &'static PlayerInput,
+        Has<InWater>,
+        Option<&'static GroundMaterial>,
+        Option<&'static InCurrent>,
+        Option<&'static CharacterStats>,
+        Option<&'static mut AirJumps>,
+        Option<&'static mut JumpPhase>,
+    ),
+    (Without<GrabbingRope>, Without<Dying>),
+>;
+
 /// Handles player movement input and physics
+/// Runs on the fixed timestep, reading each player's own `PlayerInput`
+/// Players hanging from a rope are driven by `swing_on_rope` instead
+/// Speed and jump force are scaled by the character's `CharacterStats`, and
+/// characters with air jumps can jump again until they touch the ground
+/// The `JumpPhase` tracks the rise of a jump, so letting go of Jump early
+/// keeps only `jump_cut` of the upward speed
 pub fn move_player(
     time: Res<Time>,
-    mut controllers: Query<(
-        &mut KinematicCharacterController,
-        &mut PlayerVelocity,
-        &KinematicCharacterControllerOutput,
-    )>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    config: Res<GameConfig>,
+    mut controllers: PlayerControllers,
+    mut jumped: EventWriter<PlayerJumped>,
+    mut landed: EventWriter<PlayerLanded>,
 ) {
-    for (mut controller, mut velocity, output) in controllers.iter_mut() {
+    for (
+        player,
+        mut controller,
+        mut velocity,
+        output,
+        transform,
+        input,
+        in_water,
+        ground,
+        current,
+        stats,
+        mut air_jumps,
+        mut phase,
+    ) in controllers.iter_mut()
+    {
+        let material = ground.map(|ground| ground.0).unwrap_or_default();
+        let stats = stats.copied().unwrap_or_default();
+        let position = transform.translation.truncate();
         if output.grounded {
-            velocity.0.y = 0.0;
+            if velocity.0.y < -LAND_MIN_SPEED {
+                landed.write(PlayerLanded {
+                    player,
+                    position,
+                    speed: -velocity.0.y,
+                });
+            }
+            // Bouncy ground reflects hard landings, everything else stops the fall
+            velocity.0.y = if velocity.0.y < -TILE_BOUNCE_MIN_SPEED {
+                -velocity.0.y * material.restitution
+            } else {
+                0.0
+            };
+            if let Some(air_jumps) = air_jumps.as_mut() {
+                air_jumps.0 = stats.air_jumps;
+            }
         }
+        let mut next_phase = match phase.as_deref() {
+            _ if output.grounded => JumpPhase::Grounded,
+            Some(JumpPhase::Grounded) => JumpPhase::Airborne,
+            Some(&current) => current,
+            None => JumpPhase::Airborne,
+        };
 
-        velocity.0.y += GRAVITY * time.delta_secs();
+        // Water reduces gravity and caps the sinking speed
+        if in_water {
+            velocity.0.y += config.gravity * WATER_GRAVITY_SCALE * time.delta_secs();
+            velocity.0.y = velocity.0.y.max(-WATER_MAX_SINK_SPEED);
+        } else {
+            velocity.0.y += config.gravity * time.delta_secs();
+        }
 
         let mut horizontal_movement = 0.0;
-        if keyboard.pressed(KeyCode::KeyA) || keyboard.pressed(KeyCode::ArrowLeft) {
+        if input.pressed(InputAction::Left) {
             horizontal_movement -= 1.0;
         }
-        if keyboard.pressed(KeyCode::KeyD) || keyboard.pressed(KeyCode::ArrowRight) {
+        if input.pressed(InputAction::Right) {
             horizontal_movement += 1.0;
         }
-        velocity.0.x = horizontal_movement * PLAYER_SPEED;
+        velocity.0.x =
+            horizontal_movement * config.player_speed * stats.speed * material.speed_factor;
+        if in_water {
+            velocity.0.x *= WATER_SPEED_FACTOR;
+        }
 
-        if (keyboard.just_pressed(KeyCode::KeyW)
-            || keyboard.just_pressed(KeyCode::Space)
-            || keyboard.just_pressed(KeyCode::ArrowUp))
-            && output.grounded
-        {
-            velocity.0.y = JUMP_FORCE;
+        // Down + Jump drops through one-way platforms instead of jumping
+        let holding_down = input.pressed(InputAction::Down);
+        if (input.buffered(InputAction::Jump) || input.buffered(InputAction::Up)) && !holding_down {
+            let air_jump = !output.grounded
+                && air_jumps.as_mut().is_some_and(|air_jumps| {
+                    let left = air_jumps.0 > 0;
+                    air_jumps.0 = air_jumps.0.saturating_sub(1);
+                    left
+                });
+            if output.grounded || air_jump {
+                velocity.0.y = config.jump_force * stats.jump;
+                jumped.write(PlayerJumped { player, position });
+                next_phase = if air_jump {
+                    JumpPhase::AirJumping
+                } else {
+                    JumpPhase::Rising
+                };
+            }
+        }
+
+        // Letting go of Jump mid-rise cuts it short, for variable jump height
+        if matches!(next_phase, JumpPhase::Rising | JumpPhase::AirJumping) {
+            let holding_jump = input.pressed(InputAction::Jump) || input.pressed(InputAction::Up);
+            if velocity.0.y <= 0.0 {
+                next_phase = JumpPhase::Airborne;
+            } else if !holding_jump {
+                velocity.0.y *= config.jump_cut;
+                next_phase = JumpPhase::Airborne;
+            }
+        }
+        if let Some(phase) = phase.as_mut() {
+            if **phase != next_phase {
+                **phase = next_phase;
+            }
         }
 
-        controller.translation = Some(velocity.0 * time.delta_secs());
+        // Currents carry the player on top of their own movement
+        let flow = current.map(|current| current.0).unwrap_or_default();
+        controller.translation = Some((velocity.0 + flow) * time.delta_secs());
     }
 }
 
@@ -0,0 +1,174 @@
+//! Background music: a looping track per level, crossfaded when it changes,
+//! with stems layered in as the music intensity rises
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::components::{
+    AudioBus, AudioGain, BossArena, BossArenaState, Health, LevelData, MusicController,
+    MusicIntensity, MusicPlayer, MusicStem, MusicStemDef, PlayerVelocity,
+};
+use crate::constants::*;
+
+impl Default for MusicController {
+    fn default() -> Self {
+        Self {
+            level_track: None,
+            boss_track: None,
+            crossfade: MUSIC_CROSSFADE_TIME,
+            stems: Vec::new(),
+        }
+    }
+}
+
+impl MusicController {
+    /// Track that should be playing right now
+    pub fn wanted(&self) -> Option<&str> {
+        self.boss_track.as_deref().or(self.level_track.as_deref())
+    }
+}
+
+/// Asset path of a music file in `assets/audio/`, or `None` (with a warning)
+/// when the file isn't shipped
+pub(crate) fn music_asset(name: &str) -> Option<String> {
+    let asset_path = format!("audio/{}", name);
+    if Path::new("assets").join(&asset_path).exists() {
+        Some(asset_path)
+    } else {
+        warn!("Music '{}' not found", asset_path);
+        None
+    }
+}
+
+/// Switches to the level's track; any boss track ends with the old level
+pub fn setup_level_music(mut music: ResMut<MusicController>, level: Option<Res<LevelData>>) {
+    let def = level.and_then(|level| level.music.clone());
+    music.level_track = def.as_ref().and_then(|def| music_asset(&def.track));
+    music.stems = def
+        .as_ref()
+        .map(|def| {
+            def.stems
+                .iter()
+                .filter_map(|stem| {
+                    Some(MusicStemDef {
+                        track: music_asset(&stem.track)?,
+                        threshold: stem.threshold,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    music.crossfade = def.map_or(MUSIC_CROSSFADE_TIME, |def| def.crossfade);
+    music.boss_track = None;
+}
+
+/// Sets the music intensity from the game: a boss fight is combat, and being
+/// low on health raises it too
+pub fn update_music_intensity(
+    mut intensity: ResMut<MusicIntensity>,
+    arenas: Query<&BossArena>,
+    players: Query<&Health, With<PlayerVelocity>>,
+) {
+    let combat = arenas
+        .iter()
+        .any(|arena| arena.state == BossArenaState::Locked);
+    let low_health = players
+        .single()
+        .is_ok_and(|health| health.current <= LOW_HEALTH);
+
+    let target = if combat {
+        MUSIC_INTENSITY_COMBAT
+    } else if low_health {
+        MUSIC_INTENSITY_LOW_HEALTH
+    } else {
+        0.0
+    };
+    if intensity.0 != target {
+        intensity.0 = target;
+    }
+}
+
+/// Starts the wanted track (and its stems, so they stay in sync) and
+/// crossfades it in while every other track fades out and despawns
+/// Uses real time so music keeps fading in menus and pauses; the fade is the
+/// track's gain on the music bus
+pub fn crossfade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    music: Res<MusicController>,
+    intensity: Res<MusicIntensity>,
+    asset_server: Res<AssetServer>,
+    mut players: Query<(Entity, &mut MusicPlayer, &mut AudioGain), Without<MusicStem>>,
+    mut stems: Query<(Entity, &mut MusicStem, &mut AudioGain), Without<MusicPlayer>>,
+) {
+    let wanted = music.wanted();
+    if let Some(track) = wanted {
+        if !players.iter().any(|(_, player, _)| player.track == track) {
+            if music.level_track.as_deref() == Some(track) {
+                for stem in &music.stems {
+                    commands.spawn((
+                        Name::new(format!("Music stem {}", stem.track)),
+                        AudioPlayer::new(asset_server.load(stem.track.clone())),
+                        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+                        AudioBus::Music,
+                        AudioGain(0.0),
+                        MusicStem {
+                            track: track.to_string(),
+                            threshold: stem.threshold,
+                            fade: 0.0,
+                        },
+                    ));
+                }
+            }
+            commands.spawn((
+                Name::new(format!("Music {}", track)),
+                AudioPlayer::new(asset_server.load(track.to_string())),
+                PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+                AudioBus::Music,
+                AudioGain(0.0),
+                MusicPlayer {
+                    track: track.to_string(),
+                    fade: 0.0,
+                },
+            ));
+        }
+    }
+
+    let step = if music.crossfade > 0.0 {
+        time.delta_secs() / music.crossfade
+    } else {
+        1.0
+    };
+    for (entity, mut player, mut gain) in players.iter_mut() {
+        if wanted == Some(player.track.as_str()) {
+            player.fade = (player.fade + step).min(1.0);
+        } else {
+            player.fade -= step;
+            if player.fade <= 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        }
+        gain.0 = player.fade;
+    }
+
+    // Stems follow the intensity, scaled by their track's fade
+    let stem_step = time.delta_secs() / MUSIC_STEM_FADE_TIME;
+    for (entity, mut stem, mut gain) in stems.iter_mut() {
+        let Some(track_fade) = players
+            .iter()
+            .find(|(_, player, _)| player.track == stem.track)
+            .map(|(_, player, _)| player.fade)
+        else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        stem.fade = if intensity.0 >= stem.threshold {
+            (stem.fade + stem_step).min(1.0)
+        } else {
+            (stem.fade - stem_step).max(0.0)
+        };
+        gain.0 = track_fade * stem.fade;
+    }
+}
@@ -0,0 +1,715 @@
+//! Optional networked play between two instances over UDP, started from the
+//! console (`net host [port]`, `net join <address>`, `net leave`)
+//!
+//! The host simulates both players: the client's player is a regular player
+//! with a `PlayerController::Remote`, driven by the inputs the client sends
+//! each fixed step. The host sends snapshots of every player (position,
+//! velocity, animation frame and facing) and of the level being played.
+//! The client predicts its own player by simulating it locally right away,
+//! and corrects it when the host's position at the last acknowledged input
+//! drifts too far from the prediction; the host's player is a
+//! `NetworkPuppet` drawn between the last two snapshots
+//! Packets are `NetMessage`s encoded as JSON; inputs are resent until the
+//! host acknowledges them, snapshots are simply superseded
+//! The host simulates the client's inputs one per fixed step, as the client
+//! did, and acknowledges each once its step has run
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::{Component, Path};
+
+use crate::components::{
+    ActiveCheckpoint, AnimationHandles, AnimationState, CharacterRoster, CurrentLevel, DrawLayer,
+    GameState, GhostFrame, LevelRegistry, NetInputFrame, NetMessage, NetPlayerState,
+    NetworkCommand, NetworkPuppet, NetworkRole, NetworkSession, PlayableCharacter,
+    PlayerController, PlayerInput, PlayerVelocity, Settings,
+};
+use crate::constants::*;
+use crate::systems::characters::spawn_player;
+use crate::systems::level_loader::switch_level;
+
+/// Largest packet accepted; snapshots of two players are far smaller
+const PACKET_SIZE: usize = 16 * 1024;
+/// Snapshot id of the host's player and of the client's
+const HOST_PLAYER: u8 = 0;
+const CLIENT_PLAYER: u8 = 1;
+
+impl NetworkSession {
+    fn open(role: NetworkRole, socket: UdpSocket, peer: Option<SocketAddr>) -> Self {
+        Self {
+            role,
+            socket,
+            peer,
+            welcomed: false,
+            silence: 0.0,
+            send_timer: Timer::from_seconds(1.0 / NET_SEND_RATE, TimerMode::Repeating),
+            tick: 0,
+            acked_tick: 0,
+            simulating_tick: None,
+            pending_inputs: Vec::new(),
+            predictions: VecDeque::new(),
+        }
+    }
+
+    /// Sends `message` to the peer, if there is one yet
+    fn send(&self, message: &NetMessage) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        match serde_json::to_vec(message) {
+            Ok(bytes) => {
+                if let Err(e) = self.socket.send_to(&bytes, peer) {
+                    debug!("Failed to send to {}: {}", peer, e);
+                }
+            }
+            Err(e) => error!("Failed to encode network message: {}", e),
+        }
+    }
+
+    /// Host: queues the client's input frames newer than any already queued
+    /// or simulated, dropping the oldest past `NET_MAX_PENDING_INPUTS`
+    fn queue_inputs(&mut self, frames: Vec<NetInputFrame>) {
+        for frame in frames {
+            let latest = self
+                .pending_inputs
+                .last()
+                .map_or(self.acked_tick, |queued| queued.tick);
+            if frame.tick > latest.max(self.simulating_tick.unwrap_or(0)) {
+                self.pending_inputs.push(frame);
+            }
+        }
+        let excess = self
+            .pending_inputs
+            .len()
+            .saturating_sub(NET_MAX_PENDING_INPUTS);
+        self.pending_inputs.drain(..excess);
+    }
+
+    /// Host: takes the oldest queued input frame to simulate in this fixed
+    /// step
+    fn next_input(&mut self) -> Option<NetInputFrame> {
+        if self.pending_inputs.is_empty() {
+            return None;
+        }
+        let frame = self.pending_inputs.remove(0);
+        self.simulating_tick = Some(frame.tick);
+        Some(frame)
+    }
+
+    /// Host: acknowledges the input frame simulated in the fixed step that
+    /// just ran, if there was one
+    fn acknowledge_simulated(&mut self) {
+        if let Some(tick) = self.simulating_tick.take() {
+            self.acked_tick = tick;
+        }
+    }
+
+    /// Client: drops the inputs and predictions the host acknowledged up to
+    /// `ack`, returning the prediction made for `ack` itself
+    fn acknowledge(&mut self, ack: u32) -> Option<Vec2> {
+        self.pending_inputs.retain(|frame| frame.tick > ack);
+        let predicted = self
+            .predictions
+            .iter()
+            .find(|(tick, _)| *tick == ack)
+            .map(|(_, position)| *position);
+        self.predictions.retain(|(tick, _)| *tick > ack);
+        predicted
+    }
+}
+
+/// Whether `level`, received from the host, is one of the registry's levels
+/// and a relative asset path that stays inside the asset directory
+fn is_known_level(registry: &LevelRegistry, level: &str) -> bool {
+    let path = Path::new(level);
+    !level.contains("://")
+        && !path.is_absolute()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        && registry.levels.iter().any(|known| known == level)
+}
+
+/// Binds a non-blocking UDP socket
+fn bind(address: &str) -> Result<UdpSocket, String> {
+    let socket =
+        UdpSocket::bind(address).map_err(|e| format!("Failed to bind '{}': {}", address, e))?;
+    socket
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure socket: {}", e))?;
+    Ok(socket)
+}
+
+/// Opens a session as the host on `port`
+fn host(port: u16) -> Result<NetworkSession, String> {
+    let socket = bind(&format!("0.0.0.0:{}", port))?;
+    info!("Hosting on port {}", port);
+    Ok(NetworkSession::open(NetworkRole::Host, socket, None))
+}
+
+/// Opens a session as a client of the host at `address` (`NET_DEFAULT_PORT`
+/// when it has no port)
+fn join(address: &str) -> Result<NetworkSession, String> {
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, NET_DEFAULT_PORT)
+    };
+    let peer = address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| format!("Unknown host '{}'", address))?;
+    let socket = bind("0.0.0.0:0")?;
+    info!("Joining {}", peer);
+    Ok(NetworkSession::open(
+        NetworkRole::Client,
+        socket,
+        Some(peer),
+    ))
+}
+
+/// Starts or ends the session on console requests; a new session replaces
+/// the open one
+pub fn handle_network_commands(
+    mut commands: Commands,
+    mut requests: EventReader<NetworkCommand>,
+    session: Option<Res<NetworkSession>>,
+) {
+    let Some(request) = requests.read().last() else {
+        return;
+    };
+    if let Some(session) = &session {
+        session.send(&NetMessage::Bye);
+        commands.remove_resource::<NetworkSession>();
+    }
+
+    let opened = match request {
+        NetworkCommand::Host(port) => host(*port),
+        NetworkCommand::Join(address) => join(address),
+        NetworkCommand::Leave => {
+            if session.is_some() {
+                info!("Left the network session");
+            }
+            return;
+        }
+    };
+    match opened {
+        Ok(session) => commands.insert_resource(session),
+        Err(e) => error!("{}", e),
+    }
+}
+
+/// Spawns the host's player on a client, as a sprite moved by snapshots
+fn spawn_network_puppet(world: &mut World, character_id: &str, frame: GhostFrame) {
+    let roster = world
+        .get_resource::<CharacterRoster>()
+        .cloned()
+        .unwrap_or_default();
+    let character = roster.get(character_id);
+    let asset_server = world.resource::<AssetServer>().clone();
+    let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+    let (_, handles) = character.animations(&asset_server, &mut layouts);
+    let mut sprite = character.sprite(&handles);
+    frame.show(&mut sprite, &handles);
+    world.spawn((
+        Name::new(format!("Network player ({})", character.name)),
+        sprite,
+        Transform::from_translation(frame.position.extend(0.0)),
+        DrawLayer::Sorted {
+            feet: PLAYER_CONTACT_HALF_HEIGHT,
+        },
+        handles,
+        PlayableCharacter(character.id.clone()),
+        NetworkPuppet {
+            previous: frame.position,
+            latest: frame.position,
+            ..default()
+        },
+    ));
+}
+
+/// Reads every packet that arrived since the last frame and applies it:
+/// the host spawns the client's player and feeds it the client's inputs,
+/// the client follows the host's level (starting to play from the menu),
+/// moves the puppet and corrects its own player's prediction
+/// Runs before the fixed main loop each frame, after the local input is read;
+/// the session ends when the peer leaves or goes silent for `NET_TIMEOUT`
+#[allow(clippy::too_many_arguments)]
+pub fn receive_network_messages(
+    mut commands: Commands,
+    real_time: Res<Time<Real>>,
+    mut session: ResMut<NetworkSession>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    current_level: Option<Res<CurrentLevel>>,
+    registry: Res<LevelRegistry>,
+    checkpoint: Res<ActiveCheckpoint>,
+    mut players: Query<(
+        &PlayerController,
+        &mut PlayerInput,
+        &mut Transform,
+        &mut PlayerVelocity,
+    )>,
+    mut puppets: Query<
+        (&mut NetworkPuppet, &mut Sprite, &AnimationHandles),
+        Without<PlayerVelocity>,
+    >,
+) {
+    session.silence += real_time.delta_secs();
+
+    let mut buffer = [0; PACKET_SIZE];
+    loop {
+        let (length, from) = match session.socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            // The peer's port closing shows up as a failed receive on some
+            // platforms; the timeout ends the session if it doesn't return
+            Err(e) => {
+                debug!("Failed to receive: {}", e);
+                break;
+            }
+        };
+        let message: NetMessage = match serde_json::from_slice(&buffer[..length]) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Ignoring malformed packet from {}: {}", from, e);
+                continue;
+            }
+        };
+        let from_peer = session.peer == Some(from);
+        match (session.role, message) {
+            (NetworkRole::Host, NetMessage::Hello { character }) => {
+                if session.peer.is_some() && !from_peer {
+                    continue;
+                }
+                session.peer = Some(from);
+                if !session.welcomed {
+                    session.welcomed = true;
+                    info!("{} joined as '{}'", from, character);
+                    let position = checkpoint.position;
+                    commands.queue(move |world: &mut World| {
+                        spawn_player(world, &character, position, PlayerController::Remote);
+                    });
+                }
+                session.send(&NetMessage::Welcome);
+            }
+            (NetworkRole::Host, NetMessage::Input { frames }) if from_peer => {
+                session.queue_inputs(frames);
+            }
+            (NetworkRole::Client, NetMessage::Welcome) if from_peer => {
+                if !session.welcomed {
+                    session.welcomed = true;
+                    info!("Joined {}", from);
+                }
+            }
+            (
+                NetworkRole::Client,
+                NetMessage::Snapshot {
+                    level,
+                    ack,
+                    players: states,
+                },
+            ) if from_peer => {
+                if !is_known_level(&registry, &level) {
+                    error!("The host is playing unknown level '{}', leaving", level);
+                    session.send(&NetMessage::Bye);
+                    commands.remove_resource::<NetworkSession>();
+                    return;
+                }
+                if current_level
+                    .as_ref()
+                    .is_none_or(|current| current.path != level)
+                {
+                    info!("Following the host to '{}'", level);
+                    commands.queue(move |world: &mut World| switch_level(world, &level));
+                }
+                if *state.get() == GameState::Menu {
+                    next_state.set(GameState::Playing);
+                }
+
+                for player_state in states {
+                    match player_state.id {
+                        HOST_PLAYER => {
+                            let frame = player_state.frame;
+                            match puppets.single_mut() {
+                                Ok((mut puppet, mut sprite, handles)) => {
+                                    puppet.previous = puppet.latest;
+                                    puppet.latest = frame.position;
+                                    puppet.interval = puppet.since_latest;
+                                    puppet.since_latest = 0.0;
+                                    frame.show(&mut sprite, handles);
+                                }
+                                Err(_) => {
+                                    let character = player_state.character;
+                                    commands.queue(move |world: &mut World| {
+                                        spawn_network_puppet(world, &character, frame);
+                                    });
+                                }
+                            }
+                        }
+                        CLIENT_PLAYER if ack > session.acked_tick => {
+                            reconcile(&mut session, &mut players, ack, &player_state);
+                        }
+                        _ => {}
+                    }
+                }
+                session.acked_tick = session.acked_tick.max(ack);
+            }
+            (_, NetMessage::Bye) if from_peer => {
+                info!("{} left the network session", from);
+                commands.remove_resource::<NetworkSession>();
+                return;
+            }
+            (_, message) => {
+                debug!("Ignoring {:?} from {}", message, from);
+                continue;
+            }
+        }
+        session.silence = 0.0;
+    }
+
+    if session.silence > NET_TIMEOUT {
+        warn!(
+            "No packets for {} seconds, network session ended",
+            NET_TIMEOUT
+        );
+        commands.remove_resource::<NetworkSession>();
+    }
+}
+
+/// Compares the host's position of the client's player at the acknowledged
+/// input tick with where it was predicted then, and moves the player (and
+/// the later predictions) by the difference when it is too large
+fn reconcile(
+    session: &mut NetworkSession,
+    players: &mut Query<(
+        &PlayerController,
+        &mut PlayerInput,
+        &mut Transform,
+        &mut PlayerVelocity,
+    )>,
+    ack: u32,
+    state: &NetPlayerState,
+) {
+    let Some(predicted) = session.acknowledge(ack) else {
+        return;
+    };
+
+    let error = state.frame.position - predicted;
+    if error.length() <= NET_CORRECTION_DISTANCE {
+        return;
+    }
+    debug!("Correcting prediction by {}", error);
+    for (_, position) in session.predictions.iter_mut() {
+        *position += error;
+    }
+    for (controller, _, mut transform, mut velocity) in players.iter_mut() {
+        if *controller == PlayerController::Keyboard {
+            transform.translation += error.extend(0.0);
+            velocity.0 = state.velocity;
+        }
+    }
+}
+
+/// On the host, gives the client's player the next input frame received from
+/// the client, one per fixed step; with none queued it keeps holding what it
+/// held
+/// Runs before movement
+pub fn apply_network_input(
+    mut session: ResMut<NetworkSession>,
+    mut players: Query<(&PlayerController, &mut PlayerInput)>,
+) {
+    if session.role != NetworkRole::Host {
+        return;
+    }
+    let frame = session.next_input();
+    for (controller, mut input) in players.iter_mut() {
+        if *controller != PlayerController::Remote {
+            continue;
+        }
+        input.just_pressed.clear();
+        let Some(frame) = &frame else {
+            continue;
+        };
+        input.held = frame.held.clone();
+        for &action in &frame.pressed {
+            if !input.buffered(action) {
+                input.buffered.push(action);
+            }
+            input.just_pressed.push(action);
+        }
+    }
+}
+
+/// Counts the fixed step and, on a client, keeps its player's input for
+/// sending to the host
+/// Runs after movement, before the buffered presses are cleared
+pub fn capture_network_input(
+    mut session: ResMut<NetworkSession>,
+    players: Query<(&PlayerController, &PlayerInput)>,
+) {
+    session.tick += 1;
+    if session.role != NetworkRole::Client || !session.welcomed {
+        return;
+    }
+    let tick = session.tick;
+    for (controller, input) in players.iter() {
+        if *controller == PlayerController::Keyboard {
+            session.pending_inputs.push(NetInputFrame {
+                tick,
+                held: input.held.clone(),
+                pressed: input.buffered.clone(),
+            });
+        }
+    }
+    let excess = session
+        .pending_inputs
+        .len()
+        .saturating_sub(NET_MAX_PENDING_INPUTS);
+    session.pending_inputs.drain(..excess);
+}
+
+/// Remembers where a client's player ended the fixed step, for comparing
+/// with the host's position once the host acknowledges the step's input; on
+/// the host, acknowledges the client input the step simulated
+/// Runs after Rapier's writeback
+pub fn record_network_prediction(
+    mut session: ResMut<NetworkSession>,
+    players: Query<(&PlayerController, &Transform)>,
+) {
+    if session.role == NetworkRole::Host {
+        session.acknowledge_simulated();
+        return;
+    }
+    if !session.welcomed {
+        return;
+    }
+    let tick = session.tick;
+    for (controller, transform) in players.iter() {
+        if *controller == PlayerController::Keyboard {
+            session
+                .predictions
+                .push_back((tick, transform.translation.truncate()));
+        }
+    }
+    while session.predictions.len() > NET_MAX_PENDING_INPUTS {
+        session.predictions.pop_front();
+    }
+}
+
+/// Sends `NET_SEND_RATE` packets a second: the host a snapshot of every
+/// player, the client its hello until welcomed and then its unacknowledged
+/// inputs
+pub fn send_network_messages(
+    real_time: Res<Time<Real>>,
+    mut session: ResMut<NetworkSession>,
+    settings: Res<Settings>,
+    current_level: Option<Res<CurrentLevel>>,
+    players: Query<(
+        &PlayerController,
+        &PlayableCharacter,
+        &Transform,
+        &Sprite,
+        &AnimationState,
+        &PlayerVelocity,
+    )>,
+) {
+    if !session.send_timer.tick(real_time.delta()).just_finished() {
+        return;
+    }
+
+    let message = match session.role {
+        NetworkRole::Host => {
+            let Some(current_level) = current_level else {
+                return;
+            };
+            let states = players
+                .iter()
+                .filter_map(
+                    |(controller, character, transform, sprite, state, velocity)| {
+                        let id = match controller {
+                            PlayerController::Keyboard => HOST_PLAYER,
+                            PlayerController::Remote => CLIENT_PLAYER,
+                            PlayerController::Gamepad(_) => return None,
+                        };
+                        Some(NetPlayerState {
+                            id,
+                            character: character.0.clone(),
+                            frame: GhostFrame::capture(transform, sprite, *state),
+                            velocity: velocity.0,
+                        })
+                    },
+                )
+                .collect();
+            NetMessage::Snapshot {
+                level: current_level.path.clone(),
+                ack: session.acked_tick,
+                players: states,
+            }
+        }
+        NetworkRole::Client if !session.welcomed => NetMessage::Hello {
+            character: settings.character.clone(),
+        },
+        NetworkRole::Client => NetMessage::Input {
+            frames: session.pending_inputs.clone(),
+        },
+    };
+    session.send(&message);
+}
+
+/// Moves puppets between their last two snapshot positions, one snapshot
+/// interval behind the host
+pub fn update_network_puppets(
+    real_time: Res<Time<Real>>,
+    mut puppets: Query<(&mut NetworkPuppet, &mut Transform)>,
+) {
+    for (mut puppet, mut transform) in puppets.iter_mut() {
+        puppet.since_latest += real_time.delta_secs();
+        let t = if puppet.interval > 0.0 {
+            (puppet.since_latest / puppet.interval).min(1.0)
+        } else {
+            1.0
+        };
+        let position = puppet.previous.lerp(puppet.latest, t);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+/// Removes the other instance's player once the session ends
+pub fn despawn_network_players(
+    mut commands: Commands,
+    players: Query<(Entity, &PlayerController)>,
+    puppets: Query<Entity, With<NetworkPuppet>>,
+) {
+    for (entity, controller) in players.iter() {
+        if *controller == PlayerController::Remote {
+            commands.entity(entity).despawn();
+        }
+    }
+    for entity in puppets.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::InputAction;
+
+    fn session(role: NetworkRole) -> NetworkSession {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        NetworkSession::open(role, socket, None)
+    }
+
+    fn frame(tick: u32) -> NetInputFrame {
+        NetInputFrame {
+            tick,
+            held: vec![InputAction::Right],
+            pressed: Vec::new(),
+        }
+    }
+
+    fn queued_ticks(session: &NetworkSession) -> Vec<u32> {
+        session
+            .pending_inputs
+            .iter()
+            .map(|frame| frame.tick)
+            .collect()
+    }
+
+    #[test]
+    fn host_simulates_one_input_frame_per_step() {
+        let mut session = session(NetworkRole::Host);
+        session.queue_inputs(vec![frame(1), frame(2), frame(3)]);
+
+        assert_eq!(session.next_input().map(|frame| frame.tick), Some(1));
+        // Not acknowledged until the step has been simulated
+        assert_eq!(session.acked_tick, 0);
+        session.acknowledge_simulated();
+        assert_eq!(session.acked_tick, 1);
+
+        assert_eq!(session.next_input().map(|frame| frame.tick), Some(2));
+        session.acknowledge_simulated();
+        assert_eq!(session.acked_tick, 2);
+        assert_eq!(queued_ticks(&session), vec![3]);
+    }
+
+    #[test]
+    fn host_ignores_resent_input_frames() {
+        let mut session = session(NetworkRole::Host);
+        session.queue_inputs(vec![frame(1), frame(2)]);
+        session.next_input();
+        session.acknowledge_simulated();
+
+        // The client resends everything it hasn't seen acknowledged
+        session.queue_inputs(vec![frame(1), frame(2), frame(3)]);
+        assert_eq!(queued_ticks(&session), vec![2, 3]);
+
+        // Frame 2 is being simulated when the next packet arrives
+        session.next_input();
+        session.queue_inputs(vec![frame(2), frame(3), frame(4)]);
+        assert_eq!(queued_ticks(&session), vec![3, 4]);
+    }
+
+    #[test]
+    fn host_keeps_the_last_input_without_new_frames() {
+        let mut session = session(NetworkRole::Host);
+        assert!(session.next_input().is_none());
+        session.acknowledge_simulated();
+        assert_eq!(session.acked_tick, 0);
+    }
+
+    #[test]
+    fn host_drops_the_oldest_input_frames_past_the_limit() {
+        let mut session = session(NetworkRole::Host);
+        let count = NET_MAX_PENDING_INPUTS as u32 + 10;
+        session.queue_inputs((1..=count).map(frame).collect());
+        assert_eq!(session.pending_inputs.len(), NET_MAX_PENDING_INPUTS);
+        assert_eq!(session.pending_inputs[0].tick, 11);
+    }
+
+    #[test]
+    fn client_drops_acknowledged_inputs_and_predictions() {
+        let mut session = session(NetworkRole::Client);
+        session.pending_inputs = (1..=4).map(frame).collect();
+        session.predictions = (1..=4)
+            .map(|tick| (tick, Vec2::splat(tick as f32)))
+            .collect();
+
+        assert_eq!(session.acknowledge(2), Some(Vec2::splat(2.0)));
+        assert_eq!(queued_ticks(&session), vec![3, 4]);
+        assert_eq!(
+            session
+                .predictions
+                .iter()
+                .map(|(tick, _)| *tick)
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        // Already dropped
+        assert_eq!(session.acknowledge(2), None);
+    }
+
+    #[test]
+    fn only_registry_levels_are_followed() {
+        let registry = LevelRegistry {
+            levels: vec![
+                "levels/level1.level.ron".to_string(),
+                "../levels/level2.level.ron".to_string(),
+                "/etc/level.level.ron".to_string(),
+            ],
+        };
+        assert!(is_known_level(&registry, "levels/level1.level.ron"));
+        assert!(!is_known_level(&registry, "levels/level3.level.ron"));
+        // Even when the registry lists them
+        assert!(!is_known_level(&registry, "../levels/level2.level.ron"));
+        assert!(!is_known_level(&registry, "/etc/level.level.ron"));
+        assert!(!is_known_level(
+            &registry,
+            "tests://levels/level1.level.ron"
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! Level objectives: progress tracking, the completion gate and bonuses
+
+use bevy::prelude::*;
+
+use crate::components::{
+    BossDefeated, CollectibleKind, CurrentLevel, LevelCompleted, LevelData, LevelTimer, Objective,
+    ObjectiveKind, Objectives, SaveData, ScoreEvent, ScoreSource,
+};
+use crate::systems::time_attack::format_time;
+
+impl Objective {
+    /// Count needed to complete the objective (1 for one-off objectives)
+    fn target(&self) -> u32 {
+        match self.def.kind {
+            ObjectiveKind::CollectCoins(count) | ObjectiveKind::CollectGems(count) => count,
+            ObjectiveKind::ReachGoalWithin(_) | ObjectiveKind::DefeatBoss => 1,
+        }
+    }
+
+    fn advance(&mut self, amount: u32) {
+        self.progress = (self.progress + amount).min(self.target());
+        self.complete = self.progress >= self.target();
+    }
+
+    /// HUD line describing the objective and its progress
+    pub fn label(&self) -> String {
+        let mark = if self.complete { "[x]" } else { "[ ]" };
+        let description = match self.def.kind {
+            ObjectiveKind::CollectCoins(count) => {
+                format!("Collect {} coins ({}/{})", count, self.progress, count)
+            }
+            ObjectiveKind::CollectGems(count) => {
+                format!("Collect {} gems ({}/{})", count, self.progress, count)
+            }
+            ObjectiveKind::ReachGoalWithin(seconds) => {
+                format!("Reach the goal within {}", format_time(seconds))
+            }
+            ObjectiveKind::DefeatBoss => "Defeat the boss".to_string(),
+        };
+        let required = if self.def.required { " *" } else { "" };
+        format!("{} {}{}", mark, description, required)
+    }
+}
+
+impl Objectives {
+    /// Whether a required objective still blocks the level's completion
+    pub fn required_pending(&self) -> bool {
+        self.entries.iter().any(|objective| {
+            objective.def.required
+                && !objective.complete
+                && !matches!(objective.def.kind, ObjectiveKind::ReachGoalWithin(_))
+        })
+    }
+}
+
+/// Builds the `Objectives` resource from the level's objectives
+/// Collectibles picked up in earlier sessions (and so not spawned again)
+/// already count towards collect objectives
+pub fn setup_objectives(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    save_data: Res<SaveData>,
+) {
+    let Some(level) = level else {
+        commands.insert_resource(Objectives::default());
+        return;
+    };
+
+    let collected = current_level
+        .as_ref()
+        .and_then(|current| save_data.collected.get(&current.path));
+    let already_collected = |kind: CollectibleKind| {
+        level
+            .collectibles
+            .iter()
+            .filter(|collectible| collectible.kind == kind)
+            .filter(|collectible| {
+                collected
+                    .is_some_and(|keys| keys.contains(&collectible.position.round().as_ivec2()))
+            })
+            .count() as u32
+    };
+
+    let entries = level
+        .objectives
+        .iter()
+        .map(|def| {
+            let mut objective = Objective {
+                def: def.clone(),
+                progress: 0,
+                complete: false,
+            };
+            match def.kind {
+                ObjectiveKind::CollectCoins(_) => {
+                    objective.advance(already_collected(CollectibleKind::Coin))
+                }
+                ObjectiveKind::CollectGems(_) => {
+                    objective.advance(already_collected(CollectibleKind::Gem))
+                }
+                ObjectiveKind::ReachGoalWithin(_) | ObjectiveKind::DefeatBoss => {}
+            }
+            objective
+        })
+        .collect();
+    commands.insert_resource(Objectives { entries });
+}
+
+/// Advances collect objectives on pickups and the boss objective when the
+/// boss is defeated
+pub fn track_objectives(
+    mut score_events: EventReader<ScoreEvent>,
+    mut boss_defeated: EventReader<BossDefeated>,
+    mut objectives: ResMut<Objectives>,
+) {
+    for event in score_events.read() {
+        let ScoreSource::Pickup(kind) = event.source else {
+            continue;
+        };
+        for objective in objectives.entries.iter_mut() {
+            match (objective.def.kind, kind) {
+                (ObjectiveKind::CollectCoins(_), CollectibleKind::Coin)
+                | (ObjectiveKind::CollectGems(_), CollectibleKind::Gem) => objective.advance(1),
+                _ => {}
+            }
+        }
+    }
+
+    if boss_defeated.read().last().is_some() {
+        for objective in objectives.entries.iter_mut() {
+            if objective.def.kind == ObjectiveKind::DefeatBoss {
+                objective.advance(1);
+            }
+        }
+    }
+}
+
+/// Checks time limits when the level is completed and awards the bonus of
+/// every completed objective
+pub fn award_objective_bonuses(
+    mut completed: EventReader<LevelCompleted>,
+    timer: Res<LevelTimer>,
+    mut objectives: ResMut<Objectives>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    if completed.read().last().is_none() {
+        return;
+    }
+
+    for objective in objectives.entries.iter_mut() {
+        if let ObjectiveKind::ReachGoalWithin(seconds) = objective.def.kind {
+            if timer.elapsed <= seconds {
+                objective.advance(1);
+            }
+        }
+        if objective.complete && objective.def.bonus > 0 {
+            score_events.write(ScoreEvent {
+                source: ScoreSource::ObjectiveBonus,
+                points: objective.def.bonus,
+                position: None,
+            });
+        }
+    }
+}
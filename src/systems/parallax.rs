@@ -0,0 +1,207 @@
+//! Parallax background systems
+
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+use crate::components::{
+    BackgroundIndex, LevelData, LevelEntity, MainCamera, ParallaxConfig, ParallaxLayer,
+    ParallaxLayerConfig,
+};
+use crate::constants::*;
+
+impl Default for ParallaxConfig {
+    fn default() -> Self {
+        let layer = |texture: &str, speed_x, speed_y, depth| ParallaxLayerConfig {
+            texture: texture.to_string(),
+            speed_x,
+            speed_y,
+            y_offset: 0.0,
+            depth,
+            tint: [1.0, 1.0, 1.0, 1.0],
+            repeat: true,
+            repeat_y: false,
+            auto_scroll: 0.0,
+        };
+        Self {
+            layers: vec![
+                layer(
+                    "scene/background_0.png",
+                    PARALLAX_BACKGROUND_0_SPEED,
+                    PARALLAX_BACKGROUND_0_SPEED_Y,
+                    -30.0,
+                ),
+                layer(
+                    "scene/background_1.png",
+                    PARALLAX_BACKGROUND_1_SPEED,
+                    PARALLAX_BACKGROUND_1_SPEED_Y,
+                    -20.0,
+                ),
+                layer(
+                    "scene/background_2.png",
+                    PARALLAX_BACKGROUND_2_SPEED,
+                    PARALLAX_BACKGROUND_2_SPEED_Y,
+                    -10.0,
+                ),
+            ],
+        }
+    }
+}
+
+/// Loads the parallax layer configuration from a RON file
+pub fn load_parallax_config(path: &str) -> Result<ParallaxConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read parallax config '{}': {}", path, e))?;
+    ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse parallax config '{}': {}", path, e))
+}
+
+/// Spawns the parallax background layers scaled to the window size
+/// The level's own parallax entry wins over the global config file.
+/// Repeating layers get three copies side by side (and stacked, for vertical
+/// repetition) so they can wrap seamlessly
+pub fn setup_parallax_backgrounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    windows: Query<&Window>,
+    level: Option<Res<LevelData>>,
+) {
+    let config = match level.as_ref().and_then(|level| level.parallax.clone()) {
+        Some(config) => config,
+        None => load_parallax_config(PARALLAX_CONFIG_PATH).unwrap_or_else(|e| {
+            warn!("{}, using built-in parallax layers", e);
+            ParallaxConfig::default()
+        }),
+    };
+
+    let (window_width, window_height) = windows
+        .single()
+        .map(|window| (window.width(), window.height()))
+        .unwrap_or((DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT));
+    let size = Vec2::new(window_width, window_height) * BACKGROUND_SCALE_FACTOR;
+
+    for (layer_index, layer) in config.layers.iter().enumerate() {
+        let texture: Handle<Image> = asset_server.load(layer.texture.clone());
+        let [r, g, b, a] = layer.tint;
+        let columns = if layer.repeat { -1..=1 } else { 0..=0 };
+        let rows = if layer.repeat_y { -1..=1 } else { 0..=0 };
+
+        for row in rows {
+            for index in columns.clone() {
+                commands.spawn((
+                    Name::new(format!("Background {} ({}, {})", layer_index, index, row)),
+                    LevelEntity,
+                    Sprite {
+                        image: texture.clone(),
+                        color: Color::srgba(r, g, b, a),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_xyz(
+                        index as f32 * size.x,
+                        layer.y_offset + row as f32 * size.y,
+                        layer.depth,
+                    ),
+                    ParallaxLayer {
+                        speed_multiplier: layer.speed_x,
+                        vertical_speed_multiplier: layer.speed_y,
+                        y_offset: layer.y_offset,
+                        repeat_width: size.x,
+                        repeat_height: size.y,
+                        layer_depth: layer.depth,
+                        repeat: layer.repeat,
+                        repeat_vertical: layer.repeat_y,
+                        auto_scroll_speed: layer.auto_scroll,
+                        scroll_offset: 0.0,
+                        tint: Color::srgba(r, g, b, a),
+                    },
+                    BackgroundIndex { index, row },
+                ));
+            }
+        }
+    }
+
+    commands.insert_resource(config);
+}
+
+/// Advances the constant drift of auto-scrolling layers
+pub fn auto_scroll_parallax(time: Res<Time>, mut layer_query: Query<&mut ParallaxLayer>) {
+    for mut layer in layer_query.iter_mut() {
+        if layer.auto_scroll_speed != 0.0 {
+            layer.scroll_offset += layer.auto_scroll_speed * time.delta_secs();
+        }
+    }
+}
+
+/// Moves background layers relative to the camera and wraps them horizontally
+/// (and vertically for layers with `repeat_vertical`)
+/// A multiplier of 0 keeps the layer glued to the camera (infinitely far away),
+/// 1 keeps it fixed in the world like the tiles
+/// Layers are only recomputed when the camera moved or the layer itself
+/// changed (added, auto-scrolled, resized), and transforms are only written
+/// when they actually move, so static scenes skip transform propagation too
+pub fn update_parallax(
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut layer_query: Query<
+        (&mut Transform, Ref<ParallaxLayer>, &BackgroundIndex),
+        Without<MainCamera>,
+    >,
+    mut last_camera_pos: Local<Option<Vec3>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let camera_pos = camera_transform.translation;
+    let camera_moved = last_camera_pos
+        .is_none_or(|last| last.distance_squared(camera_pos) > PARALLAX_CAMERA_EPSILON.powi(2));
+    if camera_moved {
+        *last_camera_pos = Some(camera_pos);
+    }
+    // Unchanged layers keep the position computed for the last camera update
+    let camera_pos = last_camera_pos.unwrap_or(camera_pos);
+
+    let _span = info_span!("parallax_layers", layers = layer_query.iter().len()).entered();
+    for (mut transform, layer, background_index) in layer_query.iter_mut() {
+        if !camera_moved && !layer.is_changed() {
+            continue;
+        }
+
+        // How far the layer has scrolled on screen
+        let scrolled = camera_pos.x * layer.speed_multiplier + layer.scroll_offset;
+        let x = if layer.repeat {
+            camera_pos.x - scrolled.rem_euclid(layer.repeat_width)
+                + background_index.index as f32 * layer.repeat_width
+        } else {
+            camera_pos.x - scrolled
+        };
+
+        let scrolled_y = camera_pos.y * layer.vertical_speed_multiplier - layer.y_offset;
+        let y = if layer.repeat_vertical {
+            camera_pos.y - scrolled_y.rem_euclid(layer.repeat_height)
+                + background_index.row as f32 * layer.repeat_height
+        } else {
+            camera_pos.y - scrolled_y
+        };
+
+        let translation = Vec3::new(x, y, layer.layer_depth);
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+/// Rescales background layers when the window is resized so they keep covering the screen
+pub fn update_background_size_on_resize(
+    mut resize_events: EventReader<WindowResized>,
+    mut layer_query: Query<(&mut Sprite, &mut ParallaxLayer)>,
+) {
+    let Some(event) = resize_events.read().last() else {
+        return;
+    };
+    let size = Vec2::new(event.width, event.height) * BACKGROUND_SCALE_FACTOR;
+
+    for (mut sprite, mut layer) in layer_query.iter_mut() {
+        sprite.custom_size = Some(size);
+        layer.repeat_width = size.x;
+        layer.repeat_height = size.y;
+    }
+}
@@ -0,0 +1,214 @@
+//! Particle effects: short-lived sprites for run dust, landing puffs, debris
+//! and pickup sparkles
+//!
+//! Bursts are spawned by `spawn_particles()`, either directly by gameplay
+//! systems or by the emitters below from gameplay events. Emitters skip
+//! bursts outside the camera view and particles leaving it are released, so
+//! effects only cost anything on screen. Particle entities come from the
+//! `Pool<Particle>`, so bursts reuse the sprites of expired ones
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use crate::components::{
+    AnimationFrameEvent, AnimationState, GroundMaterial, InWater, LevelData, MainCamera, Particle,
+    ParticleEffect, PlayerLanded, Pool, Surface, TileRemoved,
+};
+use crate::constants::*;
+use crate::systems::camera::camera_view;
+use crate::systems::tiles::tile_to_world;
+
+const DEBRIS_COLOR: Color = Color::srgb(0.45, 0.38, 0.3);
+
+impl Surface {
+    /// Color of the dust kicked up from the surface
+    fn dust_color(self) -> Color {
+        match self {
+            Surface::Grass => Color::srgb(0.55, 0.65, 0.35),
+            Surface::Stone => Color::srgb(0.7, 0.68, 0.62),
+            Surface::Wood => Color::srgb(0.6, 0.48, 0.32),
+            Surface::Metal => Color::srgb(0.75, 0.78, 0.82),
+        }
+    }
+}
+
+/// Spawns a burst of `effect` particles at `position`
+pub(crate) fn spawn_particles(
+    commands: &mut Commands,
+    pool: &mut Pool<Particle>,
+    effect: ParticleEffect,
+    position: Vec2,
+    color: Color,
+) {
+    let mut rng = rand::thread_rng();
+    let (count, lifetime, gravity) = match effect {
+        ParticleEffect::Sparkle => (PICKUP_PARTICLE_COUNT, PICKUP_PARTICLE_LIFETIME, 0.0),
+        ParticleEffect::RunDust => (DUST_PARTICLE_COUNT, DUST_PARTICLE_LIFETIME, -40.0),
+        ParticleEffect::LandingPuff => (LANDING_PARTICLE_COUNT, LANDING_PARTICLE_LIFETIME, 0.0),
+        ParticleEffect::Debris => (
+            DEBRIS_PARTICLE_COUNT,
+            DEBRIS_PARTICLE_LIFETIME,
+            DEBRIS_GRAVITY,
+        ),
+    };
+
+    for i in 0..count {
+        let (velocity, size) = match effect {
+            ParticleEffect::Sparkle => {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                (Vec2::from_angle(angle) * PICKUP_PARTICLE_SPEED, 3.0)
+            }
+            ParticleEffect::RunDust => (
+                Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(5.0..20.0)),
+                rng.gen_range(1.5..3.0),
+            ),
+            ParticleEffect::LandingPuff => {
+                // Half to each side, low along the ground
+                let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                (
+                    Vec2::new(side * rng.gen_range(30.0..90.0), rng.gen_range(0.0..25.0)),
+                    rng.gen_range(2.0..4.0),
+                )
+            }
+            ParticleEffect::Debris => (
+                Vec2::new(rng.gen_range(-80.0..80.0), rng.gen_range(80.0..200.0)),
+                rng.gen_range(2.0..4.5),
+            ),
+        };
+        pool.acquire(commands).insert((
+            Sprite::from_color(color, Vec2::splat(size)),
+            Transform::from_translation(position.extend(1.0)),
+            Particle {
+                velocity,
+                gravity,
+                lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+/// Camera view grown by `PARTICLE_CULL_MARGIN`
+fn particle_view(cameras: &Query<(&Transform, &Projection), With<MainCamera>>) -> Option<Rect> {
+    cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection))
+        .map(|view| view.inflate(PARTICLE_CULL_MARGIN))
+}
+
+/// Run dust at the feet of running characters on the footstep frames,
+/// colored by the surface underfoot
+pub fn emit_run_dust(
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Particle>>,
+    mut frame_events: EventReader<AnimationFrameEvent>,
+    characters: Query<(
+        &GroundMaterial,
+        &KinematicCharacterControllerOutput,
+        &Transform,
+        Has<InWater>,
+    )>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+) {
+    let view = particle_view(&cameras);
+    for event in frame_events.read() {
+        if event.state != AnimationState::Run || !FOOTSTEP_FRAMES.contains(&event.frame) {
+            continue;
+        }
+        let Ok((ground, output, transform, in_water)) = characters.get(event.entity) else {
+            continue;
+        };
+        let feet = transform.translation.truncate() - Vec2::Y * PLAYER_CONTACT_HALF_HEIGHT;
+        if !output.grounded || in_water || view.is_some_and(|view| !view.contains(feet)) {
+            continue;
+        }
+        spawn_particles(
+            &mut commands,
+            &mut pool,
+            ParticleEffect::RunDust,
+            feet,
+            ground.0.surface.dust_color(),
+        );
+    }
+}
+
+/// Dust puff at the feet of the player on a hard landing
+pub fn emit_landing_dust(
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Particle>>,
+    mut landed: EventReader<PlayerLanded>,
+    grounds: Query<&GroundMaterial>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+) {
+    let view = particle_view(&cameras);
+    for event in landed.read() {
+        let feet = event.position - Vec2::Y * PLAYER_CONTACT_HALF_HEIGHT;
+        if view.is_some_and(|view| !view.contains(feet)) {
+            continue;
+        }
+        let surface = grounds
+            .get(event.player)
+            .map(|ground| ground.0.surface)
+            .unwrap_or_default();
+        spawn_particles(
+            &mut commands,
+            &mut pool,
+            ParticleEffect::LandingPuff,
+            feet,
+            surface.dust_color(),
+        );
+    }
+}
+
+/// Debris where a tile of the level was removed
+pub fn emit_tile_debris(
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Particle>>,
+    mut removed: EventReader<TileRemoved>,
+    level: Option<Res<LevelData>>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+) {
+    let Some(level) = level else {
+        removed.clear();
+        return;
+    };
+    let view = particle_view(&cameras);
+    for event in removed.read() {
+        let position = tile_to_world(&level, event.coords.x, event.coords.y);
+        if view.is_some_and(|view| !view.contains(position)) {
+            continue;
+        }
+        spawn_particles(
+            &mut commands,
+            &mut pool,
+            ParticleEffect::Debris,
+            position,
+            DEBRIS_COLOR,
+        );
+    }
+}
+
+/// Moves and fades particles, releasing them when they expire or leave the
+/// camera view
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite), Without<MainCamera>>,
+) {
+    let view = particle_view(&cameras);
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        let position = transform.translation.truncate();
+        if particle.lifetime.finished() || view.is_some_and(|view| !view.contains(position)) {
+            Pool::<Particle>::release(&mut commands, entity);
+            continue;
+        }
+        particle.velocity.y -= particle.gravity * time.delta_secs();
+        transform.translation += (particle.velocity * time.delta_secs()).extend(0.0);
+        sprite
+            .color
+            .set_alpha(particle.lifetime.fraction_remaining());
+    }
+}
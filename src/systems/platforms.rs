@@ -0,0 +1,242 @@
+//! Moving and one-way platform systems
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    InputAction, LevelData, LevelEntity, MovingPlatform, OneWayPlatform, PlatformPassThrough,
+    PlatformPathMode, PlayerInput, PlayerVelocity, RenderInterpolation,
+};
+use crate::constants::PLATFORM_DROP_TIME;
+use crate::systems::characters::PLAYER_GROUP;
+
+/// Collision group of one-way platform colliders, so character controllers can
+/// filter them out while passing through
+pub const ONE_WAY_PLATFORM_GROUP: Group = Group::GROUP_2;
+
+/// Rapier hook turning `OneWayPlatform` colliders into one-way surfaces for
+/// rigid bodies: contacts are only kept when they push the body upwards
+#[derive(SystemParam)]
+pub struct OneWayPlatformHook<'w, 's> {
+    platforms: Query<'w, 's, (), With<OneWayPlatform>>,
+}
+
+impl BevyPhysicsHooks for OneWayPlatformHook<'_, '_> {
+    fn modify_solver_contacts(&self, context: ContactModificationContextView) {
+        // The allowed normal is the platform's local +Y, expressed from collider1's side
+        let allowed_local_n1 = if self.platforms.contains(context.collider1()) {
+            Vect::Y
+        } else if self.platforms.contains(context.collider2()) {
+            -Vect::Y
+        } else {
+            return;
+        };
+        context
+            .raw
+            .update_as_oneway_platform(&allowed_local_n1.into(), 0.1);
+    }
+}
+
+/// Spawns the moving platforms defined in the level data
+pub fn spawn_moving_platforms(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, platform) in level.moving_platforms.iter().enumerate() {
+        let Some(&start) = platform.waypoints.first() else {
+            warn!("Moving platform {} has no waypoints, skipping", index);
+            continue;
+        };
+
+        commands.spawn((
+            Name::new(format!("Moving Platform {}", index)),
+            LevelEntity,
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(platform.size.x / 2.0, platform.size.y / 2.0),
+            Sprite::from_color(Color::srgb(0.55, 0.4, 0.25), platform.size),
+            Transform::from_translation(start.extend(0.0)),
+            RenderInterpolation::default(),
+            MovingPlatform {
+                waypoints: platform.waypoints.clone(),
+                speed: platform.speed,
+                mode: platform.mode,
+                target: usize::min(1, platform.waypoints.len() - 1),
+                forward: true,
+                delta: Vec2::ZERO,
+            },
+        ));
+    }
+}
+
+/// Moves platforms towards their next waypoint
+pub fn move_platforms(time: Res<Time>, mut query: Query<(&mut MovingPlatform, &mut Transform)>) {
+    for (mut platform, mut transform) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        let target = platform.waypoints[platform.target];
+        let step = platform.speed * time.delta_secs();
+        let to_target = target - position;
+
+        let new_position = if to_target.length() <= step {
+            advance_waypoint(&mut platform);
+            target
+        } else {
+            position + to_target.normalize() * step
+        };
+
+        platform.delta = new_position - position;
+        transform.translation = new_position.extend(transform.translation.z);
+    }
+}
+
+fn advance_waypoint(platform: &mut MovingPlatform) {
+    let count = platform.waypoints.len();
+    next_waypoint(
+        &mut platform.target,
+        &mut platform.forward,
+        platform.mode,
+        count,
+    );
+}
+
+/// Picks the waypoint after `target` on a path of `count` waypoints walked
+/// in `mode`; also used by moving hazards
+pub(crate) fn next_waypoint(
+    target: &mut usize,
+    forward: &mut bool,
+    mode: PlatformPathMode,
+    count: usize,
+) {
+    if count < 2 {
+        return;
+    }
+    match mode {
+        PlatformPathMode::Loop => *target = (*target + 1) % count,
+        PlatformPathMode::Manual => {}
+        PlatformPathMode::PingPong => {
+            if *forward && *target == count - 1 {
+                *forward = false;
+            } else if !*forward && *target == 0 {
+                *forward = true;
+            }
+            *target = if *forward { *target + 1 } else { *target - 1 };
+        }
+    }
+}
+
+/// Carries characters standing on a moving platform along with it
+/// Must run after `move_player` so the platform delta is added on top of the
+/// player's own movement for this frame
+pub fn carry_on_platforms(
+    platforms: Query<(&MovingPlatform, &Transform)>,
+    mut characters: Query<
+        (
+            &mut KinematicCharacterController,
+            &KinematicCharacterControllerOutput,
+            &Transform,
+        ),
+        With<PlayerVelocity>,
+    >,
+) {
+    for (mut controller, output, transform) in characters.iter_mut() {
+        if !output.grounded {
+            continue;
+        }
+
+        // Standing on = touching a platform that is below us
+        let carried = output.collisions.iter().find_map(|collision| {
+            platforms
+                .get(collision.entity)
+                .ok()
+                .filter(|(_, platform_transform)| {
+                    platform_transform.translation.y < transform.translation.y
+                })
+                .map(|(platform, _)| platform.delta)
+        });
+
+        if let Some(delta) = carried {
+            let translation = controller.translation.unwrap_or(Vec2::ZERO);
+            controller.translation = Some(translation + delta);
+        }
+    }
+}
+
+/// Characters that can pass through one-way platforms
+type PassThroughCharacters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut KinematicCharacterController,
+        &'static KinematicCharacterControllerOutput,
+        &'static PlayerVelocity,
+        &'static mut PlatformPassThrough,
+        &'static Collider,
+        &'static Transform,
+        Option<&'static PlayerInput>,
+    ),
+>;
+
+/// Lets kinematic characters pass through one-way platforms
+/// Character controllers don't run physics hooks, so instead the platform
+/// collision group is filtered out while the character moves upwards, is
+/// dropping down (Down + Jump while standing on a platform) or still overlaps
+/// a platform it jumped or dropped into. Other players are always filtered
+/// out, so co-op players never block each other
+pub fn update_one_way_platforms(
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    one_way_platforms: Query<(), With<OneWayPlatform>>,
+    mut characters: PassThroughCharacters,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    // Sensors (water, pickups, triggers) keep the default groups, so only
+    // the platforms themselves count as overlapping
+    let is_one_way_platform = |entity: Entity| one_way_platforms.contains(entity);
+    let platform_filter = QueryFilter::new()
+        .groups(CollisionGroups::new(Group::ALL, ONE_WAY_PLATFORM_GROUP))
+        .exclude_sensors()
+        .predicate(&is_one_way_platform);
+
+    for (mut controller, output, velocity, mut pass_through, collider, transform, input) in
+        characters.iter_mut()
+    {
+        let down = input.is_some_and(|input| input.pressed(InputAction::Down));
+        let jump = input.is_some_and(|input| {
+            input.buffered(InputAction::Jump) || input.buffered(InputAction::Up)
+        });
+        pass_through.drop_timer = (pass_through.drop_timer - time.delta_secs()).max(0.0);
+
+        let on_one_way_platform = output
+            .collisions
+            .iter()
+            .any(|collision| one_way_platforms.contains(collision.entity));
+        if down && jump && output.grounded && on_one_way_platform {
+            pass_through.drop_timer = PLATFORM_DROP_TIME;
+        }
+
+        // Turning the platforms back on while inside one would pop the
+        // character up onto it (or stop it at the apex of a jump), so they
+        // stay off until it is clear
+        let mut overlapping = false;
+        context.intersections_with_shape(
+            transform.translation.truncate(),
+            0.0,
+            collider,
+            platform_filter,
+            |_| {
+                overlapping = true;
+                false
+            },
+        );
+
+        let passing = velocity.0.y > 0.0 || pass_through.drop_timer > 0.0 || overlapping;
+        let mut filter = Group::ALL.difference(PLAYER_GROUP);
+        if passing {
+            filter = filter.difference(ONE_WAY_PLATFORM_GROUP);
+        }
+        controller.filter_groups = Some(CollisionGroups::new(Group::ALL, filter));
+    }
+}
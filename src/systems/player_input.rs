@@ -0,0 +1,94 @@
+//! Per-player input: each player's keyboard or gamepad read into their
+//! `PlayerInput`, with presses buffered for the fixed-step systems
+
+use bevy::prelude::*;
+
+use crate::components::{InputAction, PlayerController, PlayerInput, Settings};
+use crate::constants::*;
+
+impl PlayerInput {
+    /// Whether `action` is held this frame
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.held.contains(&action)
+    }
+
+    /// Whether `action` started being held this frame
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether `action` was pressed since the last fixed step
+    pub fn buffered(&self, action: InputAction) -> bool {
+        self.buffered.contains(&action)
+    }
+}
+
+/// Gamepad layout: left stick or d-pad to move, south button (A / cross)
+/// to jump and west button (X / square) to interact
+fn gamepad_pressed(gamepad: &Gamepad, action: InputAction) -> bool {
+    let stick = gamepad.left_stick();
+    match action {
+        InputAction::Left => {
+            gamepad.pressed(GamepadButton::DPadLeft) || stick.x < -GAMEPAD_STICK_THRESHOLD
+        }
+        InputAction::Right => {
+            gamepad.pressed(GamepadButton::DPadRight) || stick.x > GAMEPAD_STICK_THRESHOLD
+        }
+        InputAction::Up => {
+            gamepad.pressed(GamepadButton::DPadUp) || stick.y > GAMEPAD_STICK_THRESHOLD
+        }
+        InputAction::Down => {
+            gamepad.pressed(GamepadButton::DPadDown) || stick.y < -GAMEPAD_STICK_THRESHOLD
+        }
+        InputAction::Jump => gamepad.pressed(GamepadButton::South),
+        InputAction::Interact => gamepad.pressed(GamepadButton::West),
+    }
+}
+
+/// Reads every player's actions from their keyboard bindings or gamepad and
+/// records the new presses until the next fixed step consumes them
+/// Runs before the fixed main loop each frame
+pub fn read_player_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    gamepads: Query<&Gamepad>,
+    mut players: Query<(&PlayerController, &mut PlayerInput)>,
+) {
+    for (controller, mut input) in players.iter_mut() {
+        let held: Vec<InputAction> = match controller {
+            PlayerController::Keyboard => InputAction::ALL
+                .into_iter()
+                .filter(|&action| settings.key_bindings.pressed(&keyboard, action))
+                .collect(),
+            PlayerController::Gamepad(gamepad) => {
+                gamepads.get(*gamepad).map_or(Vec::new(), |gamepad| {
+                    InputAction::ALL
+                        .into_iter()
+                        .filter(|&action| gamepad_pressed(gamepad, action))
+                        .collect()
+                })
+            }
+            // Filled from the client's packets by `receive_network_messages()`
+            PlayerController::Remote => continue,
+        };
+        let just_pressed: Vec<InputAction> = held
+            .iter()
+            .copied()
+            .filter(|&action| !input.pressed(action))
+            .collect();
+        for &action in &just_pressed {
+            if !input.buffered(action) {
+                input.buffered.push(action);
+            }
+        }
+        input.held = held;
+        input.just_pressed = just_pressed;
+    }
+}
+
+/// Forgets the buffered presses once a fixed step has seen them
+pub fn clear_buffered_input(mut players: Query<&mut PlayerInput>) {
+    for mut input in players.iter_mut() {
+        input.buffered.clear();
+    }
+}
@@ -0,0 +1,78 @@
+//! Entity pools: frequently spawned entities (particles, popups) are hidden
+//! and reused instead of despawned and spawned again
+
+use bevy::ecs::entity::Entities;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::components::Pool;
+
+impl<T: Component> Pool<T> {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            free: Vec::with_capacity(capacity),
+            capacity,
+            spawned: 0,
+            reused: 0,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Spawns `count` hidden entities up front so the first bursts don't
+    /// have to
+    pub fn prewarm(&mut self, commands: &mut Commands, count: usize) {
+        for _ in 0..count.min(self.capacity) {
+            let entity = commands
+                .spawn((Name::new(self.name), Visibility::Hidden))
+                .id();
+            self.free.push(entity);
+            self.spawned += 1;
+        }
+    }
+
+    /// Takes a free entity, visible again, or spawns a new one when there is
+    /// none; insert `T` and the rest of the entity's components on it
+    /// Free entities keep their old components, so insert every one the
+    /// entity relies on
+    pub fn acquire<'a>(&mut self, commands: &'a mut Commands) -> EntityCommands<'a> {
+        while let Some(entity) = self.free.pop() {
+            // Skips entities despawned while they were waiting
+            if commands.get_entity(entity).is_ok() {
+                self.reused += 1;
+                let mut entity_commands = commands.entity(entity);
+                entity_commands.insert(Visibility::Inherited);
+                return entity_commands;
+            }
+        }
+        self.spawned += 1;
+        commands.spawn(Name::new(self.name))
+    }
+
+    /// Hands an entity back to the pool by removing its `T`
+    pub fn release(commands: &mut Commands, entity: Entity) {
+        commands.entity(entity).remove::<T>();
+    }
+}
+
+/// Returns entities that lost their `T` to the pool, hidden; entities that
+/// were despawned are left out, and free entities beyond the pool's
+/// capacity are despawned
+pub fn return_to_pool<T: Component>(
+    mut commands: Commands,
+    mut removed: RemovedComponents<T>,
+    entities: &Entities,
+    mut pool: ResMut<Pool<T>>,
+) {
+    for entity in removed.read() {
+        if !entities.contains(entity) {
+            continue;
+        }
+        if pool.free.len() >= pool.capacity {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        commands.entity(entity).insert(Visibility::Hidden);
+        pool.free.push(entity);
+    }
+}
@@ -0,0 +1,92 @@
+//! Floating damage and score numbers, pooled so bursts of pickups don't
+//! spawn and despawn entities every frame
+
+use bevy::prelude::*;
+
+use crate::components::{DamageDealt, Pool, Popup, Score, ScoreEvent};
+use crate::constants::*;
+
+const SCORE_POPUP_COLOR: Color = Color::srgb(1.0, 0.9, 0.3);
+const DAMAGE_POPUP_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+
+/// Creates the popup pool with its hidden entities spawned up front
+pub fn setup_popup_pool(mut commands: Commands) {
+    let mut pool = Pool::<Popup>::new("Popup", POPUP_POOL_SIZE);
+    pool.prewarm(&mut commands, POPUP_POOL_SIZE);
+    commands.insert_resource(pool);
+}
+
+/// Shows a popup from the pool at `position`
+fn show_popup(
+    commands: &mut Commands,
+    pool: &mut Pool<Popup>,
+    position: Vec2,
+    text: String,
+    color: Color,
+) {
+    pool.acquire(commands).insert((
+        Text2d(text),
+        TextFont {
+            font_size: POPUP_FONT_SIZE,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(position.extend(POPUP_Z)),
+        Popup {
+            timer: Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once),
+        },
+    ));
+}
+
+/// Shows popups for points scored at a position and for damage taken
+/// Runs before the score is updated so the combo multiplier shown is the
+/// one the points are awarded with
+pub fn spawn_popups(
+    mut commands: Commands,
+    mut score_events: EventReader<ScoreEvent>,
+    mut damage_events: EventReader<DamageDealt>,
+    score: Res<Score>,
+    targets: Query<&GlobalTransform>,
+    mut pool: ResMut<Pool<Popup>>,
+) {
+    for event in score_events.read() {
+        let Some(position) = event.position else {
+            continue;
+        };
+        let text = if score.combo > 0 {
+            format!("+{} x{}", event.points, score.multiplier())
+        } else {
+            format!("+{}", event.points)
+        };
+        show_popup(&mut commands, &mut pool, position, text, SCORE_POPUP_COLOR);
+    }
+
+    for event in damage_events.read() {
+        let Ok(target) = targets.get(event.target) else {
+            continue;
+        };
+        show_popup(
+            &mut commands,
+            &mut pool,
+            target.translation().truncate(),
+            format!("-{}", event.amount),
+            DAMAGE_POPUP_COLOR,
+        );
+    }
+}
+
+/// Makes popups rise and fade, returning them to the pool once they expire
+pub fn update_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut Transform, &mut TextColor, &mut Popup)>,
+) {
+    for (entity, mut transform, mut color, mut popup) in popups.iter_mut() {
+        popup.timer.tick(time.delta());
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - popup.timer.fraction());
+        if popup.timer.finished() {
+            Pool::<Popup>::release(&mut commands, entity);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Prefabs: entity blueprints loaded from a RON file and instantiated by id,
+//! from a level's `entities` layer or the editor palette
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier2d::prelude::*;
+use std::collections::HashMap;
+
+use crate::components::{
+    Buoyant, Collectible, DrawLayer, Enemy, EntityDef, LevelData, LevelEntity, Loot, MainCamera,
+    Prefab, PrefabBody, PrefabCollider, PrefabPalette, PrefabRegistry, SleepsOffScreen,
+};
+use crate::constants::*;
+
+/// Loads the prefabs from a RON file (a map of id to `Prefab`)
+pub fn load_prefab_file(path: &str) -> Result<PrefabRegistry, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read prefabs '{}': {}", path, e))?;
+    let prefabs: HashMap<String, Prefab> = ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse prefabs '{}': {}", path, e))?;
+    Ok(PrefabRegistry(prefabs))
+}
+
+/// Inserts the `PrefabRegistry` resource; levels place no prefabs without it
+pub fn load_prefabs(mut commands: Commands) {
+    let registry = load_prefab_file(PREFABS_PATH).unwrap_or_else(|e| {
+        warn!("{}, no prefabs will be spawned", e);
+        PrefabRegistry::default()
+    });
+    commands.insert_resource(registry);
+}
+
+impl PrefabRegistry {
+    /// Spawns the prefab `id` at `position` as a level entity
+    /// Returns `None` (with a warning) for an unknown id
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        asset_server: &AssetServer,
+        id: &str,
+        position: Vec2,
+    ) -> Option<Entity> {
+        let Some(prefab) = self.0.get(id) else {
+            warn!("Unknown prefab '{}'", id);
+            return None;
+        };
+
+        let (r, g, b) = prefab.sprite.color;
+        let mut sprite = match &prefab.sprite.image {
+            Some(path) => Sprite::from_image(asset_server.load(path)),
+            None => Sprite::from_color(Color::WHITE, Vec2::ONE),
+        };
+        sprite.color = Color::srgb(r, g, b);
+        sprite.custom_size = Some(prefab.sprite.size);
+
+        let mut entity = commands.spawn((
+            Name::new(id.to_string()),
+            LevelEntity,
+            sprite,
+            Transform::from_translation(position.extend(0.0)),
+            // Pickups float over everything else
+            if prefab.collectible.is_some() || prefab.power_up.is_some() {
+                DrawLayer::Front
+            } else {
+                DrawLayer::standing(prefab.sprite.size)
+            },
+        ));
+
+        if let Some(collider) = prefab.collider {
+            entity.insert(match collider {
+                PrefabCollider::Cuboid {
+                    half_width,
+                    half_height,
+                } => Collider::cuboid(half_width, half_height),
+                PrefabCollider::Ball { radius } => Collider::ball(radius),
+            });
+            match prefab.body {
+                PrefabBody::Fixed => {
+                    entity.insert(RigidBody::Fixed);
+                }
+                PrefabBody::Sensor => {
+                    entity.insert(Sensor);
+                }
+                PrefabBody::Dynamic {
+                    density,
+                    friction,
+                    lock_rotation,
+                } => {
+                    entity.insert((
+                        RigidBody::Dynamic,
+                        ColliderMassProperties::Density(density),
+                        Friction::coefficient(friction),
+                        if lock_rotation {
+                            LockedAxes::ROTATION_LOCKED
+                        } else {
+                            LockedAxes::empty()
+                        },
+                    ));
+                }
+            }
+        }
+
+        if let Some(kind) = prefab.prop {
+            entity.insert((
+                kind,
+                // Needed by the buoyancy system
+                ReadMassProperties::default(),
+                ExternalForce::default(),
+                Damping::default(),
+                Buoyant {
+                    buoyancy: PROP_BUOYANCY,
+                    water_damping: PROP_WATER_DAMPING,
+                },
+            ));
+        }
+        if let Some(kind) = prefab.collectible {
+            // Keyed like the level's own collectibles, so collecting it is saved
+            entity.insert(Collectible {
+                kind,
+                key: Some(position.round().as_ivec2()),
+            });
+        }
+        if let Some(power_up) = prefab.power_up {
+            entity.insert(power_up);
+        }
+        if let Some(enemy) = prefab.enemy {
+            let mut hit_cooldown = Timer::from_seconds(ENEMY_HIT_COOLDOWN, TimerMode::Once);
+            // Able to hurt the player straight away
+            hit_cooldown.tick(hit_cooldown.duration());
+            entity.insert((
+                Enemy {
+                    damage: enemy.damage,
+                    speed: enemy.speed,
+                    origin: position,
+                    patrol_distance: enemy.patrol_distance,
+                    direction: 1.0,
+                    half_size: prefab.sprite.size / 2.0,
+                    hit_cooldown,
+                },
+                SleepsOffScreen,
+            ));
+        }
+        if let Some(table) = &prefab.loot {
+            entity.insert(Loot(table.clone()));
+        }
+        Some(entity.id())
+    }
+}
+
+/// Spawns the level's `entities` layer from the prefab registry
+pub fn spawn_level_entities(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    registry: Res<PrefabRegistry>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    for placed in &level.entities {
+        registry.spawn(
+            &mut commands,
+            &asset_server,
+            &placed.prefab,
+            placed.position,
+        );
+    }
+}
+
+/// Editor window listing the prefabs; the selected one is placed by
+/// clicking in the level
+pub fn prefab_palette_ui(
+    mut contexts: EguiContexts,
+    registry: Res<PrefabRegistry>,
+    mut palette: ResMut<PrefabPalette>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut ids: Vec<&String> = registry.0.keys().collect();
+    ids.sort();
+    egui::Window::new("Prefabs")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 40.0])
+        .show(ctx, |ui| {
+            if ids.is_empty() {
+                ui.label(format!("No prefabs in '{}'", PREFABS_PATH));
+            }
+            for id in ids {
+                let selected = palette.selected.as_ref() == Some(id);
+                if ui.selectable_label(selected, id.as_str()).clicked() {
+                    palette.selected = if selected { None } else { Some(id.clone()) };
+                }
+            }
+        });
+}
+
+/// Left click in the editor places the selected prefab at the cursor and
+/// adds it to the level's `entities`
+#[allow(clippy::too_many_arguments)]
+pub fn place_prefabs(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    palette: Res<PrefabPalette>,
+    registry: Res<PrefabRegistry>,
+    asset_server: Res<AssetServer>,
+    level: Option<ResMut<LevelData>>,
+) {
+    let (Some(id), Some(mut level)) = (&palette.selected, level) else {
+        return;
+    };
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // Clicks on the palette itself
+    if contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.is_pointer_over_area())
+    {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+
+    if registry
+        .spawn(&mut commands, &asset_server, id, position)
+        .is_some()
+    {
+        debug!("Placed prefab '{}' at {}", id, position);
+        level.entities.push(EntityDef {
+            prefab: id.clone(),
+            position,
+        });
+    }
+}
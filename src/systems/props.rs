@@ -0,0 +1,57 @@
+//! Dynamic physics props (crates, barrels) the player can push around
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{Buoyant, DrawLayer, LevelData, LevelEntity, PropKind};
+use crate::constants::*;
+
+/// Spawns the props defined in the level data as dynamic rigid bodies
+/// Crates keep their rotation locked so they stack cleanly, barrels roll
+pub fn spawn_props(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, prop) in level.props.iter().enumerate() {
+        let (collider, density, locked_axes, sprite) = match prop.kind {
+            PropKind::Crate => (
+                Collider::cuboid(CRATE_SIZE / 2.0, CRATE_SIZE / 2.0),
+                CRATE_DENSITY,
+                LockedAxes::ROTATION_LOCKED,
+                Sprite::from_color(Color::srgb(0.6, 0.42, 0.2), Vec2::splat(CRATE_SIZE)),
+            ),
+            PropKind::Barrel => (
+                Collider::ball(BARREL_RADIUS),
+                BARREL_DENSITY,
+                LockedAxes::empty(),
+                Sprite::from_color(
+                    Color::srgb(0.45, 0.3, 0.2),
+                    Vec2::splat(BARREL_RADIUS * 2.0),
+                ),
+            ),
+        };
+
+        commands.spawn((
+            Name::new(format!("{:?} {}", prop.kind, index)),
+            LevelEntity,
+            prop.kind,
+            RigidBody::Dynamic,
+            collider,
+            ColliderMassProperties::Density(density),
+            Friction::coefficient(0.7),
+            locked_axes,
+            DrawLayer::standing(sprite.custom_size.unwrap_or_default()),
+            sprite,
+            Transform::from_translation(prop.position.extend(0.0)),
+            // Needed by the buoyancy system
+            ReadMassProperties::default(),
+            ExternalForce::default(),
+            Damping::default(),
+            Buoyant {
+                buoyancy: PROP_BUOYANCY,
+                water_damping: PROP_WATER_DAMPING,
+            },
+        ));
+    }
+}
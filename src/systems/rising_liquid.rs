@@ -0,0 +1,128 @@
+//! Rising liquid: lava or water that floods the level from below, killing
+//! the players it reaches
+
+use bevy::prelude::*;
+
+use crate::components::{
+    DamageEvent, Dying, Health, LevelData, LevelEntity, LiquidKind, PlayerRespawned,
+    PlayerVelocity, RisingLiquid,
+};
+use crate::constants::*;
+
+const LAVA_COLOR: Color = Color::srgba(1.0, 0.35, 0.05, 0.9);
+const FLOOD_WATER_COLOR: Color = Color::srgba(0.15, 0.35, 0.8, 0.6);
+
+impl RisingLiquid {
+    /// Puts the surface back at `surface` with the starting speed and delay
+    fn reset(&mut self, surface: f32) {
+        self.surface = surface;
+        self.speed = self.def.speed;
+        self.delay.reset();
+    }
+}
+
+/// Spawns the level's rising liquid, spanning the level's width
+pub fn spawn_rising_liquid(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(def) = level.as_ref().and_then(|level| level.rising_liquid.clone()) else {
+        return;
+    };
+
+    let color = match def.kind {
+        LiquidKind::Lava => LAVA_COLOR,
+        LiquidKind::Water => FLOOD_WATER_COLOR,
+    };
+    commands.spawn((
+        Name::new(format!("Rising {:?}", def.kind)),
+        LevelEntity,
+        Sprite::from_color(color, Vec2::ONE),
+        Transform::default(),
+        RisingLiquid {
+            surface: def.start,
+            speed: def.speed,
+            delay: Timer::from_seconds(def.delay, TimerMode::Once),
+            def,
+        },
+    ));
+}
+
+/// Raises the surface, speeding up as it goes, and stretches the liquid's
+/// sprite from the surface down past the bottom of the level
+pub fn raise_liquid(
+    time: Res<Time>,
+    level: Option<Res<LevelData>>,
+    mut liquids: Query<(&mut RisingLiquid, &mut Sprite, &mut Transform)>,
+) {
+    let Some(level) = level else {
+        return;
+    };
+    let width = level.width as f32 * TILE_SIZE_16;
+    let top = LEVEL_ORIGIN_Y + level.height as f32 * TILE_SIZE_16;
+    let bottom = LEVEL_ORIGIN_Y - KILL_PLANE_MARGIN;
+
+    for (mut liquid, mut sprite, mut transform) in liquids.iter_mut() {
+        if liquid.delay.tick(time.delta()).finished() {
+            let dt = time.delta_secs();
+            liquid.surface = (liquid.surface + liquid.speed * dt).min(top);
+            let max_speed = liquid.def.max_speed.unwrap_or(f32::INFINITY);
+            liquid.speed = (liquid.speed + liquid.def.acceleration * dt).min(max_speed);
+        }
+
+        let height = (liquid.surface - bottom).max(0.0);
+        sprite.custom_size = Some(Vec2::new(width, height));
+        transform.translation = Vec3::new(
+            LEVEL_ORIGIN_X + width / 2.0,
+            bottom + height / 2.0,
+            RISING_LIQUID_Z,
+        );
+    }
+}
+
+/// Players not already dying, with their health
+type LivingPlayerHealth<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static Health),
+    (With<PlayerVelocity>, Without<Dying>),
+>;
+
+/// Kills players the liquid reaches: lava as soon as it touches their feet,
+/// water once it is over their head
+pub fn rising_liquid_contact(
+    players: LivingPlayerHealth,
+    liquids: Query<&RisingLiquid>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for liquid in liquids.iter() {
+        let reach = match liquid.def.kind {
+            LiquidKind::Lava => -PLAYER_CONTACT_HALF_HEIGHT,
+            LiquidKind::Water => PLAYER_CONTACT_HALF_HEIGHT,
+        };
+        for (player, transform, health) in players.iter() {
+            if health.current > 0 && transform.translation.y + reach < liquid.surface {
+                damage_events.write(DamageEvent {
+                    target: player,
+                    amount: health.current,
+                });
+            }
+        }
+    }
+}
+
+/// Lowers the liquid below respawned players and restarts its rise, so a
+/// respawn point isn't left under the surface
+pub fn reset_rising_liquid(
+    mut respawned: EventReader<PlayerRespawned>,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut liquids: Query<&mut RisingLiquid>,
+) {
+    for event in respawned.read() {
+        let Ok(transform) = players.get(event.player) else {
+            continue;
+        };
+        let floor = transform.translation.y - RISING_LIQUID_RESPAWN_GAP;
+        for mut liquid in liquids.iter_mut() {
+            let surface = liquid.surface.min(floor);
+            liquid.reset(surface);
+        }
+    }
+}
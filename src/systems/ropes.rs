@@ -0,0 +1,196 @@
+//! Ropes and chains built from jointed rigid bodies
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    CharacterStats, Dying, GameConfig, GrabbingRope, InputAction, JumpPhase, LevelData,
+    LevelEntity, PlayerInput, PlayerJumped, PlayerVelocity, RopeSegment,
+};
+use crate::constants::*;
+
+/// Spawns the ropes defined in the level data
+/// Each rope is a chain of small sensor segments linked by revolute joints to
+/// a fixed anchor, so the character controller never collides with it. An
+/// optional platform hangs from the last segment.
+pub fn spawn_ropes(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    let half_length = ROPE_SEGMENT_LENGTH / 2.0;
+    for (index, rope) in level.ropes.iter().enumerate() {
+        let mut parent = commands
+            .spawn((
+                Name::new(format!("Rope {} anchor", index)),
+                LevelEntity,
+                RigidBody::Fixed,
+                Transform::from_translation(rope.anchor.extend(0.0)),
+            ))
+            .id();
+        let mut parent_anchor = Vec2::ZERO;
+
+        let segments = (rope.length / ROPE_SEGMENT_LENGTH).ceil().max(1.0) as u32;
+        for segment in 0..segments {
+            let position = rope.anchor - Vec2::Y * (segment as f32 + 0.5) * ROPE_SEGMENT_LENGTH;
+            let joint = RevoluteJointBuilder::new()
+                .local_anchor1(parent_anchor)
+                .local_anchor2(Vec2::Y * half_length);
+            parent = commands
+                .spawn((
+                    Name::new(format!("Rope {} segment {}", index, segment)),
+                    LevelEntity,
+                    RopeSegment,
+                    RigidBody::Dynamic,
+                    Collider::cuboid(ROPE_SEGMENT_WIDTH / 2.0, half_length),
+                    Sensor,
+                    AdditionalMassProperties::Mass(ROPE_SEGMENT_MASS),
+                    Velocity::default(),
+                    ExternalImpulse::default(),
+                    ImpulseJoint::new(parent, joint),
+                    Sprite::from_color(
+                        Color::srgb(0.55, 0.4, 0.25),
+                        Vec2::new(ROPE_SEGMENT_WIDTH, ROPE_SEGMENT_LENGTH),
+                    ),
+                    Transform::from_translation(position.extend(0.5)),
+                ))
+                .id();
+            parent_anchor = -Vec2::Y * half_length;
+        }
+
+        if let Some(size) = rope.platform {
+            let position =
+                rope.anchor - Vec2::Y * (segments as f32 * ROPE_SEGMENT_LENGTH + size.y / 2.0);
+            let joint = RevoluteJointBuilder::new()
+                .local_anchor1(parent_anchor)
+                .local_anchor2(Vec2::Y * size.y / 2.0);
+            commands.spawn((
+                Name::new(format!("Rope {} platform", index)),
+                LevelEntity,
+                RigidBody::Dynamic,
+                Collider::cuboid(size.x / 2.0, size.y / 2.0),
+                LockedAxes::ROTATION_LOCKED,
+                Friction::coefficient(1.0),
+                ImpulseJoint::new(parent, joint),
+                Sprite::from_color(Color::srgb(0.5, 0.35, 0.2), size),
+                Transform::from_translation(position.extend(0.0)),
+            ));
+        }
+    }
+}
+
+/// Players that can grab a rope
+type RopeGrabbers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        &'static KinematicCharacterControllerOutput,
+        &'static PlayerInput,
+    ),
+    (With<PlayerVelocity>, Without<GrabbingRope>, Without<Dying>),
+>;
+
+/// Grabs the closest rope segment while the player is airborne and holding Up
+pub fn grab_rope(
+    mut commands: Commands,
+    players: RopeGrabbers,
+    segments: Query<(Entity, &Transform), With<RopeSegment>>,
+) {
+    for (player, player_transform, output, input) in players.iter() {
+        if output.grounded || !input.pressed(InputAction::Up) {
+            continue;
+        }
+        let player_pos = player_transform.translation.truncate();
+        let closest = segments
+            .iter()
+            .map(|(entity, transform)| {
+                (
+                    entity,
+                    transform.translation.truncate().distance(player_pos),
+                )
+            })
+            .filter(|(_, distance)| *distance <= ROPE_GRAB_RANGE)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((segment, _)) = closest {
+            // Hanging ends the jump, so a drop off the rope isn't cut short
+            // like a rise
+            commands
+                .entity(player)
+                .insert((GrabbingRope { segment }, JumpPhase::Airborne));
+        }
+    }
+}
+
+/// Players hanging from a rope
+type RopeClimbers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        &'static mut KinematicCharacterController,
+        &'static mut PlayerVelocity,
+        &'static GrabbingRope,
+        &'static PlayerInput,
+        Option<&'static CharacterStats>,
+        Option<&'static mut JumpPhase>,
+    ),
+>;
+
+/// Keeps a grabbing player attached to their rope segment
+/// Left/Right pump the swing, Space jumps off (keeping any upward swing) and
+/// Down drops straight off
+pub fn swing_on_rope(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut players: RopeClimbers,
+    mut segments: Query<(&Transform, &Velocity, &mut ExternalImpulse), With<RopeSegment>>,
+    mut jumped: EventWriter<PlayerJumped>,
+) {
+    for (player, player_transform, mut controller, mut velocity, grabbing, input, stats, phase) in
+        players.iter_mut()
+    {
+        let Ok((segment_transform, segment_velocity, mut impulse)) =
+            segments.get_mut(grabbing.segment)
+        else {
+            commands.entity(player).remove::<GrabbingRope>();
+            continue;
+        };
+
+        let mut swing = 0.0;
+        if input.pressed(InputAction::Left) {
+            swing -= 1.0;
+        }
+        if input.pressed(InputAction::Right) {
+            swing += 1.0;
+        }
+        impulse.impulse = Vec2::X * swing * ROPE_SWING_IMPULSE * time.delta_secs();
+
+        velocity.0 = segment_velocity.linvel;
+        if input.buffered(InputAction::Jump) {
+            velocity.0.y =
+                velocity.0.y.max(0.0) + config.jump_force * stats.map_or(1.0, |stats| stats.jump);
+            jumped.write(PlayerJumped {
+                player,
+                position: player_transform.translation.truncate(),
+            });
+            if let Some(mut phase) = phase {
+                *phase = JumpPhase::Rising;
+            }
+            commands.entity(player).remove::<GrabbingRope>();
+            continue;
+        }
+        if input.buffered(InputAction::Down) {
+            commands.entity(player).remove::<GrabbingRope>();
+            continue;
+        }
+
+        let offset =
+            segment_transform.translation.truncate() - player_transform.translation.truncate();
+        controller.translation = Some(offset);
+    }
+}
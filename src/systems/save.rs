@@ -0,0 +1,53 @@
+//! Save file with the player's progress (collected items, best times)
+
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::components::{SaveData, SaveDirectory};
+use crate::constants::{SAVE_DIRECTORY, SAVE_FILE};
+
+impl Default for SaveDirectory {
+    fn default() -> Self {
+        Self(SAVE_DIRECTORY.to_string())
+    }
+}
+
+impl SaveDirectory {
+    /// Path of the file `name` in this directory
+    pub fn file(&self, name: &str) -> String {
+        format!("{}/{}", self.0, name)
+    }
+}
+
+/// Loads the save data from a RON file
+pub fn load_save_file(path: &str) -> Result<SaveData, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read save file '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse save file '{}': {}", path, e))
+}
+
+/// Writes the save data to a RON file, creating its directory if needed
+pub fn write_save_file(path: &str, save_data: &SaveData) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create save directory '{}': {}", dir.display(), e))?;
+    }
+    let contents = ron::ser::to_string_pretty(save_data, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize save data: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write save file '{}': {}", path, e))
+}
+
+/// Inserts the `SaveData` resource from the save file (empty on first run)
+pub fn load_save(mut commands: Commands, saves: Res<SaveDirectory>) {
+    let path = saves.file(SAVE_FILE);
+    let save_data = if Path::new(&path).exists() {
+        load_save_file(&path).unwrap_or_else(|e| {
+            warn!("{}, starting a new save", e);
+            SaveData::default()
+        })
+    } else {
+        SaveData::default()
+    };
+    commands.insert_resource(save_data);
+}
@@ -0,0 +1,90 @@
+//! Score keeping: score events, combo multipliers and the end-of-level tally
+
+use bevy::prelude::*;
+
+use crate::components::{
+    CollectibleKind, LevelCompleted, LevelTimer, Score, ScoreEvent, ScoreSource,
+};
+use crate::constants::*;
+
+impl ScoreEvent {
+    /// Score event for picking up a collectible at `position`
+    pub fn pickup(kind: CollectibleKind, position: Vec2) -> Self {
+        let points = match kind {
+            CollectibleKind::Coin => COIN_POINTS,
+            CollectibleKind::Gem => GEM_POINTS,
+        };
+        Self {
+            source: ScoreSource::Pickup(kind),
+            points,
+            position: Some(position),
+        }
+    }
+}
+
+impl Score {
+    /// Multiplier applied to the next chained scoring event
+    pub fn multiplier(&self) -> u32 {
+        (self.combo + 1).min(MAX_COMBO_MULTIPLIER)
+    }
+}
+
+/// Drops the combo once its window runs out
+pub fn update_score_timers(time: Res<Time>, mut score: ResMut<Score>) {
+    if score.combo > 0 {
+        score.combo_timer -= time.delta_secs();
+        if score.combo_timer <= 0.0 {
+            score.combo = 0;
+        }
+    }
+}
+
+/// Adds scoring events to the score
+/// Pickups and kills chain into combos, each link raising the multiplier
+pub fn apply_score_events(mut events: EventReader<ScoreEvent>, mut score: ResMut<Score>) {
+    for event in events.read() {
+        let points = match event.source {
+            ScoreSource::TimeBonus | ScoreSource::ObjectiveBonus => event.points,
+            ScoreSource::Pickup(_) | ScoreSource::EnemyKill => {
+                let points = event.points * score.multiplier();
+                score.combo += 1;
+                score.combo_timer = COMBO_WINDOW;
+                points
+            }
+        };
+        score.total += points;
+        score.level_points += points;
+    }
+}
+
+/// Tallies the level when it's completed: awards the time bonus for every
+/// second under par and logs the breakdown
+pub fn tally_level_score(
+    mut completed: EventReader<LevelCompleted>,
+    mut score_events: EventWriter<ScoreEvent>,
+    score: Res<Score>,
+    timer: Res<LevelTimer>,
+) {
+    if completed.read().last().is_none() {
+        return;
+    }
+
+    let seconds_under_par = (LEVEL_PAR_TIME - timer.elapsed).max(0.0) as u32;
+    let time_bonus = seconds_under_par * TIME_BONUS_PER_SECOND;
+    if time_bonus > 0 {
+        score_events.write(ScoreEvent {
+            source: ScoreSource::TimeBonus,
+            points: time_bonus,
+            position: None,
+        });
+    }
+
+    info!(
+        "Level complete in {:.1}s - points: {}, time bonus: {}, level total: {}, score: {}",
+        timer.elapsed,
+        score.level_points,
+        time_bonus,
+        score.level_points + time_bonus,
+        score.total + time_bonus
+    );
+}
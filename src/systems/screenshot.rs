@@ -0,0 +1,128 @@
+//! Screenshots: F12 saves the current frame as a timestamped PNG, optionally
+//! with the HUD hidden for a clean capture
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::components::{HiddenForScreenshot, Settings};
+use crate::constants::SCREENSHOTS_DIR;
+
+/// `YYYY-MM-DD_HH-MM-SS` in UTC for a time since the Unix epoch
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Free screenshot path for the current time, numbered when several are
+/// taken within the same second
+fn screenshot_path() -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let stem = format!("screenshot_{}", format_timestamp(secs));
+    let dir = Path::new(SCREENSHOTS_DIR);
+    let mut path = dir.join(format!("{}.png", stem));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}_{}.png", stem, n));
+    }
+    path
+}
+
+/// Top-level UI nodes, hidden for screenshots without the HUD
+type UiRoots<'w, 's> =
+    Query<'w, 's, (Entity, &'static mut Visibility), (With<Node>, Without<ChildOf>)>;
+
+/// F12 captures the primary window to `screenshots/`; with the
+/// `screenshot_hide_hud` setting, the UI roots are hidden for the captured
+/// frame and shown again by `restore_hud()`
+/// Egui windows (debug tools, settings) are not hidden
+pub fn take_screenshot(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut ui_roots: UiRoots,
+) {
+    // Shift+F12 freezes the simulation instead
+    if !keyboard.just_pressed(KeyCode::F12)
+        || keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+    {
+        return;
+    }
+    if let Err(e) = std::fs::create_dir_all(SCREENSHOTS_DIR) {
+        error!("Failed to create '{}': {}", SCREENSHOTS_DIR, e);
+        return;
+    }
+
+    if settings.screenshot_hide_hud {
+        for (entity, mut visibility) in ui_roots.iter_mut() {
+            if *visibility != Visibility::Hidden {
+                commands
+                    .entity(entity)
+                    .insert(HiddenForScreenshot(*visibility));
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+
+    let path = screenshot_path();
+    info!("Saving screenshot to '{}'", path.display());
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path))
+        .observe(restore_hud);
+}
+
+/// Shows the UI hidden for a screenshot again once it has been captured
+fn restore_hud(
+    _trigger: Trigger<ScreenshotCaptured>,
+    mut commands: Commands,
+    mut hidden: Query<(Entity, &HiddenForScreenshot, &mut Visibility)>,
+) {
+    for (entity, previous, mut visibility) in hidden.iter_mut() {
+        *visibility = previous.0;
+        commands.entity(entity).remove::<HiddenForScreenshot>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_the_unix_epoch() {
+        assert_eq!(format_timestamp(0), "1970-01-01_00-00-00");
+    }
+
+    #[test]
+    fn formats_leap_days() {
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29_00-00-00");
+        assert_eq!(format_timestamp(1_709_251_199), "2024-02-29_23-59-59");
+        // 2100 is not a leap year
+        assert_eq!(format_timestamp(4_107_456_000), "2100-02-28_00-00-00");
+        assert_eq!(format_timestamp(4_107_542_400), "2100-03-01_00-00-00");
+    }
+}
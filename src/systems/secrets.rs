@@ -0,0 +1,83 @@
+//! Secret areas: fake walls that fade out while the player is behind them
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    LevelData, LevelEntity, PlayerVelocity, SecretArea, SecretCover, TileFade, TileGrid, TileIndex,
+};
+use crate::constants::*;
+use crate::systems::tiles::tile_to_world;
+
+/// Spawns the level's secret areas and turns the tiles they cover into
+/// passable cover drawn in front of the player
+/// Runs after the tilemap is spawned
+pub fn spawn_secrets(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    grid: Option<Res<TileGrid>>,
+    tiles: Query<&Transform, With<TileIndex>>,
+) {
+    let (Some(level), Some(grid)) = (level, grid) else {
+        return;
+    };
+
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    let last_tile = UVec2::new(level.width, level.height).saturating_sub(UVec2::ONE);
+    for (index, secret) in level.secrets.iter().enumerate() {
+        let max = secret.max.min(last_tile);
+        let top_left = tile_to_world(&level, secret.min.x, secret.min.y);
+        let bottom_right = tile_to_world(&level, max.x, max.y);
+        let area = Rect::from_corners(top_left - half_tile, bottom_right + half_tile);
+
+        for entity in grid.in_rect(&level, area) {
+            let Ok(transform) = tiles.get(entity) else {
+                continue;
+            };
+            commands.entity(entity).insert((
+                SecretCover,
+                ColliderDisabled,
+                Transform::from_translation(
+                    transform.translation.truncate().extend(SECRET_COVER_Z),
+                ),
+            ));
+        }
+        commands.spawn((
+            Name::new(format!("Secret area {}", index)),
+            LevelEntity,
+            SecretArea {
+                area,
+                revealed: false,
+            },
+        ));
+    }
+}
+
+/// Fades a secret's cover out when a player enters it and back in once
+/// every player has left
+pub fn reveal_secrets(
+    mut commands: Commands,
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut secrets: Query<&mut SecretArea>,
+    covers: Query<(Entity, &Transform), With<SecretCover>>,
+) {
+    for mut secret in secrets.iter_mut() {
+        let inside = players
+            .iter()
+            .any(|transform| secret.area.contains(transform.translation.truncate()));
+        if inside == secret.revealed {
+            continue;
+        }
+        secret.revealed = inside;
+        if inside {
+            info!("Secret area found");
+        }
+
+        let target = if inside { SECRET_REVEALED_ALPHA } else { 1.0 };
+        for (entity, transform) in covers.iter() {
+            if secret.area.contains(transform.translation.truncate()) {
+                commands.entity(entity).insert(TileFade { target });
+            }
+        }
+    }
+}
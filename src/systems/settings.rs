@@ -0,0 +1,270 @@
+//! Player settings: persistence, the settings screen and applying them
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PrimaryWindow, VideoModeSelection, WindowMode};
+use bevy_egui::{egui, EguiContexts};
+use std::path::Path;
+
+use crate::components::{
+    AudioBus, InputAction, KeyBindings, SaveDirectory, Settings, WindowModeSetting,
+};
+use crate::constants::{DEFAULT_CHARACTER, SETTINGS_FILE};
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            ambient_volume: 0.8,
+            window_mode: WindowModeSetting::Windowed,
+            key_bindings: KeyBindings::default(),
+            screen_shake: true,
+            screenshot_hide_hud: false,
+            character: DEFAULT_CHARACTER.to_string(),
+            show_ghost: true,
+            speedrun_timer: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Effective volume of an audio bus, master volume included
+    pub fn bus_volume(&self, bus: AudioBus) -> Volume {
+        let volume = match bus {
+            AudioBus::Music => self.music_volume,
+            AudioBus::Sfx => self.sfx_volume,
+            AudioBus::Ambient => self.ambient_volume,
+        };
+        Volume::Linear(self.master_volume * volume)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            left: KeyCode::KeyA,
+            right: KeyCode::KeyD,
+            up: KeyCode::KeyW,
+            down: KeyCode::KeyS,
+            jump: KeyCode::Space,
+            interact: KeyCode::KeyE,
+        }
+    }
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 6] = [
+        InputAction::Left,
+        InputAction::Right,
+        InputAction::Up,
+        InputAction::Down,
+        InputAction::Jump,
+        InputAction::Interact,
+    ];
+
+    /// Fixed alternate key that always triggers the action
+    fn alternate(self) -> Option<KeyCode> {
+        match self {
+            InputAction::Left => Some(KeyCode::ArrowLeft),
+            InputAction::Right => Some(KeyCode::ArrowRight),
+            InputAction::Up => Some(KeyCode::ArrowUp),
+            InputAction::Down => Some(KeyCode::ArrowDown),
+            InputAction::Jump | InputAction::Interact => None,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: InputAction) -> KeyCode {
+        match action {
+            InputAction::Left => self.left,
+            InputAction::Right => self.right,
+            InputAction::Up => self.up,
+            InputAction::Down => self.down,
+            InputAction::Jump => self.jump,
+            InputAction::Interact => self.interact,
+        }
+    }
+
+    fn key_mut(&mut self, action: InputAction) -> &mut KeyCode {
+        match action {
+            InputAction::Left => &mut self.left,
+            InputAction::Right => &mut self.right,
+            InputAction::Up => &mut self.up,
+            InputAction::Down => &mut self.down,
+            InputAction::Jump => &mut self.jump,
+            InputAction::Interact => &mut self.interact,
+        }
+    }
+
+    pub fn pressed(&self, keyboard: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        keyboard.pressed(self.key(action))
+            || action.alternate().is_some_and(|key| keyboard.pressed(key))
+    }
+
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        keyboard.just_pressed(self.key(action))
+            || action
+                .alternate()
+                .is_some_and(|key| keyboard.just_pressed(key))
+    }
+}
+
+/// Loads the settings from a RON file
+pub fn load_settings_file(path: &str) -> Result<Settings, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read settings '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse settings '{}': {}", path, e))
+}
+
+/// Writes the settings to a RON file, creating its directory if needed
+pub fn save_settings_file(path: &str, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            format!(
+                "Failed to create settings directory '{}': {}",
+                dir.display(),
+                e
+            )
+        })?;
+    }
+    let contents = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write settings '{}': {}", path, e))
+}
+
+/// Inserts the `Settings` resource from the settings file (defaults on first run)
+pub fn load_settings(mut commands: Commands, saves: Res<SaveDirectory>) {
+    let path = saves.file(SETTINGS_FILE);
+    let settings = if Path::new(&path).exists() {
+        load_settings_file(&path).unwrap_or_else(|e| {
+            warn!("{}, using default settings", e);
+            Settings::default()
+        })
+    } else {
+        Settings::default()
+    };
+    commands.insert_resource(settings);
+}
+
+/// Applies the window mode whenever the settings change
+/// Volumes are applied by `apply_audio_buses()`
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Ok(mut window) = windows.single_mut() {
+        window.mode = match settings.window_mode {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::BorderlessFullscreen => {
+                WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+            }
+            WindowModeSetting::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+            }
+        };
+    }
+}
+
+/// Settings window, available from the menu and pause screens
+/// Changes apply immediately; "Save" writes them to the settings file. To
+/// rebind a key, click it and press the new key.
+pub fn settings_ui(
+    mut contexts: EguiContexts,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    saves: Res<SaveDirectory>,
+    mut rebinding: Local<Option<InputAction>>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    // Edit a copy so change detection only fires on real edits
+    let mut edited = settings.clone();
+    if let Some(action) = *rebinding {
+        if let Some(&key) = keyboard.get_just_pressed().next() {
+            if key != KeyCode::Escape {
+                *edited.key_bindings.key_mut(action) = key;
+            }
+            *rebinding = None;
+        }
+    }
+
+    egui::Window::new("Settings")
+        .default_open(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -20.0])
+        .show(ctx, |ui| {
+            ui.heading("Audio");
+            ui.add(egui::Slider::new(&mut edited.master_volume, 0.0..=1.0).text("Master"));
+            ui.add(egui::Slider::new(&mut edited.music_volume, 0.0..=1.0).text("Music"));
+            ui.add(egui::Slider::new(&mut edited.sfx_volume, 0.0..=1.0).text("SFX"));
+            ui.add(egui::Slider::new(&mut edited.ambient_volume, 0.0..=1.0).text("Ambient"));
+
+            ui.heading("Video");
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut edited.window_mode,
+                    WindowModeSetting::Windowed,
+                    "Windowed",
+                );
+                ui.radio_value(
+                    &mut edited.window_mode,
+                    WindowModeSetting::BorderlessFullscreen,
+                    "Borderless",
+                );
+                ui.radio_value(
+                    &mut edited.window_mode,
+                    WindowModeSetting::Fullscreen,
+                    "Fullscreen",
+                );
+            });
+            ui.checkbox(&mut edited.screen_shake, "Screen shake");
+            ui.checkbox(
+                &mut edited.screenshot_hide_hud,
+                "Hide HUD in screenshots (F12)",
+            );
+            ui.checkbox(&mut edited.show_ghost, "Best-run ghost");
+            ui.checkbox(&mut edited.speedrun_timer, "Speedrun timer and splits");
+
+            ui.heading("Controls");
+            for action in InputAction::ALL {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:?}", action));
+                    let label = if *rebinding == Some(action) {
+                        "Press a key...".to_string()
+                    } else {
+                        format!("{:?}", edited.key_bindings.key(action))
+                    };
+                    if ui.button(label).clicked() {
+                        *rebinding = Some(action);
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    let path = saves.file(SETTINGS_FILE);
+                    match save_settings_file(&path, &edited) {
+                        Ok(()) => info!("Saved settings to '{}'", path),
+                        Err(e) => error!("{}", e),
+                    }
+                }
+                if ui.button("Reset to defaults").clicked() {
+                    edited = Settings::default();
+                }
+            });
+        });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+}
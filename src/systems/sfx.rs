@@ -0,0 +1,275 @@
+//! Sound effects: a registry of named sounds played in response to gameplay
+//! events, spatially when they have a position
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::path::Path;
+
+use crate::components::{
+    AnimationFrameEvent, AnimationState, Attenuation, AudioBus, AudioEmitter, AudioGain,
+    DamageDealt, GroundMaterial, InWater, LevelData, LevelEntity, PlayerJumped, PlayerLanded,
+    Settings, SoundEvent, SoundRegistry, SoundRegistryConfig, Surface,
+};
+use crate::constants::*;
+
+impl SoundRegistry {
+    /// Random sample of a sound, if any of its files is shipped
+    pub(crate) fn sample(&self, name: &str, rng: &mut impl Rng) -> Option<Handle<AudioSource>> {
+        self.handles.get(name)?.choose(rng).cloned()
+    }
+
+    /// Linear gain of a sound: its category's volume times its own
+    pub(crate) fn volume(&self, name: &str) -> Option<f32> {
+        let sound = self.config.sounds.get(name)?;
+        let category = self
+            .config
+            .categories
+            .get(&sound.category)
+            .copied()
+            .unwrap_or(1.0);
+        Some(category * sound.volume)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self {
+            min_distance: EMITTER_MIN_DISTANCE,
+            max_distance: EMITTER_MAX_DISTANCE,
+        }
+    }
+}
+
+impl Attenuation {
+    /// Volume factor at `distance` from the listener, fading linearly between
+    /// the two distances
+    pub fn factor(&self, distance: f32) -> f32 {
+        let range = self.max_distance - self.min_distance;
+        if range <= 0.0 {
+            return if distance <= self.max_distance {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        (1.0 - (distance - self.min_distance) / range).clamp(0.0, 1.0)
+    }
+}
+
+impl Surface {
+    /// Name of the footstep sound in the sound registry
+    fn footstep_sound(self) -> &'static str {
+        match self {
+            Surface::Grass => "footstep_grass",
+            Surface::Stone => "footstep_stone",
+            Surface::Wood => "footstep_wood",
+            Surface::Metal => "footstep_metal",
+        }
+    }
+}
+
+/// Loads the sound registry from a RON file
+pub fn load_sound_registry_file(path: &str) -> Result<SoundRegistryConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read sound registry '{}': {}", path, e))?;
+    ron::from_str(&contents)
+        .map_err(|e| format!("Failed to parse sound registry '{}': {}", path, e))
+}
+
+/// Inserts the `SoundRegistry` resource, loading the sounds whose file is
+/// shipped and skipping the rest
+pub fn load_sound_registry(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let config = load_sound_registry_file(SOUND_REGISTRY_PATH).unwrap_or_else(|e| {
+        warn!("{}, playing no sound effects", e);
+        SoundRegistryConfig::default()
+    });
+
+    let mut handles = std::collections::HashMap::new();
+    for (name, sound) in &config.sounds {
+        let mut samples = Vec::new();
+        for file in std::iter::once(&sound.file).chain(&sound.variants) {
+            if Path::new("assets").join(file).exists() {
+                samples.push(asset_server.load(file.clone()));
+            } else {
+                warn!("Sound '{}' not found for '{}'", file, name);
+            }
+        }
+        if !samples.is_empty() {
+            handles.insert(name.clone(), samples);
+        }
+    }
+    commands.insert_resource(SoundRegistry { config, handles });
+}
+
+/// Plays a one-shot for every `SoundEvent` (a random sample of the sound) on
+/// the SFX bus, scaled by the sound's category and its own volume
+/// Positioned sounds are spatial, heard relative to the camera's listener
+pub fn play_sound_events(
+    mut commands: Commands,
+    mut events: EventReader<SoundEvent>,
+    registry: Option<Res<SoundRegistry>>,
+    settings: Res<Settings>,
+) {
+    let Some(registry) = registry else {
+        events.clear();
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        let (Some(handle), Some(gain)) = (
+            registry.sample(event.name, &mut rng),
+            registry.volume(event.name),
+        ) else {
+            continue;
+        };
+        let volume = settings.bus_volume(AudioBus::Sfx).to_linear() * gain;
+
+        commands.spawn((
+            Name::new(format!("Sound {}", event.name)),
+            AudioPlayer::new(handle),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(volume))
+                .with_spatial(event.position.is_some()),
+            AudioBus::Sfx,
+            AudioGain(gain),
+            Transform::from_translation(event.position.unwrap_or_default().extend(0.0)),
+        ));
+    }
+}
+
+/// Plays the hurt sound where damage is taken
+pub fn play_damage_sounds(
+    mut damage_events: EventReader<DamageDealt>,
+    targets: Query<&GlobalTransform>,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for event in damage_events.read() {
+        sound_events.write(SoundEvent {
+            name: "hurt",
+            position: targets
+                .get(event.target)
+                .ok()
+                .map(|transform| transform.translation().truncate()),
+        });
+    }
+}
+
+/// Plays the jump and land sounds where the player jumps and lands
+pub fn play_movement_sounds(
+    mut jumped: EventReader<PlayerJumped>,
+    mut landed: EventReader<PlayerLanded>,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for event in jumped.read() {
+        sound_events.write(SoundEvent {
+            name: "jump",
+            position: Some(event.position),
+        });
+    }
+    for event in landed.read() {
+        sound_events.write(SoundEvent {
+            name: "land",
+            position: Some(event.position),
+        });
+    }
+}
+
+/// Plays a footstep on the run animation's contact frames, with the sound of
+/// the surface underfoot
+/// Nothing plays in the air or in water
+pub fn play_footsteps(
+    mut frame_events: EventReader<AnimationFrameEvent>,
+    characters: Query<(
+        &GroundMaterial,
+        &KinematicCharacterControllerOutput,
+        &Transform,
+        Has<InWater>,
+    )>,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for event in frame_events.read() {
+        if event.state != AnimationState::Run || !FOOTSTEP_FRAMES.contains(&event.frame) {
+            continue;
+        }
+        let Ok((ground, output, transform, in_water)) = characters.get(event.entity) else {
+            continue;
+        };
+        if !output.grounded || in_water {
+            continue;
+        }
+        sound_events.write(SoundEvent {
+            name: ground.0.surface.footstep_sound(),
+            position: Some(transform.translation.truncate()),
+        });
+    }
+}
+
+/// Spawns the looping sound sources defined in the level data
+pub fn spawn_sound_emitters(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for emitter in &level.sound_emitters {
+        commands.spawn((
+            Name::new(format!("Sound emitter {}", emitter.sound)),
+            LevelEntity,
+            Transform::from_translation(emitter.position.extend(0.0)),
+            Visibility::default(),
+            AudioEmitter {
+                sound: emitter.sound.clone(),
+                attenuation: emitter.attenuation,
+            },
+        ));
+    }
+}
+
+/// Starts the looping spatial sound of new emitters on the ambient bus,
+/// silent until `update_audio_emitters()` sets their gain
+/// Emitters whose sound isn't shipped stay silent
+pub fn start_audio_emitters(
+    mut commands: Commands,
+    registry: Option<Res<SoundRegistry>>,
+    emitters: Query<(Entity, &AudioEmitter), Added<AudioEmitter>>,
+) {
+    let Some(registry) = registry else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    for (entity, emitter) in emitters.iter() {
+        if let Some(handle) = registry.sample(&emitter.sound, &mut rng) {
+            commands.entity(entity).insert((
+                AudioPlayer::new(handle),
+                PlaybackSettings::LOOP
+                    .with_volume(Volume::Linear(0.0))
+                    .with_spatial(true),
+                AudioBus::Ambient,
+                AudioGain(0.0),
+            ));
+        }
+    }
+}
+
+/// Fades every emitter with its distance to the listener (the camera), on top
+/// of the panning done by spatial audio
+pub fn update_audio_emitters(
+    registry: Option<Res<SoundRegistry>>,
+    listeners: Query<&GlobalTransform, With<SpatialListener>>,
+    mut emitters: Query<(&AudioEmitter, &GlobalTransform, &mut AudioGain)>,
+) {
+    let (Some(registry), Ok(listener)) = (registry, listeners.single()) else {
+        return;
+    };
+    let listener_pos = listener.translation().truncate();
+
+    for (emitter, transform, mut gain) in emitters.iter_mut() {
+        let volume = registry.volume(&emitter.sound).unwrap_or(0.0);
+        let distance = transform.translation().truncate().distance(listener_pos);
+        gain.0 = volume * emitter.attenuation.factor(distance);
+    }
+}
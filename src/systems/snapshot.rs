@@ -0,0 +1,132 @@
+//! Debug snapshots of the world saved as a `DynamicScene`
+//!
+//! A snapshot holds the player, the level's entities and the progress
+//! resources, serialized through the reflection registry. Entities are
+//! matched by id when it's restored, so a snapshot only applies to the same
+//! session and level it was taken in: entities that no longer exist are
+//! skipped and the level itself isn't rebuilt
+
+use std::path::Path;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::scene::serde::SceneDeserializer;
+use serde::de::DeserializeSeed;
+
+use crate::components::{
+    ActiveCheckpoint, AnimationState, BossArena, Checkpoint, Chest, Collectible, Door,
+    FacingDirection, FallingHazard, Gate, Health, KeyRing, LevelEntity, LevelTimer, Lives,
+    MovingPlatform, PlayerStats, PlayerVelocity, SaveDirectory, Score, SecretArea, SnapshotRequest,
+    Switch,
+};
+use crate::constants::SNAPSHOT_FILE;
+
+/// Builds a scene with the gameplay state of the player and level entities
+fn extract_snapshot(world: &mut World) -> DynamicScene {
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<LevelEntity>, With<PlayerVelocity>)>>()
+        .iter(world)
+        .collect();
+
+    DynamicSceneBuilder::from_world(world)
+        .deny_all()
+        .allow_component::<Transform>()
+        .allow_component::<PlayerVelocity>()
+        .allow_component::<Health>()
+        .allow_component::<AnimationState>()
+        .allow_component::<FacingDirection>()
+        .allow_component::<KeyRing>()
+        .allow_component::<Door>()
+        .allow_component::<Chest>()
+        .allow_component::<Switch>()
+        .allow_component::<Gate>()
+        .allow_component::<MovingPlatform>()
+        .allow_component::<FallingHazard>()
+        .allow_component::<SecretArea>()
+        .allow_component::<Checkpoint>()
+        .allow_component::<BossArena>()
+        .allow_component::<Collectible>()
+        .deny_all_resources()
+        .allow_resource::<Score>()
+        .allow_resource::<Lives>()
+        .allow_resource::<LevelTimer>()
+        .allow_resource::<ActiveCheckpoint>()
+        .allow_resource::<PlayerStats>()
+        .extract_entities(entities.into_iter())
+        .extract_resources()
+        .build()
+}
+
+/// Writes a snapshot of the world to a scene file, creating its directory if
+/// needed
+pub fn save_snapshot_file(world: &mut World, path: &str) -> Result<(), String> {
+    let scene = extract_snapshot(world);
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let contents = scene
+        .serialize(&registry)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            format!(
+                "Failed to create snapshot directory '{}': {}",
+                dir.display(),
+                e
+            )
+        })?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Failed to write snapshot '{}': {}", path, e))
+}
+
+/// Restores a snapshot onto the entities it was taken from
+/// Returns how many entities were restored
+pub fn load_snapshot_file(world: &mut World, path: &str) -> Result<usize, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read snapshot '{}': {}", path, e))?;
+    let mut scene = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let mut deserializer = ron::de::Deserializer::from_str(&contents)
+            .map_err(|e| format!("Failed to parse snapshot '{}': {}", path, e))?;
+        SceneDeserializer {
+            type_registry: &registry,
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("Failed to parse snapshot '{}': {}", path, e))?
+    };
+
+    // Map every entity onto itself; ones despawned since are left out rather
+    // than spawned again without their sprites and colliders
+    scene
+        .entities
+        .retain(|entity| world.entities().contains(entity.entity));
+    let mut entity_map: EntityHashMap<Entity> = scene
+        .entities
+        .iter()
+        .map(|entity| (entity.entity, entity.entity))
+        .collect();
+    scene
+        .write_to_world(world, &mut entity_map)
+        .map_err(|e| format!("Failed to restore snapshot '{}': {}", path, e))?;
+    Ok(scene.entities.len())
+}
+
+/// Handles the console's `snapshot save` / `snapshot load` requests
+pub fn handle_snapshot_requests(world: &mut World) {
+    let requests: Vec<SnapshotRequest> = world
+        .resource_mut::<Events<SnapshotRequest>>()
+        .drain()
+        .collect();
+    let path = world.resource::<SaveDirectory>().file(SNAPSHOT_FILE);
+    for request in requests {
+        match request {
+            SnapshotRequest::Save => match save_snapshot_file(world, &path) {
+                Ok(()) => info!("Saved snapshot to '{}'", path),
+                Err(e) => error!("{}", e),
+            },
+            SnapshotRequest::Load => match load_snapshot_file(world, &path) {
+                Ok(count) => info!("Restored {} entities from '{}'", count, path),
+                Err(e) => error!("{}", e),
+            },
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Optional speedrun overlay: a real-time clock, splits at each checkpoint
+//! compared against the personal best, and a summary on the results screen
+//!
+//! The personal-best splits of each level live in `saves/splits.ron`, next
+//! to the save file
+
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::components::{
+    ActiveCheckpoint, CurrentLevel, GameState, LevelCompleted, LevelData, LevelTimer,
+    SaveDirectory, Settings, SpeedrunOverlay, SpeedrunRecords, SpeedrunSplitsText, SpeedrunTimer,
+    SpeedrunTimerText, Split, SplitRun,
+};
+use crate::constants::*;
+use crate::systems::time_attack::format_time;
+
+const AHEAD_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+const BEHIND_COLOR: Color = Color::srgb(0.95, 0.35, 0.3);
+/// Name of the split taken at the goal
+const GOAL_SPLIT: &str = "Goal";
+
+impl SplitRun {
+    /// Time of the goal split, once the run is finished
+    pub fn total(&self) -> Option<f32> {
+        self.splits
+            .last()
+            .filter(|split| split.name == GOAL_SPLIT)
+            .map(|split| split.time)
+    }
+
+    fn time_of(&self, name: &str) -> Option<f32> {
+        self.splits
+            .iter()
+            .find(|split| split.name == name)
+            .map(|split| split.time)
+    }
+}
+
+/// Formats a difference to the personal best as signed seconds.tenths
+pub fn format_delta(seconds: f32) -> String {
+    if seconds < 0.0 {
+        format!("-{:.1}", -seconds)
+    } else {
+        format!("+{:.1}", seconds)
+    }
+}
+
+/// Loads the speedrun records from a RON file
+pub fn load_speedrun_records_file(path: &str) -> Result<SpeedrunRecords, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read splits '{}': {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse splits '{}': {}", path, e))
+}
+
+/// Writes the speedrun records to a RON file, creating its directory if needed
+pub fn write_speedrun_records_file(path: &str, records: &SpeedrunRecords) -> Result<(), String> {
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create save directory '{}': {}", dir.display(), e))?;
+    }
+    let contents = ron::ser::to_string_pretty(records, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize splits: {}", e))?;
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write splits '{}': {}", path, e))
+}
+
+/// Inserts the `SpeedrunRecords` resource from its file (empty on first run)
+pub fn load_speedrun_records(mut commands: Commands, saves: Res<SaveDirectory>) {
+    let path = saves.file(SPLITS_FILE);
+    let records = if Path::new(&path).exists() {
+        load_speedrun_records_file(&path).unwrap_or_else(|e| {
+            warn!("{}, starting without personal bests", e);
+            SpeedrunRecords::default()
+        })
+    } else {
+        SpeedrunRecords::default()
+    };
+    commands.insert_resource(records);
+}
+
+/// Spawns the speedrun overlay at the top center of the screen, hidden until
+/// the setting turns it on
+pub fn setup_speedrun_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Speedrun overlay"),
+            SpeedrunOverlay,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HUD_MARGIN),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format_time(0.0)),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 1.5,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                SpeedrunTimerText,
+            ));
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: HUD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                TextLayout::new_with_justify(JustifyText::Center),
+                SpeedrunSplitsText,
+            ));
+        });
+}
+
+/// Advances the real-time clock of the attempt; a new level or a reset level
+/// timer starts a new attempt against the level's personal best
+pub fn update_speedrun_timer(
+    real_time: Res<Time<Real>>,
+    level_timer: Res<LevelTimer>,
+    current_level: Option<Res<CurrentLevel>>,
+    records: Res<SpeedrunRecords>,
+    mut speedrun: ResMut<SpeedrunTimer>,
+) {
+    let Some(current_level) = current_level else {
+        return;
+    };
+    if speedrun.level != current_level.path || level_timer.elapsed < speedrun.level_time {
+        *speedrun = SpeedrunTimer {
+            level: current_level.path.clone(),
+            personal_best: records.0.get(&current_level.path).cloned(),
+            ..default()
+        };
+    }
+    speedrun.level_time = level_timer.elapsed;
+    if !speedrun.finished {
+        speedrun.elapsed += real_time.delta_secs();
+    }
+}
+
+/// Takes a split when a checkpoint becomes the active one for the first time
+/// in the attempt
+pub fn record_speedrun_splits(
+    active: Res<ActiveCheckpoint>,
+    level: Option<Res<LevelData>>,
+    mut speedrun: ResMut<SpeedrunTimer>,
+) {
+    if !active.is_changed() || speedrun.finished {
+        return;
+    }
+    let Some(level) = level else {
+        return;
+    };
+    // The level start and respawn resets aren't checkpoints
+    let Some(index) = level
+        .checkpoints
+        .iter()
+        .position(|checkpoint| checkpoint.position == active.position)
+    else {
+        return;
+    };
+
+    let name = format!("Checkpoint {}", index + 1);
+    if speedrun.run.time_of(&name).is_none() {
+        let time = speedrun.elapsed;
+        speedrun.run.splits.push(Split { name, time });
+    }
+}
+
+/// Takes the goal split when the level is completed, saving the run as the
+/// new personal best when it is faster
+pub fn finish_speedrun(
+    mut completed: EventReader<LevelCompleted>,
+    mut speedrun: ResMut<SpeedrunTimer>,
+    mut records: ResMut<SpeedrunRecords>,
+    saves: Res<SaveDirectory>,
+) {
+    if completed.read().last().is_none() || speedrun.finished {
+        return;
+    }
+    let time = speedrun.elapsed;
+    speedrun.finished = true;
+    speedrun.run.splits.push(Split {
+        name: GOAL_SPLIT.to_string(),
+        time,
+    });
+
+    let best = speedrun
+        .personal_best
+        .as_ref()
+        .and_then(|personal_best| personal_best.total());
+    if best.is_none_or(|best| time < best) {
+        records
+            .0
+            .insert(speedrun.level.clone(), speedrun.run.clone());
+        if let Err(e) = write_speedrun_records_file(&saves.file(SPLITS_FILE), &records) {
+            error!("{}", e);
+        }
+        info!("New speedrun personal best: {}", format_time(time));
+    }
+}
+
+/// One line per split of the run: its time and the difference to the
+/// personal best's split of the same name
+fn split_lines(run: &SplitRun, personal_best: Option<&SplitRun>) -> String {
+    run.splits
+        .iter()
+        .map(|split| {
+            let delta = personal_best
+                .and_then(|personal_best| personal_best.time_of(&split.name))
+                .map(|best| format!("  {}", format_delta(split.time - best)))
+                .unwrap_or_default();
+            format!("{}  {}{}", split.name, format_time(split.time), delta)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Shows or hides the overlay with the setting, and refreshes its clock and
+/// splits; the clock turns red while the attempt is behind the personal
+/// best's next split, and green or red against the personal best at the end
+pub fn update_speedrun_overlay(
+    settings: Res<Settings>,
+    speedrun: Res<SpeedrunTimer>,
+    mut overlays: Query<&mut Visibility, With<SpeedrunOverlay>>,
+    mut timer_texts: Query<(&mut Text, &mut TextColor), With<SpeedrunTimerText>>,
+    mut splits_texts: Query<&mut Text, (With<SpeedrunSplitsText>, Without<SpeedrunTimerText>)>,
+) {
+    for mut visibility in overlays.iter_mut() {
+        visibility.set_if_neq(if settings.speedrun_timer {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    if !settings.speedrun_timer {
+        return;
+    }
+
+    let personal_best = speedrun.personal_best.as_ref();
+    let next_best = personal_best.and_then(|personal_best| {
+        personal_best
+            .splits
+            .iter()
+            .find(|split| speedrun.run.time_of(&split.name).is_none())
+    });
+    let color = if speedrun.finished {
+        match (
+            speedrun.run.total(),
+            personal_best.and_then(SplitRun::total),
+        ) {
+            (Some(total), Some(best)) if total >= best => BEHIND_COLOR,
+            _ => AHEAD_COLOR,
+        }
+    } else if next_best.is_some_and(|next| speedrun.elapsed > next.time) {
+        BEHIND_COLOR
+    } else {
+        Color::WHITE
+    };
+    for (mut text, mut text_color) in timer_texts.iter_mut() {
+        text.0 = format_time(speedrun.elapsed);
+        text_color.set_if_neq(TextColor(color));
+    }
+
+    let splits = split_lines(&speedrun.run, personal_best);
+    for mut text in splits_texts.iter_mut() {
+        if text.0 != splits {
+            text.0 = splits.clone();
+        }
+    }
+}
+
+/// Adds the run's splits and their comparison with the previous personal
+/// best to the results screen
+pub fn spawn_speedrun_summary(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    speedrun: Res<SpeedrunTimer>,
+) {
+    if !settings.speedrun_timer || !speedrun.finished {
+        return;
+    }
+    let personal_best = speedrun.personal_best.as_ref();
+    let total = speedrun.run.total().unwrap_or(speedrun.elapsed);
+    let verdict = match personal_best.and_then(SplitRun::total) {
+        Some(best) if total < best => format!(
+            "New personal best! ({}, was {})",
+            format_delta(total - best),
+            format_time(best)
+        ),
+        Some(best) => format!("Personal best: {}", format_time(best)),
+        None => "First recorded run".to_string(),
+    };
+    let summary = format!(
+        "Speedrun\n{}\n\n{}",
+        split_lines(&speedrun.run, personal_best),
+        verdict
+    );
+
+    commands.spawn((
+        Name::new("Speedrun summary"),
+        StateScoped(GameState::Results),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(20.0),
+            left: Val::Px(HUD_MARGIN * 2.0),
+            ..default()
+        },
+        GlobalZIndex(1),
+        Text::new(summary),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
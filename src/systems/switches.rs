@@ -0,0 +1,180 @@
+//! Switches toggling linked elevators and gates
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    DrawLayer, Elevator, Gate, LevelData, LevelEntity, MovingPlatform, PlatformPathMode,
+    PlayerVelocity, Switch, TileFade, TileGrid, TileIndex,
+};
+use crate::constants::*;
+use crate::systems::tiles::tile_to_world;
+
+const SWITCH_OFF_COLOR: Color = Color::srgb(0.8, 0.2, 0.2);
+const SWITCH_ON_COLOR: Color = Color::srgb(0.2, 0.8, 0.3);
+
+/// Spawns switches, elevators and gates from the level data
+pub fn spawn_switches(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    for (index, switch) in level.switches.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Switch {}", index)),
+            LevelEntity,
+            Sprite::from_color(SWITCH_OFF_COLOR, Vec2::splat(SWITCH_SIZE)),
+            Transform::from_translation(switch.position.extend(0.0)),
+            DrawLayer::standing(Vec2::splat(SWITCH_SIZE)),
+            Switch {
+                targets: switch.targets.clone(),
+                on: false,
+                touching: false,
+            },
+        ));
+    }
+
+    for elevator in &level.elevators {
+        commands.spawn((
+            Name::new(format!("Elevator '{}'", elevator.id)),
+            LevelEntity,
+            RigidBody::KinematicPositionBased,
+            Collider::cuboid(elevator.size.x / 2.0, elevator.size.y / 2.0),
+            Sprite::from_color(Color::srgb(0.5, 0.5, 0.6), elevator.size),
+            Transform::from_translation(elevator.from.extend(0.0)),
+            MovingPlatform {
+                waypoints: vec![elevator.from, elevator.to],
+                speed: elevator.speed,
+                mode: PlatformPathMode::Manual,
+                target: 0,
+                forward: true,
+                delta: Vec2::ZERO,
+            },
+            Elevator {
+                id: elevator.id.clone(),
+            },
+        ));
+    }
+
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    let last_tile = UVec2::new(level.width, level.height).saturating_sub(UVec2::ONE);
+    for gate in &level.gates {
+        let max = gate.max.min(last_tile);
+        let top_left = tile_to_world(&level, gate.min.x, gate.min.y);
+        let bottom_right = tile_to_world(&level, max.x, max.y);
+        commands.spawn((
+            Name::new(format!("Gate '{}'", gate.id)),
+            LevelEntity,
+            Gate {
+                id: gate.id.clone(),
+                area: Rect::from_corners(top_left - half_tile, bottom_right + half_tile),
+                tiles: gate.tiles.clone(),
+                open: gate.open,
+            },
+        ));
+    }
+}
+
+/// Toggles a switch when the player starts touching it and forwards the new
+/// state to every linked elevator and gate
+pub fn update_switches(
+    players: Query<&Transform, With<PlayerVelocity>>,
+    mut switches: Query<(&mut Switch, &mut Sprite, &Transform), Without<PlayerVelocity>>,
+    mut elevators: Query<(&Elevator, &mut MovingPlatform)>,
+    mut gates: Query<&mut Gate>,
+) {
+    for (mut switch, mut sprite, transform) in switches.iter_mut() {
+        let area =
+            Rect::from_center_size(transform.translation.truncate(), Vec2::splat(SWITCH_SIZE));
+        let touching = players
+            .iter()
+            .any(|player| area.contains(player.translation.truncate()));
+        let just_touched = touching && !switch.touching;
+        switch.touching = touching;
+        if !just_touched {
+            continue;
+        }
+
+        switch.on = !switch.on;
+        sprite.color = if switch.on {
+            SWITCH_ON_COLOR
+        } else {
+            SWITCH_OFF_COLOR
+        };
+        info!("Switch toggled {}", if switch.on { "ON" } else { "OFF" });
+
+        for target in &switch.targets {
+            for (elevator, mut platform) in elevators.iter_mut() {
+                if &elevator.id == target {
+                    platform.target = if switch.on { 1 } else { 0 };
+                }
+            }
+            for mut gate in gates.iter_mut() {
+                if &gate.id == target {
+                    gate.open = !gate.open;
+                }
+            }
+        }
+    }
+}
+
+/// Disables the colliders of open gates' tiles and ghosts them, restoring
+/// them when the gate closes again
+pub fn apply_gate_states(
+    mut commands: Commands,
+    gates: Query<&Gate, Changed<Gate>>,
+    level: Option<Res<LevelData>>,
+    grid: Option<Res<TileGrid>>,
+    tiles: Query<&TileIndex>,
+) {
+    let (Some(level), Some(grid)) = (level, grid) else {
+        return;
+    };
+    for gate in gates.iter() {
+        for entity in grid.in_rect(&level, gate.area) {
+            let Ok(tile_index) = tiles.get(entity) else {
+                continue;
+            };
+            if !gate.tiles.is_empty() && !gate.tiles.contains(&tile_index.index) {
+                continue;
+            }
+            if gate.open {
+                commands.entity(entity).insert((
+                    ColliderDisabled,
+                    TileFade {
+                        target: GATE_OPEN_ALPHA,
+                    },
+                ));
+            } else {
+                commands
+                    .entity(entity)
+                    .remove::<ColliderDisabled>()
+                    .insert(TileFade { target: 1.0 });
+            }
+        }
+    }
+}
+
+/// Eases gate and secret cover tiles towards their target alpha so toggles
+/// and reveals are visible
+pub fn fade_gate_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tiles: Query<(Entity, &TileFade, &mut Sprite, &InheritedVisibility)>,
+) {
+    let t = (GATE_FADE_SPEED * time.delta_secs()).min(1.0);
+    for (entity, fade, mut sprite, visibility) in tiles.iter_mut() {
+        // Tiles in culled chunks resume fading when shown
+        if !visibility.get() {
+            continue;
+        }
+        let alpha = sprite.color.alpha();
+        let alpha = alpha + (fade.target - alpha) * t;
+        if (fade.target - alpha).abs() < 0.01 {
+            sprite.color.set_alpha(fade.target);
+            commands.entity(entity).remove::<TileFade>();
+        } else {
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
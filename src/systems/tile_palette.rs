@@ -0,0 +1,539 @@
+//! Tile palette: the editor's view of the tileset, where any tile can be
+//! picked and painted into the level with the mouse, one at a time or as
+//! rectangles and lines
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::collections::HashMap;
+
+use crate::components::{
+    LevelData, LightingEditor, MainCamera, PrefabPalette, TileChunk, TileCollisionMap, TileDrag,
+    TileGrid, TileId, TilePalette, TilePlaced, TileTool, TilesetInfo, TilesetRegistry,
+};
+use crate::constants::*;
+use crate::systems::level_loader::{tile_group_name, EMPTY_TILE};
+use crate::systems::tiles::{
+    collision_type, spawn_tile, spawn_tile_chunk, tile_to_world, world_to_tile,
+};
+
+/// UV rectangle of tile `index` within the tileset texture
+fn tile_uv(index: TileId) -> egui::Rect {
+    let size = egui::vec2(1.0 / TILES_PER_ROW as f32, 1.0 / TILES_PER_COLUMN as f32);
+    let min = egui::pos2(
+        (index % TILES_PER_ROW) as f32 * size.x,
+        (index / TILES_PER_ROW) as f32 * size.y,
+    );
+    egui::Rect::from_min_size(min, size)
+}
+
+/// Editor window showing the whole tileset as a grid of buttons, plus the
+/// selected tile with its group and collision
+pub fn tile_palette_ui(
+    mut contexts: EguiContexts,
+    registry: Option<Res<TilesetRegistry>>,
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut palette: ResMut<TilePalette>,
+) {
+    let Some(tileset) = registry
+        .as_ref()
+        .and_then(|registry| registry.tilesets.get(registry.current_tileset))
+    else {
+        return;
+    };
+    let texture = contexts.add_image(tileset.texture_handle.clone());
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let tile_image = |index: TileId, size: f32| {
+        egui::Image::new(egui::load::SizedTexture::new(texture, [size, size])).uv(tile_uv(index))
+    };
+    egui::Window::new("Tiles")
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .show(ctx, |ui| {
+            match palette.selected {
+                Some(index) => {
+                    ui.horizontal(|ui| {
+                        ui.add(tile_image(index, TILE_PALETTE_PREVIEW_SIZE));
+                        ui.vertical(|ui| {
+                            ui.monospace(format!("Index: {}", index));
+                            ui.monospace(format!(
+                                "Group: {}",
+                                tile_group_name(index).unwrap_or("Unknown")
+                            ));
+                            if let Some(collision_map) = &collision_map {
+                                ui.monospace(format!(
+                                    "Collision: {}",
+                                    collision_type(collision_map, index)
+                                ));
+                            }
+                        });
+                    });
+                }
+                None => {
+                    ui.label("No tile selected");
+                }
+            }
+            ui.horizontal(|ui| {
+                for tool in TileTool::ALL {
+                    if ui
+                        .selectable_label(palette.tool == tool, tool.name())
+                        .clicked()
+                    {
+                        palette.tool = tool;
+                        palette.drag = None;
+                    }
+                }
+            });
+            if palette.tool == TileTool::Fill {
+                ui.add(
+                    egui::Slider::new(&mut palette.max_fill_area, 1..=TILE_FILL_MAX_AREA)
+                        .logarithmic(true)
+                        .text("Max fill area"),
+                );
+            }
+            ui.label("Left click paints, right click erases");
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(TILE_PALETTE_BUTTON_SIZE * 8.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("tile_palette_grid")
+                        .spacing([2.0, 2.0])
+                        .show(ui, |ui| {
+                            for index in 0..TILES_PER_ROW * TILES_PER_COLUMN {
+                                let selected = palette.selected == Some(index);
+                                let button = egui::Button::image(tile_image(
+                                    index,
+                                    TILE_PALETTE_BUTTON_SIZE,
+                                ))
+                                .selected(selected);
+                                if ui
+                                    .add(button)
+                                    .on_hover_text(format!("Tile {}", index))
+                                    .clicked()
+                                {
+                                    palette.selected = if selected { None } else { Some(index) };
+                                }
+                                if index % TILES_PER_ROW == TILES_PER_ROW - 1 {
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+        });
+}
+
+impl Default for TilePalette {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            tool: TileTool::default(),
+            drag: None,
+            max_fill_area: TILE_FILL_DEFAULT_AREA,
+        }
+    }
+}
+
+impl TileTool {
+    /// Every tool, in the order the palette lists them
+    pub const ALL: [TileTool; 5] = [
+        TileTool::Brush,
+        TileTool::Rectangle,
+        TileTool::HollowRectangle,
+        TileTool::Line,
+        TileTool::Fill,
+    ];
+
+    /// Label in the Tiles window, with the hotkey
+    pub fn name(self) -> &'static str {
+        match self {
+            TileTool::Brush => "Brush (B)",
+            TileTool::Rectangle => "Rectangle (R)",
+            TileTool::HollowRectangle => "Hollow rectangle (H)",
+            TileTool::Line => "Line (L)",
+            TileTool::Fill => "Fill (F)",
+        }
+    }
+
+    /// Key selecting the tool in the editor
+    pub fn hotkey(self) -> KeyCode {
+        match self {
+            TileTool::Brush => KeyCode::KeyB,
+            TileTool::Rectangle => KeyCode::KeyR,
+            TileTool::HollowRectangle => KeyCode::KeyH,
+            TileTool::Line => KeyCode::KeyL,
+            TileTool::Fill => KeyCode::KeyF,
+        }
+    }
+
+    /// Grid cells covered by dragging the tool from `start` to `end`
+    pub fn cells(self, start: UVec2, end: UVec2) -> Vec<UVec2> {
+        let (min, max) = (start.min(end), start.max(end));
+        match self {
+            TileTool::Brush | TileTool::Fill => vec![end],
+            TileTool::Rectangle | TileTool::HollowRectangle => (min.y..=max.y)
+                .flat_map(|y| (min.x..=max.x).map(move |x| UVec2::new(x, y)))
+                .filter(|cell| {
+                    self == TileTool::Rectangle
+                        || cell.x == min.x
+                        || cell.x == max.x
+                        || cell.y == min.y
+                        || cell.y == max.y
+                })
+                .collect(),
+            TileTool::Line => {
+                let delta = end.as_vec2() - start.as_vec2();
+                let steps = delta.x.abs().max(delta.y.abs()) as u32;
+                (0..=steps)
+                    .map(|step| {
+                        let t = if steps == 0 {
+                            0.0
+                        } else {
+                            step as f32 / steps as f32
+                        };
+                        (start.as_vec2() + delta * t).round().as_uvec2()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Contiguous cells (4-connected) with the same tile as `start`
+/// Returns `None` when there are more than `max_area` of them
+fn flood_fill_region(level: &LevelData, start: UVec2, max_area: u32) -> Option<Vec<UVec2>> {
+    let target = level.tiles.get(start.x, start.y)?;
+    let mut region = vec![start];
+    let mut visited = std::collections::HashSet::from([start]);
+    let mut next = 0;
+    while let Some(&cell) = region.get(next) {
+        next += 1;
+        let neighbors = [
+            cell.x.checked_sub(1).map(|x| UVec2::new(x, cell.y)),
+            Some(UVec2::new(cell.x + 1, cell.y)),
+            cell.y.checked_sub(1).map(|y| UVec2::new(cell.x, y)),
+            Some(UVec2::new(cell.x, cell.y + 1)),
+        ];
+        for neighbor in neighbors.into_iter().flatten() {
+            if level.tiles.get(neighbor.x, neighbor.y) == Some(target) && visited.insert(neighbor) {
+                if region.len() as u32 >= max_area {
+                    return None;
+                }
+                region.push(neighbor);
+            }
+        }
+    }
+    Some(region)
+}
+
+/// Picks the tile tool with its hotkey (B, R, H, L, F) while the editor is open
+pub fn select_tile_tool(
+    mut contexts: EguiContexts,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut palette: ResMut<TilePalette>,
+) {
+    // Typing in an editor field
+    if contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_keyboard_input())
+    {
+        return;
+    }
+    if let Some(tool) = TileTool::ALL
+        .into_iter()
+        .find(|tool| keyboard.just_pressed(tool.hotkey()))
+    {
+        palette.tool = tool;
+        palette.drag = None;
+    }
+}
+
+/// Sets level grid cells to a tile, respawning the tile entities
+struct TilePainter<'a> {
+    level: &'a mut LevelData,
+    grid: &'a mut TileGrid,
+    tileset: &'a TilesetInfo,
+    collision_map: &'a TileCollisionMap,
+    /// Tile chunks by chunk coordinates, including the ones spawned while
+    /// painting
+    chunks: HashMap<UVec2, Entity>,
+}
+
+impl TilePainter<'_> {
+    /// Sets `coords` to `index` (`EMPTY_TILE` erases)
+    /// Returns whether a tile was spawned there
+    fn paint(&mut self, commands: &mut Commands, coords: UVec2, index: TileId) -> bool {
+        // Dragging over tiles already painted
+        if self.level.tiles.get(coords.x, coords.y) == Some(index) {
+            return false;
+        }
+        self.level.tiles.set(coords.x, coords.y, index);
+        // Replaced directly rather than through `prune_tile_grid()`, so
+        // painting throws no debris
+        if let Some(old) = self.grid.tiles.remove(&coords) {
+            commands.entity(old).despawn();
+        }
+        if index == EMPTY_TILE {
+            return false;
+        }
+
+        let position = tile_to_world(self.level, coords.x, coords.y);
+        let level = &*self.level;
+        let chunk = *self
+            .chunks
+            .entry(coords / TILE_CHUNK_SIZE)
+            .or_insert_with_key(|&key| spawn_tile_chunk(commands, level, key));
+        let tile = spawn_tile(
+            commands,
+            self.tileset,
+            self.collision_map,
+            chunk,
+            position,
+            index,
+        );
+        self.grid.tiles.insert(coords, tile);
+        true
+    }
+}
+
+/// Paints the selected tile with the current tool: the brush paints under
+/// the cursor while the left button is held (right erases), the rectangle
+/// and line tools paint their cells when the drag is released and the fill
+/// tool replaces the clicked region, up to `TilePalette::max_fill_area` tiles
+/// Both update the level's `tiles`; tiles painted as water or collectibles
+/// only turn into those on the next load of the level
+#[allow(clippy::too_many_arguments)]
+pub fn paint_tiles(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut palette: ResMut<TilePalette>,
+    (prefabs, lighting): (Res<PrefabPalette>, Res<LightingEditor>),
+    (registry, collision_map): (Option<Res<TilesetRegistry>>, Option<Res<TileCollisionMap>>),
+    level: Option<ResMut<LevelData>>,
+    grid: Option<ResMut<TileGrid>>,
+    chunks: Query<(Entity, &TileChunk)>,
+    mut tiles_placed: EventWriter<TilePlaced>,
+) {
+    // Prefab and light placing take the clicks while active
+    let Some(selected) = palette.selected else {
+        return;
+    };
+    if prefabs.selected.is_some() || lighting.placing {
+        palette.drag = None;
+        return;
+    }
+    let (Some(mut level), Some(mut grid), Some(registry), Some(collision_map)) =
+        (level, grid, registry, collision_map)
+    else {
+        return;
+    };
+    let Some(tileset) = registry.tilesets.get(registry.current_tileset) else {
+        return;
+    };
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let cursor = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+        .and_then(|position| world_to_tile(&level, position));
+    // Clicks on the editor windows
+    let over_ui = contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.is_pointer_over_area());
+
+    let cells = match (palette.tool, palette.drag) {
+        (TileTool::Brush, _) => {
+            let index = if mouse.pressed(MouseButton::Left) {
+                selected
+            } else if mouse.pressed(MouseButton::Right) {
+                EMPTY_TILE
+            } else {
+                return;
+            };
+            let Some(coords) = cursor.filter(|_| !over_ui) else {
+                return;
+            };
+            vec![(coords, index)]
+        }
+        (TileTool::Fill, _) => {
+            let index = if mouse.just_pressed(MouseButton::Left) {
+                selected
+            } else if mouse.just_pressed(MouseButton::Right) {
+                EMPTY_TILE
+            } else {
+                return;
+            };
+            let Some(start) = cursor.filter(|_| !over_ui) else {
+                return;
+            };
+            let Some(region) = flood_fill_region(&level, start, palette.max_fill_area) else {
+                warn!(
+                    "Fill at {} covers more than {} tiles, nothing filled",
+                    start, palette.max_fill_area
+                );
+                return;
+            };
+            region.into_iter().map(|coords| (coords, index)).collect()
+        }
+        (tool, Some(drag)) => {
+            let button = if drag.erase {
+                MouseButton::Right
+            } else {
+                MouseButton::Left
+            };
+            if !mouse.just_released(button) {
+                // Cells outside the level keep the last end inside it
+                if let Some(end) = cursor.filter(|&end| end != drag.end) {
+                    palette.drag = Some(TileDrag { end, ..drag });
+                }
+                return;
+            }
+            palette.drag = None;
+            let index = if drag.erase { EMPTY_TILE } else { selected };
+            tool.cells(drag.start, drag.end)
+                .into_iter()
+                .map(|coords| (coords, index))
+                .collect()
+        }
+        (_, None) => {
+            let erase = mouse.just_pressed(MouseButton::Right);
+            if let Some(start) = cursor.filter(|_| !over_ui) {
+                if erase || mouse.just_pressed(MouseButton::Left) {
+                    palette.drag = Some(TileDrag {
+                        start,
+                        end: start,
+                        erase,
+                    });
+                }
+            }
+            return;
+        }
+    };
+
+    let chunks = chunks
+        .iter()
+        .filter_map(|(entity, chunk)| {
+            // The chunk's bottom left tile
+            let coords = world_to_tile(&level, chunk.rect.min + TILE_SIZE_16 / 2.0)?;
+            Some((coords / TILE_CHUNK_SIZE, entity))
+        })
+        .collect();
+    let mut painter = TilePainter {
+        level: &mut level,
+        grid: &mut grid,
+        tileset,
+        collision_map: &collision_map,
+        chunks,
+    };
+    for (coords, index) in cells {
+        if painter.paint(&mut commands, coords, index) {
+            tiles_placed.write(TilePlaced { coords });
+        }
+    }
+}
+
+/// Outlines the cells of the rectangle or line being dragged, red when it
+/// erases
+pub fn draw_tile_tool_preview(
+    mut gizmos: Gizmos,
+    palette: Res<TilePalette>,
+    level: Option<Res<LevelData>>,
+) {
+    let (Some(drag), Some(level)) = (palette.drag, level) else {
+        return;
+    };
+    let color = if drag.erase {
+        Color::srgba(1.0, 0.3, 0.3, 0.8)
+    } else {
+        Color::srgba(1.0, 1.0, 1.0, 0.8)
+    };
+    for cell in palette.tool.cells(drag.start, drag.end) {
+        gizmos.rect_2d(
+            tile_to_world(&level, cell.x, cell.y),
+            Vec2::splat(TILE_SIZE_16),
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TileLayer;
+
+    /// Level with the given rows of tiles
+    fn level(rows: Vec<Vec<TileId>>) -> LevelData {
+        let tiles = TileLayer::try_from(rows).unwrap();
+        LevelData {
+            width: tiles.width(),
+            height: tiles.height(),
+            tiles,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fill_stops_one_cell_past_the_maximum_area() {
+        let level = level(vec![vec![180, 180, 180, 180, 176]]);
+        let region = flood_fill_region(&level, UVec2::new(1, 0), 4).unwrap();
+        assert_eq!(region.len(), 4);
+        assert!(flood_fill_region(&level, UVec2::new(1, 0), 3).is_none());
+    }
+
+    #[test]
+    fn fill_stays_inside_the_level() {
+        let level = level(vec![vec![180, 180, 176], vec![180, 180, 180]]);
+        // From the corners, where the neighbors above and to the left would
+        // underflow and those below and to the right are outside the level
+        let region = flood_fill_region(&level, UVec2::ZERO, 100).unwrap();
+        assert_eq!(region.len(), 5);
+        assert!(!region.contains(&UVec2::new(2, 0)));
+        let region = flood_fill_region(&level, UVec2::new(2, 1), 100).unwrap();
+        assert_eq!(region.len(), 5);
+        assert_eq!(
+            flood_fill_region(&level, UVec2::new(2, 0), 100),
+            Some(vec![UVec2::new(2, 0)])
+        );
+        assert!(flood_fill_region(&level, UVec2::new(3, 0), 100).is_none());
+    }
+
+    #[test]
+    fn lines_round_to_the_nearest_cells() {
+        let cells = TileTool::Line.cells(UVec2::ZERO, UVec2::new(4, 2));
+        // Halfway cells round up
+        assert_eq!(
+            cells,
+            [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)].map(|(x, y)| UVec2::new(x, y))
+        );
+        let mut back = TileTool::Line.cells(UVec2::new(4, 2), UVec2::ZERO);
+        back.reverse();
+        assert_eq!(back, cells);
+
+        let diagonal = TileTool::Line.cells(UVec2::new(3, 0), UVec2::new(0, 3));
+        assert_eq!(
+            diagonal,
+            [(3, 0), (2, 1), (1, 2), (0, 3)].map(|(x, y)| UVec2::new(x, y))
+        );
+    }
+
+    #[test]
+    fn hollow_rectangles_keep_their_border() {
+        let cell = UVec2::new(2, 3);
+        assert_eq!(TileTool::HollowRectangle.cells(cell, cell), vec![cell]);
+
+        let cells = TileTool::HollowRectangle.cells(UVec2::new(3, 3), UVec2::new(1, 1));
+        assert_eq!(cells.len(), 8);
+        assert!(!cells.contains(&UVec2::new(2, 2)));
+        assert_eq!(
+            TileTool::Rectangle
+                .cells(UVec2::new(3, 3), UVec2::new(1, 1))
+                .len(),
+            9
+        );
+    }
+}
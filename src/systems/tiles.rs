@@ -0,0 +1,429 @@
+//! Tile map systems: spawning level tiles and their colliders
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    CurrentLevel, GroundMaterial, LevelData, LevelEntity, LevelLoaded, LevelLoadingText,
+    MainCamera, OneWayPlatform, Tile, TileChunk, TileCollisionMap, TileGrid, TileId, TileIndex,
+    TileMaterial, TilePlaced, TileRemoved, TileSpawnQueue, TileType, TilesetInfo, TilesetRegistry,
+};
+use crate::constants::*;
+use crate::systems::camera::camera_view;
+use crate::systems::level_loader::{create_collision_map, EMPTY_TILE};
+use crate::systems::platforms::ONE_WAY_PLATFORM_GROUP;
+
+/// Converts level grid coordinates (top-left origin, Y down) to the world
+/// position of the tile's center
+pub fn tile_to_world(level: &LevelData, x: u32, y: u32) -> Vec2 {
+    Vec2::new(
+        LEVEL_ORIGIN_X + (x as f32 + 0.5) * TILE_SIZE_16,
+        LEVEL_ORIGIN_Y + ((level.height - 1 - y) as f32 + 0.5) * TILE_SIZE_16,
+    )
+}
+
+impl TileGrid {
+    /// Tile entity at level grid coordinates
+    pub fn get(&self, coords: UVec2) -> Option<Entity> {
+        self.tiles.get(&coords).copied()
+    }
+
+    /// Tile entities whose centers lie in a world rectangle
+    pub fn in_rect(&self, level: &LevelData, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        // Grid columns/rows (rows counted from the bottom) of the tile centers
+        let column = |x: f32| (x - LEVEL_ORIGIN_X) / TILE_SIZE_16 - 0.5;
+        let row = |y: f32| (y - LEVEL_ORIGIN_Y) / TILE_SIZE_16 - 0.5;
+        let min_x = column(rect.min.x).ceil().max(0.0) as i32;
+        let max_x = column(rect.max.x).floor().min(level.width as f32 - 1.0) as i32;
+        let min_row = row(rect.min.y).ceil().max(0.0) as i32;
+        let max_row = row(rect.max.y).floor().min(level.height as f32 - 1.0) as i32;
+
+        let height = level.height;
+        (min_row..=max_row)
+            .flat_map(move |row| {
+                (min_x..=max_x).map(move |x| UVec2::new(x as u32, height - 1 - row as u32))
+            })
+            .filter_map(|coords| self.get(coords))
+    }
+}
+
+/// Converts a world position to the level grid coordinates of the tile
+/// containing it, or `None` outside the level
+pub fn world_to_tile(level: &LevelData, position: Vec2) -> Option<UVec2> {
+    let column = ((position.x - LEVEL_ORIGIN_X) / TILE_SIZE_16).floor();
+    let row_from_bottom = ((position.y - LEVEL_ORIGIN_Y) / TILE_SIZE_16).floor();
+    if column < 0.0
+        || row_from_bottom < 0.0
+        || column >= level.width as f32
+        || row_from_bottom >= level.height as f32
+    {
+        return None;
+    }
+    Some(UVec2::new(
+        column as u32,
+        level.height - 1 - row_from_bottom as u32,
+    ))
+}
+
+/// Loads the tileset and queues every non-empty tile of the level for
+/// `spawn_queued_tiles()`, showing a loading text until they're all in
+pub fn setup_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    level: Option<Res<LevelData>>,
+) {
+    let texture_handle: Handle<Image> = asset_server.load("scene/tileset.png");
+    let layout_handle = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+        UVec2::splat(TILESET_TILE_SIZE),
+        TILES_PER_ROW,
+        TILES_PER_COLUMN,
+        None,
+        None,
+    ));
+
+    let tiles: Vec<(UVec2, TileId)> = level
+        .map(|level| {
+            level
+                .tiles
+                .iter()
+                .filter(|&(_, index)| index != EMPTY_TILE)
+                .collect()
+        })
+        .unwrap_or_default();
+    commands.spawn((
+        Name::new("Level loading text"),
+        LevelEntity,
+        LevelLoadingText,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(10.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        GlobalZIndex(2),
+        Text::new("Loading level..."),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+    ));
+
+    commands.insert_resource(TilesetRegistry {
+        tilesets: vec![TilesetInfo {
+            tile_size: TILESET_TILE_SIZE,
+            tiles_per_row: TILES_PER_ROW,
+            tiles_per_column: TILES_PER_COLUMN,
+            texture_handle,
+            layout_handle,
+        }],
+        current_tileset: 0,
+    });
+    commands.insert_resource(create_collision_map());
+    commands.insert_resource(TileGrid::default());
+    commands.insert_resource(TileSpawnQueue { tiles, ..default() });
+}
+
+/// The level and tileset queued tiles are spawned from
+#[derive(SystemParam)]
+pub struct TileSources<'w> {
+    level: Option<Res<'w, LevelData>>,
+    current_level: Option<Res<'w, CurrentLevel>>,
+    collision_map: Res<'w, TileCollisionMap>,
+    registry: Res<'w, TilesetRegistry>,
+}
+
+/// Spawns the next `TILE_SPAWN_BUDGET` queued tiles, so large levels load
+/// over a few frames instead of hitching on one
+/// Tiles are grouped under `TileChunk` parents (which carry `LevelEntity`)
+/// so off-screen chunks can be hidden as a whole. Once the queue is empty it
+/// is removed, the loading text despawned and `LevelLoaded` sent
+pub fn spawn_queued_tiles(
+    mut commands: Commands,
+    mut queue: ResMut<TileSpawnQueue>,
+    sources: TileSources,
+    mut grid: ResMut<TileGrid>,
+    mut loading_texts: Query<(Entity, &mut Text), With<LevelLoadingText>>,
+    mut level_loaded: EventWriter<LevelLoaded>,
+) {
+    let TileSources {
+        level,
+        current_level,
+        collision_map,
+        registry,
+    } = sources;
+    let (Some(level), Some(tileset)) = (level, registry.tilesets.get(registry.current_tileset))
+    else {
+        return;
+    };
+
+    let _span = info_span!("spawn_tiles").entered();
+    let end = (queue.next + TILE_SPAWN_BUDGET).min(queue.tiles.len());
+    let queue = queue.as_mut();
+    for &(coords, index) in &queue.tiles[queue.next..end] {
+        let position = tile_to_world(&level, coords.x, coords.y);
+        let chunk_key = coords / TILE_CHUNK_SIZE;
+        let chunk = *queue
+            .chunks
+            .entry(chunk_key)
+            .or_insert_with(|| spawn_tile_chunk(&mut commands, &level, chunk_key));
+        let tile = spawn_tile(
+            &mut commands,
+            tileset,
+            &collision_map,
+            chunk,
+            position,
+            index,
+        );
+        grid.tiles.insert(coords, tile);
+    }
+    queue.next = end;
+
+    if queue.next < queue.tiles.len() {
+        let percent = queue.next * 100 / queue.tiles.len();
+        for (_, mut text) in loading_texts.iter_mut() {
+            text.0 = format!("Loading level... {}%", percent);
+        }
+        return;
+    }
+    for (entity, _) in loading_texts.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<TileSpawnQueue>();
+    level_loaded.write(LevelLoaded {
+        path: current_level
+            .map(|current| current.path.clone())
+            .unwrap_or_default(),
+    });
+}
+
+/// Spawns tile `index` at `position` under `chunk`, typed from the
+/// collision map; `update_tile_collisions()` adds its collider
+pub(crate) fn spawn_tile(
+    commands: &mut Commands,
+    tileset: &TilesetInfo,
+    collision_map: &TileCollisionMap,
+    chunk: Entity,
+    position: Vec2,
+    index: TileId,
+) -> Entity {
+    let tile_type = if collision_map.solid_tiles.contains(&index) {
+        TileType::Ground
+    } else if collision_map.platform_tiles.contains(&index) {
+        TileType::Platform
+    } else {
+        TileType::Decoration
+    };
+
+    commands
+        .spawn((
+            Sprite {
+                image: tileset.texture_handle.clone(),
+                texture_atlas: Some(TextureAtlas {
+                    layout: tileset.layout_handle.clone(),
+                    index: index as usize,
+                }),
+                ..default()
+            },
+            Transform::from_translation(position.extend(TILE_LAYER_Z)),
+            TileIndex {
+                index,
+                tileset_x: index % TILES_PER_ROW,
+                tileset_y: index / TILES_PER_ROW,
+            },
+            Tile {
+                tile_type,
+                solid: tile_type == TileType::Ground,
+            },
+            ChildOf(chunk),
+        ))
+        .id()
+}
+
+/// How a tile collides, from the collision map
+pub(crate) fn collision_type(collision_map: &TileCollisionMap, index: TileId) -> &'static str {
+    if collision_map.solid_tiles.contains(&index) {
+        "Solid"
+    } else if collision_map.platform_tiles.contains(&index) {
+        "One-way platform"
+    } else if collision_map.water_tiles.contains(&index) {
+        "Water"
+    } else if collision_map.collectible_tiles.contains_key(&index) {
+        "Collectible"
+    } else {
+        "None"
+    }
+}
+
+/// Drops despawned tiles from the `TileGrid`, sending `TileRemoved` for each
+/// Tiles of a replaced level aren't in the new grid, so switching levels
+/// sends nothing
+pub fn prune_tile_grid(
+    mut removed: RemovedComponents<TileIndex>,
+    grid: Option<ResMut<TileGrid>>,
+    mut tiles_removed: EventWriter<TileRemoved>,
+) {
+    let removed: std::collections::HashSet<Entity> = removed.read().collect();
+    let Some(mut grid) = grid else {
+        return;
+    };
+    if !removed.is_empty() {
+        grid.tiles.retain(|&coords, entity| {
+            let keep = !removed.contains(entity);
+            if !keep {
+                tiles_removed.write(TileRemoved { coords });
+            }
+            keep
+        });
+    }
+}
+
+/// Spawns the (empty) chunk covering the `key` square of the level grid
+pub(crate) fn spawn_tile_chunk(commands: &mut Commands, level: &LevelData, key: UVec2) -> Entity {
+    let min = key * TILE_CHUNK_SIZE;
+    let max = (min + UVec2::splat(TILE_CHUNK_SIZE - 1))
+        .min(UVec2::new(level.width, level.height).saturating_sub(UVec2::ONE));
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    let top_left = tile_to_world(level, min.x, min.y);
+    let bottom_right = tile_to_world(level, max.x, max.y);
+    commands
+        .spawn((
+            Name::new(format!("Tile chunk {}", key)),
+            LevelEntity,
+            TileChunk {
+                rect: Rect::from_corners(top_left - half_tile, bottom_right + half_tile),
+            },
+            Transform::default(),
+            Visibility::default(),
+        ))
+        .id()
+}
+
+/// Hides the tile chunks outside the camera view (plus a margin)
+pub fn cull_tile_chunks(
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut chunks: Query<(&TileChunk, &mut Visibility)>,
+) {
+    let Some(view) = cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection))
+    else {
+        return;
+    };
+    let view = view.inflate(TILE_CHUNK_CULL_MARGIN);
+
+    for (chunk, mut visibility) in chunks.iter_mut() {
+        let wanted = if chunk.rect.intersect(view).is_empty() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        visibility.set_if_neq(wanted);
+    }
+}
+
+/// Adds colliders to tiles based on the collision map
+/// Solid tiles get a full-size box, platform tiles a thin one-way box. Tiles
+/// with a material also get its friction/restitution and the `TileMaterial`
+/// itself, so characters can look up what they're standing on
+/// Only runs when the collision map changes, a level's tiles finish spawning
+/// or a `TilePlaced` event fires (see `tile_collisions_dirty()`), and inserts
+/// the components in batches rather than per tile
+pub fn update_tile_collisions(
+    mut commands: Commands,
+    collision_map: Option<Res<TileCollisionMap>>,
+    tile_query: Query<(Entity, &TileIndex), Without<Collider>>,
+) {
+    let Some(collision_map) = collision_map else {
+        return;
+    };
+    let _span = info_span!("insert_tile_colliders", tiles = tile_query.iter().len()).entered();
+    let half = TILE_SIZE_16 / 2.0;
+
+    let mut solids = Vec::new();
+    let mut platforms = Vec::new();
+    let mut materials = Vec::new();
+    for (entity, tile_index) in tile_query.iter() {
+        if collision_map.solid_tiles.contains(&tile_index.index) {
+            solids.push((entity, Collider::cuboid(half, half)));
+        } else if collision_map.platform_tiles.contains(&tile_index.index) {
+            platforms.push((
+                entity,
+                (
+                    Collider::cuboid(half, half / 2.0),
+                    OneWayPlatform,
+                    ActiveHooks::MODIFY_SOLVER_CONTACTS,
+                    CollisionGroups::new(ONE_WAY_PLATFORM_GROUP, Group::ALL),
+                ),
+            ));
+        } else {
+            continue;
+        }
+
+        if let Some(material) = collision_map.materials.get(&tile_index.index) {
+            materials.push((
+                entity,
+                (
+                    *material,
+                    Friction::coefficient(material.friction),
+                    Restitution::coefficient(material.restitution),
+                ),
+            ));
+        }
+    }
+    commands.insert_batch(solids);
+    commands.insert_batch(platforms);
+    commands.insert_batch(materials);
+}
+
+/// Run condition for `update_tile_collisions()`: the collision map was
+/// (re)inserted or changed, a level finished loading, or a tile was placed at
+/// runtime
+pub fn tile_collisions_dirty(
+    collision_map: Option<Res<TileCollisionMap>>,
+    mut level_loaded: EventReader<LevelLoaded>,
+    mut tiles_placed: EventReader<TilePlaced>,
+) -> bool {
+    let map_changed = collision_map.is_some_and(|map| map.is_changed());
+    // Drain both readers so the events only trigger a single run
+    let spawned = level_loaded.read().count() > 0;
+    let placed = tiles_placed.read().count() > 0;
+    map_changed || spawned || placed
+}
+
+/// Records the material of the tile each grounded character stands on
+/// Anything else underfoot (or being airborne) resets it to the default material
+pub fn update_ground_material(
+    tiles: Query<(&TileMaterial, &Transform)>,
+    mut characters: Query<(
+        &KinematicCharacterControllerOutput,
+        &Transform,
+        &mut GroundMaterial,
+    )>,
+) {
+    for (output, transform, mut ground) in characters.iter_mut() {
+        let underfoot = output
+            .grounded
+            .then(|| {
+                output.collisions.iter().find_map(|collision| {
+                    tiles
+                        .get(collision.entity)
+                        .ok()
+                        .filter(|(_, tile_transform)| {
+                            tile_transform.translation.y < transform.translation.y
+                        })
+                        .map(|(material, _)| *material)
+                })
+            })
+            .flatten()
+            .unwrap_or_default();
+
+        if ground.0 != underfoot {
+            ground.0 = underfoot;
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Level timer, best times and time-attack medals
+
+use bevy::prelude::*;
+
+use crate::components::{
+    CurrentLevel, GhostRecorder, LevelCompleted, LevelData, LevelTimer, Medal, MedalTimes,
+    SaveData, SaveDirectory,
+};
+use crate::constants::SAVE_FILE;
+use crate::systems::save::write_save_file;
+
+impl MedalTimes {
+    /// Best medal earned by finishing in `time` seconds
+    pub fn medal_for(&self, time: f32) -> Option<Medal> {
+        if time <= self.gold {
+            Some(Medal::Gold)
+        } else if time <= self.silver {
+            Some(Medal::Silver)
+        } else if time <= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats seconds as minutes:seconds.tenths
+pub fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.0) as u32;
+    format!("{:02}:{:04.1}", minutes, seconds % 60.0)
+}
+
+/// Advances the attempt clock while playing
+pub fn update_level_timer(time: Res<Time>, mut timer: ResMut<LevelTimer>) {
+    timer.elapsed += time.delta_secs();
+}
+
+/// Records the completion time: updates and saves the level's best time,
+/// along with the run's ghost recording, and reports the medal earned
+/// against the level's thresholds
+pub fn record_level_time(
+    mut completed: EventReader<LevelCompleted>,
+    timer: Res<LevelTimer>,
+    level: Option<Res<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    recorder: Res<GhostRecorder>,
+    mut save_data: ResMut<SaveData>,
+    saves: Res<SaveDirectory>,
+) {
+    if completed.read().last().is_none() {
+        return;
+    }
+    let Some(current_level) = current_level else {
+        return;
+    };
+
+    let time = timer.elapsed;
+    let best = save_data.best_times.get(&current_level.path).copied();
+    if best.is_none_or(|best| time < best) {
+        save_data
+            .best_times
+            .insert(current_level.path.clone(), time);
+        if recorder.level == current_level.path {
+            save_data
+                .ghosts
+                .insert(current_level.path.clone(), recorder.recording.clone());
+        }
+        if let Err(e) = write_save_file(&saves.file(SAVE_FILE), &save_data) {
+            error!("{}", e);
+        }
+        info!("New best time: {}", format_time(time));
+    }
+
+    let medal = level
+        .as_ref()
+        .and_then(|level| level.medals)
+        .and_then(|medals| medals.medal_for(time));
+    match medal {
+        Some(medal) => info!("Finished in {} - {:?} medal", format_time(time), medal),
+        None => info!("Finished in {}", format_time(time)),
+    }
+}
@@ -0,0 +1,102 @@
+//! Day/night cycle systems for tinting backgrounds and tiles
+
+use bevy::prelude::*;
+
+use crate::components::{ParallaxLayer, TileIndex, TimeOfDay};
+
+/// Palette keyframes across the cycle (position, color), must start at 0.0 and end at 1.0
+const DAY_NIGHT_PALETTE: [(f32, Color); 7] = [
+    (0.0, Color::srgb(0.25, 0.28, 0.5)),  // night
+    (0.2, Color::srgb(0.25, 0.28, 0.5)),  // night
+    (0.28, Color::srgb(1.0, 0.75, 0.65)), // dawn
+    (0.36, Color::WHITE),                 // day
+    (0.64, Color::WHITE),                 // day
+    (0.74, Color::srgb(1.0, 0.6, 0.45)),  // dusk
+    (1.0, Color::srgb(0.25, 0.28, 0.5)),  // night
+];
+
+/// Advances the time of day
+pub fn update_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.paused || time_of_day.cycle_duration <= 0.0 {
+        return;
+    }
+    let step = time.delta_secs() / time_of_day.cycle_duration;
+    time_of_day.time = (time_of_day.time + step).fract();
+}
+
+/// Tile sprites tinted with the time of day
+type TintedTiles<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Sprite, &'static InheritedVisibility),
+    (With<TileIndex>, Without<ParallaxLayer>),
+>;
+
+/// Tints background layers (and optionally tiles) with the current palette color
+/// Sprites already at their tint aren't written, so a paused (or slow) cycle
+/// doesn't mark the whole tilemap changed every frame
+pub fn apply_day_night_tint(
+    time_of_day: Res<TimeOfDay>,
+    mut background_query: Query<(&mut Sprite, &ParallaxLayer)>,
+    mut tile_query: TintedTiles,
+) {
+    let ambient = palette_color(time_of_day.time);
+
+    for (mut sprite, layer) in background_query.iter_mut() {
+        let color = multiply_colors(layer.tint, ambient);
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+
+    if time_of_day.tint_tiles {
+        // Keep the tile's own alpha (ghosted gate tiles); tiles in culled
+        // chunks catch up once their chunk is shown, before it's on screen
+        for (mut sprite, visibility) in tile_query.iter_mut() {
+            if !visibility.get() {
+                continue;
+            }
+            let color = ambient.with_alpha(sprite.color.alpha());
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+/// Samples the palette at a position in the cycle
+fn palette_color(time: f32) -> Color {
+    for window in DAY_NIGHT_PALETTE.windows(2) {
+        let (start, from) = window[0];
+        let (end, to) = window[1];
+        if time <= end {
+            let t = if end > start {
+                (time - start) / (end - start)
+            } else {
+                0.0
+            };
+            return lerp_colors(from, to, t);
+        }
+    }
+    DAY_NIGHT_PALETTE[DAY_NIGHT_PALETTE.len() - 1].1
+}
+
+fn lerp_colors(from: Color, to: Color, t: f32) -> Color {
+    let (a, b) = (from.to_srgba(), to.to_srgba());
+    Color::srgba(
+        a.red + (b.red - a.red) * t,
+        a.green + (b.green - a.green) * t,
+        a.blue + (b.blue - a.blue) * t,
+        a.alpha + (b.alpha - a.alpha) * t,
+    )
+}
+
+fn multiply_colors(a: Color, b: Color) -> Color {
+    let (a, b) = (a.to_srgba(), b.to_srgba());
+    Color::srgba(
+        a.red * b.red,
+        a.green * b.green,
+        a.blue * b.blue,
+        a.alpha * b.alpha,
+    )
+}
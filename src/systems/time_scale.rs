@@ -0,0 +1,87 @@
+//! Debug time controls: slow motion, freezing the simulation and stepping it
+//! one frame at a time (for jump arcs and collisions)
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{DebugTime, TileSpawnQueue};
+use crate::constants::*;
+
+impl Default for DebugTime {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            frozen: false,
+            step_frames: 0,
+        }
+    }
+}
+
+impl DebugTime {
+    /// Whether the simulation advances this frame
+    pub fn running(&self) -> bool {
+        !self.frozen || self.step_frames > 0
+    }
+}
+
+/// Run condition gating gameplay systems while the simulation is frozen or
+/// the level's tiles are still spawning
+pub fn simulation_running(
+    debug_time: Res<DebugTime>,
+    loading: Option<Res<TileSpawnQueue>>,
+) -> bool {
+    debug_time.running() && loading.is_none()
+}
+
+/// F11 cycles the time scale (1×, 0.5×, 0.25×), Shift+F12 freezes the
+/// simulation and `.` steps a single frame while frozen
+/// `sync_world_pause()` applies them to virtual time and physics
+pub fn debug_time_controls(keyboard: Res<ButtonInput<KeyCode>>, mut debug_time: ResMut<DebugTime>) {
+    if keyboard.just_pressed(KeyCode::F11) {
+        let next = DEBUG_TIME_SCALES
+            .iter()
+            .position(|&scale| scale == debug_time.scale)
+            .map_or(0, |index| (index + 1) % DEBUG_TIME_SCALES.len());
+        debug_time.scale = DEBUG_TIME_SCALES[next];
+        debug!("Time scale: {}x", debug_time.scale);
+    }
+    // Plain F12 takes a screenshot
+    if keyboard.just_pressed(KeyCode::F12)
+        && keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+    {
+        debug_time.frozen = !debug_time.frozen;
+        debug_time.step_frames = 0;
+        debug!(
+            "Simulation: {}",
+            if debug_time.frozen {
+                "FROZEN"
+            } else {
+                "RUNNING"
+            }
+        );
+    }
+    if debug_time.frozen && keyboard.just_pressed(KeyCode::Period) {
+        // Time unpaused now only advances from the next frame on, which is
+        // the one that gets simulated
+        debug_time.step_frames = 2;
+    }
+}
+
+/// Counts down a frame step and freezes time and physics again once it's
+/// done, before the next frame's time update
+pub fn finish_debug_step(
+    mut debug_time: ResMut<DebugTime>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: Query<&mut RapierConfiguration>,
+) {
+    if debug_time.step_frames == 0 {
+        return;
+    }
+    debug_time.step_frames -= 1;
+    if !debug_time.running() {
+        time.pause();
+        for mut config in physics.iter_mut() {
+            config.physics_pipeline_active = false;
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Reflection registration of the game's components, resources and states,
+//! used by the world inspector and by scene snapshots
+
+use bevy::prelude::*;
+
+use crate::components::{
+    ActiveCheckpoint, ActiveCutscene, ActiveDialogue, AirJumps, AmbientPlayer, AnimationCollection,
+    AnimationConfig, AnimationHandles, AnimationState, ArenaBarrier, AudioBus, AudioEmitter,
+    AudioGain, BackgroundIndex, BlockBounce, BossArena, Buoyant, CameraFollow, CameraLock,
+    CameraPath, CameraShake, CameraTracker, CharacterPhysicsConfig, CharacterRoster,
+    CharacterSelectText, CharacterStats, Checkpoint, Chest, ChestLid, ChestOpening, Collectible,
+    CurrentLevel, CurrentLevelAsset, DebugConsole, DebugSettings, DebugTime, DialogueBodyText,
+    DialogueChoicesText, DialoguePortrait, DialogueSpeakerText, DialogueTrigger, Door, DoorOpening,
+    Dormant, DrawLayer, Dying, EditorState, Elevator, Enemy, FacingDirection, FallingHazard,
+    FreeCamera, GameConfig, GameState, Gate, Ghost, GhostRecorder, Goal, GrabbingRope,
+    GroundMaterial, Health, HiddenForScreenshot, HintPopup, HintTrigger, HitBlock,
+    HudCollectiblesText, HudHeart, HudLiquidText, HudLivesText, HudObjectivesText, HudScoreText,
+    HudTimerText, InCurrent, InWater, JumpPhase, KeyItem, KeyRing, Leaderboard,
+    LeaderboardInitialsText, LeaderboardReturn, Level, LevelData, LevelEntity, LevelLoadingText,
+    LevelRegistry, LevelTimer, LightSource, LightingEditor, LightingOverlay, Lives, Loot,
+    LootTables, MainCamera, Minimap, MinimapFrame, MinimapMarker, MovingHazard, MovingPlatform,
+    MusicController, MusicIntensity, MusicPlayer, MusicStem, NetworkPuppet, Objectives,
+    OneWayPlatform, ParallaxConfig, ParallaxLayer, Particle, PendingLeaderboardEntry, PlacedLight,
+    PlatformPassThrough, PlayableCharacter, PlayerController, PlayerInput, PlayerStats,
+    PlayerVelocity, Popup, PowerUp, PrefabPalette, PrefabRegistry, PrimaryPlayer, PropKind,
+    RenderInterpolation, RespawnZone, RisingLiquid, RopeSegment, SaveData, SaveDirectory, Score,
+    ScreenFade, SecretArea, SecretCover, Settings, SleepsOffScreen, SoundRegistry, SpeedrunOverlay,
+    SpeedrunRecords, SpeedrunSplitsText, SpeedrunTimer, SpeedrunTimerText, Switch, Tile, TileChunk,
+    TileCollisionMap, TileFade, TileGrid, TileIndex, TileMap, TileMaterial, TilePalette,
+    TileSpawnQueue, TilesetInfo, TilesetRegistry, TimeOfDay, WaterCurrent, WaterVolume, Weather,
+    WeatherFog, WeatherParticle,
+};
+
+/// Registers every reflected component, resource and state of the game
+/// Types nested in them (level definitions, enums) are registered along
+/// with them
+pub fn register_types(app: &mut App) {
+    app
+        // Components
+        .register_type::<PlayerVelocity>()
+        .register_type::<AnimationState>()
+        .register_type::<FacingDirection>()
+        .register_type::<AnimationConfig>()
+        .register_type::<Dormant>()
+        .register_type::<SleepsOffScreen>()
+        .register_type::<AnimationCollection>()
+        .register_type::<AnimationHandles>()
+        .register_type::<Tile>()
+        .register_type::<ParallaxLayer>()
+        .register_type::<BackgroundIndex>()
+        .register_type::<MainCamera>()
+        .register_type::<TileMap>()
+        .register_type::<CameraFollow>()
+        .register_type::<CameraPath>()
+        .register_type::<CameraTracker>()
+        .register_type::<TileChunk>()
+        .register_type::<LevelLoadingText>()
+        .register_type::<TileIndex>()
+        .register_type::<TilesetInfo>()
+        .register_type::<BossArena>()
+        .register_type::<ArenaBarrier>()
+        .register_type::<AudioBus>()
+        .register_type::<AudioGain>()
+        .register_type::<AudioEmitter>()
+        .register_type::<AmbientPlayer>()
+        .register_type::<MusicPlayer>()
+        .register_type::<MusicStem>()
+        .register_type::<CameraLock>()
+        .register_type::<LevelEntity>()
+        .register_type::<Goal>()
+        .register_type::<DialogueTrigger>()
+        .register_type::<HintTrigger>()
+        .register_type::<HintPopup>()
+        .register_type::<DialoguePortrait>()
+        .register_type::<DialogueSpeakerText>()
+        .register_type::<DialogueBodyText>()
+        .register_type::<DialogueChoicesText>()
+        .register_type::<KeyItem>()
+        .register_type::<KeyRing>()
+        .register_type::<Door>()
+        .register_type::<DoorOpening>()
+        .register_type::<Collectible>()
+        .register_type::<Chest>()
+        .register_type::<ChestLid>()
+        .register_type::<ChestOpening>()
+        .register_type::<PowerUp>()
+        .register_type::<Particle>()
+        .register_type::<LightSource>()
+        .register_type::<PlacedLight>()
+        .register_type::<LightingOverlay>()
+        .register_type::<DrawLayer>()
+        .register_type::<RenderInterpolation>()
+        .register_type::<Health>()
+        .register_type::<HudHeart>()
+        .register_type::<HudCollectiblesText>()
+        .register_type::<HudTimerText>()
+        .register_type::<HiddenForScreenshot>()
+        .register_type::<MinimapFrame>()
+        .register_type::<MinimapMarker>()
+        .register_type::<HudScoreText>()
+        .register_type::<HudLiquidText>()
+        .register_type::<HudObjectivesText>()
+        .register_type::<HudLivesText>()
+        .register_type::<Dying>()
+        .register_type::<ScreenFade>()
+        .register_type::<Checkpoint>()
+        .register_type::<SpeedrunOverlay>()
+        .register_type::<SpeedrunTimerText>()
+        .register_type::<SpeedrunSplitsText>()
+        .register_type::<MovingHazard>()
+        .register_type::<RisingLiquid>()
+        .register_type::<RespawnZone>()
+        .register_type::<FallingHazard>()
+        .register_type::<Popup>()
+        .register_type::<LeaderboardInitialsText>()
+        .register_type::<NetworkPuppet>()
+        .register_type::<Ghost>()
+        .register_type::<RopeSegment>()
+        .register_type::<GrabbingRope>()
+        .register_type::<Switch>()
+        .register_type::<Elevator>()
+        .register_type::<Gate>()
+        .register_type::<TileFade>()
+        .register_type::<SecretArea>()
+        .register_type::<SecretCover>()
+        .register_type::<HitBlock>()
+        .register_type::<BlockBounce>()
+        .register_type::<Enemy>()
+        .register_type::<Loot>()
+        .register_type::<PropKind>()
+        .register_type::<OneWayPlatform>()
+        .register_type::<PlatformPassThrough>()
+        .register_type::<MovingPlatform>()
+        .register_type::<Level>()
+        .register_type::<TileMaterial>()
+        .register_type::<GroundMaterial>()
+        .register_type::<WaterVolume>()
+        .register_type::<InWater>()
+        .register_type::<WaterCurrent>()
+        .register_type::<InCurrent>()
+        .register_type::<Buoyant>()
+        .register_type::<PlayableCharacter>()
+        .register_type::<CharacterStats>()
+        .register_type::<AirJumps>()
+        .register_type::<JumpPhase>()
+        .register_type::<CharacterSelectText>()
+        .register_type::<PlayerController>()
+        .register_type::<PrimaryPlayer>()
+        .register_type::<PlayerInput>()
+        .register_type::<WeatherParticle>()
+        .register_type::<WeatherFog>()
+        // Resources
+        .register_type::<ParallaxConfig>()
+        .register_type::<TileSpawnQueue>()
+        .register_type::<TileGrid>()
+        .register_type::<LevelData>()
+        .register_type::<ActiveCutscene>()
+        .register_type::<Objectives>()
+        .register_type::<DebugConsole>()
+        .register_type::<FreeCamera>()
+        .register_type::<CameraShake>()
+        .register_type::<DebugSettings>()
+        .register_type::<DebugTime>()
+        .register_type::<SoundRegistry>()
+        .register_type::<MusicController>()
+        .register_type::<MusicIntensity>()
+        .register_type::<LevelRegistry>()
+        .register_type::<ActiveDialogue>()
+        .register_type::<CurrentLevel>()
+        .register_type::<CurrentLevelAsset>()
+        .register_type::<LootTables>()
+        .register_type::<PrefabRegistry>()
+        .register_type::<PrefabPalette>()
+        .register_type::<TilePalette>()
+        .register_type::<LightingEditor>()
+        .register_type::<Settings>()
+        .register_type::<Lives>()
+        .register_type::<ActiveCheckpoint>()
+        .register_type::<PlayerStats>()
+        .register_type::<Score>()
+        .register_type::<LevelTimer>()
+        .register_type::<SpeedrunRecords>()
+        .register_type::<SpeedrunTimer>()
+        .register_type::<Minimap>()
+        .register_type::<Leaderboard>()
+        .register_type::<PendingLeaderboardEntry>()
+        .register_type::<LeaderboardReturn>()
+        .register_type::<SaveData>()
+        .register_type::<SaveDirectory>()
+        .register_type::<GhostRecorder>()
+        .register_type::<TilesetRegistry>()
+        .register_type::<TileCollisionMap>()
+        .register_type::<CharacterPhysicsConfig>()
+        .register_type::<CharacterRoster>()
+        .register_type::<GameConfig>()
+        .register_type::<Weather>()
+        .register_type::<TimeOfDay>()
+        // States
+        .register_type::<GameState>()
+        .register_type::<EditorState>();
+}
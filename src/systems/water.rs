@@ -0,0 +1,217 @@
+//! Water volume systems: buoyancy for dynamic bodies, slow sinking for the
+//! player, and currents pushing both along
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::{
+    Buoyant, GameConfig, InCurrent, InWater, LevelData, LevelEntity, PlayerVelocity, SoundEvent,
+    TileCollisionMap, WaterCurrent, WaterVolume,
+};
+use crate::constants::*;
+use crate::systems::tiles::tile_to_world;
+
+/// Groups contiguous water tiles into rectangles (grid coordinates, inclusive)
+/// Greedy: extend each unvisited water tile right as far as possible, then
+/// down while the whole span is still water
+pub fn merge_water_regions(level: &LevelData, collision_map: &TileCollisionMap) -> Vec<URect> {
+    let width = level.width as usize;
+    let height = level.height as usize;
+    let is_water = |x: usize, y: usize| {
+        level
+            .tiles
+            .get(x as u32, y as u32)
+            .is_some_and(|tile| collision_map.water_tiles.contains(&tile))
+    };
+    let mut used = vec![vec![false; width]; height];
+    let mut regions = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if used[y][x] || !is_water(x, y) {
+                continue;
+            }
+
+            let mut x1 = x;
+            while x1 + 1 < width && is_water(x1 + 1, y) && !used[y][x1 + 1] {
+                x1 += 1;
+            }
+            let mut y1 = y;
+            while y1 + 1 < height && (x..=x1).all(|xx| is_water(xx, y1 + 1) && !used[y1 + 1][xx]) {
+                y1 += 1;
+            }
+
+            for row in used.iter_mut().take(y1 + 1).skip(y) {
+                for cell in row.iter_mut().take(x1 + 1).skip(x) {
+                    *cell = true;
+                }
+            }
+            regions.push(URect::new(x as u32, y as u32, x1 as u32, y1 as u32));
+        }
+    }
+
+    regions
+}
+
+/// Spawns one sensor volume per rectangle of contiguous water tiles
+pub fn spawn_water_volumes(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    collision_map: Option<Res<TileCollisionMap>>,
+) {
+    let (Some(level), Some(collision_map)) = (level, collision_map) else {
+        return;
+    };
+
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    for region in merge_water_regions(&level, &collision_map) {
+        // Grid Y grows downwards, so the top-left tile gives min X / max Y
+        let top_left = tile_to_world(&level, region.min.x, region.min.y);
+        let bottom_right = tile_to_world(&level, region.max.x, region.max.y);
+        let rect = Rect::from_corners(
+            Vec2::new(top_left.x, bottom_right.y) - half_tile,
+            Vec2::new(bottom_right.x, top_left.y) + half_tile,
+        );
+
+        commands.spawn((
+            Name::new(format!("Water Volume ({}, {})", region.min.x, region.min.y)),
+            LevelEntity,
+            Collider::cuboid(rect.half_size().x, rect.half_size().y),
+            Sensor,
+            Transform::from_translation(rect.center().extend(0.0)),
+            WaterVolume {
+                rect,
+                sound: level
+                    .tiles
+                    .get(region.min.x, region.min.y)
+                    .and_then(|tile| collision_map.contact_sounds.get(&tile))
+                    .copied(),
+            },
+        ));
+    }
+}
+
+/// Spawns the level's water current zones
+pub fn spawn_water_currents(mut commands: Commands, level: Option<Res<LevelData>>) {
+    let Some(level) = level else {
+        return;
+    };
+
+    let half_tile = Vec2::splat(TILE_SIZE_16 / 2.0);
+    let last_tile = UVec2::new(level.width, level.height).saturating_sub(UVec2::ONE);
+    for (index, current) in level.water_currents.iter().enumerate() {
+        let max = current.max.min(last_tile);
+        let top_left = tile_to_world(&level, current.min.x, current.min.y);
+        let bottom_right = tile_to_world(&level, max.x, max.y);
+        commands.spawn((
+            Name::new(format!("Water current {}", index)),
+            LevelEntity,
+            WaterCurrent {
+                area: Rect::from_corners(top_left - half_tile, bottom_right + half_tile),
+                flow: current.flow,
+            },
+        ));
+    }
+}
+
+/// Combined flow of the currents at `position` (overlapping currents add up)
+fn current_at<'a>(currents: impl Iterator<Item = &'a WaterCurrent>, position: Vec2) -> Vec2 {
+    currents
+        .filter(|current| current.area.contains(position))
+        .map(|current| current.flow)
+        .sum()
+}
+
+/// Characters checked against the water volumes and currents
+type WaterCharacters<'w, 's> =
+    Query<'w, 's, (Entity, &'static Transform, Has<InWater>, Has<InCurrent>), With<PlayerVelocity>>;
+
+/// Flags characters that are inside a water volume, and the currents
+/// carrying them while they are; entering a volume plays its splash
+pub fn update_water_state(
+    mut commands: Commands,
+    volumes: Query<&WaterVolume>,
+    currents: Query<&WaterCurrent>,
+    characters: WaterCharacters,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for (entity, transform, was_in_water, was_in_current) in characters.iter() {
+        let position = transform.translation.truncate();
+        let volume = volumes.iter().find(|volume| volume.rect.contains(position));
+        let in_water = volume.is_some();
+
+        if let (Some(volume), false) = (volume, was_in_water) {
+            commands.entity(entity).insert(InWater);
+            if let Some(name) = volume.sound {
+                sound_events.write(SoundEvent {
+                    name,
+                    position: Some(position),
+                });
+            }
+        } else if !in_water && was_in_water {
+            commands.entity(entity).remove::<InWater>();
+        }
+
+        let flow = if in_water {
+            current_at(currents.iter(), position)
+        } else {
+            Vec2::ZERO
+        };
+        if flow != Vec2::ZERO {
+            commands.entity(entity).insert(InCurrent(flow));
+        } else if was_in_current {
+            commands.entity(entity).remove::<InCurrent>();
+        }
+    }
+}
+
+/// Pushes submerged dynamic bodies up and slows them down; currents push
+/// them along until they drift at the current's speed
+pub fn apply_buoyancy(
+    config: Res<GameConfig>,
+    volumes: Query<&WaterVolume>,
+    currents: Query<&WaterCurrent>,
+    mut bodies: Query<(
+        &Transform,
+        &Buoyant,
+        &ReadMassProperties,
+        &mut ExternalForce,
+        &mut Damping,
+    )>,
+) {
+    for (transform, buoyant, mass_properties, mut force, mut damping) in bodies.iter_mut() {
+        let position = transform.translation.truncate();
+        let submerged = volumes.iter().any(|volume| volume.rect.contains(position));
+
+        if submerged {
+            let mass = mass_properties.get().mass;
+            // Against water damping this settles at exactly the flow speed
+            let drift = current_at(currents.iter(), position) * mass * buoyant.water_damping;
+            force.force = Vec2::new(0.0, -config.gravity * mass * buoyant.buoyancy) + drift;
+            damping.linear_damping = buoyant.water_damping;
+        } else {
+            force.force = Vec2::ZERO;
+            damping.linear_damping = 0.0;
+        }
+    }
+}
+
+/// Outlines water currents and draws arrows along their flow in the editor
+pub fn draw_water_currents(mut gizmos: Gizmos, currents: Query<&WaterCurrent>) {
+    let color = Color::srgba(0.3, 0.7, 1.0, 0.8);
+    for current in currents.iter() {
+        gizmos.rect_2d(current.area.center(), current.area.size(), color);
+
+        let arrow = current.flow.normalize_or_zero() * WATER_CURRENT_ARROW_SPACING * 0.4;
+        let mut x = current.area.min.x + WATER_CURRENT_ARROW_SPACING / 2.0;
+        while x < current.area.max.x {
+            let mut y = current.area.min.y + WATER_CURRENT_ARROW_SPACING / 2.0;
+            while y < current.area.max.y {
+                let center = Vec2::new(x, y);
+                gizmos.arrow_2d(center - arrow, center + arrow, color);
+                y += WATER_CURRENT_ARROW_SPACING;
+            }
+            x += WATER_CURRENT_ARROW_SPACING;
+        }
+    }
+}
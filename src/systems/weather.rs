@@ -0,0 +1,206 @@
+//! Weather: rain and snow particles blown by the wind, and a fog overlay
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{
+    LevelData, LevelEntity, MainCamera, Weather, WeatherFog, WeatherKind, WeatherParticle,
+};
+use crate::constants::*;
+use crate::systems::camera::camera_view;
+
+/// Sets up the level's weather and spawns its fog overlay
+/// Levels without a `weather` entry are clear; the runtime toggle carries over
+pub fn setup_weather(
+    mut commands: Commands,
+    level: Option<Res<LevelData>>,
+    previous: Option<Res<Weather>>,
+) {
+    let enabled = previous.is_none_or(|weather| weather.enabled);
+    let weather = match level.as_ref().and_then(|level| level.weather.as_ref()) {
+        Some(def) => Weather {
+            kind: def.kind,
+            wind: def.wind,
+            intensity: def.intensity,
+            fog: def.fog.clamp(0.0, 1.0),
+            enabled,
+        },
+        None => Weather {
+            enabled,
+            ..default()
+        },
+    };
+
+    if weather.fog > 0.0 {
+        commands.spawn((
+            Name::new("Weather fog"),
+            LevelEntity,
+            WeatherFog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.8, 0.82, 0.86, weather.fog)),
+            // Below the HUD and menus
+            GlobalZIndex(-1),
+            if enabled {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+        ));
+    }
+    commands.insert_resource(weather);
+}
+
+/// Toggles the weather effects (F9)
+pub fn toggle_weather(keyboard: Res<ButtonInput<KeyCode>>, mut weather: ResMut<Weather>) {
+    if keyboard.just_pressed(KeyCode::F9) {
+        weather.enabled = !weather.enabled;
+        info!("Weather: {}", if weather.enabled { "ON" } else { "OFF" });
+    }
+}
+
+/// Shows or hides the fog overlay with the weather toggle
+pub fn update_weather_fog(
+    weather: Res<Weather>,
+    mut fogs: Query<&mut Visibility, With<WeatherFog>>,
+) {
+    if !weather.is_changed() {
+        return;
+    }
+    for mut visibility in fogs.iter_mut() {
+        *visibility = if weather.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Spawns rain drops or snowflakes just above the camera view
+/// Particles get a random z among the parallax layers; farther ones are
+/// smaller, fainter and slower
+pub fn spawn_weather_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    particles: Query<(), With<WeatherParticle>>,
+    mut pending: Local<f32>,
+) {
+    let (rate, fall_speed) = match weather.kind {
+        WeatherKind::Rain => (RAIN_SPAWN_RATE, RAIN_FALL_SPEED),
+        WeatherKind::Snow => (SNOW_SPAWN_RATE, SNOW_FALL_SPEED),
+        WeatherKind::Clear => return,
+    };
+    if !weather.enabled {
+        *pending = 0.0;
+        return;
+    }
+    let Some(view) = cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection))
+    else {
+        return;
+    };
+
+    *pending += rate * weather.intensity * time.delta_secs();
+    let count =
+        (*pending as usize).min(WEATHER_MAX_PARTICLES.saturating_sub(particles.iter().len()));
+    *pending = pending.fract();
+
+    // Spawn upwind too, so the wind doesn't leave an empty strip on one side
+    let drift = weather.wind * view.height() / fall_speed;
+    let min_x = view.min.x - drift.max(0.0);
+    let max_x = view.max.x - drift.min(0.0);
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let depth: f32 = rng.gen();
+        let scale = 0.5 + 0.5 * depth;
+        let velocity = Vec2::new(weather.wind, -fall_speed) * scale;
+        let position = Vec2::new(
+            rng.gen_range(min_x..=max_x),
+            view.max.y + rng.gen_range(0.0..=16.0),
+        );
+        let z = WEATHER_MIN_Z + (WEATHER_MAX_Z - WEATHER_MIN_Z) * depth;
+
+        let (sprite, rotation) = match weather.kind {
+            WeatherKind::Rain => (
+                Sprite::from_color(
+                    Color::srgba(0.7, 0.8, 1.0, 0.3 + 0.4 * depth),
+                    Vec2::new(1.0, 8.0) * scale,
+                ),
+                // The drop's long side points along its velocity
+                Quat::from_rotation_z(velocity.to_angle() - std::f32::consts::FRAC_PI_2),
+            ),
+            WeatherKind::Snow | WeatherKind::Clear => (
+                Sprite::from_color(
+                    Color::srgba(1.0, 1.0, 1.0, 0.5 + 0.5 * depth),
+                    Vec2::splat(3.0 * scale),
+                ),
+                Quat::IDENTITY,
+            ),
+        };
+        commands.spawn((
+            LevelEntity,
+            sprite,
+            Transform::from_translation(position.extend(z)).with_rotation(rotation),
+            WeatherParticle {
+                velocity,
+                depth,
+                phase: rng.gen_range(0.0..std::f32::consts::TAU),
+            },
+        ));
+    }
+}
+
+/// The main camera, seen from the weather particles
+type WeatherCameras<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, &'static Projection),
+    (With<MainCamera>, Without<WeatherParticle>),
+>;
+
+/// Moves weather particles (snowflakes sway) and despawns the ones that left
+/// the view, or all of them when the weather is off
+pub fn update_weather_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    cameras: WeatherCameras,
+    mut particles: Query<(Entity, &WeatherParticle, &mut Transform)>,
+) {
+    let view = cameras
+        .single()
+        .ok()
+        .and_then(|(transform, projection)| camera_view(transform, projection));
+    let active = weather.enabled && weather.kind != WeatherKind::Clear;
+
+    for (entity, particle, mut transform) in particles.iter_mut() {
+        let Some(view) = view.filter(|_| active) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let mut velocity = particle.velocity;
+        if weather.kind == WeatherKind::Snow {
+            velocity.x +=
+                (time.elapsed_secs() * 2.0 + particle.phase).sin() * SNOW_SWAY * particle.depth;
+        }
+        transform.translation += (velocity * time.delta_secs()).extend(0.0);
+
+        let position = transform.translation.truncate();
+        let margin = view.width();
+        if position.y < view.min.y - 16.0
+            || position.x < view.min.x - margin
+            || position.x > view.max.x + margin
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
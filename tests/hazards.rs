@@ -0,0 +1,49 @@
+//! Falling hazards on a test level, run headless
+
+use bevy::prelude::*;
+use bevy_sidescroller::components::{FallingHazard, FallingHazardState};
+use bevy_sidescroller::headless::{headless_app, load_level, player_health, run_frames};
+
+/// Frames for the stalactite to shake, fall and reach the landed player
+const DROP_FRAMES: u32 = 120;
+
+/// Height of the level's only falling hazard, and where it hangs
+fn hazard_heights(app: &mut App) -> (f32, f32) {
+    let (hazard, transform) = app
+        .world_mut()
+        .query::<(&FallingHazard, &Transform)>()
+        .single(app.world())
+        .expect("expected exactly one falling hazard");
+    (transform.translation.y, hazard.origin.y)
+}
+
+#[test]
+fn stalactite_falls_on_the_player_below() {
+    let mut app = headless_app();
+    load_level(&mut app, "tests://levels/stalactite_over_spawn.level.ron");
+    let health = player_health(&mut app);
+    assert_eq!(health.current, health.max);
+
+    run_frames(&mut app, DROP_FRAMES);
+    let health = player_health(&mut app);
+    assert!(
+        health.current < health.max,
+        "player wasn't hit: {}/{}",
+        health.current,
+        health.max
+    );
+    // Broken where it landed, since the player didn't respawn
+    let (y, origin_y) = hazard_heights(&mut app);
+    assert!(
+        y < origin_y,
+        "stalactite didn't fall: {} from {}",
+        y,
+        origin_y
+    );
+    let broken = app
+        .world_mut()
+        .query::<&FallingHazard>()
+        .iter(app.world())
+        .all(|hazard| matches!(hazard.state, FallingHazardState::Broken));
+    assert!(broken, "stalactite didn't break on the player");
+}
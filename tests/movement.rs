@@ -0,0 +1,113 @@
+//! Movement and collision on a real level, run headless
+
+use bevy_sidescroller::components::InputAction;
+use bevy_sidescroller::constants::{DEFAULT_LEVEL_PATH, LEVEL_ORIGIN_Y, TILE_SIZE_16};
+use bevy_sidescroller::headless::{
+    headless_app, hold, load_level, player_health, player_position, press, release, run_frames,
+};
+
+/// Frames for the player to fall from its spawn point and come to rest
+const SETTLE_FRAMES: u32 = 120;
+
+#[test]
+fn player_lands_on_the_ground() {
+    let mut app = headless_app();
+    load_level(&mut app, DEFAULT_LEVEL_PATH);
+    run_frames(&mut app, SETTLE_FRAMES);
+
+    let landed = player_position(&mut app);
+    run_frames(&mut app, 30);
+    let later = player_position(&mut app);
+    assert!(
+        (later.y - landed.y).abs() < 0.5,
+        "player still moving vertically: {} -> {}",
+        landed.y,
+        later.y
+    );
+    let health = player_health(&mut app);
+    assert_eq!(health.current, health.max);
+}
+
+#[test]
+fn player_walks_right_and_left() {
+    let mut app = headless_app();
+    load_level(&mut app, DEFAULT_LEVEL_PATH);
+    run_frames(&mut app, SETTLE_FRAMES);
+
+    let start = player_position(&mut app);
+    hold(&mut app, InputAction::Right, 30);
+    let right = player_position(&mut app);
+    assert!(right.x > start.x, "{} -> {}", start.x, right.x);
+
+    hold(&mut app, InputAction::Left, 30);
+    let left = player_position(&mut app);
+    assert!(left.x < right.x, "{} -> {}", right.x, left.x);
+}
+
+#[test]
+fn player_jumps_and_comes_back_down() {
+    let mut app = headless_app();
+    load_level(&mut app, DEFAULT_LEVEL_PATH);
+    run_frames(&mut app, SETTLE_FRAMES);
+
+    let ground = player_position(&mut app);
+    hold(&mut app, InputAction::Jump, 10);
+    let airborne = player_position(&mut app);
+    assert!(airborne.y > ground.y, "{} -> {}", ground.y, airborne.y);
+
+    run_frames(&mut app, SETTLE_FRAMES);
+    let landed = player_position(&mut app);
+    assert!(
+        (landed.y - ground.y).abs() < 1.0,
+        "{} -> {}",
+        ground.y,
+        landed.y
+    );
+}
+
+/// Highest the player gets over `frames` updates
+fn peak_height(app: &mut bevy::prelude::App, frames: u32) -> f32 {
+    (0..frames)
+        .map(|_| {
+            run_frames(app, 1);
+            player_position(app).y
+        })
+        .fold(f32::MIN, f32::max)
+}
+
+#[test]
+fn releasing_jump_early_jumps_lower() {
+    let [tapped, held] = [2, 40].map(|held_frames| {
+        let mut app = headless_app();
+        load_level(&mut app, DEFAULT_LEVEL_PATH);
+        run_frames(&mut app, SETTLE_FRAMES);
+
+        let ground = player_position(&mut app).y;
+        press(&mut app, InputAction::Jump);
+        let held = peak_height(&mut app, held_frames);
+        release(&mut app, InputAction::Jump);
+        held.max(peak_height(&mut app, SETTLE_FRAMES)) - ground
+    });
+    assert!(tapped > 0.0, "tapped jump didn't leave the ground");
+    assert!(tapped < held, "tapped {} vs held {}", tapped, held);
+}
+
+#[test]
+fn one_way_platform_holds_the_player_in_water() {
+    let mut app = headless_app();
+    load_level(
+        &mut app,
+        "tests://levels/one_way_platform_in_water.level.csv",
+    );
+    run_frames(&mut app, SETTLE_FRAMES * 2);
+
+    // Top of the platform, 6 tiles over the bottom of the level
+    let platform_top = LEVEL_ORIGIN_Y + 6.0 * TILE_SIZE_16;
+    let landed = player_position(&mut app);
+    assert!(
+        landed.y > platform_top,
+        "player fell through the platform: {} <= {}",
+        landed.y,
+        platform_top
+    );
+}